@@ -14,3 +14,22 @@ fn bench_read(bencher: &mut test::Bencher) {
         }
     });
 }
+
+#[bench]
+fn bench_read_from_slice_large(bencher: &mut test::Bencher) {
+    let mut gpx_bytes = br#"<gpx version="1.1" creator="bench"><trk><trkseg>"#.to_vec();
+    for i in 0..10_000 {
+        let point = format!(
+            r#"<trkpt lat="{}" lon="{}"><ele>{}</ele><time>2023-06-01T12:{:02}:{:02}Z</time></trkpt>"#,
+            37.0 + f64::from(i) * 1e-5,
+            -121.0 - f64::from(i) * 1e-5,
+            i % 500,
+            i / 60 % 60,
+            i % 60
+        );
+        gpx_bytes.extend_from_slice(point.as_bytes());
+    }
+    gpx_bytes.extend_from_slice(b"</trkseg></trk></gpx>");
+
+    bencher.iter(|| test::black_box(gpx::read_from_slice(&gpx_bytes).unwrap()));
+}
@@ -0,0 +1,566 @@
+//! analysis finds notable parts of a recorded activity, like the stops made
+//! along the way, breaks it down into splits or heart rate zones, and
+//! describes how densely it was sampled.
+
+use geo_types::{CoordFloat, Point};
+
+use crate::measure::{distance, elapsed, elevation, position, ElevationCounter};
+use crate::{Duration, ElevationChange, Time, Track, TrackSegment, Waypoint};
+
+/// A stop made during an activity, see [`TrackSegment::stops`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stop {
+    /// Time of the first point of the stop.
+    pub start_time: Time,
+
+    /// Time of the last point of the stop.
+    pub end_time: Time,
+
+    /// Mean position of the points of the stop.
+    pub location: Point<f64>,
+
+    /// Time elapsed from `start_time` to `end_time`.
+    pub duration: Duration,
+}
+
+/// A part of an activity covering a fixed distance, see [`Track::splits`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Split {
+    /// Distance covered, in meters. This is the split interval for all but
+    /// the last split, which may be shorter.
+    pub distance: f64,
+
+    /// Time taken to cover the split, if the points around it have times.
+    pub duration: Option<Duration>,
+
+    /// Time it would take to cover a full split interval at the split's
+    /// average speed, e.g. minutes per kilometer for 1000 m splits.
+    pub pace: Option<Duration>,
+
+    /// Ascent and descent within the split, counting every change.
+    pub elevation: ElevationChange,
+}
+
+/// How densely a track segment was sampled, see
+/// [`TrackSegment::sampling`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sampling {
+    /// Number of points.
+    pub points: usize,
+
+    /// Points per kilometer, if the segment has any length.
+    pub points_per_km: Option<f64>,
+
+    /// Shortest time between two consecutive timestamped points.
+    pub min_interval: Option<Duration>,
+
+    /// Median time between two consecutive timestamped points.
+    pub median_interval: Option<Duration>,
+
+    /// Time between two consecutive timestamped points that 95 percent of
+    /// the intervals don't exceed, which tells about gaps in recording.
+    pub p95_interval: Option<Duration>,
+
+    /// Longest time between two consecutive timestamped points.
+    pub max_interval: Option<Duration>,
+}
+
+impl<T: CoordFloat> Track<T> {
+    /// Describes how densely each segment of the track was sampled, see
+    /// [`TrackSegment::sampling`].
+    pub fn sampling(&self) -> Vec<Sampling> {
+        self.segments.iter().map(TrackSegment::sampling).collect()
+    }
+
+    /// Sums up the time spent in each heart rate zone over all segments of
+    /// the track, see [`TrackSegment::time_in_heart_rate_zones`].
+    pub fn time_in_heart_rate_zones(&self, boundaries: &[u16]) -> Vec<Duration> {
+        let mut zones = vec![Duration::ZERO; boundaries.len() + 1];
+        for segment in &self.segments {
+            for (total, time) in zones
+                .iter_mut()
+                .zip(segment.time_in_heart_rate_zones(boundaries))
+            {
+                *total += time;
+            }
+        }
+        zones
+    }
+
+    /// Breaks the track down into splits of `interval` meters each, e.g.
+    /// 1000.0 for kilometers or 1609.344 for miles.
+    ///
+    /// Times and elevations at the split boundaries are interpolated
+    /// between the surrounding points. The gaps between segments add no
+    /// distance, but the time spent in them counts towards the split
+    /// they fall into.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is not positive.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Duration, Time, Track, TrackSegment, Waypoint};
+    ///
+    /// let start = Time::UNIX_EPOCH;
+    /// let mut segment = TrackSegment::new();
+    /// // Running north for about 2.5 km in 15 minutes.
+    /// for (latitude, minutes) in [(0.0, 0), (0.0226, 15)] {
+    ///     let mut point = Waypoint::new(Point::new(0.0, latitude));
+    ///     point.time = Some(start + Duration::minutes(minutes));
+    ///     segment.points.push(point);
+    /// }
+    /// let mut track = Track::new();
+    /// track.segments.push(segment);
+    ///
+    /// let splits = track.splits(1000.0);
+    /// assert_eq!(splits.len(), 3);
+    /// assert_eq!(splits[0].pace.unwrap().whole_minutes(), 6);
+    /// assert!(splits[2].distance < 1000.0);
+    /// ```
+    pub fn splits(&self, interval: f64) -> Vec<Split> {
+        assert!(interval > 0.0, "interval must be positive");
+
+        let mut splits = SplitBuilder::new(interval);
+        for segment in &self.segments {
+            let mut points = segment.points.iter();
+            let Some(first) = points.next() else {
+                continue;
+            };
+            splits.add_point(first.time, elevation(first));
+            let mut previous = first;
+            for point in points {
+                splits.add_step(previous, point);
+                previous = point;
+            }
+        }
+        splits.finish()
+    }
+
+    /// Detects the stops made in all segments of the track, see
+    /// [`TrackSegment::stops`].
+    pub fn stops(&self, max_radius: f64, min_duration: Duration) -> Vec<Stop> {
+        self.segments
+            .iter()
+            .flat_map(|segment| segment.stops(max_radius, min_duration))
+            .collect()
+    }
+}
+
+impl<T: CoordFloat> TrackSegment<T> {
+    /// Describes how densely the segment was sampled, by distance and by
+    /// time, e.g. to decide whether it should be
+    /// [simplified](TrackSegment::simplified) before further processing.
+    ///
+    /// Intervals are taken between consecutive points with times, skipping
+    /// points without one. Percentiles are the nearest ranks.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Duration, Time, TrackSegment, Waypoint};
+    ///
+    /// let mut segment = TrackSegment::new();
+    /// // Walking north for about 1.1 km, logging every second with a gap.
+    /// for (latitude, seconds) in [(0.0, 0), (0.0025, 1), (0.005, 2), (0.0075, 3), (0.01, 63)] {
+    ///     let mut point = Waypoint::new(Point::new(0.0, latitude));
+    ///     point.time = Some(Time::UNIX_EPOCH + Duration::seconds(seconds));
+    ///     segment.points.push(point);
+    /// }
+    ///
+    /// let sampling = segment.sampling();
+    /// assert_eq!(sampling.points_per_km.unwrap().round(), 5.0);
+    /// assert_eq!(sampling.median_interval, Some(Duration::seconds(1)));
+    /// assert_eq!(sampling.max_interval, Some(Duration::minutes(1)));
+    /// ```
+    pub fn sampling(&self) -> Sampling {
+        let length = self.length_m();
+        let mut intervals: Vec<Duration> = self
+            .points
+            .iter()
+            .filter_map(|point| point.time)
+            .collect::<Vec<Time>>()
+            .windows(2)
+            .map(|times| elapsed(times[0], times[1]))
+            .collect();
+        intervals.sort_unstable();
+        let percentile = |percent: usize| {
+            let rank = ((percent * intervals.len() + 99) / 100).max(1);
+            intervals.get(rank - 1).copied()
+        };
+        Sampling {
+            points: self.points.len(),
+            points_per_km: (length > 0.0).then(|| self.points.len() as f64 / length * 1000.0),
+            min_interval: intervals.first().copied(),
+            median_interval: percentile(50),
+            p95_interval: percentile(95),
+            max_interval: intervals.last().copied(),
+        }
+    }
+
+    /// Sums up the time spent in each heart rate zone along the segment.
+    ///
+    /// The zones are delimited by the ascending heart rates in `boundaries`:
+    /// zone 0 is below the first boundary, zone 1 from the first up to the
+    /// second, and so on, giving one more zone than there are boundaries.
+    /// The time between two consecutive timestamped points counts towards
+    /// the zone of the heart rate recorded at the earlier point, see
+    /// [`TrackPointExtension`](crate::TrackPointExtension); intervals
+    /// starting at a point without a heart rate are not counted.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Duration, Time, TrackPointExtension, TrackSegment, Waypoint};
+    ///
+    /// let mut segment = TrackSegment::new();
+    /// for (heart_rate, minutes) in [(110, 0), (150, 10), (175, 40), (160, 45)] {
+    ///     let mut point = Waypoint::new(Point::new(0.0, 0.0));
+    ///     point.time = Some(Time::UNIX_EPOCH + Duration::minutes(minutes));
+    ///     point.track_point_extension = Some(TrackPointExtension {
+    ///         heart_rate: Some(heart_rate),
+    ///         ..Default::default()
+    ///     });
+    ///     segment.points.push(point);
+    /// }
+    ///
+    /// let zones = segment.time_in_heart_rate_zones(&[120, 140, 170]);
+    /// assert_eq!(
+    ///     zones,
+    ///     [10, 0, 30, 5].map(Duration::minutes),
+    /// );
+    /// ```
+    pub fn time_in_heart_rate_zones(&self, boundaries: &[u16]) -> Vec<Duration> {
+        let mut zones = vec![Duration::ZERO; boundaries.len() + 1];
+        let mut timed = self
+            .points
+            .iter()
+            .filter_map(|point| Some((point, point.time?)));
+        let Some(mut previous) = timed.next() else {
+            return zones;
+        };
+        for current in timed {
+            let heart_rate = previous
+                .0
+                .track_point_extension
+                .as_ref()
+                .and_then(|extension| extension.heart_rate);
+            if let Some(heart_rate) = heart_rate {
+                let zone = boundaries.partition_point(|&boundary| boundary <= heart_rate);
+                zones[zone] += elapsed(previous.1, current.1);
+            }
+            previous = current;
+        }
+        zones
+    }
+
+    /// Detects the stops made along the segment.
+    ///
+    /// A stop is a run of consecutive timestamped points that all lie within
+    /// `max_radius` meters of the run's first point and that lasts at least
+    /// `min_duration`. This also catches pauses where the logger stopped
+    /// recording, as long as it was not moved. Points without a time are
+    /// skipped.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Duration, Time, TrackSegment, Waypoint};
+    ///
+    /// let start = Time::UNIX_EPOCH;
+    /// let mut segment = TrackSegment::new();
+    /// for (latitude, seconds) in [(0.0, 0), (0.001, 10), (0.001, 70), (0.001, 130), (0.002, 140)] {
+    ///     let mut point = Waypoint::new(Point::new(0.0, latitude));
+    ///     point.time = Some(start + Duration::seconds(seconds));
+    ///     segment.points.push(point);
+    /// }
+    ///
+    /// let stops = segment.stops(20.0, Duration::minutes(1));
+    /// assert_eq!(stops.len(), 1);
+    /// assert_eq!(stops[0].duration, Duration::minutes(2));
+    /// assert_eq!(stops[0].location, Point::new(0.0, 0.001));
+    /// ```
+    pub fn stops(&self, max_radius: f64, min_duration: Duration) -> Vec<Stop> {
+        let timed: Vec<(&Waypoint<T>, Time)> = self
+            .points
+            .iter()
+            .filter_map(|point| Some((point, point.time?)))
+            .collect();
+
+        let mut stops = Vec::new();
+        let mut start = 0;
+        while start < timed.len() {
+            let (anchor, start_time) = timed[start];
+            let end = timed[start..]
+                .iter()
+                .position(|(point, _)| distance(anchor, point) > max_radius)
+                .map_or(timed.len(), |length| start + length)
+                - 1;
+            let end_time = timed[end].1;
+            let duration = elapsed(start_time, end_time);
+            if end > start && duration >= min_duration {
+                stops.push(Stop {
+                    start_time,
+                    end_time,
+                    location: mean_position(&timed[start..=end]),
+                    duration,
+                });
+                start = end + 1;
+            } else {
+                start += 1;
+            }
+        }
+        stops
+    }
+}
+
+/// Collects splits while walking along a track.
+struct SplitBuilder {
+    interval: f64,
+    splits: Vec<Split>,
+    start_time: Option<Time>,
+    last_time: Option<Time>,
+    distance: f64,
+    elevation: ElevationCounter,
+}
+
+impl SplitBuilder {
+    fn new(interval: f64) -> SplitBuilder {
+        SplitBuilder {
+            interval,
+            splits: Vec::new(),
+            start_time: None,
+            last_time: None,
+            distance: 0.0,
+            elevation: ElevationCounter::new(0.0),
+        }
+    }
+
+    /// Records a point reached without covering any distance, like the
+    /// first point of a segment.
+    fn add_point(&mut self, time: Option<Time>, elevation: Option<f64>) {
+        if self.splits.is_empty() && self.distance == 0.0 && self.start_time.is_none() {
+            self.start_time = time;
+        }
+        if time.is_some() {
+            self.last_time = time;
+        }
+        if let Some(elevation) = elevation {
+            self.elevation.add(elevation);
+        }
+    }
+
+    fn add_step<T: CoordFloat>(&mut self, a: &Waypoint<T>, b: &Waypoint<T>) {
+        let length = distance(a, b);
+        let mut covered = 0.0;
+        while length - covered >= self.interval - self.distance {
+            covered += self.interval - self.distance;
+            let fraction = covered / length;
+            let time = match (a.time, b.time) {
+                (Some(a), Some(b)) => Some(a + elapsed(a, b) * fraction),
+                _ => None,
+            };
+            let elevation = match (elevation(a), elevation(b)) {
+                (Some(a), Some(b)) => Some(a + (b - a) * fraction),
+                _ => None,
+            };
+            self.distance = self.interval;
+            self.add_point(time, elevation);
+            self.close(time, elevation);
+        }
+        self.distance += length - covered;
+        self.add_point(b.time, elevation(b));
+    }
+
+    /// Ends the current split, starting the next one at `time` and
+    /// `elevation`.
+    fn close(&mut self, time: Option<Time>, elevation: Option<f64>) {
+        let duration = match (self.start_time, time) {
+            (Some(start), Some(end)) => Some(end - start),
+            _ => None,
+        };
+        self.splits.push(Split {
+            distance: self.distance,
+            duration,
+            pace: duration.map(|duration| duration * (self.interval / self.distance)),
+            elevation: self.elevation.change,
+        });
+        self.start_time = time;
+        self.distance = 0.0;
+        self.elevation = ElevationCounter::new(0.0);
+        if let Some(elevation) = elevation {
+            self.elevation.add(elevation);
+        }
+    }
+
+    fn finish(mut self) -> Vec<Split> {
+        if self.distance > 0.0 {
+            self.close(self.last_time, None);
+        }
+        self.splits
+    }
+}
+
+fn mean_position<T: CoordFloat>(points: &[(&Waypoint<T>, Time)]) -> Point<f64> {
+    let count = points.len() as f64;
+    let (x, y) = points.iter().fold((0.0, 0.0), |(x, y), (point, _)| {
+        let point = position(point);
+        (x + point.x(), y + point.y())
+    });
+    Point::new(x / count, y / count)
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::Point;
+
+    use super::Sampling;
+    use crate::{
+        Duration, ElevationChange, Time, Track, TrackPointExtension, TrackSegment, Waypoint,
+    };
+
+    #[test]
+    fn stops_in_track() {
+        let at = |seconds| Time::UNIX_EPOCH + Duration::seconds(seconds);
+        let mut segment = TrackSegment::new();
+        for (latitude, seconds) in [
+            (0.0, Some(0)),
+            (0.00002, Some(100)),
+            (0.5, None),
+            (0.0, Some(200)),
+            (0.01, Some(210)),
+            (0.01, Some(220)),
+            (0.02, Some(230)),
+            (0.02, Some(900)),
+        ] {
+            let mut point = Waypoint::new(Point::new(0.0, latitude));
+            point.time = seconds.map(at);
+            segment.points.push(point);
+        }
+        let mut track = Track::new();
+        track.segments.push(segment);
+        track.segments.push(TrackSegment::new());
+
+        let stops = track.stops(5.0, Duration::minutes(1));
+        assert_eq!(stops.len(), 2);
+        assert_eq!(stops[0].start_time, at(0));
+        assert_eq!(stops[0].end_time, at(200));
+        assert!((stops[0].location.y() - 0.00002 / 3.0).abs() < 1e-12);
+        assert_eq!(stops[1].start_time, at(230));
+        assert_eq!(stops[1].duration, Duration::seconds(670));
+
+        assert!(track.stops(5.0, Duration::hours(1)).is_empty());
+    }
+
+    #[test]
+    fn time_in_heart_rate_zones() {
+        let mut segment = TrackSegment::new();
+        for (heart_rate, seconds) in [
+            (Some(100), Some(0)),
+            (None, Some(10)),
+            (Some(130), None),
+            (Some(140), Some(30)),
+            (Some(100), Some(60)),
+        ] {
+            let mut point = Waypoint::new(Point::new(0.0, 0.0));
+            point.time = seconds.map(|seconds| Time::UNIX_EPOCH + Duration::seconds(seconds));
+            point.track_point_extension = Some(TrackPointExtension {
+                heart_rate,
+                ..Default::default()
+            });
+            segment.points.push(point);
+        }
+        let mut track = Track::new();
+        track.segments.push(segment.clone());
+        track.segments.push(segment);
+
+        let zones = track.time_in_heart_rate_zones(&[120, 140]);
+        assert_eq!(zones, [20, 0, 60].map(Duration::seconds));
+        assert_eq!(track.time_in_heart_rate_zones(&[]), [Duration::seconds(80)]);
+    }
+
+    #[test]
+    fn splits_across_segments() {
+        let at = |seconds| Time::UNIX_EPOCH + Duration::seconds(seconds);
+        let segment = |points: &[(f64, f64, i64)]| TrackSegment {
+            points: points
+                .iter()
+                .map(|&(latitude, elevation, seconds)| {
+                    let mut point = Waypoint::new(Point::new(0.0, latitude));
+                    point.elevation = Some(elevation);
+                    point.time = Some(at(seconds));
+                    point
+                })
+                .collect(),
+        };
+        // 0.01 degrees of latitude are about 1105.7 m.
+        let mut track = Track::new();
+        track
+            .segments
+            .push(segment(&[(0.0, 0.0, 0), (0.01, 110.0, 1000)]));
+        track.segments.push(TrackSegment::new());
+        track
+            .segments
+            .push(segment(&[(0.5, 50.0, 2000), (0.51, 50.0, 2500)]));
+
+        let splits = track.splits(1000.0);
+        assert_eq!(splits.len(), 3);
+        assert_eq!(splits[0].distance, 1000.0);
+        let seconds = |index: usize| splits[index].duration.unwrap().as_seconds_f64();
+        assert!((seconds(0) - 904.4).abs() < 0.1);
+        assert_eq!(splits[0].elevation.ascent.round(), 99.0);
+        // The second split spans the gap between the segments.
+        assert!((seconds(1) - (2404.4 - 904.4)).abs() < 0.1);
+        assert_eq!(
+            splits[1].elevation,
+            ElevationChange {
+                ascent: splits[1].elevation.ascent,
+                descent: 60.0
+            }
+        );
+        assert!((splits[2].distance - 211.4).abs() < 0.1);
+        assert!((seconds(2) - 95.6).abs() < 0.1);
+        let pace = splits[2].pace.unwrap().as_seconds_f64();
+        assert!((pace - 95.6 * 1000.0 / 211.4).abs() < 1.0);
+
+        assert!(Track::<f64>::new().splits(1000.0).is_empty());
+    }
+
+    #[test]
+    fn sampling_of_segments() {
+        let mut segment = TrackSegment::new();
+        for (latitude, seconds) in [
+            (0.0, Some(0)),
+            (0.001, Some(5)),
+            (0.002, None),
+            (0.003, Some(7)),
+            (0.004, Some(8)),
+            (0.005, Some(9)),
+        ] {
+            let mut point = Waypoint::new(Point::new(0.0, latitude));
+            point.time = seconds.map(|seconds| Time::UNIX_EPOCH + Duration::seconds(seconds));
+            segment.points.push(point);
+        }
+        let mut track = Track::new();
+        track.segments.push(segment);
+        track.segments.push(TrackSegment::new());
+
+        let sampling = track.sampling();
+        assert_eq!(sampling.len(), 2);
+        assert_eq!(sampling[0].points, 6);
+        assert!((sampling[0].points_per_km.unwrap() - 6.0 / 0.5528).abs() < 0.01);
+        assert_eq!(sampling[0].min_interval, Some(Duration::seconds(1)));
+        assert_eq!(sampling[0].median_interval, Some(Duration::seconds(1)));
+        assert_eq!(sampling[0].p95_interval, Some(Duration::seconds(5)));
+        assert_eq!(sampling[0].max_interval, Some(Duration::seconds(5)));
+        assert_eq!(
+            sampling[1],
+            Sampling {
+                points: 0,
+                points_per_km: None,
+                min_interval: None,
+                median_interval: None,
+                p95_interval: None,
+                max_interval: None,
+            }
+        );
+    }
+}
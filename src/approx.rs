@@ -0,0 +1,255 @@
+//! approx provides approximate equality comparisons for GPX documents.
+//!
+//! Documents that went through a write/read round trip rarely compare equal
+//! with `PartialEq`, since floats get reformatted and timestamps may lose
+//! precision. The `approx_eq` methods compare numbers and times within a
+//! [`Tolerance`] instead, while all other fields still have to match exactly.
+
+use geo_types::{CoordFloat, Point, Rect};
+
+use crate::{Duration, Gpx, Metadata, Route, Time, Track, TrackSegment, Waypoint};
+
+/// Tolerances used by the `approx_eq` methods.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+    /// Maximum difference between two latitudes or longitudes, in degrees.
+    pub coordinate: f64,
+
+    /// Maximum difference between two other numeric values, like elevations,
+    /// speeds or dilutions of precision.
+    pub value: f64,
+
+    /// Maximum difference between two timestamps.
+    pub time: Duration,
+}
+
+impl Default for Tolerance {
+    /// Tolerates differences of 1e-9 degrees (well below a millimeter), 1e-6
+    /// for other values and one millisecond.
+    fn default() -> Tolerance {
+        Tolerance {
+            coordinate: 1e-9,
+            value: 1e-6,
+            time: Duration::milliseconds(1),
+        }
+    }
+}
+
+impl<T: CoordFloat> Gpx<T> {
+    /// Compares two documents, allowing numbers and times to differ within
+    /// `tolerance`.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Gpx, Tolerance, Waypoint};
+    ///
+    /// let mut a: Gpx = Default::default();
+    /// a.waypoints.push(Waypoint::new(Point::new(-121.97, 37.24)));
+    /// let mut b = a.clone();
+    /// b.waypoints[0] = Waypoint::new(Point::new(-121.97 + 1e-12, 37.24));
+    ///
+    /// assert_ne!(a, b);
+    /// assert!(a.approx_eq(&b, &Tolerance::default()));
+    /// ```
+    pub fn approx_eq(&self, other: &Gpx<T>, tolerance: &Tolerance) -> bool {
+        self.version == other.version
+            && self.creator == other.creator
+            && option_approx_eq(&self.metadata, &other.metadata, |a, b| {
+                a.approx_eq(b, tolerance)
+            })
+            && slice_approx_eq(&self.waypoints, &other.waypoints, |a, b| {
+                a.approx_eq(b, tolerance)
+            })
+            && slice_approx_eq(&self.tracks, &other.tracks, |a, b| {
+                a.approx_eq(b, tolerance)
+            })
+            && slice_approx_eq(&self.routes, &other.routes, |a, b| {
+                a.approx_eq(b, tolerance)
+            })
+    }
+}
+
+impl<T: CoordFloat> Metadata<T> {
+    /// Compares two metadata elements, allowing the time and bounds to differ
+    /// within `tolerance`.
+    pub fn approx_eq(&self, other: &Metadata<T>, tolerance: &Tolerance) -> bool {
+        self.name == other.name
+            && self.description == other.description
+            && self.author == other.author
+            && self.links == other.links
+            && time_approx_eq(&self.time, &other.time, tolerance)
+            && self.keywords == other.keywords
+            && self.copyright == other.copyright
+            && option_approx_eq(&self.bounds, &other.bounds, |a, b| {
+                rect_approx_eq(a, b, tolerance)
+            })
+    }
+}
+
+impl<T: CoordFloat> Route<T> {
+    /// Compares two routes, allowing their points to differ within
+    /// `tolerance`.
+    pub fn approx_eq(&self, other: &Route<T>, tolerance: &Tolerance) -> bool {
+        self.name == other.name
+            && self.comment == other.comment
+            && self.description == other.description
+            && self.source == other.source
+            && self.links == other.links
+            && self.number == other.number
+            && self.type_ == other.type_
+            && slice_approx_eq(&self.points, &other.points, |a, b| {
+                a.approx_eq(b, tolerance)
+            })
+    }
+}
+
+impl<T: CoordFloat> Track<T> {
+    /// Compares two tracks, allowing their points to differ within
+    /// `tolerance`.
+    pub fn approx_eq(&self, other: &Track<T>, tolerance: &Tolerance) -> bool {
+        self.name == other.name
+            && self.comment == other.comment
+            && self.description == other.description
+            && self.source == other.source
+            && self.links == other.links
+            && self.type_ == other.type_
+            && self.number == other.number
+            && slice_approx_eq(&self.segments, &other.segments, |a, b| {
+                a.approx_eq(b, tolerance)
+            })
+    }
+}
+
+impl<T: CoordFloat> TrackSegment<T> {
+    /// Compares two track segments, allowing their points to differ within
+    /// `tolerance`.
+    pub fn approx_eq(&self, other: &TrackSegment<T>, tolerance: &Tolerance) -> bool {
+        slice_approx_eq(&self.points, &other.points, |a, b| {
+            a.approx_eq(b, tolerance)
+        })
+    }
+}
+
+impl<T: CoordFloat> Waypoint<T> {
+    /// Compares two waypoints, allowing coordinates, numeric values and the
+    /// time to differ within `tolerance`.
+    #[allow(deprecated)]
+    pub fn approx_eq(&self, other: &Waypoint<T>, tolerance: &Tolerance) -> bool {
+        let value_eq = |a: &Option<f64>, b: &Option<f64>| {
+            option_approx_eq(a, b, |a, b| float_approx_eq(*a, *b, tolerance.value))
+        };
+
+        point_approx_eq(self.point(), other.point(), tolerance)
+            && option_approx_eq(&self.elevation, &other.elevation, |a, b| {
+                float_approx_eq(*a, *b, tolerance.value)
+            })
+            && option_approx_eq(&self.speed, &other.speed, |a, b| {
+                float_approx_eq(*a, *b, tolerance.value)
+            })
+            && time_approx_eq(&self.time, &other.time, tolerance)
+            && self.details().name == other.details().name
+            && self.details().comment == other.details().comment
+            && self.details().description == other.details().description
+            && self.details().source == other.details().source
+            && self.details().links == other.details().links
+            && self.details().symbol == other.details().symbol
+            && self.details().type_ == other.details().type_
+            && value_eq(&self.details().geoidheight, &other.details().geoidheight)
+            && self.details().fix == other.details().fix
+            && self.details().sat == other.details().sat
+            && value_eq(&self.details().hdop, &other.details().hdop)
+            && value_eq(&self.details().vdop, &other.details().vdop)
+            && value_eq(&self.details().pdop, &other.details().pdop)
+            && value_eq(&self.details().age, &other.details().age)
+            && value_eq(&self.details().dgps_age, &other.details().dgps_age)
+            && self.details().dgpsid == other.details().dgpsid
+            && option_approx_eq(
+                &self.track_point_extension,
+                &other.track_point_extension,
+                |a, b| {
+                    value_eq(&a.air_temperature, &b.air_temperature)
+                        && value_eq(&a.water_temperature, &b.water_temperature)
+                        && value_eq(&a.depth, &b.depth)
+                        && a.heart_rate == b.heart_rate
+                        && a.cadence == b.cadence
+                },
+            )
+    }
+}
+
+fn float_approx_eq<F: CoordFloat>(a: F, b: F, epsilon: f64) -> bool {
+    a == b || (a - b).abs().to_f64().map_or(false, |diff| diff <= epsilon)
+}
+
+fn point_approx_eq<T: CoordFloat>(a: Point<T>, b: Point<T>, tolerance: &Tolerance) -> bool {
+    float_approx_eq(a.x(), b.x(), tolerance.coordinate)
+        && float_approx_eq(a.y(), b.y(), tolerance.coordinate)
+}
+
+fn rect_approx_eq<T: CoordFloat>(a: &Rect<T>, b: &Rect<T>, tolerance: &Tolerance) -> bool {
+    point_approx_eq(a.min().into(), b.min().into(), tolerance)
+        && point_approx_eq(a.max().into(), b.max().into(), tolerance)
+}
+
+fn time_approx_eq(a: &Option<Time>, b: &Option<Time>, tolerance: &Tolerance) -> bool {
+    option_approx_eq(a, b, |a, b| (*a - *b).abs() <= tolerance.time)
+}
+
+fn option_approx_eq<V, F>(a: &Option<V>, b: &Option<V>, f: F) -> bool
+where
+    F: Fn(&V, &V) -> bool,
+{
+    match (a, b) {
+        (Some(a), Some(b)) => f(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+fn slice_approx_eq<V, F>(a: &[V], b: &[V], f: F) -> bool
+where
+    F: Fn(&V, &V) -> bool,
+{
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| f(a, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::Point;
+
+    use super::Tolerance;
+    use crate::{Duration, Time, Waypoint};
+
+    #[test]
+    fn waypoint_approx_eq() {
+        let time = Time::UNIX_EPOCH;
+        let mut a = Waypoint::new(Point::new(-121.97, 37.24));
+        a.elevation = Some(553.21);
+        a.time = Some(time);
+
+        let mut b = Waypoint::new(Point::new(-121.97 + 1e-10, 37.24));
+        b.elevation = Some(553.21 + 1e-7);
+        b.time = Some(time + Duration::microseconds(500));
+        assert!(a.approx_eq(&b, &Tolerance::default()));
+
+        b.time = Some(time + Duration::seconds(1));
+        assert!(!a.approx_eq(&b, &Tolerance::default()));
+
+        b.time = a.time;
+        b.details_mut().name = Some("different".into());
+        assert!(!a.approx_eq(&b, &Tolerance::default()));
+    }
+
+    #[test]
+    fn waypoint_approx_eq_coordinates() {
+        let a = Waypoint::new(Point::new(1.0, 2.0));
+        let b = Waypoint::new(Point::new(1.0, 2.001));
+        let tolerance = Tolerance {
+            coordinate: 0.01,
+            ..Default::default()
+        };
+
+        assert!(!a.approx_eq(&b, &Tolerance::default()));
+        assert!(a.approx_eq(&b, &tolerance));
+    }
+}
@@ -0,0 +1,69 @@
+//! batch reads many files at once, spreading the parsing over all cores.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+
+use crate::errors::GpxResult;
+use crate::{read_from_path, Gpx};
+
+/// Reads the GPX files at `paths` in parallel.
+///
+/// Gives one result per path, in the order of `paths`, so that a broken
+/// file doesn't keep the others from being read.
+///
+/// ```
+/// use gpx::read_many;
+///
+/// let results = read_many(&["tests/fixtures/wikipedia_example.gpx", "missing.gpx"]);
+/// assert!(results[0].is_ok());
+/// assert!(results[1].is_err());
+/// ```
+pub fn read_many<P: AsRef<Path> + Sync>(paths: &[P]) -> Vec<GpxResult<Gpx>> {
+    paths.par_iter().map(read_from_path).collect()
+}
+
+/// Reads all files with the `.gpx` extension in the directory `dir` in
+/// parallel, without descending into subdirectories.
+///
+/// Gives each file's path with its result, sorted by path. Fails only if the
+/// directory itself can't be listed.
+pub fn read_dir<P: AsRef<Path>>(dir: P) -> GpxResult<Vec<(PathBuf, GpxResult<Gpx>)>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_gpx = path
+            .extension()
+            .map_or(false, |extension| extension.eq_ignore_ascii_case("gpx"));
+        if is_gpx && path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    let results = read_many(&paths);
+    Ok(paths.into_iter().zip(results).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::read_dir;
+
+    #[test]
+    fn read_fixtures_dir() {
+        let results = read_dir("tests/fixtures").unwrap();
+        assert!(results.len() > 10);
+        assert!(results.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(results
+            .iter()
+            .all(|(path, _)| path.extension().unwrap() == "gpx"));
+
+        let (_, wikipedia) = results
+            .iter()
+            .find(|(path, _)| path.ends_with("wikipedia_example.gpx"))
+            .unwrap();
+        assert_eq!(wikipedia.as_ref().unwrap().tracks.len(), 1);
+
+        assert!(read_dir("tests/missing").is_err());
+    }
+}
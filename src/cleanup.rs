@@ -0,0 +1,579 @@
+//! cleanup removes noise that GPS loggers commonly record, like long runs of
+//! identical points, spikes or timestamps out of order.
+
+use geo_types::{CoordFloat, Point};
+
+use crate::measure::{distance, elapsed, elevation};
+use crate::{Fix, Gpx, Route, Time, Track, TrackSegment, Waypoint};
+
+/// The fixes [`Gpx::cleanup`] applies.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CleanupOptions {
+    /// Removes the points at latitude and longitude 0, which loggers record
+    /// before they have a position, see [`Gpx::remove_null_island`].
+    pub remove_zero_points: bool,
+
+    /// Removes the points whose fix is [`Fix::None`].
+    pub remove_unfixed_points: bool,
+
+    /// Removes consecutive points of tracks and routes lying within this
+    /// many meters of each other, see [`TrackSegment::dedup_points`].
+    pub dedup_tolerance: Option<f64>,
+
+    /// Sorts the points of track segments by time, see
+    /// [`TrackSegment::sort_by_time`].
+    pub sort_by_time: bool,
+
+    /// Removes track segments without points and tracks without segments.
+    pub remove_empty: bool,
+
+    /// Sets the bounds of the metadata to those of the remaining points,
+    /// see [`Gpx::update_bounds`].
+    pub update_bounds: bool,
+}
+
+impl Default for CleanupOptions {
+    /// Applies all fixes, removing exact duplicates only.
+    fn default() -> CleanupOptions {
+        CleanupOptions {
+            remove_zero_points: true,
+            remove_unfixed_points: true,
+            dedup_tolerance: Some(0.0),
+            sort_by_time: true,
+            remove_empty: true,
+            update_bounds: true,
+        }
+    }
+}
+
+impl<T: CoordFloat> Gpx<T> {
+    /// Applies the common fixes for data straight from loggers in one go,
+    /// so that importers get a sane document.
+    ///
+    /// The points are removed first, then the remaining points of each
+    /// segment are sorted and deduplicated, and finally the empty parts are
+    /// removed and the bounds are updated.
+    ///
+    /// ```
+    /// use geo_types::line_string;
+    /// use gpx::{CleanupOptions, Gpx, Track, TrackSegment};
+    ///
+    /// let mut track = Track::new();
+    /// track.segments.push(TrackSegment::from(line_string![
+    ///     (x: 0.0, y: 0.0),
+    ///     (x: 8.54, y: 47.37),
+    ///     (x: 8.54, y: 47.37),
+    ///     (x: 8.55, y: 47.38),
+    /// ]));
+    /// track.segments.push(TrackSegment::new());
+    /// let mut gpx: Gpx = Default::default();
+    /// gpx.tracks.push(track);
+    ///
+    /// gpx.cleanup(CleanupOptions::default());
+    /// assert_eq!(gpx.tracks[0].segments.len(), 1);
+    /// assert_eq!(gpx.tracks[0].segments[0].points.len(), 2);
+    /// assert!(gpx.metadata.unwrap().bounds.is_some());
+    /// ```
+    pub fn cleanup(&mut self, options: CleanupOptions) {
+        if options.remove_zero_points {
+            self.remove_null_island();
+        }
+        let keep = |point: &Waypoint<T>| {
+            !(options.remove_unfixed_points && point.details().fix == Some(Fix::None))
+        };
+
+        self.waypoints.retain(keep);
+        for route in &mut self.routes {
+            route.points.retain(keep);
+            if let Some(tolerance) = options.dedup_tolerance {
+                route.dedup_points(tolerance);
+            }
+        }
+        for track in &mut self.tracks {
+            for segment in &mut track.segments {
+                segment.points.retain(keep);
+                if options.sort_by_time {
+                    segment.sort_by_time();
+                }
+                if let Some(tolerance) = options.dedup_tolerance {
+                    segment.dedup_points(tolerance);
+                }
+            }
+            if options.remove_empty {
+                track.segments.retain(|segment| !segment.points.is_empty());
+            }
+        }
+        if options.remove_empty {
+            self.tracks.retain(|track| !track.segments.is_empty());
+        }
+        if options.update_bounds {
+            self.update_bounds();
+        }
+    }
+
+    /// Removes the waypoints, route points and track points at latitude and
+    /// longitude 0, "null island", which some devices record before they
+    /// have a position. Returns the number of removed points.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Gpx, Waypoint};
+    ///
+    /// let mut gpx: Gpx = Default::default();
+    /// gpx.waypoints.push(Waypoint::new(Point::new(0.0, 0.0)));
+    /// gpx.waypoints.push(Waypoint::new(Point::new(8.54, 47.37)));
+    ///
+    /// assert_eq!(gpx.remove_null_island(), 1);
+    /// assert_eq!(gpx.waypoints.len(), 1);
+    /// ```
+    pub fn remove_null_island(&mut self) -> usize {
+        let is_null_island =
+            |point: &Waypoint<T>| point.point() == Point::new(T::zero(), T::zero());
+        let mut removed = remove_points(&mut self.waypoints, is_null_island);
+        for route in &mut self.routes {
+            removed += remove_points(&mut route.points, is_null_island);
+        }
+        for segment in self.tracks.iter_mut().flat_map(|track| &mut track.segments) {
+            removed += remove_points(&mut segment.points, is_null_island);
+        }
+        removed
+    }
+
+    /// Clears the elevations that are one of the `sentinels`, like 0 or
+    /// -9999, which some devices record when they don't know the elevation.
+    /// The points themselves are kept. Returns the number of cleared
+    /// elevations.
+    ///
+    /// Only use 0 as a sentinel for documents that can't be at sea level.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Gpx, Waypoint};
+    ///
+    /// let mut gpx: Gpx = Default::default();
+    /// for elevation in [-9999.0, 408.0] {
+    ///     let mut point = Waypoint::new(Point::new(8.54, 47.37));
+    ///     point.elevation = Some(elevation);
+    ///     gpx.waypoints.push(point);
+    /// }
+    ///
+    /// assert_eq!(gpx.clear_elevation_sentinels(&[0.0, -9999.0]), 1);
+    /// assert_eq!(gpx.waypoints[0].elevation, None);
+    /// assert_eq!(gpx.waypoints[1].elevation, Some(408.0));
+    /// ```
+    pub fn clear_elevation_sentinels(&mut self, sentinels: &[f64]) -> usize {
+        let route_points = self.routes.iter_mut().flat_map(|route| &mut route.points);
+        let track_points = self
+            .tracks
+            .iter_mut()
+            .flat_map(|track| &mut track.segments)
+            .flat_map(|segment| &mut segment.points);
+        let mut cleared = 0;
+        for point in self
+            .waypoints
+            .iter_mut()
+            .chain(route_points)
+            .chain(track_points)
+        {
+            if elevation(point).map_or(false, |elevation| sentinels.contains(&elevation)) {
+                point.elevation = None;
+                cleared += 1;
+            }
+        }
+        cleared
+    }
+}
+
+/// Removes the `points` for which `remove` holds, giving their number.
+fn remove_points<T: CoordFloat>(
+    points: &mut Vec<Waypoint<T>>,
+    remove: impl Fn(&Waypoint<T>) -> bool,
+) -> usize {
+    let count = points.len();
+    points.retain(|point| !remove(point));
+    count - points.len()
+}
+
+impl<T: CoordFloat> Track<T> {
+    /// Removes consecutive duplicate points from all segments of the track,
+    /// see [`TrackSegment::dedup_points`].
+    pub fn dedup_points(&mut self, tolerance: f64) {
+        for segment in &mut self.segments {
+            segment.dedup_points(tolerance);
+        }
+    }
+
+    /// Sorts the points of each segment by time, see
+    /// [`TrackSegment::sort_by_time`].
+    pub fn sort_by_time(&mut self) {
+        for segment in &mut self.segments {
+            segment.sort_by_time();
+        }
+    }
+
+    /// Removes spikes from all segments of the track, see
+    /// [`TrackSegment::remove_spikes`]. Returns the number of removed points.
+    pub fn remove_spikes(&mut self, max_speed: f64) -> usize {
+        self.segments
+            .iter_mut()
+            .map(|segment| segment.remove_spikes(max_speed))
+            .sum()
+    }
+}
+
+impl<T: CoordFloat> TrackSegment<T> {
+    /// Removes consecutive points lying within `tolerance` meters of the last
+    /// point that was kept, e.g. while the logger was idling. Of each run of
+    /// duplicates, the first point and its data are kept.
+    ///
+    /// ```
+    /// use geo_types::line_string;
+    /// use gpx::TrackSegment;
+    ///
+    /// let mut segment = TrackSegment::from(line_string![
+    ///     (x: 0.0, y: 0.0),
+    ///     (x: 0.0, y: 0.0),
+    ///     (x: 0.0, y: 0.000001),
+    ///     (x: 0.0, y: 0.001),
+    /// ]);
+    ///
+    /// segment.dedup_points(1.0);
+    /// assert_eq!(segment.points.len(), 2);
+    /// ```
+    pub fn dedup_points(&mut self, tolerance: f64) {
+        self.points
+            .dedup_by(|point, kept| distance(kept, point) <= tolerance);
+    }
+
+    /// Finds the indices of the timestamped points whose time is earlier than
+    /// that of the preceding timestamped point, which some buggy loggers
+    /// produce. Points without a time are skipped.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Duration, Time, TrackSegment, Waypoint};
+    ///
+    /// let start = Time::UNIX_EPOCH;
+    /// let mut segment = TrackSegment::new();
+    /// for seconds in [0, 2, 1, 3] {
+    ///     let mut point = Waypoint::new(Point::new(seconds as f64, 0.0));
+    ///     point.time = Some(start + Duration::seconds(seconds));
+    ///     segment.points.push(point);
+    /// }
+    ///
+    /// assert_eq!(segment.out_of_order_times(), [2]);
+    /// segment.sort_by_time();
+    /// assert!(segment.out_of_order_times().is_empty());
+    /// ```
+    pub fn out_of_order_times(&self) -> Vec<usize> {
+        let mut latest: Option<Time> = None;
+        let mut indices = Vec::new();
+        for (index, point) in self.points.iter().enumerate() {
+            let Some(time) = point.time else {
+                continue;
+            };
+            match latest {
+                Some(latest) if time < latest => indices.push(index),
+                _ => latest = Some(time),
+            }
+        }
+        indices
+    }
+
+    /// Sorts the points by time. The sort is stable, and points without a
+    /// time stay right after the timestamped point they followed.
+    pub fn sort_by_time(&mut self) {
+        let mut latest = None;
+        let mut keyed: Vec<(Option<Time>, Waypoint<T>)> = self
+            .points
+            .drain(..)
+            .map(|point| {
+                latest = point.time.or(latest);
+                (latest, point)
+            })
+            .collect();
+        keyed.sort_by_key(|(key, _)| *key);
+        self.points = keyed.into_iter().map(|(_, point)| point).collect();
+    }
+
+    /// Finds the indices of the points that are physically impossible jumps.
+    ///
+    /// A timestamped point is a spike if reaching it from the last point that
+    /// is not a spike implies a speed above `max_speed` meters per second.
+    /// The first timestamped point is trusted as the initial reference, so a
+    /// spike at the very start goes unnoticed. Points without a time are
+    /// never flagged.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Duration, Time, TrackSegment, Waypoint};
+    ///
+    /// let start = Time::UNIX_EPOCH;
+    /// let mut segment = TrackSegment::new();
+    /// // The third point is about 11 km off, reached within a second.
+    /// for (latitude, seconds) in [(0.0, 0), (0.0001, 1), (0.1, 2), (0.0002, 3)] {
+    ///     let mut point = Waypoint::new(Point::new(0.0, latitude));
+    ///     point.time = Some(start + Duration::seconds(seconds));
+    ///     segment.points.push(point);
+    /// }
+    ///
+    /// assert_eq!(segment.find_spikes(50.0), [2]);
+    /// assert_eq!(segment.remove_spikes(50.0), 1);
+    /// assert_eq!(segment.points.len(), 3);
+    /// ```
+    pub fn find_spikes(&self, max_speed: f64) -> Vec<usize> {
+        let timed: Vec<(usize, &Waypoint<T>, Time)> = self
+            .points
+            .iter()
+            .enumerate()
+            .filter_map(|(index, point)| Some((index, point, point.time?)))
+            .collect();
+        let speed = |a: (&Waypoint<T>, Time), b: (&Waypoint<T>, Time)| {
+            // Moving without any time passing is infinitely fast.
+            let seconds = elapsed(a.1, b.1).as_seconds_f64();
+            let meters = distance(a.0, b.0);
+            if seconds > 0.0 {
+                meters / seconds
+            } else if meters > 0.0 {
+                f64::INFINITY
+            } else {
+                0.0
+            }
+        };
+
+        let mut spikes = Vec::new();
+        let Some(&(_, first, first_time)) = timed.first() else {
+            return spikes;
+        };
+        let mut reference = (first, first_time);
+        for &(index, point, time) in &timed[1..] {
+            if speed(reference, (point, time)) > max_speed {
+                spikes.push(index);
+            } else {
+                reference = (point, time);
+            }
+        }
+        spikes
+    }
+
+    /// Removes the points found by [`TrackSegment::find_spikes`] and returns
+    /// how many were removed.
+    pub fn remove_spikes(&mut self, max_speed: f64) -> usize {
+        let spikes = self.find_spikes(max_speed);
+        let mut index = 0;
+        self.points.retain(|_| {
+            index += 1;
+            spikes.binary_search(&(index - 1)).is_err()
+        });
+        spikes.len()
+    }
+}
+
+impl<T: CoordFloat> Route<T> {
+    /// Removes consecutive duplicate points from the route, see
+    /// [`TrackSegment::dedup_points`].
+    pub fn dedup_points(&mut self, tolerance: f64) {
+        self.points
+            .dedup_by(|point, kept| distance(kept, point) <= tolerance);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::{line_string, Point};
+
+    use super::CleanupOptions;
+    use crate::{Duration, Fix, Gpx, Route, Time, Track, TrackSegment, Waypoint};
+
+    #[test]
+    fn dedup_points_keeps_first_of_run() {
+        let mut segment = TrackSegment::new();
+        // Each step is about 1.1 m, so a run drifting slowly away from the
+        // first point is only cut once it gets further than the tolerance.
+        for (i, latitude) in [0.0, 0.0, 0.00001, 0.00002, 0.00003, 0.0]
+            .iter()
+            .enumerate()
+        {
+            let mut point = Waypoint::new(Point::new(0.0, *latitude));
+            point.details_mut().name = Some(i.to_string());
+            segment.points.push(point);
+        }
+        let mut track = Track::new();
+        track.segments.push(segment);
+
+        track.dedup_points(2.5);
+        let names: Vec<_> = track.segments[0]
+            .points
+            .iter()
+            .map(|point| point.details().name.as_deref().unwrap())
+            .collect();
+        assert_eq!(names, ["0", "4", "5"]);
+    }
+
+    #[test]
+    fn remove_spikes_from_track() {
+        let start = Time::UNIX_EPOCH;
+        let mut segment = TrackSegment::new();
+        for (latitude, seconds) in [
+            (0.0, Some(0)),
+            (0.0001, Some(1)),
+            (0.5, None),
+            (0.1, Some(2)),
+            (0.1, Some(3)),
+            (0.0003, Some(4)),
+            (0.0004, Some(5)),
+            (0.2, Some(6)),
+        ] {
+            let mut point = Waypoint::new(Point::new(0.0, latitude));
+            point.time = seconds.map(|seconds| start + Duration::seconds(seconds));
+            segment.points.push(point);
+        }
+        let mut track = Track::new();
+        track.segments.push(segment);
+
+        // A run of two spikes is caught, and an untimed point is left alone.
+        assert_eq!(track.segments[0].find_spikes(50.0), [3, 4, 7]);
+        assert_eq!(track.remove_spikes(50.0), 3);
+        assert_eq!(track.segments[0].points.len(), 5);
+        assert_eq!(track.remove_spikes(50.0), 0);
+    }
+
+    #[test]
+    fn sort_by_time_keeps_untimed_points_in_place() {
+        let start = Time::UNIX_EPOCH;
+        let mut segment = TrackSegment::new();
+        for (i, seconds) in [None, Some(5), None, Some(1), Some(5), Some(3)]
+            .into_iter()
+            .enumerate()
+        {
+            let mut point = Waypoint::new(Point::new(0.0, 0.0));
+            point.details_mut().name = Some(i.to_string());
+            point.time = seconds.map(|seconds| start + Duration::seconds(seconds));
+            segment.points.push(point);
+        }
+
+        assert_eq!(segment.out_of_order_times(), [3, 5]);
+
+        let mut track = Track::new();
+        track.segments.push(segment);
+        track.sort_by_time();
+        let names: Vec<_> = track.segments[0]
+            .points
+            .iter()
+            .map(|point| point.details().name.as_deref().unwrap())
+            .collect();
+        assert_eq!(names, ["0", "3", "5", "1", "2", "4"]);
+    }
+
+    #[test]
+    fn route_dedup_points() {
+        let mut route = Route::from(line_string![
+            (x: 1.0, y: 1.0),
+            (x: 1.0, y: 1.0),
+            (x: 2.0, y: 1.0),
+            (x: 1.0, y: 1.0),
+        ]);
+
+        route.dedup_points(0.0);
+        assert_eq!(route.points.len(), 3);
+    }
+
+    #[test]
+    fn cleanup_document() {
+        let at = |seconds| Some(Time::UNIX_EPOCH + Duration::seconds(seconds));
+        let mut segment = TrackSegment::new();
+        for (x, seconds, fix) in [
+            (0.0, 0, None),
+            (2.0, 2, None),
+            (1.0, 1, Some(Fix::ThreeDimensional)),
+            (1.0, 1, None),
+            (5.0, 3, Some(Fix::None)),
+        ] {
+            let mut point = Waypoint::new(Point::new(x, if x == 0.0 { 0.0 } else { 1.0 }));
+            point.time = at(seconds);
+            if fix.is_some() {
+                point.details_mut().fix = fix;
+            }
+            segment.points.push(point);
+        }
+        let mut track = Track::new();
+        track.segments.push(segment);
+        track.segments.push(TrackSegment::new());
+        let mut empty = Track::new();
+        empty.name = Some("Empty".to_string());
+        let mut gpx = Gpx {
+            waypoints: vec![Waypoint::new(Point::new(0.0, 0.0))],
+            tracks: vec![track, empty],
+            routes: vec![Route::from(
+                line_string![(x: 3.0, y: 3.0), (x: 3.0, y: 3.0)],
+            )],
+            ..Default::default()
+        };
+
+        let mut untouched = gpx.clone();
+        untouched.cleanup(CleanupOptions {
+            remove_zero_points: false,
+            remove_unfixed_points: false,
+            dedup_tolerance: None,
+            sort_by_time: false,
+            remove_empty: false,
+            update_bounds: false,
+        });
+        assert_eq!(untouched, gpx);
+
+        gpx.cleanup(CleanupOptions::default());
+        assert!(gpx.waypoints.is_empty());
+        assert_eq!(gpx.routes[0].points.len(), 1);
+        assert_eq!(gpx.tracks.len(), 1);
+        assert_eq!(gpx.tracks[0].segments.len(), 1);
+        let xs: Vec<f64> = gpx.tracks[0].segments[0]
+            .points
+            .iter()
+            .map(|point| point.point().x())
+            .collect();
+        assert_eq!(xs, [1.0, 2.0]);
+        assert_eq!(
+            gpx.tracks[0].segments[0].points[0].details().fix,
+            Some(Fix::ThreeDimensional)
+        );
+        let bounds = gpx.metadata.unwrap().bounds.unwrap();
+        assert_eq!((bounds.min().x, bounds.max().x), (1.0, 3.0));
+    }
+
+    #[test]
+    fn null_island_and_elevation_sentinels() {
+        let point = |x: f64, y: f64, elevation: f64| {
+            let mut point = Waypoint::new(Point::new(x, y));
+            point.elevation = Some(elevation);
+            point
+        };
+        let mut track = Track::new();
+        track.segments.push(TrackSegment {
+            points: vec![
+                point(0.0, 0.0, 0.0),
+                point(0.0, 0.0, 0.0),
+                point(1.0, 0.0, 0.0),
+            ],
+        });
+        let mut route = Route::new();
+        route.points.push(point(0.0, 0.0, 5.0));
+        route.points.push(point(0.0, 1.0, -9999.0));
+        let mut gpx = Gpx {
+            waypoints: vec![point(2.0, 2.0, 12.0)],
+            tracks: vec![track],
+            routes: vec![route],
+            ..Default::default()
+        };
+
+        assert_eq!(gpx.remove_null_island(), 3);
+        assert_eq!(gpx.tracks[0].segments[0].points.len(), 1);
+        assert_eq!(gpx.routes[0].points.len(), 1);
+        assert_eq!(gpx.remove_null_island(), 0);
+
+        assert_eq!(gpx.clear_elevation_sentinels(&[-9999.0]), 1);
+        assert_eq!(gpx.routes[0].points[0].elevation, None);
+        assert_eq!(gpx.clear_elevation_sentinels(&[0.0, -9999.0]), 1);
+        assert_eq!(gpx.tracks[0].segments[0].points[0].elevation, None);
+        assert_eq!(gpx.waypoints[0].elevation, Some(12.0));
+    }
+}
@@ -0,0 +1,339 @@
+//! csv converts trackpoints to and from flat CSV tables, one row per point.
+
+use std::fmt::Display;
+use std::io::{Read, Write};
+
+use geo_types::{CoordFloat, Point};
+
+use crate::errors::{GpxError, GpxResult};
+use crate::measure::{distance, elapsed, from_f64};
+use crate::parser::time;
+use crate::{Gpx, GpxVersion, Track, TrackPointExtension, TrackSegment, Waypoint};
+
+/// A column of a CSV table of trackpoints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CsvColumn {
+    /// Latitude in degrees, `lat`.
+    Latitude,
+    /// Longitude in degrees, `lon`.
+    Longitude,
+    /// Elevation in meters, `ele`.
+    Elevation,
+    /// Time in ISO 8601 format, `time`.
+    Time,
+    /// Speed in meters per second, `speed`.
+    Speed,
+    /// Heart rate in beats per minute, `hr`.
+    HeartRate,
+    /// Cadence in revolutions or steps per minute, `cad`.
+    Cadence,
+    /// Name of the point, `name`.
+    Name,
+    /// Name of the track the point belongs to, `track`.
+    Track,
+    /// Index of the segment within its track, `segment`.
+    Segment,
+}
+
+impl CsvColumn {
+    /// The columns written by default: position, elevation and time.
+    pub const DEFAULT: &'static [CsvColumn] = &[
+        CsvColumn::Latitude,
+        CsvColumn::Longitude,
+        CsvColumn::Elevation,
+        CsvColumn::Time,
+    ];
+
+    /// Gives the name of the column in the header row.
+    pub fn header(self) -> &'static str {
+        match self {
+            CsvColumn::Latitude => "lat",
+            CsvColumn::Longitude => "lon",
+            CsvColumn::Elevation => "ele",
+            CsvColumn::Time => "time",
+            CsvColumn::Speed => "speed",
+            CsvColumn::HeartRate => "hr",
+            CsvColumn::Cadence => "cad",
+            CsvColumn::Name => "name",
+            CsvColumn::Track => "track",
+            CsvColumn::Segment => "segment",
+        }
+    }
+
+    /// Recognizes a header, ignoring case and accepting common aliases like
+    /// `latitude`, `lng` or `heart_rate`.
+    pub fn from_header(header: &str) -> Option<CsvColumn> {
+        let column = match header.trim().to_ascii_lowercase().as_str() {
+            "lat" | "latitude" => CsvColumn::Latitude,
+            "lon" | "lng" | "long" | "longitude" => CsvColumn::Longitude,
+            "ele" | "elevation" | "alt" | "altitude" => CsvColumn::Elevation,
+            "time" | "timestamp" => CsvColumn::Time,
+            "speed" => CsvColumn::Speed,
+            "hr" | "heart_rate" | "heartrate" => CsvColumn::HeartRate,
+            "cad" | "cadence" => CsvColumn::Cadence,
+            "name" => CsvColumn::Name,
+            "track" => CsvColumn::Track,
+            "segment" => CsvColumn::Segment,
+            _ => return None,
+        };
+        Some(column)
+    }
+}
+
+/// Writes the trackpoints of all tracks as a CSV table with the given
+/// columns, preceded by a header row. Missing values are left empty.
+///
+/// Points without a recorded speed get the average speed since the previous
+/// point of their segment.
+///
+/// ```
+/// use geo_types::{line_string, MultiLineString};
+/// use gpx::{to_csv, CsvColumn, Gpx, Track};
+///
+/// let mut gpx: Gpx = Default::default();
+/// gpx.tracks.push(Track::from(MultiLineString::new(vec![
+///     line_string![(x: -121.97, y: 37.24), (x: -121.96, y: 37.25)],
+/// ])));
+///
+/// let mut csv = Vec::new();
+/// to_csv(&gpx, &[CsvColumn::Latitude, CsvColumn::Longitude], &mut csv).unwrap();
+/// assert_eq!(String::from_utf8(csv).unwrap(), "lat,lon\n37.24,-121.97\n37.25,-121.96\n");
+/// ```
+pub fn to_csv<W: Write, T: CoordFloat + Display>(
+    gpx: &Gpx<T>,
+    columns: &[CsvColumn],
+    writer: W,
+) -> GpxResult<()> {
+    let mut writer = ::csv::Writer::from_writer(writer);
+    writer.write_record(columns.iter().map(|column| column.header()))?;
+    for track in &gpx.tracks {
+        for (index, segment) in track.segments.iter().enumerate() {
+            let mut previous: Option<&Waypoint<T>> = None;
+            for point in &segment.points {
+                let extension = point.track_point_extension.as_ref();
+                let mut record = Vec::with_capacity(columns.len());
+                for column in columns {
+                    let value = match column {
+                        CsvColumn::Latitude => point.point().y().to_string(),
+                        CsvColumn::Longitude => point.point().x().to_string(),
+                        CsvColumn::Elevation => to_string(point.elevation),
+                        CsvColumn::Time => match point.time {
+                            Some(time) => time.format()?,
+                            None => String::new(),
+                        },
+                        CsvColumn::Speed => to_string(
+                            point
+                                .speed
+                                .or_else(|| speed(previous?, point).map(from_f64)),
+                        ),
+                        CsvColumn::HeartRate => to_string(extension.and_then(|e| e.heart_rate)),
+                        CsvColumn::Cadence => to_string(extension.and_then(|e| e.cadence)),
+                        CsvColumn::Name => point.details().name.clone().unwrap_or_default(),
+                        CsvColumn::Track => track.name.clone().unwrap_or_default(),
+                        CsvColumn::Segment => index.to_string(),
+                    };
+                    record.push(value);
+                }
+                writer.write_record(&record)?;
+                previous = Some(point);
+            }
+        }
+    }
+    writer.flush().map_err(::csv::Error::from)?;
+    Ok(())
+}
+
+fn to_string<V: ToString>(value: Option<V>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Gives the average speed between two points, in meters per second.
+fn speed<T: CoordFloat>(previous: &Waypoint<T>, point: &Waypoint<T>) -> Option<f64> {
+    let seconds = elapsed(previous.time?, point.time?).as_seconds_f64();
+    (seconds > 0.0).then(|| distance(previous, point) / seconds)
+}
+
+/// Reads trackpoints from a CSV table with a header row.
+///
+/// The columns are recognized by their headers, see
+/// [`CsvColumn::from_header`], and unknown columns are ignored. Latitude and
+/// longitude are required. Rows go to a single track, unless there is a
+/// `track` column, in which case a new track starts whenever its value
+/// changes; the same holds for segments and the `segment` column.
+///
+/// ```
+/// use gpx::from_csv;
+///
+/// let csv = "time,latitude,longitude,hr\n\
+///     2023-06-01T12:00:00Z,47.3,8.5,120\n\
+///     2023-06-01T12:00:05Z,47.31,8.51,\n";
+///
+/// let gpx = from_csv(csv.as_bytes()).unwrap();
+/// let points = &gpx.tracks[0].segments[0].points;
+/// assert_eq!(points.len(), 2);
+/// assert_eq!(points[0].track_point_extension.as_ref().unwrap().heart_rate, Some(120));
+/// assert!(points[1].track_point_extension.is_none());
+/// ```
+pub fn from_csv<R: Read>(reader: R) -> GpxResult<Gpx> {
+    let mut reader = ::csv::Reader::from_reader(reader);
+    let columns: Vec<Option<CsvColumn>> = reader
+        .headers()?
+        .iter()
+        .map(CsvColumn::from_header)
+        .collect();
+    let position = |required: CsvColumn| {
+        columns
+            .iter()
+            .position(|column| *column == Some(required))
+            .ok_or(GpxError::MissingColumn(required.header()))
+    };
+    let latitude_index = position(CsvColumn::Latitude)?;
+    let longitude_index = position(CsvColumn::Longitude)?;
+
+    let mut gpx = Gpx {
+        version: GpxVersion::Gpx11,
+        ..Default::default()
+    };
+    let mut track_key: Option<String> = None;
+    let mut segment_key: Option<String> = None;
+    for record in reader.records() {
+        let record = record?;
+        let value = |index| record.get(index).unwrap_or_default().trim();
+        let latitude: f64 = value(latitude_index).parse()?;
+        let longitude: f64 = value(longitude_index).parse()?;
+        if !(-90.0..=90.0).contains(&latitude) {
+            return Err(GpxError::LonLatOutOfBoundsError(
+                "latitude",
+                "[-90.0, 90.0]",
+                latitude,
+            ));
+        }
+        if !(-180.0..=180.0).contains(&longitude) {
+            return Err(GpxError::LonLatOutOfBoundsError(
+                "Longitude",
+                "[-180.0, 180.0]",
+                longitude,
+            ));
+        }
+
+        let mut point = Waypoint::new(Point::new(longitude, latitude));
+        let mut extension = TrackPointExtension::default();
+        let mut track = None;
+        let mut segment = None;
+        for (index, column) in columns.iter().enumerate() {
+            let value = value(index);
+            let Some(column) = column else {
+                continue;
+            };
+            if value.is_empty() {
+                continue;
+            }
+            match column {
+                CsvColumn::Latitude | CsvColumn::Longitude => {}
+                CsvColumn::Elevation => point.elevation = Some(value.parse()?),
+                CsvColumn::Time => point.time = Some(time::parse(value)?),
+                CsvColumn::Speed => point.speed = Some(value.parse()?),
+                CsvColumn::HeartRate => extension.heart_rate = Some(value.parse()?),
+                CsvColumn::Cadence => extension.cadence = Some(value.parse()?),
+                CsvColumn::Name => point.details_mut().name = Some(value.to_string()),
+                CsvColumn::Track => track = Some(value.to_string()),
+                CsvColumn::Segment => segment = Some(value.to_string()),
+            }
+        }
+        if extension != TrackPointExtension::default() {
+            point.track_point_extension = Some(extension);
+        }
+
+        if gpx.tracks.is_empty() || track != track_key {
+            let mut new_track = Track::new();
+            new_track.name = track.clone();
+            gpx.tracks.push(new_track);
+            track_key = track;
+            segment_key = None;
+        }
+        let current = gpx.tracks.last_mut().unwrap();
+        if current.segments.is_empty() || segment != segment_key {
+            current.segments.push(TrackSegment::new());
+            segment_key = segment;
+        }
+        current.segments.last_mut().unwrap().points.push(point);
+    }
+    Ok(gpx)
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::Point;
+
+    use super::{from_csv, to_csv, CsvColumn};
+    use crate::errors::GpxError;
+    use crate::{Duration, Gpx, Time, Track, TrackPointExtension, TrackSegment, Waypoint};
+
+    #[test]
+    fn csv_round_trip() {
+        let mut segment = TrackSegment::new();
+        for i in 0..3 {
+            let mut point = Waypoint::new(Point::new(8.5, 47.3 + f64::from(i) * 0.001));
+            point.elevation = Some(400.0 + f64::from(i));
+            point.time = Some(Time::UNIX_EPOCH + Duration::seconds(i.into()));
+            point.track_point_extension = Some(TrackPointExtension {
+                heart_rate: Some(120 + i as u16),
+                ..Default::default()
+            });
+            segment.points.push(point);
+        }
+        let mut track = Track::new();
+        track.name = Some("Morning, run".into());
+        track.segments = vec![segment.clone(), segment];
+        let gpx = Gpx {
+            tracks: vec![track.clone(), Track::new(), track],
+            ..Default::default()
+        };
+
+        let columns = [
+            CsvColumn::Track,
+            CsvColumn::Segment,
+            CsvColumn::Latitude,
+            CsvColumn::Longitude,
+            CsvColumn::Elevation,
+            CsvColumn::Time,
+            CsvColumn::HeartRate,
+            CsvColumn::Speed,
+        ];
+        let mut csv = Vec::new();
+        to_csv(&gpx, &columns, &mut csv).unwrap();
+        let text = String::from_utf8(csv.clone()).unwrap();
+        assert!(text.starts_with("track,segment,lat,lon,ele,time,hr,speed\n"));
+        assert!(text.contains("\"Morning, run\",1,47.302,8.5,402,1970-01-01T00:00:02"));
+        assert!(text.lines().nth(1).unwrap().ends_with(",120,"));
+        assert!(text.lines().nth(2).unwrap().contains(",121,111.176"));
+
+        // Consecutive tracks of the same name can't be told apart.
+        let read = from_csv(csv.as_slice()).unwrap();
+        assert_eq!(read.tracks.len(), 1);
+        assert_eq!(read.tracks[0].segments.len(), 4);
+        let first = &gpx.tracks[0].segments[0].points[1];
+        let point = &read.tracks[0].segments[0].points[1];
+        assert_eq!(point.point(), first.point());
+        assert_eq!(point.elevation, first.elevation);
+        assert_eq!(point.time, first.time);
+        assert_eq!(point.track_point_extension, first.track_point_extension);
+    }
+
+    #[test]
+    fn csv_requires_position() {
+        assert!(matches!(
+            from_csv("lat,ele\n1,2\n".as_bytes()),
+            Err(GpxError::MissingColumn("lon"))
+        ));
+        assert!(from_csv("lat,lon\n91,2\n".as_bytes()).is_err());
+        assert!(from_csv("lat,lon\n1,x\n".as_bytes()).is_err());
+
+        let gpx = from_csv("Latitude,Lng,notes\n1,2,hello\n".as_bytes()).unwrap();
+        assert_eq!(
+            gpx.tracks[0].segments[0].points[0].point(),
+            Point::new(2.0, 1.0)
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! dense stores huge track segments compactly, as one column per value
+//! instead of one waypoint per point.
+
+use geo_types::{CoordFloat, LineString, Point};
+
+use crate::{Time, TrackSegment, Waypoint};
+
+/// A track segment stored as columns of latitudes, longitudes, elevations
+/// and times.
+///
+/// Each point takes a few dozen bytes instead of the hundreds of a
+/// [`Waypoint`], and computations over one value, like the elevation
+/// profile, read contiguous memory. Names, links, fix details and
+/// extensions are not kept, so converting back to a [`TrackSegment`] gives
+/// waypoints with just position, elevation and time.
+///
+/// The columns always have the same length, which is why they can only be
+/// changed through [`DenseTrackSegment::push`].
+///
+/// ```
+/// use geo_types::Point;
+/// use gpx::{DenseTrackSegment, TrackSegment, Waypoint};
+///
+/// let mut segment = TrackSegment::new();
+/// segment.points.push(Waypoint::new(Point::new(-121.97, 37.24)));
+/// segment.points.push(Waypoint::new(Point::new(-121.96, 37.25)));
+///
+/// let dense = DenseTrackSegment::from(&segment);
+/// assert_eq!(dense.latitudes(), &[37.24, 37.25]);
+/// assert_eq!(TrackSegment::from(dense), segment);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct DenseTrackSegment<T: CoordFloat = f64> {
+    latitudes: Vec<T>,
+    longitudes: Vec<T>,
+    elevations: Vec<Option<T>>,
+    times: Vec<Option<Time>>,
+}
+
+impl<T: CoordFloat> Default for DenseTrackSegment<T> {
+    fn default() -> DenseTrackSegment<T> {
+        DenseTrackSegment {
+            latitudes: Vec::new(),
+            longitudes: Vec::new(),
+            elevations: Vec::new(),
+            times: Vec::new(),
+        }
+    }
+}
+
+impl<T: CoordFloat> DenseTrackSegment<T> {
+    /// Creates an empty segment.
+    pub fn new() -> DenseTrackSegment<T> {
+        Default::default()
+    }
+
+    /// Creates an empty segment with room for `capacity` points.
+    pub fn with_capacity(capacity: usize) -> DenseTrackSegment<T> {
+        DenseTrackSegment {
+            latitudes: Vec::with_capacity(capacity),
+            longitudes: Vec::with_capacity(capacity),
+            elevations: Vec::with_capacity(capacity),
+            times: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Appends a point.
+    pub fn push(&mut self, point: Point<T>, elevation: Option<T>, time: Option<Time>) {
+        self.latitudes.push(point.y());
+        self.longitudes.push(point.x());
+        self.elevations.push(elevation);
+        self.times.push(time);
+    }
+
+    /// Gives the number of points.
+    pub fn len(&self) -> usize {
+        self.latitudes.len()
+    }
+
+    /// Tells whether the segment has no points.
+    pub fn is_empty(&self) -> bool {
+        self.latitudes.is_empty()
+    }
+
+    /// Gives the latitudes of the points, in degrees.
+    pub fn latitudes(&self) -> &[T] {
+        &self.latitudes
+    }
+
+    /// Gives the longitudes of the points, in degrees.
+    pub fn longitudes(&self) -> &[T] {
+        &self.longitudes
+    }
+
+    /// Gives the elevations of the points, in meters.
+    pub fn elevations(&self) -> &[Option<T>] {
+        &self.elevations
+    }
+
+    /// Gives the times of the points.
+    pub fn times(&self) -> &[Option<Time>] {
+        &self.times
+    }
+
+    /// Gives the position of the point at `index`, if there is one.
+    pub fn point(&self, index: usize) -> Option<Point<T>> {
+        Some(Point::new(
+            *self.longitudes.get(index)?,
+            *self.latitudes.get(index)?,
+        ))
+    }
+
+    /// Iterates over the positions of the points.
+    pub fn points(&self) -> impl Iterator<Item = Point<T>> + '_ {
+        self.longitudes
+            .iter()
+            .zip(&self.latitudes)
+            .map(|(&x, &y)| Point::new(x, y))
+    }
+
+    /// Gives the linestring of the segment's points.
+    pub fn linestring(&self) -> LineString<T> {
+        self.points().collect()
+    }
+}
+
+impl<T: CoordFloat> From<&TrackSegment<T>> for DenseTrackSegment<T> {
+    fn from(segment: &TrackSegment<T>) -> DenseTrackSegment<T> {
+        let mut dense = DenseTrackSegment::with_capacity(segment.points.len());
+        for point in &segment.points {
+            dense.push(point.point(), point.elevation, point.time);
+        }
+        dense
+    }
+}
+
+impl<T: CoordFloat> From<TrackSegment<T>> for DenseTrackSegment<T> {
+    fn from(segment: TrackSegment<T>) -> DenseTrackSegment<T> {
+        DenseTrackSegment::from(&segment)
+    }
+}
+
+impl<T: CoordFloat> From<DenseTrackSegment<T>> for TrackSegment<T> {
+    fn from(dense: DenseTrackSegment<T>) -> TrackSegment<T> {
+        let points = dense
+            .longitudes
+            .into_iter()
+            .zip(dense.latitudes)
+            .map(|(x, y)| Point::new(x, y))
+            .zip(dense.elevations)
+            .zip(dense.times)
+            .map(|((point, elevation), time)| {
+                let mut waypoint = Waypoint::new(point);
+                waypoint.elevation = elevation;
+                waypoint.time = time;
+                waypoint
+            })
+            .collect();
+        TrackSegment { points }
+    }
+}
+
+impl<T: CoordFloat> FromIterator<Waypoint<T>> for DenseTrackSegment<T> {
+    fn from_iter<I: IntoIterator<Item = Waypoint<T>>>(iter: I) -> DenseTrackSegment<T> {
+        let mut dense = DenseTrackSegment::new();
+        for point in iter {
+            dense.push(point.point(), point.elevation, point.time);
+        }
+        dense
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::Point;
+
+    use super::DenseTrackSegment;
+    use crate::{Time, TrackSegment, Waypoint};
+
+    #[test]
+    fn dense_segment_conversion() {
+        let mut segment: TrackSegment<f32> = TrackSegment::new();
+        let mut first = Waypoint::new(Point::new(1.0, 2.0));
+        first.elevation = Some(3.0);
+        first.time = Some(Time::UNIX_EPOCH);
+        segment.points.push(first);
+        let mut named = Waypoint::new(Point::new(4.0, 5.0));
+        named.details_mut().name = Some("dropped".into());
+        segment.points.push(named);
+
+        let dense = DenseTrackSegment::from(&segment);
+        assert_eq!(dense.len(), 2);
+        assert_eq!(dense.longitudes(), &[1.0, 4.0]);
+        assert_eq!(dense.elevations(), &[Some(3.0), None]);
+        assert_eq!(dense.times()[0], segment.points[0].time);
+        assert_eq!(dense.point(1), Some(Point::new(4.0, 5.0)));
+        assert_eq!(dense.point(2), None);
+        assert_eq!(dense.linestring(), segment.linestring());
+
+        let collected: DenseTrackSegment<f32> = segment.points.iter().cloned().collect();
+        assert_eq!(collected, dense);
+
+        let back = TrackSegment::from(dense);
+        assert_eq!(back.points[0], segment.points[0]);
+        assert_eq!(back.points[1].point(), segment.points[1].point());
+        assert_eq!(back.points[1].details().name, None);
+        assert!(DenseTrackSegment::<f64>::new().is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+//! elevation fills in missing elevations from an outside source, like a
+//! digital elevation model, which the crate doesn't carry itself.
+
+use geo_types::CoordFloat;
+
+use crate::measure::{from_f64, position};
+use crate::{Gpx, Waypoint};
+
+/// A source of terrain elevations, like SRTM tiles or a web API, see
+/// [`Gpx::fill_elevations`].
+///
+/// Closures taking a latitude and a longitude are providers too.
+pub trait ElevationProvider {
+    /// Gives the elevation in meters at `latitude` and `longitude`, if
+    /// known.
+    fn elevation(&self, latitude: f64, longitude: f64) -> Option<f64>;
+}
+
+impl<F: Fn(f64, f64) -> Option<f64>> ElevationProvider for F {
+    fn elevation(&self, latitude: f64, longitude: f64) -> Option<f64> {
+        self(latitude, longitude)
+    }
+}
+
+impl<T: CoordFloat> Gpx<T> {
+    /// Fills in the elevations of the waypoints, route points and track
+    /// points that have none from `provider`. Existing elevations are kept.
+    ///
+    /// Returns the number of filled elevations.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Gpx, Waypoint};
+    ///
+    /// let mut gpx: Gpx = Default::default();
+    /// gpx.waypoints.push(Waypoint::new(Point::new(-121.97, 37.24)));
+    /// gpx.waypoints.push(Waypoint::new(Point::new(0.0, 0.0)));
+    ///
+    /// // A provider that only knows the elevations north of the equator.
+    /// let dem = |latitude: f64, _longitude: f64| (latitude > 0.0).then_some(61.5);
+    /// assert_eq!(gpx.fill_elevations(&dem), 1);
+    /// assert_eq!(gpx.waypoints[0].elevation, Some(61.5));
+    /// assert_eq!(gpx.waypoints[1].elevation, None);
+    /// ```
+    pub fn fill_elevations(&mut self, provider: &dyn ElevationProvider) -> usize {
+        let route_points = self.routes.iter_mut().flat_map(|route| &mut route.points);
+        let track_points = self
+            .tracks
+            .iter_mut()
+            .flat_map(|track| &mut track.segments)
+            .flat_map(|segment| &mut segment.points);
+        self.waypoints
+            .iter_mut()
+            .chain(route_points)
+            .chain(track_points)
+            .map(|point| fill_elevation(point, provider))
+            .filter(|&filled| filled)
+            .count()
+    }
+}
+
+/// Fills in the elevation of `point` if it has none, telling whether it
+/// was filled.
+fn fill_elevation<T: CoordFloat>(
+    point: &mut Waypoint<T>,
+    provider: &dyn ElevationProvider,
+) -> bool {
+    if point.elevation.is_some() {
+        return false;
+    }
+    let position = position(point);
+    point.elevation = provider.elevation(position.y(), position.x()).map(from_f64);
+    point.elevation.is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::Point;
+
+    use crate::{Gpx, Route, Track, TrackSegment, Waypoint};
+
+    #[test]
+    fn fill_elevations() {
+        let mut known = Waypoint::new(Point::new(1.0, 2.0));
+        known.elevation = Some(100.0);
+        let mut track = Track::new();
+        track.segments.push(TrackSegment {
+            points: vec![known, Waypoint::new(Point::new(3.0, 4.0))],
+        });
+        let mut route = Route::new();
+        route.points.push(Waypoint::new(Point::new(5.0, 6.0)));
+        let mut gpx = Gpx {
+            tracks: vec![track],
+            routes: vec![route],
+            ..Default::default()
+        };
+
+        let filled =
+            gpx.fill_elevations(&|latitude: f64, longitude: f64| Some(latitude * longitude));
+        assert_eq!(filled, 2);
+        let points = &gpx.tracks[0].segments[0].points;
+        assert_eq!(points[0].elevation, Some(100.0));
+        assert_eq!(points[1].elevation, Some(12.0));
+        assert_eq!(gpx.routes[0].points[0].elevation, Some(30.0));
+    }
+}
@@ -5,6 +5,27 @@ use thiserror::Error;
 
 pub(crate) type GpxResult<T> = Result<T, GpxError>;
 
+/// Problems in a document that were worked around while reading it, see
+/// [`Parser::warnings`](crate::Parser::warnings).
+#[derive(Error, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum GpxWarning {
+    #[error("version `{0}` disagrees with the namespace of {1}, reading as {1}")]
+    VersionMismatch(String, crate::types::GpxVersion),
+    #[error("missing version, reading as {0} like its namespace")]
+    VersionFromNamespace(crate::types::GpxVersion),
+    #[error("`{0}` value `{1}` is out of range")]
+    ValueOutOfRange(&'static str, i64),
+    #[error("leap second `{0}` read as the last nanosecond of its minute")]
+    LeapSecond(String),
+    #[error("element `{0}` repeated in `{1}`, keeping the last")]
+    DuplicateElement(&'static str, &'static str),
+    #[error("document cut off at line {0}, column {1}, keeping what came before")]
+    Truncated(u64, u64),
+    #[error("invalid `{0}` value `{1}` in an extension, dropping it")]
+    InvalidExtensionValue(&'static str, String),
+}
+
 #[derive(Error, Debug)]
 #[non_exhaustive]
 /// Errors that can occur when reading or writing GPX files
@@ -51,4 +72,126 @@ pub enum GpxError {
     Iso8601Error(#[from] time::error::Parse),
     #[error("error trying to write ISO8601 formatted date")]
     Iso8601ErrorWriting(#[from] time::error::Format),
+    #[error("value `{0}` cannot be represented by the coordinate type")]
+    CoordinateCastError(f64),
+    #[error("invalid geometry: {0}")]
+    MismatchedGeometry(#[from] geo_types::Error),
+    #[cfg(feature = "csv")]
+    #[error("error while reading or writing CSV")]
+    CsvError(#[from] csv::Error),
+    #[cfg(feature = "json")]
+    #[error("error while reading or writing JSON")]
+    JsonError(#[from] serde_json::Error),
+    #[cfg(feature = "zip")]
+    #[error("error while reading or writing zip archive")]
+    ZipError(#[from] zip::result::ZipError),
+
+    #[cfg(feature = "kml")]
+    #[error("error while reading or writing KML")]
+    KmlError(#[from] kml::Error),
+    #[cfg(feature = "proj")]
+    #[error("error while reprojecting coordinates")]
+    ProjError(#[from] proj4rs::errors::Error),
+    #[error("archive contains no `{0}` file")]
+    MissingArchiveEntry(&'static str),
+    #[error("error while reading")]
+    IoError(#[from] std::io::Error),
+    #[error("missing `{0}` column")]
+    MissingColumn(&'static str),
+    #[error("`{0}` value `{1}` is out of range")]
+    ValueOutOfRange(&'static str, i64),
+    #[error("element `{0}` out of order in `{1}`")]
+    ElementOutOfOrder(String, &'static str),
+    #[error("invalid or unsafe URL `{0}`")]
+    InvalidUrl(String),
+    #[error("empty `{0}` element")]
+    EmptyElement(&'static str),
+}
+
+/// Broad category of a [`GpxError`], e.g. to report errors to other languages
+/// or over the network without exposing the Rust types.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum GpxErrorKind {
+    /// The input is not well-formed XML.
+    Xml,
+    /// Elements are missing, misplaced or unexpected.
+    Structure,
+    /// A value could not be parsed or is out of range.
+    Value,
+    /// The GPX version is missing or not supported.
+    Version,
+    /// Reading or writing failed.
+    Io,
+    /// Reading or writing another format, like CSV or JSON, failed.
+    Format,
+}
+
+impl GpxErrorKind {
+    /// Gives a stable, lowercase code for the kind, like `"structure"`.
+    pub fn code(self) -> &'static str {
+        match self {
+            GpxErrorKind::Xml => "xml",
+            GpxErrorKind::Structure => "structure",
+            GpxErrorKind::Value => "value",
+            GpxErrorKind::Version => "version",
+            GpxErrorKind::Io => "io",
+            GpxErrorKind::Format => "format",
+        }
+    }
+}
+
+impl GpxError {
+    /// Gives the category of the error.
+    ///
+    /// ```
+    /// use gpx::errors::GpxErrorKind;
+    ///
+    /// let error = gpx::read("<gpx version=\"1.1\"><wpt/></gpx>".as_bytes()).unwrap_err();
+    /// assert_eq!(error.kind(), GpxErrorKind::Structure);
+    /// assert_eq!(error.kind().code(), "structure");
+    /// ```
+    pub fn kind(&self) -> GpxErrorKind {
+        match self {
+            GpxError::XmlParseError(_) => GpxErrorKind::Xml,
+            GpxError::InvalidChildElement(..)
+            | GpxError::InvalidClosingTag(..)
+            | GpxError::MissingClosingTag(_)
+            | GpxError::MissingOpeningTag(_)
+            | GpxError::InvalidElementLacksAttribute(..)
+            | GpxError::TagOpenedTwice(_)
+            | GpxError::ElementOutOfOrder(..)
+            | GpxError::EmptyElement(_)
+            | GpxError::TrackSegmentError()
+            | GpxError::EventParsingError(_)
+            | GpxError::MetadataParsingError()
+            | GpxError::MissingColumn(_)
+            | GpxError::MissingArchiveEntry(_) => GpxErrorKind::Structure,
+            GpxError::ParseFloatError(_)
+            | GpxError::ParseIntegerError(_)
+            | GpxError::OutOfBounds(_)
+            | GpxError::NoStringContent
+            | GpxError::MissingEmailPartError(_)
+            | GpxError::TooManyAtsError
+            | GpxError::LonLatOutOfBoundsError(..)
+            | GpxError::Iso8601Error(_)
+            | GpxError::Iso8601ErrorWriting(_)
+            | GpxError::CoordinateCastError(_)
+            | GpxError::ValueOutOfRange(..)
+            | GpxError::InvalidUrl(_)
+            | GpxError::MismatchedGeometry(_) => GpxErrorKind::Value,
+            GpxError::UnknownVersionError(_) => GpxErrorKind::Version,
+            GpxError::XmlWriteError(_) | GpxError::IoError(_) => GpxErrorKind::Io,
+            #[cfg(feature = "csv")]
+            GpxError::CsvError(_) => GpxErrorKind::Format,
+            #[cfg(feature = "json")]
+            GpxError::JsonError(_) => GpxErrorKind::Format,
+            #[cfg(feature = "zip")]
+            GpxError::ZipError(_) => GpxErrorKind::Format,
+            #[cfg(feature = "kml")]
+            GpxError::KmlError(_) => GpxErrorKind::Format,
+            #[cfg(feature = "proj")]
+            GpxError::ProjError(_) => GpxErrorKind::Value,
+        }
+    }
 }
@@ -0,0 +1,367 @@
+//! events reads documents as a stream of typed events, for callers building
+//! their own data structures instead of [`Gpx`](crate::Gpx).
+
+use std::io::Read;
+
+use geo_types::CoordFloat;
+use xml::reader::XmlEvent;
+
+use crate::errors::{GpxError, GpxResult, GpxWarning};
+use crate::parser::gpx::{detect_version, root_attributes, Gpx10Metadata};
+use crate::parser::{
+    create_context, extensions, metadata, route, track, verify_starting_element,
+    verify_starting_tag, waypoint, Context,
+};
+use crate::{GpxVersion, Metadata, Route, Track, Waypoint};
+
+/// An event of a [`GpxEventReader`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum GpxEvent<T: CoordFloat = f64> {
+    /// The start of the document.
+    StartDocument {
+        /// The version of the document.
+        version: GpxVersion,
+        /// The creator of the document, if given.
+        creator: Option<String>,
+        /// The other attributes of the root element, see
+        /// [`Gpx::root_attributes`](crate::Gpx::root_attributes).
+        root_attributes: Vec<(String, String)>,
+    },
+    /// The metadata of the document.
+    Metadata(Metadata<T>),
+    /// A waypoint of the document.
+    Waypoint(Waypoint<T>),
+    /// A route of the document, with all of its points.
+    Route(Route<T>),
+    /// The start of a track, with its name and other properties but without
+    /// segments.
+    StartTrack(Track<T>),
+    /// The start of a segment of the current track.
+    StartSegment,
+    /// A point of the current segment.
+    TrackPoint(Waypoint<T>),
+    /// The end of the current segment.
+    EndSegment,
+    /// The end of the current track.
+    EndTrack,
+    /// The end of the document, after which there are no more events.
+    EndDocument,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    Start,
+    Document,
+    Track,
+    Segment,
+    Done,
+}
+
+/// Reads a document as a stream of [`GpxEvent`]s, holding only one point in
+/// memory at a time.
+///
+/// Tracks are given point by point, between [`GpxEvent::StartTrack`] and
+/// [`GpxEvent::EndTrack`], while waypoints, routes and the metadata are
+/// given whole. The reader stops after the first error.
+///
+/// ```
+/// use gpx::{GpxEvent, GpxEventReader};
+///
+/// let data = "<gpx version=\"1.1\"><trk><name>Loop</name><trkseg>\
+///     <trkpt lat=\"37.24\" lon=\"-121.97\"/><trkpt lat=\"37.25\" lon=\"-121.96\"/>\
+///     </trkseg></trk></gpx>";
+///
+/// let mut points = 0;
+/// for event in GpxEventReader::new(data.as_bytes()) {
+///     match event.unwrap() {
+///         GpxEvent::StartTrack(track) => assert_eq!(track.name.as_deref(), Some("Loop")),
+///         GpxEvent::TrackPoint(_) => points += 1,
+///         _ => {}
+///     }
+/// }
+/// assert_eq!(points, 2);
+/// ```
+pub struct GpxEventReader<R: Read, T: CoordFloat = f64> {
+    context: Context<R>,
+    state: State,
+    gpx10_metadata: Option<Gpx10Metadata<T>>,
+    track: Option<Track<T>>,
+}
+
+impl<R: Read> GpxEventReader<R> {
+    /// Creates a reader of the document in `reader`.
+    pub fn new(reader: R) -> GpxEventReader<R> {
+        GpxEventReader::new_as(reader)
+    }
+}
+
+impl<R: Read, T: CoordFloat> GpxEventReader<R, T> {
+    /// Creates a reader of the document in `reader`, storing coordinates as
+    /// `T`, see [`read_as`](crate::read_as).
+    pub fn new_as(reader: R) -> GpxEventReader<R, T> {
+        GpxEventReader {
+            context: create_context(reader, GpxVersion::Unknown),
+            state: State::Start,
+            gpx10_metadata: Some(Default::default()),
+            track: None,
+        }
+    }
+
+    /// Peeks at the next XML event, failing at the end of the input.
+    fn peek(&mut self, tagname: &'static str) -> GpxResult<&XmlEvent> {
+        let events = self.context.reader();
+        if let Some(Err(_)) = events.peek() {
+            if let Some(Err(error)) = events.next() {
+                return Err(error.into());
+            }
+        }
+        events
+            .peek()
+            .and_then(|event| event.as_ref().ok())
+            .ok_or(GpxError::MissingClosingTag(tagname))
+    }
+
+    /// Gives the problems worked around so far, see
+    /// [`Parser::warnings`](crate::Parser::warnings).
+    pub fn warnings(&self) -> &[GpxWarning] {
+        self.context.warnings()
+    }
+
+    fn skip_event(&mut self) {
+        self.context.reader().next();
+    }
+
+    fn advance(&mut self) -> GpxResult<GpxEvent<T>> {
+        loop {
+            match self.state {
+                State::Start => {
+                    let (name, attributes, namespace) =
+                        verify_starting_element(&mut self.context, "gpx")?;
+                    let (version, warning) = detect_version(&name, &attributes)?;
+                    if let Some(warning) = warning {
+                        self.context.warn(warning);
+                    }
+                    self.context.set_version(version);
+                    let root_attributes = root_attributes(&attributes, &namespace);
+                    let creator = attributes
+                        .into_iter()
+                        .find(|attr| attr.name.local_name == "creator")
+                        .map(|attr| attr.value);
+                    self.state = State::Document;
+                    return Ok(GpxEvent::StartDocument {
+                        version,
+                        creator,
+                        root_attributes,
+                    });
+                }
+                State::Document => {
+                    if self.context.version() == GpxVersion::Gpx10 {
+                        if let Some(gpx10_metadata) = &mut self.gpx10_metadata {
+                            if gpx10_metadata.consume(&mut self.context)? {
+                                continue;
+                            }
+                        }
+                        // The metadata of GPX 1.0 precedes everything else.
+                        if let Some(metadata) = self
+                            .gpx10_metadata
+                            .take()
+                            .and_then(Gpx10Metadata::into_metadata)
+                        {
+                            return Ok(GpxEvent::Metadata(metadata));
+                        }
+                    }
+                    let version = self.context.version();
+                    match self.peek("gpx")? {
+                        XmlEvent::StartElement { name, .. } => match name.local_name.as_str() {
+                            "metadata" if version != GpxVersion::Gpx10 => {
+                                return Ok(GpxEvent::Metadata(metadata::consume(
+                                    &mut self.context,
+                                )?));
+                            }
+                            "wpt" => {
+                                return Ok(GpxEvent::Waypoint(waypoint::consume(
+                                    &mut self.context,
+                                    "wpt",
+                                )?));
+                            }
+                            "rte" => {
+                                return Ok(GpxEvent::Route(route::consume(&mut self.context)?))
+                            }
+                            "trk" => {
+                                verify_starting_tag(&mut self.context, "trk")?;
+                                self.track = Some(Default::default());
+                                self.state = State::Track;
+                            }
+                            "extensions" => extensions::consume(&mut self.context)?,
+                            child => {
+                                return Err(GpxError::InvalidChildElement(child.into(), "gpx"));
+                            }
+                        },
+                        XmlEvent::EndElement { name } => {
+                            if name.local_name != "gpx" {
+                                return Err(GpxError::InvalidClosingTag(
+                                    name.local_name.clone(),
+                                    "gpx",
+                                ));
+                            }
+                            self.skip_event();
+                            self.state = State::Done;
+                            return Ok(GpxEvent::EndDocument);
+                        }
+                        _ => self.skip_event(),
+                    }
+                }
+                State::Track => match self.peek("track")? {
+                    XmlEvent::StartElement { name, .. } if name.local_name == "trkseg" => {
+                        // The properties of a track precede its segments.
+                        if let Some(track) = self.track.take() {
+                            return Ok(GpxEvent::StartTrack(track));
+                        }
+                        verify_starting_tag(&mut self.context, "trkseg")?;
+                        self.state = State::Segment;
+                        return Ok(GpxEvent::StartSegment);
+                    }
+                    XmlEvent::StartElement { .. } => match &mut self.track {
+                        Some(track) => track::consume_property(&mut self.context, track)?,
+                        None => track::consume_property(&mut self.context, &mut Track::<T>::new())?,
+                    },
+                    XmlEvent::EndElement { name } => {
+                        if name.local_name != "trk" {
+                            return Err(GpxError::InvalidClosingTag(
+                                name.local_name.clone(),
+                                "track",
+                            ));
+                        }
+                        if let Some(track) = self.track.take() {
+                            return Ok(GpxEvent::StartTrack(track));
+                        }
+                        self.skip_event();
+                        self.state = State::Document;
+                        return Ok(GpxEvent::EndTrack);
+                    }
+                    _ => self.skip_event(),
+                },
+                State::Segment => match self.peek("tracksegment")? {
+                    XmlEvent::StartElement { name, .. } => match name.local_name.as_str() {
+                        "trkpt" => {
+                            return Ok(GpxEvent::TrackPoint(waypoint::consume(
+                                &mut self.context,
+                                "trkpt",
+                            )?));
+                        }
+                        child => {
+                            return Err(GpxError::InvalidChildElement(
+                                child.into(),
+                                "tracksegment",
+                            ));
+                        }
+                    },
+                    XmlEvent::EndElement { name } => {
+                        if name.local_name != "trkseg" {
+                            return Err(GpxError::InvalidClosingTag(
+                                name.local_name.clone(),
+                                "trksegment",
+                            ));
+                        }
+                        self.skip_event();
+                        self.state = State::Track;
+                        return Ok(GpxEvent::EndSegment);
+                    }
+                    _ => self.skip_event(),
+                },
+                State::Done => return Err(GpxError::EventParsingError("end of document")),
+            }
+        }
+    }
+}
+
+impl<R: Read, T: CoordFloat> Iterator for GpxEventReader<R, T> {
+    type Item = GpxResult<GpxEvent<T>>;
+
+    fn next(&mut self) -> Option<GpxResult<GpxEvent<T>>> {
+        if self.state == State::Done {
+            return None;
+        }
+        let event = self.advance();
+        if event.is_err() {
+            self.state = State::Done;
+        }
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::BufReader;
+
+    use super::{GpxEvent, GpxEventReader};
+    use crate::errors::GpxResult;
+    use crate::{read, Gpx, GpxVersion, TrackSegment};
+
+    /// Builds a document from events, like `read` does.
+    fn collect(data: &[u8]) -> GpxResult<Gpx> {
+        let mut gpx = Gpx::default();
+        for event in GpxEventReader::new(data) {
+            match event? {
+                GpxEvent::StartDocument {
+                    version,
+                    creator,
+                    root_attributes,
+                } => {
+                    gpx.version = version;
+                    gpx.creator = creator;
+                    gpx.root_attributes = root_attributes;
+                }
+                GpxEvent::Metadata(metadata) => gpx.metadata = Some(metadata),
+                GpxEvent::Waypoint(waypoint) => gpx.waypoints.push(waypoint),
+                GpxEvent::Route(route) => gpx.routes.push(route),
+                GpxEvent::StartTrack(track) => gpx.tracks.push(track),
+                GpxEvent::StartSegment => {
+                    let track = gpx.tracks.last_mut().unwrap();
+                    track.segments.push(TrackSegment::new());
+                }
+                GpxEvent::TrackPoint(point) => {
+                    let track = gpx.tracks.last_mut().unwrap();
+                    track.segments.last_mut().unwrap().points.push(point);
+                }
+                GpxEvent::EndSegment | GpxEvent::EndTrack | GpxEvent::EndDocument => {}
+            }
+        }
+        Ok(gpx)
+    }
+
+    #[test]
+    fn events_match_read() {
+        for fixture in [
+            "tests/fixtures/garmin_with_extensions.gpx",
+            "tests/fixtures/strava_route_example.gpx",
+            "tests/fixtures/viking_with_route_extensions.gpx",
+            "tests/fixtures/wikipedia_example.gpx",
+        ] {
+            let data = std::fs::read(fixture).unwrap();
+            let expected = read(BufReader::new(File::open(fixture).unwrap())).unwrap();
+            assert_eq!(collect(&data).unwrap(), expected, "{fixture}");
+        }
+
+        let gpx10 = "<gpx version=\"1.0\"><name>Old</name><author>Me</author>\
+            <wpt lat=\"1\" lon=\"2\"/><trk><name>T</name></trk></gpx>";
+        let gpx = collect(gpx10.as_bytes()).unwrap();
+        assert_eq!(gpx, read(gpx10.as_bytes()).unwrap());
+        assert_eq!(gpx.version, GpxVersion::Gpx10);
+        assert_eq!(gpx.tracks[0].name.as_deref(), Some("T"));
+    }
+
+    #[test]
+    fn events_stop_at_error() {
+        let data = "<gpx version=\"1.1\"><trk><trkseg><trkpt lat=\"1\" lon=\"2\"/><foo/>";
+        let events: Vec<_> = GpxEventReader::new(data.as_bytes()).collect();
+        assert_eq!(events.len(), 5);
+        assert!(matches!(events[3], Ok(GpxEvent::TrackPoint(_))));
+        assert!(events[4].is_err());
+
+        assert!(collect(b"<gpx version=\"1.1\"><trk>").is_err());
+        assert!(collect(b"<kml></kml>").is_err());
+    }
+}
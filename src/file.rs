@@ -0,0 +1,244 @@
+//! file reads and writes documents by path.
+
+use std::fmt::Display;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use geo_types::CoordFloat;
+use xml::namespace::Namespace;
+use xml::reader::{EventReader, XmlEvent};
+
+use crate::errors::{GpxError, GpxResult};
+use crate::parser::gpx::detect_version;
+use crate::writer::write_children;
+use crate::{read_from_slice, write, Gpx, GpxVersion};
+
+/// The number of bytes at the end of a file searched for the closing tag of
+/// its root element by [`append_to_path`].
+const TAIL_LENGTH: u64 = 1024;
+
+/// Reads the GPX file at `path`.
+///
+/// With the `gzip` feature, gzip-compressed files, like `track.gpx.gz`, are
+/// recognized by their content and decompressed.
+///
+/// ```
+/// let gpx = gpx::read_from_path("tests/fixtures/wikipedia_example.gpx").unwrap();
+/// assert_eq!(gpx.tracks.len(), 1);
+/// ```
+pub fn read_from_path<P: AsRef<Path>>(path: P) -> GpxResult<Gpx> {
+    let data = fs::read(path)?;
+    #[cfg(feature = "gzip")]
+    if data.starts_with(&[0x1f, 0x8b]) {
+        use std::io::Read;
+
+        let mut decompressed = Vec::new();
+        flate2::read::MultiGzDecoder::new(data.as_slice()).read_to_end(&mut decompressed)?;
+        return read_from_slice(&decompressed);
+    }
+    read_from_slice(&data)
+}
+
+/// Writes a document as a GPX file at `path`, replacing any existing file.
+///
+/// With the `gzip` feature, the file is gzip-compressed if `path` ends with
+/// `.gz`.
+pub fn write_to_path<P: AsRef<Path>, T: CoordFloat + Display>(
+    gpx: &Gpx<T>,
+    path: P,
+) -> GpxResult<()> {
+    let path = path.as_ref();
+    let file = BufWriter::new(File::create(path)?);
+    #[cfg(feature = "gzip")]
+    if path
+        .extension()
+        .map_or(false, |extension| extension.eq_ignore_ascii_case("gz"))
+    {
+        let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        write(gpx, &mut encoder)?;
+        encoder.finish()?.flush()?;
+        return Ok(());
+    }
+    let mut file = file;
+    write(gpx, &mut file)?;
+    file.flush()?;
+    Ok(())
+}
+
+/// Appends the waypoints, tracks and routes of `gpx` to the GPX file at
+/// `path`, e.g. to add each recording to a large log file, without reading
+/// and writing the whole file again.
+///
+/// Only the start of the file, for its version and namespaces, and its end
+/// are read. The new elements are written in place of the closing
+/// `</gpx>` tag, which is written again after them. The metadata of `gpx` is
+/// left out, and that of the file, like its bounds, is kept as it is.
+///
+/// The elements are added at the end of the document, so waypoints may come
+/// after tracks, which readers like [`read`](crate::read) accept but the
+/// GPX schema doesn't allow. Only uncompressed files whose root element has
+/// no prefix can be appended to; others fail with
+/// [`GpxError::MissingClosingTag`].
+///
+/// ```
+/// use gpx::{append_to_path, read_from_path, write_to_path, Gpx, GpxVersion, Track};
+///
+/// # let dir = std::env::temp_dir().join(format!("gpx-append-doc-{}", std::process::id()));
+/// # std::fs::create_dir_all(&dir).unwrap();
+/// let path = dir.join("log.gpx");
+/// let log: Gpx = Gpx {
+///     version: GpxVersion::Gpx11,
+///     ..Default::default()
+/// };
+/// write_to_path(&log, &path).unwrap();
+///
+/// for name in ["Monday", "Tuesday"] {
+///     let mut recording: Gpx = Default::default();
+///     let mut track = Track::new();
+///     track.name = Some(name.to_string());
+///     recording.tracks.push(track);
+///     append_to_path(&recording, &path).unwrap();
+/// }
+///
+/// let log = read_from_path(&path).unwrap();
+/// assert_eq!(log.tracks.len(), 2);
+/// assert_eq!(log.tracks[1].name.as_deref(), Some("Tuesday"));
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn append_to_path<P: AsRef<Path>, T: CoordFloat + Display>(
+    gpx: &Gpx<T>,
+    path: P,
+) -> GpxResult<()> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let (version, scope) = read_root(BufReader::new(&mut file))?;
+    let (end, empty) = closing_tag_offset(&mut file)?;
+    let mut data = Vec::new();
+    if empty {
+        data.push(b'>');
+    }
+    data.extend(write_children(gpx, version, &scope)?);
+    data.extend_from_slice(b"\n</gpx>");
+    file.seek(SeekFrom::Start(end))?;
+    file.write_all(&data)?;
+    file.set_len(end + data.len() as u64)?;
+    Ok(())
+}
+
+/// Gives the version of a document and the namespaces its root element
+/// declares, reading no further than the root element.
+fn read_root<R: Read>(reader: R) -> GpxResult<(GpxVersion, Namespace)> {
+    for event in EventReader::new(reader) {
+        if let XmlEvent::StartElement {
+            name,
+            attributes,
+            namespace,
+        } = event?
+        {
+            if name.local_name != "gpx" {
+                return Err(GpxError::MissingOpeningTag("gpx"));
+            }
+            let (version, _) = detect_version(&name, &attributes)?;
+            return Ok((version, namespace));
+        }
+    }
+    Err(GpxError::MissingOpeningTag("gpx"))
+}
+
+/// Gives the offset of the closing `</gpx>` tag of a file, which may only
+/// be followed by whitespace, and whether the root element is empty, like
+/// `<gpx version="1.1"/>`, and ends at the offset of its `/>` instead.
+fn closing_tag_offset(file: &mut File) -> GpxResult<(u64, bool)> {
+    let length = file.seek(SeekFrom::End(0))?;
+    let start = length.saturating_sub(TAIL_LENGTH);
+    file.seek(SeekFrom::Start(start))?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail)?;
+    let end = tail
+        .iter()
+        .rposition(|byte| !byte.is_ascii_whitespace())
+        .map_or(0, |index| index + 1);
+    // Only the root element can end a document, so a document ending with
+    // `/>` has an empty root element.
+    let tail = &tail[..end];
+    if let Some(content) = tail.strip_suffix(b"</gpx>") {
+        // The line break before the closing tag is written again after the
+        // new elements.
+        let content = content
+            .iter()
+            .rposition(|byte| !byte.is_ascii_whitespace())
+            .map_or(content.len(), |index| index + 1);
+        Ok((start + content as u64, false))
+    } else if let Some(content) = tail.strip_suffix(b"/>") {
+        Ok((start + content.len() as u64, true))
+    } else {
+        Err(GpxError::MissingClosingTag("gpx"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use geo_types::Point;
+
+    use super::{append_to_path, read_from_path, write_to_path};
+    use crate::{DisplayColor, Gpx, Track, Waypoint};
+
+    #[test]
+    fn path_round_trip() {
+        let gpx = read_from_path("tests/fixtures/wikipedia_example.gpx").unwrap();
+        let dir = env::temp_dir().join(format!("gpx-path-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let plain = dir.join("track.gpx");
+        write_to_path(&gpx, &plain).unwrap();
+        assert!(fs::read(&plain).unwrap().starts_with(b"<?xml"));
+        assert_eq!(read_from_path(&plain).unwrap().tracks, gpx.tracks);
+
+        #[cfg(feature = "gzip")]
+        {
+            let compressed = dir.join("track.gpx.gz");
+            write_to_path(&gpx, &compressed).unwrap();
+            assert!(fs::read(&compressed).unwrap().starts_with(&[0x1f, 0x8b]));
+            assert_eq!(read_from_path(&compressed).unwrap().tracks, gpx.tracks);
+        }
+
+        assert!(read_from_path(dir.join("missing.gpx")).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn append_to_existing_file() {
+        let dir = env::temp_dir().join(format!("gpx-append-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("track.gpx");
+        fs::copy("tests/fixtures/wikipedia_example.gpx", &path).unwrap();
+        let original = read_from_path(&path).unwrap();
+
+        let mut track = Track::new();
+        track.name = Some("Appended".to_string());
+        track.display_color = Some(DisplayColor::Magenta);
+        let mut appended: Gpx = Gpx {
+            tracks: vec![track],
+            ..Default::default()
+        };
+        appended
+            .waypoints
+            .push(Waypoint::new(Point::new(-121.97, 37.24)));
+        append_to_path(&appended, &path).unwrap();
+
+        let gpx = read_from_path(&path).unwrap();
+        assert_eq!(gpx.metadata, original.metadata);
+        assert_eq!(gpx.tracks[..1], original.tracks[..]);
+        assert_eq!(gpx.tracks[1..], appended.tracks[..]);
+        assert_eq!(gpx.waypoints, appended.waypoints);
+        assert!(fs::read(&path).unwrap().ends_with(b"</gpx>"));
+
+        let unclosed = dir.join("unclosed.gpx");
+        fs::write(&unclosed, "<gpx version=\"1.1\"><trk></trk>").unwrap();
+        assert!(append_to_path(&appended, &unclosed).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,112 @@
+//! fingerprint digests tracks so that duplicates can be found, even when
+//! their files differ byte-wise.
+
+use geo_types::CoordFloat;
+
+use crate::measure::position;
+use crate::Track;
+
+/// Precision of the coordinates in the digest: 1e-5 degrees, about a meter.
+const COORDINATE_SCALE: f64 = 1e5;
+
+/// 64-bit FNV-1a, which unlike `std`'s hashers is guaranteed to stay the
+/// same across Rust versions and platforms.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Fnv1a {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+impl<T: CoordFloat> Track<T> {
+    /// Gives a digest of the track's points that stays the same across
+    /// different files of the same activity, e.g. to detect duplicate
+    /// uploads.
+    ///
+    /// Only the positions, rounded to about a meter, and the times, rounded
+    /// down to whole seconds, go into the digest. Names, elevations and
+    /// other data are left out, as are the boundaries between segments, so
+    /// exports from different tools still match. The digest is stable
+    /// across Rust versions and platforms and can be stored.
+    ///
+    /// ```
+    /// use geo_types::{line_string, MultiLineString};
+    /// use gpx::Track;
+    ///
+    /// let track = Track::from(MultiLineString::new(vec![
+    ///     line_string![(x: -121.97, y: 37.24), (x: -121.96, y: 37.25)],
+    /// ]));
+    /// let mut renamed = track.clone();
+    /// renamed.name = Some("Morning ride".into());
+    ///
+    /// assert_eq!(track.fingerprint(), renamed.fingerprint());
+    /// ```
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        for point in self.segments.iter().flat_map(|segment| &segment.points) {
+            let position = position(point);
+            for value in [position.x(), position.y()] {
+                let value = (value * COORDINATE_SCALE).round() as i64;
+                hasher.write(&value.to_le_bytes());
+            }
+            match point.time {
+                Some(time) => {
+                    hasher.write(&[1]);
+                    let seconds = time.unix_seconds();
+                    hasher.write(&seconds.to_le_bytes());
+                }
+                None => hasher.write(&[0]),
+            }
+        }
+        hasher.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::{line_string, MultiLineString, Point};
+
+    use super::Fnv1a;
+    use crate::{Duration, Time, Track, TrackSegment, Waypoint};
+
+    #[test]
+    fn fnv1a_test_vector() {
+        let mut hasher = Fnv1a::new();
+        hasher.write(b"a");
+        assert_eq!(hasher.0, 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn fingerprint_ignores_formatting_differences() {
+        let track = Track::from(MultiLineString::new(vec![line_string![
+            (x: 1.0, y: 2.0),
+            (x: 1.5, y: 2.5),
+        ]]));
+        let start = Time::UNIX_EPOCH;
+
+        let mut timed = track.clone();
+        for (i, point) in timed.segments[0].points.iter_mut().enumerate() {
+            point.time = Some(start + Duration::seconds(i as i64));
+        }
+        let mut other = timed.clone();
+        let mut point = Waypoint::new(Point::new(1.5 + 1e-9, 2.5));
+        point.elevation = Some(10.0);
+        point.time = Some(start + Duration::milliseconds(1200));
+        other.segments[0].points[1] = point;
+        other.segments.push(TrackSegment::new());
+
+        // This is a stable digest, so its value must never change.
+        assert_eq!(timed.fingerprint(), 0xce0d_c132_e4ed_9633);
+        assert_eq!(timed.fingerprint(), other.fingerprint());
+        assert_ne!(timed.fingerprint(), track.fingerprint());
+        assert_ne!(track.fingerprint(), track.reversed().fingerprint());
+    }
+}
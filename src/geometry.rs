@@ -0,0 +1,512 @@
+//! geometry applies geo's algorithms to whole GPX documents, and compares
+//! the shapes of tracks and routes.
+
+use geo::{
+    Centroid, ConvexHull, EuclideanDistance, GeodesicArea, GeodesicDistance, HausdorffDistance,
+};
+use geo_types::{coord, Coord, CoordFloat, LineString, MultiPoint, Point, Polygon};
+
+use crate::measure::{distance, position};
+use crate::{Gpx, Route, Track, TrackSegment, Waypoint};
+
+/// Mean radius of the earth in meters, as used by geo.
+pub(crate) const EARTH_RADIUS: f64 = 6_371_008.8;
+
+impl<T: CoordFloat> Gpx<T> {
+    /// Gives the mean position of all waypoints, track points and route
+    /// points of the document, or `None` if it has no points.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Gpx, Waypoint};
+    ///
+    /// let mut gpx: Gpx = Default::default();
+    /// gpx.waypoints.push(Waypoint::new(Point::new(0.0, 0.0)));
+    /// gpx.waypoints.push(Waypoint::new(Point::new(2.0, 4.0)));
+    ///
+    /// assert_eq!(gpx.centroid(), Some(Point::new(1.0, 2.0)));
+    /// ```
+    pub fn centroid(&self) -> Option<Point<f64>> {
+        self.multi_point().centroid()
+    }
+
+    /// Gives the smallest convex polygon containing all waypoints, track
+    /// points and route points of the document, e.g. to show the area it
+    /// covers. The polygon is empty if the document has no points.
+    pub fn convex_hull(&self) -> Polygon<f64> {
+        self.multi_point().convex_hull()
+    }
+
+    fn multi_point(&self) -> MultiPoint<f64> {
+        let track_points = self
+            .tracks
+            .iter()
+            .flat_map(|track| &track.segments)
+            .flat_map(|segment| &segment.points);
+        let route_points = self.routes.iter().flat_map(|route| &route.points);
+        self.waypoints
+            .iter()
+            .chain(track_points)
+            .chain(route_points)
+            .map(position)
+            .collect()
+    }
+}
+
+impl<T: CoordFloat> Track<T> {
+    /// Gives the discrete Fréchet distance to `other` in meters, see
+    /// [`TrackSegment::frechet_distance`]. The points of all segments are
+    /// compared as one continuous path.
+    ///
+    /// ```
+    /// use geo_types::{line_string, MultiLineString};
+    /// use gpx::Track;
+    ///
+    /// let track = Track::from(MultiLineString::new(vec![
+    ///     line_string![(x: 0.0, y: 0.0), (x: 0.0, y: 0.01)],
+    /// ]));
+    /// let shifted = Track::from(MultiLineString::new(vec![
+    ///     line_string![(x: 0.0001, y: 0.0), (x: 0.0001, y: 0.01)],
+    /// ]));
+    ///
+    /// // The tracks run about 11 m apart.
+    /// assert!((track.frechet_distance(&shifted).unwrap() - 11.1).abs() < 0.1);
+    /// ```
+    pub fn frechet_distance(&self, other: &Track<T>) -> Option<f64> {
+        frechet_distance(&track_points(self), &track_points(other))
+    }
+
+    /// Gives the Hausdorff distance to `other` in meters, see
+    /// [`TrackSegment::hausdorff_distance`].
+    pub fn hausdorff_distance(&self, other: &Track<T>) -> Option<f64> {
+        hausdorff_distance(&track_points(self), &track_points(other))
+    }
+
+    /// Checks whether the track is a closed loop: it has at least three
+    /// points, and it ends within `tolerance` meters of where it started.
+    ///
+    /// ```
+    /// use geo_types::{line_string, MultiLineString};
+    /// use gpx::Track;
+    ///
+    /// let track = Track::from(MultiLineString::new(vec![line_string![
+    ///     (x: 0.0, y: 0.0),
+    ///     (x: 0.001, y: 0.0),
+    ///     (x: 0.001, y: 0.001),
+    ///     (x: 0.00001, y: 0.0),
+    /// ]]));
+    ///
+    /// assert!(track.is_closed_loop(5.0));
+    /// assert!(!track.is_closed_loop(0.5));
+    /// // A triangle with two sides of about 111 m.
+    /// assert!((track.enclosed_area() - 6_093.0).abs() < 1.0);
+    /// ```
+    pub fn is_closed_loop(&self, tolerance: f64) -> bool {
+        is_closed_loop(&track_points(self), tolerance)
+    }
+
+    /// Gives the geodesic area enclosed by the track in square meters, as if
+    /// its last point was connected back to the first one.
+    ///
+    /// The area is only meaningful if the track does not cross itself.
+    pub fn enclosed_area(&self) -> f64 {
+        enclosed_area(&track_points(self))
+    }
+
+    /// Gives the shortest distance from `point` to the track in meters, see
+    /// [`TrackSegment::min_distance_to`]. The gaps between segments don't
+    /// count as part of the track.
+    pub fn min_distance_to(&self, point: Point<f64>) -> Option<f64> {
+        self.segments
+            .iter()
+            .filter_map(|segment| segment.min_distance_to(point))
+            .reduce(f64::min)
+    }
+
+    /// Checks whether the track passes within `radius` meters of `point`,
+    /// e.g. whether a ride went past a café.
+    ///
+    /// ```
+    /// use geo_types::{line_string, MultiLineString, Point};
+    /// use gpx::Track;
+    ///
+    /// let track = Track::from(MultiLineString::new(vec![
+    ///     line_string![(x: 0.0, y: 0.0), (x: 0.0, y: 0.01)],
+    /// ]));
+    /// // About 22 m east of the middle of the track.
+    /// let cafe = Point::new(0.0002, 0.005);
+    ///
+    /// assert!(track.passes_within(cafe, 25.0));
+    /// assert!(!track.passes_within(cafe, 20.0));
+    /// ```
+    pub fn passes_within(&self, point: Point<f64>, radius: f64) -> bool {
+        self.min_distance_to(point)
+            .map_or(false, |distance| distance <= radius)
+    }
+
+    /// Gives the percentage of the track's length that runs within `buffer`
+    /// meters of `other`, e.g. to find repeats of a route or rides along a
+    /// known segment. Returns `None` if the track has no length.
+    ///
+    /// The track is walked in steps of at most half the buffer, counting
+    /// the steps whose middle is within the buffer, so comparing tracks of
+    /// `n` and `m` points takes about `n × m` time, and more for small
+    /// buffers. The overlap isn't symmetric: a short track along a long one
+    /// overlaps it entirely, while the long one overlaps the short one only
+    /// in part.
+    ///
+    /// ```
+    /// use geo_types::{line_string, MultiLineString};
+    /// use gpx::Track;
+    ///
+    /// let commute = Track::from(MultiLineString::new(vec![
+    ///     line_string![(x: 0.0, y: 0.0), (x: 0.0, y: 0.01), (x: 0.01, y: 0.01)],
+    /// ]));
+    /// // The same way north, about 5 m off, then turning west instead.
+    /// let ride = Track::from(MultiLineString::new(vec![
+    ///     line_string![(x: 0.00005, y: 0.0), (x: 0.00005, y: 0.01), (x: -0.01, y: 0.01)],
+    /// ]));
+    ///
+    /// // Half of the commute, plus the first meters east of the turn.
+    /// let overlap = commute.overlap_percent(&ride, 20.0).unwrap();
+    /// assert!((overlap - 51.2).abs() < 0.1);
+    /// ```
+    pub fn overlap_percent(&self, other: &Track<T>, buffer: f64) -> Option<f64> {
+        let mut length = 0.0;
+        let mut shared = 0.0;
+        for pair in self
+            .segments
+            .iter()
+            .flat_map(|segment| segment.points.windows(2))
+        {
+            let (a, b) = (position(&pair[0]), position(&pair[1]));
+            let step = distance(&pair[0], &pair[1]);
+            let pieces = (step / (buffer / 2.0)).ceil().max(1.0);
+            for piece in 0..pieces as usize {
+                let fraction = (piece as f64 + 0.5) / pieces;
+                let middle = a + (b - a) * fraction;
+                if other.passes_within(middle, buffer) {
+                    shared += step / pieces;
+                }
+            }
+            length += step;
+        }
+        (length > 0.0).then(|| shared / length * 100.0)
+    }
+}
+
+impl<T: CoordFloat> TrackSegment<T> {
+    /// Gives the shortest geodesic distance from `point` to the segment in
+    /// meters, measured to the nearest point along the lines between the
+    /// segment's points rather than to the points themselves. Returns `None`
+    /// if the segment has no points.
+    ///
+    /// The nearest spot on each line is found on an equirectangular
+    /// projection centered on `point`, which is accurate unless the points
+    /// are hundreds of kilometers apart.
+    pub fn min_distance_to(&self, point: Point<f64>) -> Option<f64> {
+        min_distance(&self.points, point)
+    }
+
+    /// Gives the discrete Fréchet distance to `other` in meters.
+    ///
+    /// This is the shortest leash that lets a walker on each path go from
+    /// start to end, moving only forward, so it is small only if the paths
+    /// have a similar shape and direction, e.g. when riding the same loop
+    /// again. Returns `None` if either path has no points.
+    ///
+    /// Distances are measured on an equirectangular projection centered on
+    /// the first point, which is accurate for paths spanning up to a few
+    /// hundred kilometers. Comparing paths of `n` and `m` points takes
+    /// `O(n * m)` time, so consider simplifying long paths first.
+    pub fn frechet_distance(&self, other: &TrackSegment<T>) -> Option<f64> {
+        frechet_distance(&borrowed(&self.points), &borrowed(&other.points))
+    }
+
+    /// Gives the Hausdorff distance to `other` in meters: how far a point of
+    /// either path can be from the closest point of the other.
+    ///
+    /// Unlike the Fréchet distance, this ignores the order of the points.
+    /// Returns `None` if either path has no points. Distances are measured
+    /// like in [`TrackSegment::frechet_distance`].
+    pub fn hausdorff_distance(&self, other: &TrackSegment<T>) -> Option<f64> {
+        hausdorff_distance(&borrowed(&self.points), &borrowed(&other.points))
+    }
+}
+
+impl<T: CoordFloat> Route<T> {
+    /// Gives the discrete Fréchet distance to `other` in meters, see
+    /// [`TrackSegment::frechet_distance`].
+    pub fn frechet_distance(&self, other: &Route<T>) -> Option<f64> {
+        frechet_distance(&borrowed(&self.points), &borrowed(&other.points))
+    }
+
+    /// Gives the Hausdorff distance to `other` in meters, see
+    /// [`TrackSegment::hausdorff_distance`].
+    pub fn hausdorff_distance(&self, other: &Route<T>) -> Option<f64> {
+        hausdorff_distance(&borrowed(&self.points), &borrowed(&other.points))
+    }
+
+    /// Checks whether the route is a closed loop, see
+    /// [`Track::is_closed_loop`].
+    pub fn is_closed_loop(&self, tolerance: f64) -> bool {
+        is_closed_loop(&borrowed(&self.points), tolerance)
+    }
+
+    /// Gives the geodesic area enclosed by the route in square meters, see
+    /// [`Track::enclosed_area`].
+    pub fn enclosed_area(&self) -> f64 {
+        enclosed_area(&borrowed(&self.points))
+    }
+}
+
+fn track_points<T: CoordFloat>(track: &Track<T>) -> Vec<&Waypoint<T>> {
+    track
+        .segments
+        .iter()
+        .flat_map(|segment| &segment.points)
+        .collect()
+}
+
+fn borrowed<T: CoordFloat>(points: &[Waypoint<T>]) -> Vec<&Waypoint<T>> {
+    points.iter().collect()
+}
+
+fn is_closed_loop<T: CoordFloat>(points: &[&Waypoint<T>], tolerance: f64) -> bool {
+    points.len() >= 3 && distance(points[0], points[points.len() - 1]) <= tolerance
+}
+
+fn enclosed_area<T: CoordFloat>(points: &[&Waypoint<T>]) -> f64 {
+    let exterior: LineString<f64> = points.iter().map(|point| position(point)).collect();
+    Polygon::new(exterior, Vec::new()).geodesic_area_unsigned()
+}
+
+/// A path projected onto a plane, in meters.
+type ProjectedPath = Vec<Coord<f64>>;
+
+/// Projects both paths onto a plane in meters, centered on the first point
+/// of `a`. Returns `None` if either path is empty.
+fn project<T: CoordFloat>(
+    a: &[&Waypoint<T>],
+    b: &[&Waypoint<T>],
+) -> Option<(ProjectedPath, ProjectedPath)> {
+    if b.is_empty() {
+        return None;
+    }
+    let origin = position(a.first()?);
+    let scale = EARTH_RADIUS.to_radians();
+    let x_scale = scale * origin.y().to_radians().cos();
+    let project = |points: &[&Waypoint<T>]| {
+        points
+            .iter()
+            .map(|point| {
+                let point = position(point);
+                coord! {
+                    x: (point.x() - origin.x()) * x_scale,
+                    y: (point.y() - origin.y()) * scale,
+                }
+            })
+            .collect()
+    };
+    Some((project(a), project(b)))
+}
+
+/// Shortest distance from `point` to the path through `points`, in meters.
+fn min_distance<T: CoordFloat>(points: &[Waypoint<T>], point: Point<f64>) -> Option<f64> {
+    nearest_spot(points, point).map(|(_, _, distance)| distance)
+}
+
+/// Finds the spot on the path through `points` nearest to `point`, giving
+/// the index of the point starting the line it is on, how far along that
+/// line it is from 0.0 to 1.0, and its distance in meters.
+pub(crate) fn nearest_spot<T: CoordFloat>(
+    points: &[Waypoint<T>],
+    point: Point<f64>,
+) -> Option<(usize, f64, f64)> {
+    let first = position(points.first()?);
+    if points.len() == 1 {
+        return Some((0, 0.0, point.geodesic_distance(&first)));
+    }
+    let x_scale = point.y().to_radians().cos();
+    let project = |p: Point<f64>| coord! { x: (p.x() - point.x()) * x_scale, y: p.y() - point.y() };
+    points
+        .windows(2)
+        .enumerate()
+        .map(|(index, pair)| {
+            let (a, b) = (project(position(&pair[0])), project(position(&pair[1])));
+            let direction = b - a;
+            let length = direction.x * direction.x + direction.y * direction.y;
+            let along = if length > 0.0 {
+                (-(a.x * direction.x + a.y * direction.y) / length).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let nearest = if x_scale > 0.0 {
+                let nearest = a + direction * along;
+                Point::new(point.x() + nearest.x / x_scale, point.y() + nearest.y)
+            } else {
+                position(&pair[0])
+            };
+            (index, along, point.geodesic_distance(&nearest))
+        })
+        .min_by(|a, b| a.2.total_cmp(&b.2))
+}
+
+/// Discrete Fréchet distance, computed row by row to keep memory linear.
+fn frechet_distance<T: CoordFloat>(a: &[&Waypoint<T>], b: &[&Waypoint<T>]) -> Option<f64> {
+    let (a, b) = project(a, b)?;
+    let mut previous: Vec<f64> = Vec::with_capacity(b.len());
+    let mut current = vec![0.0; b.len()];
+    for (i, a) in a.iter().enumerate() {
+        for (j, b) in b.iter().enumerate() {
+            let distance = a.euclidean_distance(b);
+            current[j] = match (i, j) {
+                (0, 0) => distance,
+                (0, _) => current[j - 1].max(distance),
+                (_, 0) => previous[0].max(distance),
+                _ => previous[j]
+                    .min(previous[j - 1])
+                    .min(current[j - 1])
+                    .max(distance),
+            };
+        }
+        std::mem::swap(&mut previous, &mut current);
+        current.resize(b.len(), 0.0);
+    }
+    previous.last().copied()
+}
+
+fn hausdorff_distance<T: CoordFloat>(a: &[&Waypoint<T>], b: &[&Waypoint<T>]) -> Option<f64> {
+    let (a, b) = project(a, b)?;
+    Some(MultiPoint::from(a).hausdorff_distance(&MultiPoint::from(b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::{Area, Contains};
+    use geo_types::{line_string, Point};
+
+    use crate::{Gpx, Route, Track, TrackSegment, Waypoint};
+
+    #[test]
+    fn centroid_and_convex_hull() {
+        let mut track = Track::new();
+        track
+            .segments
+            .push(line_string![(x: 0.0, y: 0.0), (x: 2.0, y: 0.0), (x: 1.0, y: 0.5)].into());
+        let gpx = Gpx {
+            waypoints: vec![Waypoint::new(Point::new(2.0, 2.0))],
+            tracks: vec![track],
+            routes: vec![Route::from(line_string![(x: 0.0, y: 2.0)])],
+            ..Default::default()
+        };
+
+        assert_eq!(gpx.centroid(), Some(Point::new(1.0, 0.9)));
+
+        let hull = gpx.convex_hull();
+        assert_eq!(hull.unsigned_area(), 4.0);
+        assert!(hull.contains(&Point::new(1.0, 1.0)));
+
+        assert_eq!(Gpx::<f64>::default().centroid(), None);
+        assert_eq!(Gpx::<f64>::default().convex_hull().unsigned_area(), 0.0);
+    }
+
+    #[test]
+    fn frechet_and_hausdorff_distances() {
+        let line = TrackSegment::from(line_string![
+            (x: 0.0, y: 0.0),
+            (x: 0.0, y: 0.001),
+            (x: 0.0, y: 0.002),
+        ]);
+        let reversed = TrackSegment {
+            points: line.points.iter().rev().cloned().collect(),
+        };
+
+        assert_eq!(line.frechet_distance(&line), Some(0.0));
+        // Same points, but walked in the opposite direction.
+        assert_eq!(line.hausdorff_distance(&reversed), Some(0.0));
+        let frechet = line.frechet_distance(&reversed).unwrap();
+        assert!((frechet - 222.4).abs() < 0.1);
+
+        let detour = TrackSegment::from(line_string![
+            (x: 0.0, y: 0.0),
+            (x: 0.001, y: 0.001),
+            (x: 0.0, y: 0.002),
+        ]);
+        let frechet = line.frechet_distance(&detour).unwrap();
+        assert!((frechet - 111.2).abs() < 0.1);
+        assert_eq!(line.hausdorff_distance(&detour), Some(frechet));
+
+        assert_eq!(line.frechet_distance(&TrackSegment::new()), None);
+        assert_eq!(Route::<f64>::new().hausdorff_distance(&Route::new()), None);
+    }
+
+    #[test]
+    fn closed_loops_and_area() {
+        let square = line_string![
+            (x: 0.0, y: 0.0),
+            (x: 0.01, y: 0.0),
+            (x: 0.01, y: 0.01),
+            (x: 0.0, y: 0.01),
+        ];
+        let route = Route::from(square.clone());
+        assert!(!route.is_closed_loop(10.0));
+        // About 1113 m by 1106 m.
+        assert!((route.enclosed_area() - 1_230_907.2).abs() < 0.1);
+
+        let mut track = Track::new();
+        track.segments.push(square.into());
+        track.segments.push(line_string![(x: 0.0, y: 0.0)].into());
+        assert!(track.is_closed_loop(0.0));
+        assert!((track.enclosed_area() - route.enclosed_area()).abs() < 1e-6);
+
+        let there_and_back = Route::from(line_string![(x: 0.0, y: 0.0), (x: 0.0, y: 0.0)]);
+        assert!(!there_and_back.is_closed_loop(1.0));
+        assert_eq!(Route::<f64>::new().enclosed_area(), 0.0);
+    }
+
+    #[test]
+    fn min_distance_to_track() {
+        let mut track = Track::new();
+        assert_eq!(track.min_distance_to(Point::new(0.0, 0.0)), None);
+        assert!(!track.passes_within(Point::new(0.0, 0.0), 1e6));
+
+        track.segments.push(line_string![(x: 10.0, y: 0.0)].into());
+        track
+            .segments
+            .push(line_string![(x: 0.0, y: 50.0), (x: 0.01, y: 50.0), (x: 0.01, y: 50.01)].into());
+        // The start of the vertical line, about 111 m north and 72 m west.
+        let distance = track.min_distance_to(Point::new(0.011, 49.999)).unwrap();
+        assert!((distance - 132.3).abs() < 0.1, "{distance}");
+        // Level with the middle of the vertical line, 0.001° east of it.
+        let distance = track.min_distance_to(Point::new(0.011, 50.005)).unwrap();
+        assert!((distance - 71.7).abs() < 0.1, "{distance}");
+        // The lone point of the first segment.
+        assert_eq!(track.min_distance_to(Point::new(10.0, 0.0)), Some(0.0));
+        // Halfway between the segments doesn't count.
+        assert!(!track.passes_within(Point::new(5.0, 25.0), 100_000.0));
+    }
+
+    #[test]
+    fn track_overlap() {
+        let track = |coords: Vec<(f64, f64)>| {
+            let mut track = Track::new();
+            track.segments.push(TrackSegment::from(
+                coords.into_iter().collect::<geo_types::LineString>(),
+            ));
+            track
+        };
+        let north = track(vec![(0.0, 0.0), (0.0, 0.01)]);
+        let half = track(vec![(0.0001, 0.005), (0.0001, 0.01), (0.0001, 0.02)]);
+
+        let overlap = north.overlap_percent(&north, 1.0).unwrap();
+        assert!((overlap - 100.0).abs() < 1e-9, "{overlap}");
+        let overlap = north.overlap_percent(&half, 15.0).unwrap();
+        assert!((overlap - 50.0).abs() < 1.0, "{overlap}");
+        let overlap = half.overlap_percent(&north, 15.0).unwrap();
+        assert!((overlap - 100.0 / 3.0).abs() < 1.0, "{overlap}");
+        assert_eq!(north.overlap_percent(&half, 5.0), Some(0.0));
+        assert_eq!(north.overlap_percent(&Track::new(), 100.0), Some(0.0));
+        assert_eq!(Track::new().overlap_percent(&north, 100.0), None);
+    }
+}
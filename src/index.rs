@@ -0,0 +1,173 @@
+//! index builds an R-tree over the points of a document, for looking up
+//! points by position in big files without scanning all of them.
+
+use geo_types::{CoordFloat, Point, Rect};
+use rstar::primitives::GeomWithData;
+use rstar::{RTree, AABB};
+
+use crate::measure::position;
+use crate::{Gpx, Location};
+
+/// An R-tree over the waypoints, route points and track points of a
+/// document, see [`Gpx::build_rtree`].
+///
+/// Points are indexed by their longitude and latitude, so distances are
+/// measured in degrees. Away from the poles and the antimeridian this finds
+/// the same nearest points as geodesic distances would, mostly.
+#[derive(Clone, Debug)]
+pub struct PointIndex {
+    tree: RTree<GeomWithData<Point<f64>, Location>>,
+}
+
+impl PointIndex {
+    /// Gives the location of the point nearest to `point`, or `None` if the
+    /// document has no points.
+    pub fn nearest_waypoint(&self, point: Point<f64>) -> Option<Location> {
+        self.tree.nearest_neighbor(&point).map(|entry| entry.data)
+    }
+
+    /// Gives the locations of the points within `rect`, including its
+    /// edges, in no particular order.
+    pub fn points_within(&self, rect: Rect<f64>) -> Vec<Location> {
+        let envelope = AABB::from_corners(rect.min().into(), rect.max().into());
+        self.tree
+            .locate_in_envelope(&envelope)
+            .map(|entry| entry.data)
+            .collect()
+    }
+
+    /// Gives the R-tree itself, for queries beyond the ones above.
+    pub fn rtree(&self) -> &RTree<GeomWithData<Point<f64>, Location>> {
+        &self.tree
+    }
+
+    /// Gives the number of indexed points.
+    pub fn len(&self) -> usize {
+        self.tree.size()
+    }
+
+    /// Tells whether no points are indexed.
+    pub fn is_empty(&self) -> bool {
+        self.tree.size() == 0
+    }
+}
+
+impl<T: CoordFloat> Gpx<T> {
+    /// Builds an R-tree over all waypoints, route points and track points,
+    /// for finding points by position in logarithmic rather than linear
+    /// time. The index refers to the points by their [`Location`], which
+    /// [`Gpx::point_at`] resolves, so it goes stale once points are added
+    /// or removed.
+    ///
+    /// ```
+    /// use geo_types::{coord, Point, Rect};
+    /// use gpx::{Gpx, Location, Waypoint};
+    ///
+    /// let mut gpx: Gpx = Default::default();
+    /// gpx.waypoints.push(Waypoint::new(Point::new(-121.97, 37.24)));
+    /// gpx.waypoints.push(Waypoint::new(Point::new(2.35, 48.86)));
+    ///
+    /// let index = gpx.build_rtree();
+    /// let nearest = index.nearest_waypoint(Point::new(2.0, 49.0)).unwrap();
+    /// assert_eq!(nearest, Location::Waypoint { index: 1 });
+    /// assert_eq!(gpx.point_at(nearest), Some(&gpx.waypoints[1]));
+    ///
+    /// let california = Rect::new(coord! { x: -125.0, y: 32.0 }, coord! { x: -114.0, y: 42.0 });
+    /// assert_eq!(index.points_within(california), [Location::Waypoint { index: 0 }]);
+    /// ```
+    pub fn build_rtree(&self) -> PointIndex {
+        let mut entries = Vec::new();
+        for (index, waypoint) in self.waypoints.iter().enumerate() {
+            entries.push(GeomWithData::new(
+                position(waypoint),
+                Location::Waypoint { index },
+            ));
+        }
+        for (route, route_points) in self.routes.iter().enumerate() {
+            for (point, waypoint) in route_points.points.iter().enumerate() {
+                entries.push(GeomWithData::new(
+                    position(waypoint),
+                    Location::RoutePoint { route, point },
+                ));
+            }
+        }
+        for (track, track_segments) in self.tracks.iter().enumerate() {
+            for (segment, track_segment) in track_segments.segments.iter().enumerate() {
+                for (point, waypoint) in track_segment.points.iter().enumerate() {
+                    let location = Location::TrackPoint {
+                        track,
+                        segment,
+                        point,
+                    };
+                    entries.push(GeomWithData::new(position(waypoint), location));
+                }
+            }
+        }
+        PointIndex {
+            tree: RTree::bulk_load(entries),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::{coord, line_string, Point, Rect};
+
+    use crate::{Gpx, Location, Route, Track, TrackSegment, Waypoint};
+
+    #[test]
+    fn point_index_queries() {
+        let mut gpx = Gpx::default();
+        gpx.waypoints.push(Waypoint::new(Point::new(10.0, 10.0)));
+        gpx.routes.push(Route::from(
+            line_string![(x: 0.0, y: 0.0), (x: 1.0, y: 1.0)],
+        ));
+        let mut track = Track::new();
+        track.segments.push(TrackSegment::from(line_string![
+            (x: 5.0, y: 5.0),
+            (x: 6.0, y: 6.0),
+            (x: 7.0, y: 7.0),
+        ]));
+        gpx.tracks.push(track);
+
+        let index = gpx.build_rtree();
+        assert_eq!(index.len(), 6);
+        assert_eq!(
+            index.nearest_waypoint(Point::new(0.9, 1.2)),
+            Some(Location::RoutePoint { route: 0, point: 1 })
+        );
+        assert_eq!(
+            index.nearest_waypoint(Point::new(20.0, 20.0)),
+            Some(Location::Waypoint { index: 0 })
+        );
+
+        let mut within = index.points_within(Rect::new(
+            coord! { x: 0.5, y: 0.5 },
+            coord! { x: 6.0, y: 6.0 },
+        ));
+        within.sort_by_key(|location| format!("{location:?}"));
+        assert_eq!(
+            within,
+            [
+                Location::RoutePoint { route: 0, point: 1 },
+                Location::TrackPoint {
+                    track: 0,
+                    segment: 0,
+                    point: 0
+                },
+                Location::TrackPoint {
+                    track: 0,
+                    segment: 0,
+                    point: 1
+                },
+            ]
+        );
+        for location in within {
+            assert!(gpx.point_at(location).is_some());
+        }
+
+        let empty = Gpx::<f64>::default().build_rtree();
+        assert!(empty.is_empty());
+        assert_eq!(empty.nearest_waypoint(Point::new(0.0, 0.0)), None);
+    }
+}
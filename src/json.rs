@@ -0,0 +1,676 @@
+//! json converts documents to and from a stable JSON representation, so
+//! that web frontends can use parsed documents without an XML parser.
+//!
+//! Unlike the `use-serde` derives, which follow the Rust types and may change
+//! along with them, this mapping is part of the crate's stable interface. It
+//! mirrors the GPX schema, with the element names spelled out:
+//!
+//! ```json
+//! {
+//!   "version": "1.1",
+//!   "creator": "https://github.com/georust/gpx",
+//!   "metadata": {
+//!     "name": "Ride", "description": "…", "keywords": "…",
+//!     "time": "2023-06-01T12:00:00Z",
+//!     "author": { "name": "…", "email": "…", "link": { "href": "…" } },
+//!     "copyright": { "author": "…", "year": 2023, "license": "…" },
+//!     "links": [{ "href": "https://example.com", "text": "…", "type": "…" }],
+//!     "bounds": [-121.97, 37.24, -121.96, 37.25]
+//!   },
+//!   "waypoints": [{ "lat": 37.24, "lon": -121.97, "ele": 12.5 }],
+//!   "tracks": [{ "name": "…", "segments": [[{ "lat": 37.24, "lon": -121.97 }]] }],
+//!   "routes": [{ "name": "…", "points": [{ "lat": 37.24, "lon": -121.97 }] }]
+//! }
+//! ```
+//!
+//! Tracks and routes also have `comment`, `description`, `source`, `links`,
+//! `type` and `number`, and tracks the Garmin `display_color`, like
+//! `"DarkRed"`. Points have `lat` and `lon` and optionally `ele`,
+//! `time`, `speed`, `name`, `comment`, `description`, `source`, `links`,
+//! `symbol`, `type`, `geoidheight`, `fix` (`"none"`, `"2d"`, `"3d"`,
+//! `"dgps"`, `"pps"` or any other string), `sat`, `hdop`, `vdop`, `pdop`,
+//! `dgps_age`, `dgpsid` and `extension`, which holds the Garmin
+//! `TrackPointExtension` as `atemp`, `wtemp`, `depth`, `hr` and `cad`.
+//! Times are RFC 3339 strings in UTC, bounds are
+//! `[min lon, min lat, max lon, max lat]`. Missing values and empty lists
+//! are left out, as are the attributes of the root element, which only
+//! matter to XML.
+//!
+//! Numbers are written and read as `f64`, so only `Gpx<f64>` documents
+//! convert to and from JSON.
+
+use std::sync::Arc;
+
+use geo_types::{coord, Point, Rect};
+use serde::{Deserialize, Serialize};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::errors::{GpxError, GpxResult};
+use crate::{
+    DisplayColor, Fix, Gpx, GpxCopyright, GpxVersion, Link, Metadata, Person, Route, Time, Track,
+    TrackPointExtension, TrackSegment, Waypoint, WaypointDetails,
+};
+
+/// Converts a document to its JSON representation.
+///
+/// ```
+/// use geo_types::Point;
+/// use gpx::{Gpx, GpxVersion, Waypoint};
+///
+/// let mut gpx = Gpx {
+///     version: GpxVersion::Gpx11,
+///     ..Default::default()
+/// };
+/// gpx.waypoints.push(Waypoint::new(Point::new(-121.97, 37.24)));
+///
+/// assert_eq!(
+///     gpx::json::to_string(&gpx).unwrap(),
+///     r#"{"version":"1.1","waypoints":[{"lat":37.24,"lon":-121.97}]}"#
+/// );
+/// ```
+pub fn to_string(gpx: &Gpx) -> GpxResult<String> {
+    Ok(serde_json::to_string(&JsonGpx::try_from(gpx)?)?)
+}
+
+/// Converts a document to its JSON representation, indented for humans.
+pub fn to_string_pretty(gpx: &Gpx) -> GpxResult<String> {
+    Ok(serde_json::to_string_pretty(&JsonGpx::try_from(gpx)?)?)
+}
+
+/// Reads a document from its JSON representation.
+///
+/// ```
+/// let json = r#"{"version":"1.1","tracks":[{"segments":[[{"lat":37.24,"lon":-121.97}]]}]}"#;
+///
+/// let gpx = gpx::json::from_str(json).unwrap();
+/// assert_eq!(gpx.tracks[0].segments[0].points[0].point().y(), 37.24);
+/// ```
+pub fn from_str(json: &str) -> GpxResult<Gpx> {
+    serde_json::from_str::<JsonGpx>(json)?.try_into()
+}
+
+fn is_empty<V>(value: &[V]) -> bool {
+    value.is_empty()
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonGpx {
+    version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    creator: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    metadata: Option<JsonMetadata>,
+    #[serde(default, skip_serializing_if = "is_empty")]
+    waypoints: Vec<JsonWaypoint>,
+    #[serde(default, skip_serializing_if = "is_empty")]
+    tracks: Vec<JsonTrack>,
+    #[serde(default, skip_serializing_if = "is_empty")]
+    routes: Vec<JsonRoute>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    author: Option<JsonPerson>,
+    #[serde(default, skip_serializing_if = "is_empty")]
+    links: Vec<JsonLink>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    keywords: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    copyright: Option<JsonCopyright>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bounds: Option<[f64; 4]>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonPerson {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    link: Option<JsonLink>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonLink {
+    href: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    type_: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonCopyright {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    year: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    license: Option<String>,
+}
+
+/// The descriptive fields shared by tracks and routes.
+#[derive(Serialize, Deserialize)]
+struct JsonPathInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+    #[serde(default, skip_serializing_if = "is_empty")]
+    links: Vec<JsonLink>,
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    type_: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    number: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonTrack {
+    #[serde(flatten)]
+    info: JsonPathInfo,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    display_color: Option<String>,
+    #[serde(default, skip_serializing_if = "is_empty")]
+    segments: Vec<Vec<JsonWaypoint>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonRoute {
+    #[serde(flatten)]
+    info: JsonPathInfo,
+    #[serde(default, skip_serializing_if = "is_empty")]
+    points: Vec<JsonWaypoint>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonWaypoint {
+    lat: f64,
+    lon: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ele: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    speed: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+    #[serde(default, skip_serializing_if = "is_empty")]
+    links: Vec<JsonLink>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    symbol: Option<String>,
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    type_: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    geoidheight: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fix: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sat: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hdop: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    vdop: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pdop: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dgps_age: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dgpsid: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    extension: Option<JsonExtension>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonExtension {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    atemp: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    wtemp: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    depth: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hr: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cad: Option<u16>,
+}
+
+fn format_time(time: Option<Time>) -> GpxResult<Option<String>> {
+    time.map(|time| Ok(OffsetDateTime::from(time).format(&Rfc3339)?))
+        .transpose()
+}
+
+fn parse_time(time: Option<String>) -> GpxResult<Option<Time>> {
+    time.map(|time| crate::parser::time::parse(&time))
+        .transpose()
+}
+
+fn to_links(links: &[Link]) -> Vec<JsonLink> {
+    links.iter().cloned().map(JsonLink::from).collect()
+}
+
+fn from_links(links: Vec<JsonLink>) -> Vec<Link> {
+    links.into_iter().map(Link::from).collect()
+}
+
+impl TryFrom<&Gpx> for JsonGpx {
+    type Error = GpxError;
+
+    fn try_from(gpx: &Gpx) -> GpxResult<JsonGpx> {
+        let version = match gpx.version {
+            GpxVersion::Gpx10 => "1.0",
+            GpxVersion::Gpx11 => "1.1",
+            version => return Err(GpxError::UnknownVersionError(version)),
+        };
+        Ok(JsonGpx {
+            version: version.to_string(),
+            creator: gpx.creator.clone(),
+            metadata: gpx.metadata.as_ref().map(TryFrom::try_from).transpose()?,
+            waypoints: gpx
+                .waypoints
+                .iter()
+                .map(TryFrom::try_from)
+                .collect::<GpxResult<_>>()?,
+            tracks: gpx
+                .tracks
+                .iter()
+                .map(TryFrom::try_from)
+                .collect::<GpxResult<_>>()?,
+            routes: gpx
+                .routes
+                .iter()
+                .map(TryFrom::try_from)
+                .collect::<GpxResult<_>>()?,
+        })
+    }
+}
+
+impl TryFrom<JsonGpx> for Gpx {
+    type Error = GpxError;
+
+    fn try_from(json: JsonGpx) -> GpxResult<Gpx> {
+        let version = match json.version.as_str() {
+            "1.0" => GpxVersion::Gpx10,
+            "1.1" => GpxVersion::Gpx11,
+            _ => return Err(GpxError::UnknownVersionError(GpxVersion::Unknown)),
+        };
+        Ok(Gpx {
+            version,
+            creator: json.creator,
+            metadata: json.metadata.map(TryFrom::try_from).transpose()?,
+            waypoints: json
+                .waypoints
+                .into_iter()
+                .map(TryFrom::try_from)
+                .collect::<GpxResult<_>>()?,
+            tracks: json
+                .tracks
+                .into_iter()
+                .map(TryFrom::try_from)
+                .collect::<GpxResult<_>>()?,
+            routes: json
+                .routes
+                .into_iter()
+                .map(TryFrom::try_from)
+                .collect::<GpxResult<_>>()?,
+            root_attributes: Vec::new(),
+            comments: Vec::new(),
+        })
+    }
+}
+
+impl TryFrom<&Metadata> for JsonMetadata {
+    type Error = GpxError;
+
+    fn try_from(metadata: &Metadata) -> GpxResult<JsonMetadata> {
+        Ok(JsonMetadata {
+            name: metadata.name.clone(),
+            description: metadata.description.clone(),
+            author: metadata.author.clone().map(From::from),
+            links: to_links(&metadata.links),
+            time: format_time(metadata.time)?,
+            keywords: metadata.keywords.clone(),
+            copyright: metadata.copyright.clone().map(From::from),
+            bounds: metadata.bounds.map(|bounds| {
+                let (min, max) = (bounds.min(), bounds.max());
+                [min.x, min.y, max.x, max.y]
+            }),
+        })
+    }
+}
+
+impl TryFrom<JsonMetadata> for Metadata {
+    type Error = GpxError;
+
+    fn try_from(json: JsonMetadata) -> GpxResult<Metadata> {
+        Ok(Metadata {
+            name: json.name,
+            description: json.description,
+            author: json.author.map(From::from),
+            links: from_links(json.links),
+            time: parse_time(json.time)?,
+            keywords: json.keywords,
+            copyright: json.copyright.map(From::from),
+            bounds: json.bounds.map(|[min_lon, min_lat, max_lon, max_lat]| {
+                Rect::new(
+                    coord! { x: min_lon, y: min_lat },
+                    coord! { x: max_lon, y: max_lat },
+                )
+            }),
+        })
+    }
+}
+
+impl From<Person> for JsonPerson {
+    fn from(person: Person) -> JsonPerson {
+        JsonPerson {
+            name: person.name,
+            email: person.email,
+            link: person.link.map(From::from),
+        }
+    }
+}
+
+impl From<JsonPerson> for Person {
+    fn from(json: JsonPerson) -> Person {
+        Person {
+            name: json.name,
+            email: json.email,
+            link: json.link.map(From::from),
+        }
+    }
+}
+
+impl From<Link> for JsonLink {
+    fn from(link: Link) -> JsonLink {
+        JsonLink {
+            href: link.href,
+            text: link.text,
+            type_: link.type_,
+        }
+    }
+}
+
+impl From<JsonLink> for Link {
+    fn from(json: JsonLink) -> Link {
+        Link {
+            href: json.href,
+            text: json.text,
+            type_: json.type_,
+        }
+    }
+}
+
+impl From<GpxCopyright> for JsonCopyright {
+    fn from(copyright: GpxCopyright) -> JsonCopyright {
+        JsonCopyright {
+            author: copyright.author,
+            year: copyright.year,
+            license: copyright.license,
+        }
+    }
+}
+
+impl From<JsonCopyright> for GpxCopyright {
+    fn from(json: JsonCopyright) -> GpxCopyright {
+        GpxCopyright {
+            author: json.author,
+            year: json.year,
+            license: json.license,
+        }
+    }
+}
+
+impl TryFrom<&Track> for JsonTrack {
+    type Error = GpxError;
+
+    fn try_from(track: &Track) -> GpxResult<JsonTrack> {
+        Ok(JsonTrack {
+            info: JsonPathInfo {
+                name: track.name.clone(),
+                comment: track.comment.clone(),
+                description: track.description.clone(),
+                source: track.source.clone(),
+                links: to_links(&track.links),
+                type_: track.type_.clone(),
+                number: track.number,
+            },
+            display_color: track
+                .display_color
+                .as_ref()
+                .map(|color| color.name().to_string()),
+            segments: track
+                .segments
+                .iter()
+                .map(|segment| segment.points.iter().map(TryFrom::try_from).collect())
+                .collect::<GpxResult<_>>()?,
+        })
+    }
+}
+
+impl TryFrom<JsonTrack> for Track {
+    type Error = GpxError;
+
+    fn try_from(json: JsonTrack) -> GpxResult<Track> {
+        let mut track = Track::new();
+        track.name = json.info.name;
+        track.comment = json.info.comment;
+        track.description = json.info.description;
+        track.source = json.info.source;
+        track.links = from_links(json.info.links);
+        track.type_ = json.info.type_;
+        track.number = json.info.number;
+        track.display_color = json.display_color.map(DisplayColor::from);
+        for points in json.segments {
+            track.segments.push(TrackSegment {
+                points: points
+                    .into_iter()
+                    .map(TryFrom::try_from)
+                    .collect::<GpxResult<_>>()?,
+            });
+        }
+        Ok(track)
+    }
+}
+
+impl TryFrom<&Route> for JsonRoute {
+    type Error = GpxError;
+
+    fn try_from(route: &Route) -> GpxResult<JsonRoute> {
+        Ok(JsonRoute {
+            info: JsonPathInfo {
+                name: route.name.clone(),
+                comment: route.comment.clone(),
+                description: route.description.clone(),
+                source: route.source.clone(),
+                links: to_links(&route.links),
+                type_: route.type_.clone(),
+                number: route.number,
+            },
+            points: route
+                .points
+                .iter()
+                .map(TryFrom::try_from)
+                .collect::<GpxResult<_>>()?,
+        })
+    }
+}
+
+impl TryFrom<JsonRoute> for Route {
+    type Error = GpxError;
+
+    fn try_from(json: JsonRoute) -> GpxResult<Route> {
+        Ok(Route {
+            name: json.info.name,
+            comment: json.info.comment,
+            description: json.info.description,
+            source: json.info.source,
+            links: from_links(json.info.links),
+            type_: json.info.type_,
+            number: json.info.number,
+            points: json
+                .points
+                .into_iter()
+                .map(TryFrom::try_from)
+                .collect::<GpxResult<_>>()?,
+        })
+    }
+}
+
+impl TryFrom<&Waypoint> for JsonWaypoint {
+    type Error = GpxError;
+
+    fn try_from(waypoint: &Waypoint) -> GpxResult<JsonWaypoint> {
+        let fix = waypoint.details().fix.as_ref().map(|fix| match fix {
+            Fix::None => "none".to_string(),
+            Fix::TwoDimensional => "2d".to_string(),
+            Fix::ThreeDimensional => "3d".to_string(),
+            Fix::DGPS => "dgps".to_string(),
+            Fix::PPS => "pps".to_string(),
+            Fix::Other(string) => string.clone(),
+        });
+        let extension = waypoint
+            .track_point_extension
+            .as_ref()
+            .map(|extension| JsonExtension {
+                atemp: extension.air_temperature,
+                wtemp: extension.water_temperature,
+                depth: extension.depth,
+                hr: extension.heart_rate,
+                cad: extension.cadence,
+            });
+        Ok(JsonWaypoint {
+            lat: waypoint.point().y(),
+            lon: waypoint.point().x(),
+            ele: waypoint.elevation,
+            time: format_time(waypoint.time)?,
+            speed: waypoint.speed,
+            name: waypoint.details().name.clone(),
+            comment: waypoint.details().comment.clone(),
+            description: waypoint.details().description.clone(),
+            source: waypoint.details().source.clone(),
+            links: to_links(&waypoint.details().links),
+            symbol: waypoint.details().symbol.as_deref().map(String::from),
+            type_: waypoint.details().type_.as_deref().map(String::from),
+            geoidheight: waypoint.details().geoidheight,
+            fix,
+            sat: waypoint.details().sat,
+            hdop: waypoint.details().hdop,
+            vdop: waypoint.details().vdop,
+            pdop: waypoint.details().pdop,
+            dgps_age: waypoint.details().dgps_age,
+            dgpsid: waypoint.details().dgpsid,
+            extension,
+        })
+    }
+}
+
+impl TryFrom<JsonWaypoint> for Waypoint {
+    type Error = GpxError;
+
+    fn try_from(json: JsonWaypoint) -> GpxResult<Waypoint> {
+        if !(-90.0..=90.0).contains(&json.lat) {
+            return Err(GpxError::LonLatOutOfBoundsError(
+                "latitude",
+                "[-90.0, 90.0]",
+                json.lat,
+            ));
+        }
+        if !(-180.0..180.0).contains(&json.lon) {
+            return Err(GpxError::LonLatOutOfBoundsError(
+                "Longitude",
+                "[-180.0, 180.0)",
+                json.lon,
+            ));
+        }
+        let mut waypoint = Waypoint::new(Point::new(json.lon, json.lat));
+        waypoint.elevation = json.ele;
+        waypoint.time = parse_time(json.time)?;
+        waypoint.speed = json.speed;
+        waypoint.set_details(WaypointDetails {
+            name: json.name,
+            comment: json.comment,
+            description: json.description,
+            source: json.source,
+            links: from_links(json.links),
+            symbol: json.symbol.map(Arc::from),
+            type_: json.type_.map(Arc::from),
+            geoidheight: json.geoidheight,
+            fix: json.fix.map(|fix| match fix.as_str() {
+                "none" => Fix::None,
+                "2d" => Fix::TwoDimensional,
+                "3d" => Fix::ThreeDimensional,
+                "dgps" => Fix::DGPS,
+                "pps" => Fix::PPS,
+                _ => Fix::Other(fix),
+            }),
+            sat: json.sat,
+            hdop: json.hdop,
+            vdop: json.vdop,
+            pdop: json.pdop,
+            dgps_age: json.dgps_age,
+            dgpsid: json.dgpsid,
+            ..Default::default()
+        });
+        waypoint.track_point_extension = json.extension.map(|json| TrackPointExtension {
+            air_temperature: json.atemp,
+            water_temperature: json.wtemp,
+            depth: json.depth,
+            heart_rate: json.hr,
+            cadence: json.cad,
+        });
+        Ok(waypoint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::BufReader;
+
+    use super::{from_str, to_string, to_string_pretty};
+    use crate::{read, Gpx};
+
+    #[test]
+    fn json_round_trip() {
+        for fixture in [
+            "tests/fixtures/wikipedia_example.gpx",
+            "tests/fixtures/garmin-activity.gpx",
+            "tests/fixtures/gpsies_example.gpx",
+            "tests/fixtures/with_accuracy.gpx",
+            "tests/fixtures/caltopo-export.gpx",
+        ] {
+            let mut gpx: Gpx = read(BufReader::new(File::open(fixture).unwrap())).unwrap();
+            gpx.root_attributes.clear();
+            let json = to_string(&gpx).unwrap();
+            assert_eq!(from_str(&json).unwrap(), gpx, "{fixture}");
+            assert_eq!(from_str(&to_string_pretty(&gpx).unwrap()).unwrap(), gpx);
+        }
+    }
+
+    #[test]
+    fn json_rejects_invalid_documents() {
+        assert!(from_str(r#"{"version":"2.0"}"#).is_err());
+        assert!(from_str(r#"{"version":"1.1","waypoints":[{"lat":91,"lon":0}]}"#).is_err());
+        assert!(from_str(r#"{"version":"1.1","waypoints":[{"lat":0}]}"#).is_err());
+        assert!(from_str(r#"{"version":"1.1","metadata":{"time":"yesterday"}}"#).is_err());
+    }
+}
@@ -0,0 +1,498 @@
+//! kml converts documents to and from KML, the format used by Google Earth,
+//! with the georust `kml` crate.
+//!
+//! Waypoints map to `Point` placemarks and tracks to placemarks holding one
+//! line per segment. Tracks where every point has a time are written as a
+//! `gx:MultiTrack` of `gx:Track`s, so the times survive a round trip; other
+//! tracks and routes are written as `LineString`s. KML does not tell routes
+//! and tracks apart, so all lines are read back as tracks.
+//!
+//! KML lines need two points, so lines of a single point are written as a
+//! `Point` and read back as waypoints. Elevations are only written if every
+//! point of a line has one, as KML doesn't allow mixing tuples with and
+//! without altitude.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{BufReader, Read, Write};
+
+use geo_types::{CoordFloat, Point};
+use kml::types::{Coord, Element, Geometry, MultiGeometry, Placemark};
+use kml::{Kml, KmlDocument, KmlReader, KmlVersion, KmlWriter};
+
+use crate::errors::{GpxError, GpxResult};
+use crate::measure::to_f64;
+use crate::parser::time;
+use crate::{Gpx, GpxVersion, Metadata, Time, Track, TrackSegment, Waypoint, WaypointDetails};
+
+const KML_NAMESPACE: &str = "http://www.opengis.net/kml/2.2";
+const GX_NAMESPACE: &str = "http://www.google.com/kml/ext/2.2";
+
+/// Writes a document in KML format.
+///
+/// ```
+/// use geo_types::Point;
+/// use gpx::{to_kml, Gpx, Waypoint};
+///
+/// let mut gpx: Gpx = Default::default();
+/// let mut summit = Waypoint::new(Point::new(-121.97, 37.24));
+/// summit.details_mut().name = Some("Summit".into());
+/// gpx.waypoints.push(summit);
+///
+/// let mut kml = Vec::new();
+/// to_kml(&gpx, &mut kml).unwrap();
+/// assert!(String::from_utf8(kml).unwrap().contains("<coordinates>-121.97,37.24</coordinates>"));
+/// ```
+pub fn to_kml<W: Write, T: CoordFloat + Display>(gpx: &Gpx<T>, mut writer: W) -> GpxResult<()> {
+    let mut elements = Vec::new();
+    if let Some(metadata) = &gpx.metadata {
+        elements.extend(text_element("name", &metadata.name).map(Kml::Element));
+        elements.extend(text_element("description", &metadata.description).map(Kml::Element));
+    }
+    for waypoint in &gpx.waypoints {
+        let details = waypoint.details();
+        elements.push(placemark(
+            &details.name,
+            &details.description,
+            Some(Geometry::Point(
+                coords(std::slice::from_ref(waypoint))[0].into(),
+            )),
+            Vec::new(),
+        ));
+    }
+    for track in &gpx.tracks {
+        let times: Option<Vec<Time>> = track
+            .segments
+            .iter()
+            .flat_map(|segment| &segment.points)
+            .map(|point| point.time)
+            .collect();
+        let placemark = match times {
+            Some(times) if !times.is_empty() => placemark(
+                &track.name,
+                &track.description,
+                None,
+                vec![gx_multi_track(track)?],
+            ),
+            _ => {
+                let geometries = track
+                    .segments
+                    .iter()
+                    .filter(|segment| !segment.points.is_empty())
+                    .map(|segment| line(&segment.points))
+                    .collect();
+                placemark(
+                    &track.name,
+                    &track.description,
+                    Some(Geometry::MultiGeometry(MultiGeometry {
+                        geometries,
+                        attrs: HashMap::new(),
+                    })),
+                    Vec::new(),
+                )
+            }
+        };
+        elements.push(placemark);
+    }
+    for route in &gpx.routes {
+        let geometry = (!route.points.is_empty()).then(|| line(&route.points));
+        elements.push(placemark(
+            &route.name,
+            &route.description,
+            geometry,
+            Vec::new(),
+        ));
+    }
+
+    let document = Kml::KmlDocument(KmlDocument {
+        version: KmlVersion::V22,
+        attrs: HashMap::from([
+            ("xmlns".to_string(), KML_NAMESPACE.to_string()),
+            ("xmlns:gx".to_string(), GX_NAMESPACE.to_string()),
+        ]),
+        elements: vec![Kml::Document {
+            attrs: HashMap::new(),
+            elements,
+        }],
+    });
+    writer.write_all(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n")?;
+    KmlWriter::from_writer(writer).write(&document)?;
+    Ok(())
+}
+
+fn placemark(
+    name: &Option<String>,
+    description: &Option<String>,
+    geometry: Option<Geometry>,
+    children: Vec<Element>,
+) -> Kml {
+    Kml::Placemark(Placemark {
+        name: name.clone(),
+        description: description.clone(),
+        geometry,
+        children,
+        ..Default::default()
+    })
+}
+
+/// Gives the geometry of the line through `points`, which must not be
+/// empty: a `LineString`, or a `Point` if it has a single point.
+fn line<T: CoordFloat>(points: &[Waypoint<T>]) -> Geometry {
+    let coords = coords(points);
+    match coords[..] {
+        [coord] => Geometry::Point(coord.into()),
+        _ => Geometry::LineString(coords.into()),
+    }
+}
+
+/// Writes the segments of `track`, whose points all have times, as the
+/// `gx:Track`s of a `gx:MultiTrack`.
+fn gx_multi_track<T: CoordFloat>(track: &Track<T>) -> GpxResult<Element> {
+    let mut tracks = Vec::new();
+    for segment in track.segments.iter().filter(|s| !s.points.is_empty()) {
+        let mut children = Vec::new();
+        for point in &segment.points {
+            let when = point.time.map(|time| time.format()).transpose()?;
+            children.extend(text_element("when", &when));
+        }
+        for coord in coords(&segment.points) {
+            let values: Vec<String> = [Some(coord.x), Some(coord.y), coord.z]
+                .iter()
+                .flatten()
+                .map(f64::to_string)
+                .collect();
+            children.extend(text_element("gx:coord", &Some(values.join(" "))));
+        }
+        tracks.push(Element {
+            name: "gx:Track".to_string(),
+            children,
+            ..Default::default()
+        });
+    }
+    Ok(Element {
+        name: "gx:MultiTrack".to_string(),
+        children: tracks,
+        ..Default::default()
+    })
+}
+
+fn text_element(name: &str, text: &Option<String>) -> Option<Element> {
+    text.as_ref().map(|text| Element {
+        name: name.to_string(),
+        content: Some(text.clone()),
+        ..Default::default()
+    })
+}
+
+/// Gives the KML coordinates of `points`: longitude, latitude and, if all
+/// points have one, the elevation.
+fn coords<T: CoordFloat>(points: &[Waypoint<T>]) -> Vec<Coord> {
+    let with_elevation = points.iter().all(|point| point.elevation.is_some());
+    points
+        .iter()
+        .map(|point| {
+            let position = point.point();
+            Coord::new(
+                to_f64(position.x()),
+                to_f64(position.y()),
+                point.elevation.filter(|_| with_elevation).map(to_f64),
+            )
+        })
+        .collect()
+}
+
+/// Reads a document in KML format.
+///
+/// `Point` placemarks become waypoints, while placemarks with `LineString`
+/// or `gx:Track` geometries, also inside a `MultiGeometry` or
+/// `gx:MultiTrack`, become tracks with one segment per line. Other
+/// geometries, styles and folders are ignored.
+///
+/// ```
+/// use gpx::from_kml;
+///
+/// let kml = r#"<kml xmlns="http://www.opengis.net/kml/2.2"><Placemark>
+///     <name>Loop</name>
+///     <LineString><coordinates>8.5,47.3,400 8.6,47.4,420</coordinates></LineString>
+/// </Placemark></kml>"#;
+///
+/// let gpx = from_kml(kml.as_bytes()).unwrap();
+/// assert_eq!(gpx.tracks[0].name.as_deref(), Some("Loop"));
+/// assert_eq!(gpx.tracks[0].segments[0].points[1].elevation, Some(420.0));
+/// ```
+pub fn from_kml<R: Read>(reader: R) -> GpxResult<Gpx> {
+    let kml: Kml = KmlReader::from_reader(BufReader::new(reader)).read()?;
+    let mut gpx = Gpx {
+        version: GpxVersion::Gpx11,
+        ..Default::default()
+    };
+    add_elements(std::slice::from_ref(&kml), true, &mut gpx)?;
+    Ok(gpx)
+}
+
+/// Adds the placemarks of `elements` and the containers within to `gpx`.
+/// The name and description of the `top` document become the metadata.
+fn add_elements(elements: &[Kml], top: bool, gpx: &mut Gpx) -> GpxResult<()> {
+    for element in elements {
+        match element {
+            Kml::KmlDocument(document) => add_elements(&document.elements, top, gpx)?,
+            Kml::Document { elements, .. } => {
+                add_elements(elements, false, gpx)?;
+                if top {
+                    for element in elements {
+                        let Kml::Element(Element { name, content, .. }) = element else {
+                            continue;
+                        };
+                        if name != "name" && name != "description" {
+                            continue;
+                        }
+                        let text = content.as_deref().map(|text| text.trim().to_string());
+                        let metadata = gpx.metadata.get_or_insert_with(Metadata::default);
+                        if name == "name" {
+                            metadata.name = text;
+                        } else {
+                            metadata.description = text;
+                        }
+                    }
+                }
+            }
+            Kml::Folder(folder) => add_elements(&folder.elements, false, gpx)?,
+            Kml::Placemark(placemark) => add_placemark(placemark, gpx)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Geometries collected from a placemark.
+#[derive(Default)]
+struct Lines {
+    points: Vec<Waypoint>,
+    segments: Vec<TrackSegment>,
+}
+
+impl Lines {
+    fn add_geometry(&mut self, geometry: &Geometry) -> GpxResult<()> {
+        match geometry {
+            Geometry::Point(point) => self.points.push(waypoint(&point.coord)?),
+            Geometry::LineString(line) => self.segments.push(TrackSegment {
+                points: line.coords.iter().map(waypoint).collect::<GpxResult<_>>()?,
+            }),
+            Geometry::MultiGeometry(multi) => {
+                for geometry in &multi.geometries {
+                    self.add_geometry(geometry)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Adds the `gx:Track`s of `element`, also inside a `gx:MultiTrack`.
+    fn add_gx_tracks(&mut self, element: &Element) -> GpxResult<()> {
+        match element.name.as_str() {
+            "MultiTrack" => {
+                for child in &element.children {
+                    self.add_gx_tracks(child)?;
+                }
+            }
+            "Track" => {
+                let texts = |name: &'static str| {
+                    element
+                        .children
+                        .iter()
+                        .filter(move |child| child.name == name)
+                        .map(|child| child.content.as_deref().unwrap_or_default().trim())
+                };
+                let times: Vec<Time> = texts("when").map(time::parse).collect::<GpxResult<_>>()?;
+                let mut points: Vec<Waypoint> = texts("coord")
+                    .map(|text| {
+                        let values = text.split_whitespace().collect::<Vec<_>>().join(",");
+                        let coord = values.parse().map_err(|_| {
+                            GpxError::InvalidElementLacksAttribute("latitude", "coord")
+                        })?;
+                        waypoint(&coord)
+                    })
+                    .collect::<GpxResult<_>>()?;
+                if times.len() == points.len() {
+                    for (point, time) in points.iter_mut().zip(times) {
+                        point.time = Some(time);
+                    }
+                }
+                self.segments.push(TrackSegment { points });
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn add_placemark(placemark: &Placemark, gpx: &mut Gpx) -> GpxResult<()> {
+    let mut lines = Lines::default();
+    if let Some(geometry) = &placemark.geometry {
+        lines.add_geometry(geometry)?;
+    }
+    for child in &placemark.children {
+        lines.add_gx_tracks(child)?;
+    }
+    let trim = |text: &Option<String>| text.as_deref().map(|text| text.trim().to_string());
+    let name = trim(&placemark.name);
+    let description = trim(&placemark.description);
+    for mut point in lines.points {
+        point.set_details(WaypointDetails {
+            name: name.clone(),
+            description: description.clone(),
+            ..Default::default()
+        });
+        gpx.waypoints.push(point);
+    }
+    if !lines.segments.is_empty() {
+        let mut track = Track::new();
+        track.name = name;
+        track.description = description;
+        track.segments = lines.segments;
+        gpx.tracks.push(track);
+    }
+    Ok(())
+}
+
+/// Gives the waypoint at a longitude, latitude and optional elevation.
+fn waypoint(coord: &Coord) -> GpxResult<Waypoint> {
+    let (longitude, latitude) = (coord.x, coord.y);
+    if !(-90.0..=90.0).contains(&latitude) {
+        return Err(GpxError::LonLatOutOfBoundsError(
+            "latitude",
+            "[-90.0, 90.0]",
+            latitude,
+        ));
+    }
+    if !(-180.0..=180.0).contains(&longitude) {
+        return Err(GpxError::LonLatOutOfBoundsError(
+            "Longitude",
+            "[-180.0, 180.0]",
+            longitude,
+        ));
+    }
+    let mut waypoint = Waypoint::new(Point::new(longitude, latitude));
+    waypoint.elevation = coord.z;
+    Ok(waypoint)
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::Point;
+
+    use super::{from_kml, to_kml};
+    use crate::{Duration, Gpx, Route, Time, Track, TrackSegment, Waypoint};
+
+    fn timed_segment(start: i64) -> TrackSegment {
+        let mut segment = TrackSegment::new();
+        for i in 0..3 {
+            let mut point = Waypoint::new(Point::new(8.5 + (start + i) as f64 * 0.01, 47.3));
+            point.time = Some(Time::UNIX_EPOCH + Duration::seconds(start + i));
+            segment.points.push(point);
+        }
+        segment
+    }
+
+    #[test]
+    fn kml_round_trip() {
+        let mut gpx: Gpx = Default::default();
+        let mut summit = Waypoint::new(Point::new(-121.97, 37.24));
+        summit.details_mut().name = Some("Summit".into());
+        summit.elevation = Some(1200.5);
+        gpx.waypoints.push(summit);
+
+        let mut timed = Track::new();
+        timed.name = Some("Ride".into());
+        timed.segments = vec![timed_segment(0), timed_segment(10)];
+        gpx.tracks.push(timed.clone());
+        let untimed = TrackSegment {
+            points: vec![
+                Waypoint::new(Point::new(9.0, 47.0)),
+                Waypoint::new(Point::new(9.1, 47.1)),
+            ],
+        };
+        let mut mixed = Track::new();
+        mixed.segments = vec![timed_segment(20), untimed.clone()];
+        gpx.tracks.push(mixed);
+        gpx.routes.push(Route {
+            points: untimed.points.clone(),
+            ..Default::default()
+        });
+
+        let mut kml = Vec::new();
+        to_kml(&gpx, &mut kml).unwrap();
+        let read = from_kml(kml.as_slice()).unwrap();
+
+        assert_eq!(read.waypoints, gpx.waypoints);
+        assert_eq!(read.tracks.len(), 3);
+        assert_eq!(read.tracks[0], timed);
+        // Tracks with untimed points are written as lines, without times.
+        assert_eq!(read.tracks[1].segments[0].points[0].time, None);
+        assert_eq!(read.tracks[1].segments[1], untimed);
+        assert_eq!(read.tracks[2].segments[0], untimed);
+        assert!(read.routes.is_empty());
+    }
+
+    #[test]
+    fn kml_writes_valid_lines() {
+        let mut high = Waypoint::new(Point::new(9.0, 47.0));
+        high.elevation = Some(500.0);
+        let mut gpx: Gpx = Default::default();
+        gpx.routes.push(Route {
+            points: vec![high.clone(), Waypoint::new(Point::new(9.1, 47.1))],
+            ..Default::default()
+        });
+        gpx.routes.push(Route {
+            points: vec![high],
+            ..Default::default()
+        });
+
+        let mut kml = Vec::new();
+        to_kml(&gpx, &mut kml).unwrap();
+        let text = String::from_utf8(kml).unwrap();
+        // Altitudes are left out rather than mixing 2D and 3D tuples.
+        assert!(text.contains("<coordinates>9,47\n9.1,47.1</coordinates>"));
+        // A line of a single point is a point.
+        assert!(text.contains("<Point>"));
+        assert!(text.contains("<coordinates>9,47,500</coordinates>"));
+
+        let read = from_kml(text.as_bytes()).unwrap();
+        assert_eq!(read.tracks.len(), 1);
+        assert_eq!(read.waypoints.len(), 1);
+    }
+
+    #[test]
+    fn kml_nested_placemarks() {
+        let kml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <kml xmlns="http://www.opengis.net/kml/2.2" xmlns:gx="http://www.google.com/kml/ext/2.2">
+            <Document>
+                <name>Trip</name>
+                <Folder>
+                    <name>Ignored</name>
+                    <Placemark>
+                        <name>Start</name>
+                        <Style><LineStyle><color>ff0000ff</color></LineStyle></Style>
+                        <Point><coordinates> 8.5,47.3 </coordinates></Point>
+                    </Placemark>
+                    <Placemark>
+                        <Polygon><outerBoundaryIs><LinearRing>
+                            <coordinates>0,0 1,0 1,1 0,0</coordinates>
+                        </LinearRing></outerBoundaryIs></Polygon>
+                    </Placemark>
+                </Folder>
+            </Document>
+            </kml>"#;
+
+        let gpx = from_kml(kml.as_bytes()).unwrap();
+        assert_eq!(gpx.metadata.unwrap().name.as_deref(), Some("Trip"));
+        assert_eq!(gpx.waypoints.len(), 1);
+        assert_eq!(gpx.waypoints[0].details().name.as_deref(), Some("Start"));
+        assert!(gpx.tracks.is_empty());
+
+        let invalid =
+            "<kml><Placemark><Point><coordinates>8.5,97.3</coordinates></Point></Placemark></kml>";
+        assert!(from_kml(invalid.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+//! kmz reads and writes KMZ files, zip archives holding a KML document.
+
+use std::fmt::Display;
+use std::io::{Read, Seek, Write};
+
+use geo_types::CoordFloat;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::errors::{GpxError, GpxResult};
+use crate::{from_kml, to_kml, Gpx};
+
+/// Writes a document as a KMZ archive, holding the KML written by
+/// [`to_kml`] as `doc.kml`.
+pub fn to_kmz<W: Write + Seek, T: CoordFloat + Display>(gpx: &Gpx<T>, writer: W) -> GpxResult<()> {
+    let mut archive = ZipWriter::new(writer);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    archive.start_file("doc.kml", options)?;
+    to_kml(gpx, &mut archive)?;
+    archive.finish()?;
+    Ok(())
+}
+
+/// Reads a document from a KMZ archive, see [`from_kml`].
+///
+/// By convention the document is `doc.kml`; archives without one are read
+/// from their first `.kml` file. Images and other files are ignored.
+///
+/// ```
+/// use std::io::Cursor;
+///
+/// use geo_types::Point;
+/// use gpx::{from_kmz, to_kmz, Gpx, Waypoint};
+///
+/// let mut gpx: Gpx = Default::default();
+/// gpx.waypoints.push(Waypoint::new(Point::new(-121.97, 37.24)));
+///
+/// let mut kmz = Cursor::new(Vec::new());
+/// to_kmz(&gpx, &mut kmz).unwrap();
+/// kmz.set_position(0);
+/// assert_eq!(from_kmz(kmz).unwrap().waypoints, gpx.waypoints);
+/// ```
+pub fn from_kmz<R: Read + Seek>(reader: R) -> GpxResult<Gpx> {
+    let mut archive = ZipArchive::new(reader)?;
+    let name = archive
+        .file_names()
+        .filter(|name| name.to_ascii_lowercase().ends_with(".kml"))
+        .min_by_key(|name| *name != "doc.kml")
+        .ok_or(GpxError::MissingArchiveEntry("kml"))?
+        .to_string();
+    let document = archive.by_name(&name)?;
+    from_kml(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    use super::from_kmz;
+    use crate::errors::GpxError;
+
+    fn archive(files: &[(&str, &str)]) -> Cursor<Vec<u8>> {
+        let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            archive.start_file(*name, FileOptions::default()).unwrap();
+            archive.write_all(content.as_bytes()).unwrap();
+        }
+        let mut cursor = archive.finish().unwrap();
+        cursor.set_position(0);
+        cursor
+    }
+
+    #[test]
+    fn kmz_finds_document() {
+        let kml = |name| {
+            format!("<kml><Placemark><name>{name}</name><Point><coordinates>1,2</coordinates></Point></Placemark></kml>")
+        };
+        let (doc, other) = (kml("doc"), kml("other"));
+        let files = [
+            ("images/icon.png", "not a document"),
+            ("files/other.kml", other.as_str()),
+            ("doc.kml", doc.as_str()),
+        ];
+        let gpx = from_kmz(archive(&files)).unwrap();
+        assert_eq!(gpx.waypoints[0].details().name.as_deref(), Some("doc"));
+
+        let gpx = from_kmz(archive(&files[..2])).unwrap();
+        assert_eq!(gpx.waypoints[0].details().name.as_deref(), Some("other"));
+
+        assert!(matches!(
+            from_kmz(archive(&files[..1])),
+            Err(GpxError::MissingArchiveEntry("kml"))
+        ));
+        assert!(from_kmz(Cursor::new(b"not a zip")).is_err());
+    }
+}
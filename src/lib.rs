@@ -32,14 +32,95 @@
 //! ```
 
 // Export our type structs in the root, along with the read and write functions.
-pub use crate::reader::read;
+pub use crate::analysis::{Sampling, Split, Stop};
+pub use crate::approx::Tolerance;
+#[cfg(feature = "rayon")]
+pub use crate::batch::{read_dir, read_many};
+pub use crate::cleanup::CleanupOptions;
+#[cfg(feature = "csv")]
+pub use crate::csv::{from_csv, to_csv, CsvColumn};
+pub use crate::dense::DenseTrackSegment;
+pub use crate::elevation::ElevationProvider;
+pub use crate::events::{GpxEvent, GpxEventReader};
+pub use crate::file::{append_to_path, read_from_path, write_to_path};
+#[cfg(feature = "rstar")]
+pub use crate::index::PointIndex;
+#[cfg(feature = "kml")]
+pub use crate::kml::{from_kml, to_kml};
+#[cfg(feature = "zip")]
+pub use crate::kmz::{from_kmz, to_kmz};
+pub use crate::links::normalize_href;
+pub use crate::lint::{lint, Finding, LintKind, Location, Severity};
+pub use crate::logger::GpxLogger;
+pub use crate::measure::ElevationChange;
+#[cfg(feature = "nmea")]
+pub use crate::nmea::from_nmea;
+pub use crate::raw::{read_raw_tracks, RawPoint, RawTrack, RawTrackSegment};
+pub use crate::reader::{
+    read, read_as, read_from_slice, read_from_slice_as, read_with_options, DuplicatePolicy, Parser,
+    RangePolicy, ReadOptions,
+};
+pub use crate::record::GpsSource;
+pub use crate::sanitize::Extension;
+pub use crate::smooth::{KalmanSmoother, Smoother};
+pub use crate::summary::Summary;
+pub use crate::transform::AppendOptions;
 pub use crate::types::*;
-pub use crate::writer::{write, write_with_event_writer};
+pub use crate::writer::{
+    write, write_to_vec, write_track_from_iter, write_with_event_writer, write_with_hook,
+    write_with_options, DocumentItem, EmptyPolicy, ExtensionWriter, HookElement, WriteHook,
+    WriteOptions,
+};
 
+mod analysis;
+mod approx;
+#[cfg(feature = "rayon")]
+mod batch;
+mod cleanup;
+#[cfg(feature = "csv")]
+mod csv;
+mod dense;
+mod elevation;
+mod events;
+mod file;
+mod fingerprint;
+mod geometry;
+#[cfg(feature = "rstar")]
+mod index;
+#[cfg(feature = "kml")]
+mod kml;
+#[cfg(feature = "zip")]
+mod kmz;
+mod links;
+mod lint;
+mod logger;
+mod measure;
+#[cfg(feature = "nmea")]
+mod nmea;
+mod number;
 mod parser;
+#[cfg(feature = "proj")]
+mod proj;
+mod raw;
 mod reader;
+mod record;
+mod salvage;
+mod sanitize;
+mod simplify;
+mod smooth;
+mod summary;
+#[cfg(feature = "time-tz")]
+mod timezone;
+mod transform;
 mod types;
 mod writer;
 
 // Errors should be namespaced away.
 pub mod errors;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod stats;
+#[cfg(feature = "proptest")]
+pub mod strategies;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,356 @@
+//! links checks and normalizes the URLs of links, for showing documents from
+//! untrusted sources in web pages.
+
+use geo_types::CoordFloat;
+
+use crate::errors::{GpxError, GpxResult};
+use crate::{Gpx, Link, Waypoint};
+
+/// The schemes of URLs that are safe to link to from a web page.
+const SAFE_SCHEMES: &[&str] = &["http", "https", "ftp", "mailto"];
+
+/// Checks and normalizes the URL `href` of a link.
+///
+/// Relative URLs are resolved against `base`, which must be absolute.
+/// URLs with schemes that could run code or read local files when followed
+/// in a browser, like `javascript:`, `data:` or `file:`, fail with
+/// [`GpxError::InvalidUrl`], as do relative URLs without a `base`. Only
+/// `http`, `https`, `ftp` and `mailto` URLs are accepted.
+///
+/// The scheme is lowercased, `.` and `..` segments of the path are
+/// resolved, and characters that aren't allowed in URLs, like spaces and
+/// non-ASCII letters, are percent-encoded. Existing percent-encoding is
+/// kept.
+///
+/// ```
+/// use gpx::normalize_href;
+///
+/// let base = Some("https://example.com/trails/");
+/// assert_eq!(
+///     normalize_href("../photos/Gipfel Süd.jpg", base).unwrap(),
+///     "https://example.com/photos/Gipfel%20S%C3%BCd.jpg"
+/// );
+/// assert_eq!(normalize_href("HTTP://example.com", None).unwrap(), "http://example.com");
+/// assert!(normalize_href("javascript:alert(1)", base).is_err());
+/// assert!(normalize_href("photo.jpg", None).is_err());
+/// ```
+pub fn normalize_href(href: &str, base: Option<&str>) -> GpxResult<String> {
+    let encoded = percent_encode(href);
+    let reference = Parts::of(&encoded);
+    if let Some(scheme) = reference.scheme {
+        check_scheme(href, scheme)?;
+        return Ok(Parts {
+            path: &remove_dot_segments(reference.path),
+            ..reference
+        }
+        .to_string());
+    }
+
+    let base = base.ok_or_else(|| GpxError::InvalidUrl(href.to_string()))?;
+    let base = normalize_href(base, None)?;
+    let base = Parts::of(&base);
+    let merged;
+    let (authority, path, query) = if reference.authority.is_some() {
+        (
+            reference.authority,
+            remove_dot_segments(reference.path),
+            reference.query,
+        )
+    } else if reference.path.is_empty() {
+        (
+            base.authority,
+            base.path.to_string(),
+            reference.query.or(base.query),
+        )
+    } else {
+        let path = if reference.path.starts_with('/') {
+            reference.path
+        } else if base.authority.is_some() && base.path.is_empty() {
+            merged = format!("/{}", reference.path);
+            &merged
+        } else {
+            let directory = base.path.rfind('/').map_or("", |end| &base.path[..=end]);
+            merged = format!("{directory}{}", reference.path);
+            &merged
+        };
+        (base.authority, remove_dot_segments(path), reference.query)
+    };
+    Ok(Parts {
+        scheme: base.scheme,
+        authority,
+        path: &path,
+        query,
+        fragment: reference.fragment,
+    }
+    .to_string())
+}
+
+impl<T: CoordFloat> Gpx<T> {
+    /// Normalizes the URLs of all links of the document with
+    /// [`normalize_href`], removing the links whose URLs are unsafe or
+    /// can't be resolved against `base`.
+    ///
+    /// Gives the number of removed links.
+    ///
+    /// ```
+    /// use gpx::{Gpx, Link, Metadata};
+    ///
+    /// let link = |href: &str| Link {
+    ///     href: href.to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let mut gpx: Gpx = Gpx {
+    ///     metadata: Some(Metadata {
+    ///         links: vec![link("map.html"), link("javascript:alert(1)")],
+    ///         ..Default::default()
+    ///     }),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(gpx.normalize_links(Some("https://example.com/")), 1);
+    /// let links = gpx.metadata.unwrap().links;
+    /// assert_eq!(links[0].href, "https://example.com/map.html");
+    /// ```
+    pub fn normalize_links(&mut self, base: Option<&str>) -> usize {
+        let mut removed = 0;
+        if let Some(metadata) = &mut self.metadata {
+            removed += normalize_all(&mut metadata.links, base);
+            if let Some(author) = &mut metadata.author {
+                if let Some(link) = &mut author.link {
+                    if !normalize(link, base) {
+                        author.link = None;
+                        removed += 1;
+                    }
+                }
+            }
+        }
+        for route in &mut self.routes {
+            removed += normalize_all(&mut route.links, base);
+            for point in &mut route.points {
+                removed += normalize_point(point, base);
+            }
+        }
+        for track in &mut self.tracks {
+            removed += normalize_all(&mut track.links, base);
+            for point in track
+                .segments
+                .iter_mut()
+                .flat_map(|segment| &mut segment.points)
+            {
+                removed += normalize_point(point, base);
+            }
+        }
+        for point in &mut self.waypoints {
+            removed += normalize_point(point, base);
+        }
+        removed
+    }
+}
+
+/// Normalizes the URL of `link`, telling whether it could be normalized.
+fn normalize(link: &mut Link, base: Option<&str>) -> bool {
+    match normalize_href(&link.href, base) {
+        Ok(href) => {
+            link.href = href;
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Normalizes the URLs of `links`, removing those that can't be normalized
+/// and giving their number.
+fn normalize_all(links: &mut Vec<Link>, base: Option<&str>) -> usize {
+    let count = links.len();
+    links.retain_mut(|link| normalize(link, base));
+    count - links.len()
+}
+
+/// Normalizes the URLs of the links of `point` like [`normalize_all`],
+/// leaving points without links untouched.
+fn normalize_point<T: CoordFloat>(point: &mut Waypoint<T>, base: Option<&str>) -> usize {
+    if point.details().links.is_empty() {
+        return 0;
+    }
+    normalize_all(&mut point.details_mut().links, base)
+}
+
+fn check_scheme(href: &str, scheme: &str) -> GpxResult<()> {
+    if SAFE_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str()) {
+        Ok(())
+    } else {
+        Err(GpxError::InvalidUrl(href.to_string()))
+    }
+}
+
+/// Percent-encodes the characters of `href` that aren't allowed in URLs,
+/// after removing the whitespace and control characters browsers ignore.
+fn percent_encode(href: &str) -> String {
+    let href = href.trim_matches(|c: char| c <= ' ');
+    let mut encoded = String::with_capacity(href.len());
+    for c in href.chars().filter(|c| !matches!(c, '\t' | '\n' | '\r')) {
+        if c.is_ascii_alphanumeric() || "-._~:/?#[]@!$&'()*+,;=%".contains(c) {
+            encoded.push(c);
+        } else {
+            for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+                encoded.push_str(&format!("%{byte:02X}"));
+            }
+        }
+    }
+    encoded
+}
+
+/// Removes the `.` and `..` segments of a path, see RFC 3986, section 5.2.4.
+fn remove_dot_segments(path: &str) -> String {
+    let segments: Vec<&str> = path.split('/').collect();
+    let mut output: Vec<&str> = Vec::with_capacity(segments.len());
+    for (index, segment) in segments.iter().enumerate() {
+        let last = index + 1 == segments.len();
+        match *segment {
+            "." => {}
+            ".." => {
+                if output.len() > 1 || output.first().map_or(false, |first| !first.is_empty()) {
+                    output.pop();
+                }
+            }
+            segment => {
+                output.push(segment);
+                continue;
+            }
+        }
+        if last {
+            output.push("");
+        }
+    }
+    output.join("/")
+}
+
+/// The components of a URL or relative reference, see RFC 3986, section 3.
+struct Parts<'a> {
+    scheme: Option<&'a str>,
+    authority: Option<&'a str>,
+    path: &'a str,
+    query: Option<&'a str>,
+    fragment: Option<&'a str>,
+}
+
+impl<'a> Parts<'a> {
+    fn of(url: &'a str) -> Parts<'a> {
+        let (rest, fragment) = match url.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(fragment)),
+            None => (url, None),
+        };
+        let (rest, query) = match rest.split_once('?') {
+            Some((rest, query)) => (rest, Some(query)),
+            None => (rest, None),
+        };
+        let (scheme, rest) = match rest.split_once(':') {
+            Some((scheme, rest)) if is_scheme(scheme) => (Some(scheme), rest),
+            _ => (None, rest),
+        };
+        let (authority, path) = match rest.strip_prefix("//") {
+            Some(rest) => {
+                let end = rest.find('/').unwrap_or(rest.len());
+                (Some(&rest[..end]), &rest[end..])
+            }
+            None => (None, rest),
+        };
+        Parts {
+            scheme,
+            authority,
+            path,
+            query,
+            fragment,
+        }
+    }
+}
+
+impl std::fmt::Display for Parts<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(scheme) = self.scheme {
+            write!(f, "{}:", scheme.to_ascii_lowercase())?;
+        }
+        if let Some(authority) = self.authority {
+            write!(f, "//{authority}")?;
+        }
+        f.write_str(self.path)?;
+        if let Some(query) = self.query {
+            write!(f, "?{query}")?;
+        }
+        if let Some(fragment) = self.fragment {
+            write!(f, "#{fragment}")?;
+        }
+        Ok(())
+    }
+}
+
+fn is_scheme(scheme: &str) -> bool {
+    let mut chars = scheme.chars();
+    chars.next().map_or(false, |c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_href;
+
+    #[test]
+    fn resolve_relative_urls() {
+        // The examples of RFC 3986, section 5.4.
+        let base = Some("http://a/b/c/d;p?q");
+        for (reference, expected) in [
+            ("g", "http://a/b/c/g"),
+            ("./g", "http://a/b/c/g"),
+            ("g/", "http://a/b/c/g/"),
+            ("/g", "http://a/g"),
+            ("//g", "http://g"),
+            ("?y", "http://a/b/c/d;p?y"),
+            ("g?y", "http://a/b/c/g?y"),
+            ("#s", "http://a/b/c/d;p?q#s"),
+            ("g#s", "http://a/b/c/g#s"),
+            (";x", "http://a/b/c/;x"),
+            ("", "http://a/b/c/d;p?q"),
+            (".", "http://a/b/c/"),
+            ("..", "http://a/b/"),
+            ("../g", "http://a/b/g"),
+            ("../..", "http://a/"),
+            ("../../../g", "http://a/g"),
+            ("/./g", "http://a/g"),
+            ("g/../h", "http://a/b/c/h"),
+            ("g;x=1/./y", "http://a/b/c/g;x=1/y"),
+        ] {
+            assert_eq!(
+                normalize_href(reference, base).unwrap(),
+                expected,
+                "{reference}"
+            );
+        }
+    }
+
+    #[test]
+    fn reject_unsafe_urls() {
+        for href in [
+            "javascript:alert(1)",
+            " JavaScript:alert(1)",
+            "java\tscript:alert(1)",
+            "data:text/html,<script>alert(1)</script>",
+            "file:///etc/passwd",
+            "vbscript:msgbox",
+        ] {
+            assert!(
+                normalize_href(href, Some("https://example.com/")).is_err(),
+                "{href}"
+            );
+        }
+        assert!(normalize_href("a.html", Some("javascript:void")).is_err());
+        assert!(normalize_href("a.html", Some("/relative/")).is_err());
+        assert_eq!(
+            normalize_href("mailto:info@example.com", None).unwrap(),
+            "mailto:info@example.com"
+        );
+        assert_eq!(
+            normalize_href("https://example.com/a b?q=\"x\"", None).unwrap(),
+            "https://example.com/a%20b?q=%22x%22"
+        );
+    }
+}
@@ -0,0 +1,424 @@
+//! lint finds data-quality problems in a document, to show to the users who
+//! recorded or edited it.
+
+use std::fmt;
+
+use geo_types::{CoordFloat, Point};
+
+use crate::measure::{content_bounds, from_f64};
+use crate::{Gpx, GpxVersion, Track, Waypoint};
+
+/// How much a [`Finding`] matters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Worth knowing, but harmless.
+    Info,
+    /// Likely a problem of the recording, which may skew statistics, or
+    /// data that is lost when the document is written.
+    Warning,
+    /// Invalid according to the GPX schema.
+    Error,
+}
+
+/// The kind of problem of a [`Finding`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LintKind {
+    /// Trackpoints without a time, while other points of the segment have
+    /// one.
+    MissingTime,
+    /// A point at latitude and longitude 0, which GPS receivers report
+    /// without a fix.
+    ZeroCoordinates,
+    /// A DGPS station id outside the range of 0 to 1023.
+    DgpsidOutOfRange,
+    /// A track segment without points.
+    EmptySegment,
+    /// Bounds in the metadata that don't match the points of the document.
+    BoundsMismatch,
+    /// A field that the version of the document has no element for, like
+    /// the speed of a point in GPX 1.1 or the copyright in GPX 1.0, which is
+    /// left out when the document is written.
+    UnwrittenField,
+}
+
+/// Where in a document a [`Finding`] is, by index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Location {
+    /// The metadata of the document.
+    Metadata,
+    /// A waypoint of the document.
+    Waypoint { index: usize },
+    /// A point of a route.
+    RoutePoint { route: usize, point: usize },
+    /// A track of the document.
+    Track { index: usize },
+    /// A segment of a track.
+    TrackSegment { track: usize, segment: usize },
+    /// A point of a track segment.
+    TrackPoint {
+        track: usize,
+        segment: usize,
+        point: usize,
+    },
+}
+
+impl<T: CoordFloat> Gpx<T> {
+    /// Gives the waypoint, route point or track point at `location`, or
+    /// `None` if there is none.
+    pub fn point_at(&self, location: Location) -> Option<&Waypoint<T>> {
+        match location {
+            Location::Metadata | Location::Track { .. } | Location::TrackSegment { .. } => None,
+            Location::Waypoint { index } => self.waypoints.get(index),
+            Location::RoutePoint { route, point } => self.routes.get(route)?.points.get(point),
+            Location::TrackPoint {
+                track,
+                segment,
+                point,
+            } => self
+                .tracks
+                .get(track)?
+                .segments
+                .get(segment)?
+                .points
+                .get(point),
+        }
+    }
+}
+
+/// A problem found by [`lint`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Finding {
+    /// What the problem is.
+    pub kind: LintKind,
+
+    /// How much the problem matters.
+    pub severity: Severity,
+
+    /// Where the problem is.
+    pub location: Location,
+
+    /// A description of the problem, for people.
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} at {:?}: {}",
+            self.severity, self.location, self.message
+        )
+    }
+}
+
+/// Checks a document for data-quality problems.
+///
+/// The findings are ordered by their place in the document: metadata first,
+/// then waypoints, routes and tracks.
+///
+/// Fields that aren't written in the [`version`](Gpx::version) of the
+/// document are reported as [`LintKind::UnwrittenField`], so to check what
+/// would be lost when writing another version, set it before linting.
+///
+/// ```
+/// use geo_types::Point;
+/// use gpx::{lint, Gpx, LintKind, Location, Severity, Track, TrackSegment, Waypoint};
+///
+/// let mut track = Track::new();
+/// track.segments.push(TrackSegment::new());
+/// let mut gpx: Gpx = Default::default();
+/// gpx.waypoints.push(Waypoint::new(Point::new(0.0, 0.0)));
+/// gpx.tracks.push(track);
+///
+/// let findings = lint(&gpx);
+/// assert_eq!(findings[0].kind, LintKind::ZeroCoordinates);
+/// assert_eq!(findings[1].location, Location::TrackSegment { track: 0, segment: 0 });
+/// assert_eq!(findings[1].severity, Severity::Warning);
+/// ```
+pub fn lint<T: CoordFloat>(gpx: &Gpx<T>) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let version = gpx.version;
+    lint_bounds(gpx, &mut findings);
+    lint_metadata_fields(gpx, &mut findings);
+
+    for (index, waypoint) in gpx.waypoints.iter().enumerate() {
+        let location = Location::Waypoint { index };
+        lint_point(waypoint, version, location, &mut findings);
+    }
+    for (route, route_points) in gpx.routes.iter().enumerate() {
+        for (point, waypoint) in route_points.points.iter().enumerate() {
+            let location = Location::RoutePoint { route, point };
+            lint_point(waypoint, version, location, &mut findings);
+        }
+    }
+    for (track, track_segments) in gpx.tracks.iter().enumerate() {
+        lint_track_fields(track_segments, version, track, &mut findings);
+        for (segment, track_segment) in track_segments.segments.iter().enumerate() {
+            let points = &track_segment.points;
+            let location = Location::TrackSegment { track, segment };
+            if points.is_empty() {
+                findings.push(Finding {
+                    kind: LintKind::EmptySegment,
+                    severity: Severity::Warning,
+                    location,
+                    message: "the segment has no points".to_string(),
+                });
+            }
+            let untimed = points.iter().filter(|point| point.time.is_none()).count();
+            if untimed > 0 && untimed < points.len() {
+                findings.push(Finding {
+                    kind: LintKind::MissingTime,
+                    severity: Severity::Warning,
+                    location,
+                    message: format!("{untimed} of {} points have no time", points.len()),
+                });
+            }
+            for (point, waypoint) in points.iter().enumerate() {
+                let location = Location::TrackPoint {
+                    track,
+                    segment,
+                    point,
+                };
+                lint_point(waypoint, version, location, &mut findings);
+            }
+        }
+    }
+    findings
+}
+
+fn lint_point<T: CoordFloat>(
+    waypoint: &Waypoint<T>,
+    version: GpxVersion,
+    location: Location,
+    findings: &mut Vec<Finding>,
+) {
+    if waypoint.point() == Point::new(T::zero(), T::zero()) {
+        findings.push(Finding {
+            kind: LintKind::ZeroCoordinates,
+            severity: Severity::Warning,
+            location,
+            message: "the point is at latitude and longitude 0".to_string(),
+        });
+    }
+    if let Some(dgpsid) = waypoint.details().dgpsid.filter(|&dgpsid| dgpsid > 1023) {
+        findings.push(Finding {
+            kind: LintKind::DgpsidOutOfRange,
+            severity: Severity::Error,
+            location,
+            message: format!("the DGPS station id {dgpsid} is above 1023"),
+        });
+    }
+    let unwritten: &[(&str, bool)] = match version {
+        GpxVersion::Gpx10 => &[
+            (
+                "Garmin waypoint extension",
+                waypoint.details().waypoint_extension.is_some(),
+            ),
+            (
+                "Garmin route extension",
+                waypoint.route_point_extension.is_some(),
+            ),
+            (
+                "Garmin track point extension",
+                waypoint.track_point_extension.is_some(),
+            ),
+        ],
+        GpxVersion::Gpx11 => &[("speed", waypoint.speed.is_some())],
+        GpxVersion::Unknown => &[],
+    };
+    lint_unwritten(unwritten, version, location, findings);
+}
+
+/// Reports the fields of the metadata that aren't written in the version of
+/// the document.
+fn lint_metadata_fields<T: CoordFloat>(gpx: &Gpx<T>, findings: &mut Vec<Finding>) {
+    let Some(metadata) = &gpx.metadata else {
+        return;
+    };
+    if gpx.version == GpxVersion::Gpx10 {
+        let unwritten = [
+            ("copyright", metadata.copyright.is_some()),
+            ("links", !metadata.links.is_empty()),
+        ];
+        lint_unwritten(&unwritten, gpx.version, Location::Metadata, findings);
+    }
+}
+
+/// Reports the fields of `track` that aren't written in `version`.
+fn lint_track_fields<T: CoordFloat>(
+    track: &Track<T>,
+    version: GpxVersion,
+    index: usize,
+    findings: &mut Vec<Finding>,
+) {
+    if version == GpxVersion::Gpx10 {
+        let unwritten = [
+            ("display color", track.display_color.is_some()),
+            ("line style", track.line_style.is_some()),
+        ];
+        lint_unwritten(&unwritten, version, Location::Track { index }, findings);
+    }
+}
+
+/// Reports each of the `fields` that is set as not written in `version`.
+fn lint_unwritten(
+    fields: &[(&str, bool)],
+    version: GpxVersion,
+    location: Location,
+    findings: &mut Vec<Finding>,
+) {
+    let version = match version {
+        GpxVersion::Gpx10 => "1.0",
+        GpxVersion::Gpx11 => "1.1",
+        GpxVersion::Unknown => return,
+    };
+    for &(field, _) in fields.iter().filter(|(_, set)| *set) {
+        findings.push(Finding {
+            kind: LintKind::UnwrittenField,
+            severity: Severity::Warning,
+            location,
+            message: format!("GPX {version} has no element for the {field}"),
+        });
+    }
+}
+
+/// Compares the bounds of the metadata with those of all points, allowing
+/// for the rounding of bounds written with few decimals.
+fn lint_bounds<T: CoordFloat>(gpx: &Gpx<T>, findings: &mut Vec<Finding>) {
+    let tolerance: T = from_f64(1e-6);
+
+    let Some(bounds) = gpx.metadata.as_ref().and_then(|metadata| metadata.bounds) else {
+        return;
+    };
+    let content = content_bounds(gpx);
+    let (severity, message) = match content {
+        Some(content)
+            if content.min().x < bounds.min().x - tolerance
+                || content.min().y < bounds.min().y - tolerance
+                || content.max().x > bounds.max().x + tolerance
+                || content.max().y > bounds.max().y + tolerance =>
+        {
+            (Severity::Warning, "some points are outside the bounds")
+        }
+        Some(content)
+            if content.min().x > bounds.min().x + tolerance
+                || content.min().y > bounds.min().y + tolerance
+                || content.max().x < bounds.max().x - tolerance
+                || content.max().y < bounds.max().y - tolerance =>
+        {
+            (Severity::Info, "the bounds are larger than the points")
+        }
+        Some(_) => return,
+        None => (Severity::Info, "the document has bounds but no points"),
+    };
+    findings.push(Finding {
+        kind: LintKind::BoundsMismatch,
+        severity,
+        location: Location::Metadata,
+        message: message.to_string(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::{coord, Point, Rect};
+
+    use super::{lint, LintKind, Location, Severity};
+    use crate::{DisplayColor, Gpx, GpxVersion, Metadata, Time, Track, TrackSegment, Waypoint};
+
+    fn document(bounds: Rect<f64>) -> Gpx {
+        let mut timed = Waypoint::new(Point::new(1.0, 1.0));
+        timed.time = Some(Time::UNIX_EPOCH);
+        let mut station = Waypoint::new(Point::new(2.0, 2.0));
+        station.details_mut().dgpsid = Some(2000);
+        let mut track = Track::new();
+        track.segments.push(TrackSegment {
+            points: vec![timed, station],
+        });
+        Gpx {
+            metadata: Some(Metadata {
+                bounds: Some(bounds),
+                ..Default::default()
+            }),
+            tracks: vec![track],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn lint_findings() {
+        let bounds = Rect::new(coord! { x: 1.0, y: 1.0 }, coord! { x: 1.5, y: 2.0 });
+        let findings = lint(&document(bounds));
+        let kinds: Vec<_> = findings.iter().map(|finding| finding.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                LintKind::BoundsMismatch,
+                LintKind::MissingTime,
+                LintKind::DgpsidOutOfRange
+            ]
+        );
+        assert_eq!(findings[0].severity, Severity::Warning);
+        assert_eq!(
+            findings[2].location,
+            Location::TrackPoint {
+                track: 0,
+                segment: 0,
+                point: 1
+            }
+        );
+        assert_eq!(
+            findings[1].to_string(),
+            "Warning at TrackSegment { track: 0, segment: 0 }: 1 of 2 points have no time"
+        );
+    }
+
+    #[test]
+    fn lint_bounds() {
+        let exact = Rect::new(coord! { x: 1.0, y: 1.0 }, coord! { x: 2.0000001, y: 2.0 });
+        assert!(lint(&document(exact))
+            .iter()
+            .all(|finding| finding.kind != LintKind::BoundsMismatch));
+
+        let loose = Rect::new(coord! { x: 0.0, y: 1.0 }, coord! { x: 2.0, y: 2.0 });
+        assert_eq!(lint(&document(loose))[0].severity, Severity::Info);
+    }
+
+    #[test]
+    fn lint_unwritten_fields() {
+        let mut gpx = document(Rect::new(
+            coord! { x: 1.0, y: 1.0 },
+            coord! { x: 2.0, y: 2.0 },
+        ));
+        gpx.tracks[0].segments[0].points[0].speed = Some(4.2);
+        gpx.tracks[0].display_color = Some(DisplayColor::Red);
+        gpx.metadata.as_mut().unwrap().copyright = Some(Default::default());
+        assert!(lint(&gpx)
+            .iter()
+            .all(|finding| finding.kind != LintKind::UnwrittenField));
+
+        gpx.version = GpxVersion::Gpx11;
+        let unwritten: Vec<_> = lint(&gpx)
+            .into_iter()
+            .filter(|finding| finding.kind == LintKind::UnwrittenField)
+            .map(|finding| finding.to_string())
+            .collect();
+        assert_eq!(
+            unwritten,
+            ["Warning at TrackPoint { track: 0, segment: 0, point: 0 }: \
+              GPX 1.1 has no element for the speed"]
+        );
+
+        gpx.version = GpxVersion::Gpx10;
+        let locations: Vec<_> = lint(&gpx)
+            .into_iter()
+            .filter(|finding| finding.kind == LintKind::UnwrittenField)
+            .map(|finding| finding.location)
+            .collect();
+        assert_eq!(
+            locations,
+            [Location::Metadata, Location::Track { index: 0 }]
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! logger writes a track while it is being recorded, for tracking apps and
+//! data loggers that shouldn't lose the points of a crashed recording.
+
+use std::fmt::Display;
+use std::io::{self, Seek, SeekFrom, Write};
+
+use geo_types::CoordFloat;
+use xml::writer::{EmitterConfig, EventWriter, XmlEvent};
+
+use crate::errors::GpxResult;
+use crate::writer::{start_track_document, write_waypoint, write_xml_event, NoHook, WriteOptions};
+use crate::{GpxVersion, Track, Waypoint};
+
+/// The number of points a [`GpxLogger`] writes between flushes by default.
+const DEFAULT_FLUSH_INTERVAL: usize = 10;
+
+/// The closing tags a checkpointed [`GpxLogger`] writes after the last
+/// point, indented like the rest of the document.
+const CLOSING_TAGS: &str = "\n    </trkseg>\n  </trk>\n</gpx>";
+
+/// Writes a GPX 1.1 document with a single track point by point, as the
+/// points are recorded.
+///
+/// The start of the document is written right away, every pushed point is
+/// written as it comes, and the sink is flushed every few points. The
+/// closing tags are written by [`finish`](GpxLogger::finish), or when the
+/// logger is dropped, e.g. while unwinding from a panic, so the file is
+/// valid GPX up to the last flushed point. Errors while closing on drop are
+/// ignored.
+///
+/// ```
+/// use geo_types::Point;
+/// use gpx::{GpxLogger, Track, Waypoint};
+///
+/// let mut meta: Track = Track::new();
+/// meta.name = Some("Morning ride".to_string());
+///
+/// let mut logger = GpxLogger::new(Vec::new(), &meta).unwrap();
+/// logger.push(&Waypoint::new(Point::new(-121.97, 37.24))).unwrap();
+/// logger.push(&Waypoint::new(Point::new(-121.96, 37.25))).unwrap();
+/// // The signal was lost, so the next points start a new segment.
+/// logger.new_segment().unwrap();
+/// logger.push(&Waypoint::new(Point::new(-121.90, 37.30))).unwrap();
+/// let bytes = logger.finish().unwrap();
+///
+/// let gpx = gpx::read_from_slice(&bytes).unwrap();
+/// assert_eq!(gpx.tracks[0].segments.len(), 2);
+/// assert_eq!(gpx.tracks[0].segments[0].points.len(), 2);
+/// ```
+pub struct GpxLogger<W: Write> {
+    /// The writer of the document, taken when the document is closed.
+    writer: Option<EventWriter<W>>,
+    /// The number of points between flushes, never zero.
+    flush_interval: usize,
+    /// The number of points written since the last flush.
+    unflushed: usize,
+    /// Seeks the sink back over the closing tags of a checkpoint, set for
+    /// loggers made by [`checkpointed`](GpxLogger::checkpointed).
+    rewind: Option<fn(&mut W) -> io::Result<()>>,
+}
+
+impl<W: Write> GpxLogger<W> {
+    /// Starts logging to `sink` a track with the name, description and other
+    /// fields of `meta`, whose segments are ignored. The start of the
+    /// document is written and flushed right away.
+    pub fn new<T: CoordFloat + Display>(sink: W, meta: &Track<T>) -> GpxResult<GpxLogger<W>> {
+        GpxLogger::with_flush_interval(sink, meta, DEFAULT_FLUSH_INTERVAL)
+    }
+
+    /// Starts logging like [`new`](GpxLogger::new), flushing the sink every
+    /// `points` points instead of every 10. Zero is taken as one, flushing
+    /// after every point.
+    pub fn with_flush_interval<T: CoordFloat + Display>(
+        sink: W,
+        meta: &Track<T>,
+        points: usize,
+    ) -> GpxResult<GpxLogger<W>> {
+        let writer = EmitterConfig::new()
+            .perform_indent(true)
+            .create_writer(sink);
+        GpxLogger::start(writer, meta, points, None)
+    }
+
+    fn start<T: CoordFloat + Display>(
+        mut writer: EventWriter<W>,
+        meta: &Track<T>,
+        flush_interval: usize,
+        rewind: Option<fn(&mut W) -> io::Result<()>>,
+    ) -> GpxResult<GpxLogger<W>> {
+        start_track_document(meta, &mut writer)?;
+        let mut logger = GpxLogger {
+            writer: Some(writer),
+            flush_interval: flush_interval.max(1),
+            unflushed: 0,
+            rewind,
+        };
+        logger.flush()?;
+        Ok(logger)
+    }
+
+    /// Writes `point` as the next point of the current segment, flushing
+    /// the sink if enough points were written since the last flush.
+    pub fn push<T: CoordFloat + Display>(&mut self, point: &Waypoint<T>) -> GpxResult<()> {
+        write_waypoint(
+            GpxVersion::Gpx11,
+            "trkpt",
+            point,
+            self.writer(),
+            &WriteOptions::default(),
+            &mut NoHook,
+        )?;
+        self.unflushed += 1;
+        if self.unflushed >= self.flush_interval {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Ends the current segment and starts a new one, e.g. after the GPS
+    /// signal was lost. The sink is flushed.
+    pub fn new_segment(&mut self) -> GpxResult<()> {
+        let writer = self.writer();
+        write_xml_event(XmlEvent::end_element(), writer)?;
+        write_xml_event(XmlEvent::start_element("trkseg"), writer)?;
+        self.flush()
+    }
+
+    /// Flushes the points written so far to the sink. Checkpointed loggers
+    /// write a checkpoint.
+    pub fn flush(&mut self) -> GpxResult<()> {
+        let rewind = self.rewind;
+        let sink = self.writer().inner_mut();
+        if let Some(rewind) = rewind {
+            sink.write_all(CLOSING_TAGS.as_bytes())?;
+            sink.flush()?;
+            rewind(sink)?;
+        } else {
+            sink.flush()?;
+        }
+        self.unflushed = 0;
+        Ok(())
+    }
+
+    /// Writes the closing tags of the document, flushes the sink and gives
+    /// it back.
+    pub fn finish(mut self) -> GpxResult<W> {
+        self.close()?;
+        let writer = self.writer.take().expect("the logger was already closed");
+        Ok(writer.into_inner())
+    }
+
+    /// Gives the sink, e.g. to check how much was written.
+    pub fn get_ref(&self) -> &W {
+        self.writer
+            .as_ref()
+            .expect("the logger was already closed")
+            .inner_ref()
+    }
+
+    fn writer(&mut self) -> &mut EventWriter<W> {
+        self.writer.as_mut().expect("the logger was already closed")
+    }
+
+    /// Closes the segment, the track and the document, and flushes the sink.
+    fn close(&mut self) -> GpxResult<()> {
+        if self.rewind.is_some() {
+            // The closing tags of the last checkpoint are overwritten with
+            // the same tags, so none of them is left over.
+            let sink = self.writer().inner_mut();
+            sink.write_all(CLOSING_TAGS.as_bytes())?;
+            sink.flush()?;
+            return Ok(());
+        }
+        for _ in 0..3 {
+            write_xml_event(XmlEvent::end_element(), self.writer())?;
+        }
+        self.flush()
+    }
+}
+
+impl<W: Write + Seek> GpxLogger<W> {
+    /// Starts logging like [`with_flush_interval`](GpxLogger::with_flush_interval),
+    /// keeping the document valid at every flush, for long recordings and
+    /// exports that may be interrupted.
+    ///
+    /// Each flush, including those at new segments, writes a checkpoint:
+    /// the closing tags of the document are written after the last point
+    /// and flushed, and the sink is seeked back to overwrite them with the
+    /// next points. A file that is cut off after a checkpoint is a complete
+    /// document with all points up to it. Wrapping a file in a
+    /// [`BufWriter`](std::io::BufWriter) keeps the points between
+    /// checkpoints out of it, so a cut off file is also never left with a
+    /// partly overwritten checkpoint.
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    ///
+    /// use geo_types::Point;
+    /// use gpx::{GpxLogger, Track, Waypoint};
+    ///
+    /// let mut logger = GpxLogger::checkpointed(Cursor::new(Vec::new()), &Track::<f64>::new(), 2)
+    ///     .unwrap();
+    /// for i in 0..2 {
+    ///     logger.push(&Waypoint::new(Point::new(f64::from(i), 47.0))).unwrap();
+    /// }
+    /// // The second point made a checkpoint, so the sink holds a complete
+    /// // document.
+    /// let gpx = gpx::read_from_slice(logger.get_ref().get_ref()).unwrap();
+    /// assert_eq!(gpx.tracks[0].segments[0].points.len(), 2);
+    ///
+    /// logger.push(&Waypoint::new(Point::new(2.0, 47.0))).unwrap();
+    /// let gpx = gpx::read_from_slice(logger.finish().unwrap().get_ref()).unwrap();
+    /// assert_eq!(gpx.tracks[0].segments[0].points.len(), 3);
+    /// ```
+    pub fn checkpointed<T: CoordFloat + Display>(
+        sink: W,
+        meta: &Track<T>,
+        points: usize,
+    ) -> GpxResult<GpxLogger<W>> {
+        // Empty elements are written with separate closing tags, so that
+        // every event is written out in whole and checkpoints can follow
+        // any of them.
+        let writer = EmitterConfig::new()
+            .perform_indent(true)
+            .normalize_empty_elements(false)
+            .create_writer(sink);
+        GpxLogger::start(
+            writer,
+            meta,
+            points,
+            Some(|sink: &mut W| {
+                sink.seek(SeekFrom::Current(-(CLOSING_TAGS.len() as i64)))?;
+                Ok(())
+            }),
+        )
+    }
+}
+
+impl<W: Write> Drop for GpxLogger<W> {
+    fn drop(&mut self) {
+        if self.writer.is_some() {
+            let _ = self.close();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::io::{Cursor, Write};
+    use std::rc::Rc;
+
+    use geo_types::Point;
+
+    use super::GpxLogger;
+    use crate::{read_from_slice, Track, Waypoint};
+
+    /// A sink that shares what was flushed to it.
+    #[derive(Clone, Default)]
+    struct Shared {
+        written: Vec<u8>,
+        flushed: Rc<RefCell<Vec<u8>>>,
+    }
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.written.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            *self.flushed.borrow_mut() = self.written.clone();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn logger_flushes_and_closes_on_drop() {
+        let sink = Shared::default();
+        let flushed = Rc::clone(&sink.flushed);
+        let mut logger = GpxLogger::with_flush_interval(sink, &Track::<f64>::new(), 2).unwrap();
+        assert!(flushed.borrow().starts_with(b"<?xml"));
+
+        for i in 0..3 {
+            logger
+                .push(&Waypoint::new(Point::new(f64::from(i), 1.0)))
+                .unwrap();
+        }
+        let flushed_points = String::from_utf8(flushed.borrow().clone()).unwrap();
+        assert_eq!(flushed_points.matches("<trkpt").count(), 2);
+
+        drop(logger);
+        let gpx = read_from_slice(&flushed.borrow()).unwrap();
+        assert_eq!(gpx.tracks[0].segments[0].points.len(), 3);
+    }
+
+    #[test]
+    fn checkpoints_keep_the_document_valid() {
+        let mut logger =
+            GpxLogger::checkpointed(Cursor::new(Vec::new()), &Track::<f64>::new(), 100).unwrap();
+        let checkpoint = |logger: &GpxLogger<Cursor<Vec<u8>>>| {
+            read_from_slice(logger.get_ref().get_ref()).unwrap().tracks[0]
+                .segments
+                .iter()
+                .map(|segment| segment.points.len())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(checkpoint(&logger), [0]);
+
+        logger.new_segment().unwrap();
+        assert_eq!(checkpoint(&logger), [0, 0]);
+        for i in 0..3 {
+            logger
+                .push(&Waypoint::new(Point::new(f64::from(i), 1.0)))
+                .unwrap();
+        }
+        logger.new_segment().unwrap();
+        assert_eq!(checkpoint(&logger), [0, 3, 0]);
+        logger.push(&Waypoint::new(Point::new(5.0, 1.0))).unwrap();
+
+        let written = logger.finish().unwrap().into_inner();
+        assert!(written.ends_with(b"</gpx>"));
+        let gpx = read_from_slice(&written).unwrap();
+        assert_eq!(gpx.tracks[0].segments.len(), 3);
+        assert_eq!(gpx.tracks[0].segments[2].points.len(), 1);
+    }
+}
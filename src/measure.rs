@@ -0,0 +1,648 @@
+//! measure provides geodesic, elevation and time measurements of tracks and
+//! routes.
+
+use std::ops::Add;
+
+use geo::GeodesicDistance;
+use geo_types::{coord, CoordFloat, Point, Rect};
+
+use crate::{Duration, Gpx, Metadata, Route, Time, Track, TrackSegment, Waypoint};
+
+/// Cumulative elevation change along a path, in meters.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ElevationChange {
+    /// Total ascent (elevation gain).
+    pub ascent: f64,
+
+    /// Total descent (elevation loss), as a positive number.
+    pub descent: f64,
+}
+
+impl Add for ElevationChange {
+    type Output = ElevationChange;
+
+    fn add(self, other: ElevationChange) -> ElevationChange {
+        ElevationChange {
+            ascent: self.ascent + other.ascent,
+            descent: self.descent + other.descent,
+        }
+    }
+}
+
+impl<T: CoordFloat> Gpx<T> {
+    /// Sets the bounds of the metadata to those of all waypoints, route
+    /// points and track points, e.g. after editing the document, so that
+    /// the advertised bounds match the content. Documents without points
+    /// get no bounds. The bounds of each track are recomputed as well, see
+    /// [`Track::recompute_bounds`].
+    ///
+    /// ```
+    /// use geo_types::{coord, Point, Rect};
+    /// use gpx::{Gpx, Waypoint};
+    ///
+    /// let mut gpx: Gpx = Default::default();
+    /// gpx.waypoints.push(Waypoint::new(Point::new(-121.97, 37.24)));
+    /// gpx.waypoints.push(Waypoint::new(Point::new(-121.96, 37.25)));
+    ///
+    /// gpx.update_bounds();
+    /// assert_eq!(
+    ///     gpx.metadata.unwrap().bounds,
+    ///     Some(Rect::new(coord! { x: -121.97, y: 37.24 }, coord! { x: -121.96, y: 37.25 })),
+    /// );
+    /// ```
+    pub fn update_bounds(&mut self) {
+        for track in &mut self.tracks {
+            track.recompute_bounds();
+        }
+        let bounds = content_bounds(self);
+        match &mut self.metadata {
+            Some(metadata) => metadata.bounds = bounds,
+            None if bounds.is_some() => {
+                self.metadata = Some(Metadata {
+                    bounds,
+                    ..Default::default()
+                })
+            }
+            None => {}
+        }
+    }
+
+    /// Sets the time of the metadata to the earliest time of the waypoints
+    /// and track points if it has none, as file browsers and activity
+    /// importers often rely on it. Times already set are kept.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Duration, Gpx, Time, Waypoint};
+    ///
+    /// let mut gpx: Gpx = Default::default();
+    /// for minutes in [10, 5, 20] {
+    ///     let mut point = Waypoint::new(Point::new(0.0, 0.0));
+    ///     point.time = Some(Time::UNIX_EPOCH + Duration::minutes(minutes));
+    ///     gpx.waypoints.push(point);
+    /// }
+    ///
+    /// gpx.infer_metadata_time();
+    /// assert_eq!(gpx.metadata.unwrap().time, gpx.waypoints[1].time);
+    /// ```
+    pub fn infer_metadata_time(&mut self) {
+        if self
+            .metadata
+            .as_ref()
+            .map_or(false, |metadata| metadata.time.is_some())
+        {
+            return;
+        }
+        let tracks = self.tracks.iter().flat_map(|track| &track.segments);
+        let Some(time) = self
+            .waypoints
+            .iter()
+            .chain(tracks.flat_map(|segment| &segment.points))
+            .filter_map(|point| point.time)
+            .min()
+        else {
+            return;
+        };
+        self.metadata.get_or_insert_with(Default::default).time = Some(time);
+    }
+}
+
+impl<T: CoordFloat> Route<T> {
+    /// Gives the length of the route in meters, measured along the geodesics
+    /// between consecutive points on the WGS 84 ellipsoid.
+    pub fn length_m(&self) -> f64 {
+        path_length(&self.points)
+    }
+
+    /// Gives the cumulative ascent and descent along the route, see
+    /// [`TrackSegment::elevation_gain_loss`].
+    pub fn elevation_gain_loss(&self, threshold: f64) -> ElevationChange {
+        elevation_change(&self.points, threshold)
+    }
+}
+
+impl<T: CoordFloat> Track<T> {
+    /// Computes the bounds of the points of all segments of the track, or
+    /// `None` if it has no points, and stores them in
+    /// [`bounds`](Track::bounds). Call it again after editing the points,
+    /// or use [`Gpx::update_bounds`] to refresh all tracks.
+    pub fn recompute_bounds(&mut self) -> Option<Rect<T>> {
+        self.bounds = bounds(self.segments.iter().flat_map(|segment| &segment.points));
+        self.bounds
+    }
+}
+
+impl<T: CoordFloat> Track<T> {
+    /// Gives the length of the track in meters, which is the sum of the
+    /// lengths of its segments. The gaps between segments are not counted.
+    ///
+    /// ```
+    /// use geo_types::{line_string, MultiLineString};
+    /// use gpx::Track;
+    ///
+    /// let track = Track::from(MultiLineString::new(vec![
+    ///     line_string![(x: 0.0, y: 0.0), (x: 0.0, y: 1.0)],
+    ///     line_string![(x: 5.0, y: 0.0), (x: 5.0, y: 1.0)],
+    /// ]));
+    ///
+    /// // One degree of latitude at the equator is about 110.6 km.
+    /// assert!((track.length_m() - 221_148.8).abs() < 0.1);
+    /// ```
+    pub fn length_m(&self) -> f64 {
+        self.segments
+            .iter()
+            .fold(0.0, |length, segment| length + segment.length_m())
+    }
+
+    /// Gives the cumulative ascent and descent over all segments of the
+    /// track, see [`TrackSegment::elevation_gain_loss`].
+    pub fn elevation_gain_loss(&self, threshold: f64) -> ElevationChange {
+        self.segments
+            .iter()
+            .map(|segment| segment.elevation_gain_loss(threshold))
+            .fold(ElevationChange::default(), Add::add)
+    }
+
+    /// Gives the time elapsed between the first and the last timestamped
+    /// point of the track, including the gaps between segments.
+    ///
+    /// Returns `None` if the track has no timestamped points.
+    pub fn duration(&self) -> Option<Duration> {
+        let mut times = self
+            .segments
+            .iter()
+            .flat_map(|segment| &segment.points)
+            .filter_map(|point| point.time);
+        let first = times.next()?;
+        Some(elapsed(first, times.next_back().unwrap_or(first)))
+    }
+
+    /// Gives the time spent moving over all segments of the track, see
+    /// [`TrackSegment::moving_time`].
+    ///
+    /// Returns `None` if none of the segments have timestamped points.
+    pub fn moving_time(&self, min_speed: f64) -> Option<Duration> {
+        self.segments
+            .iter()
+            .filter_map(|segment| segment.moving_time(min_speed))
+            .reduce(Add::add)
+    }
+}
+
+impl<T: CoordFloat> TrackSegment<T> {
+    /// Gives the length of the segment in meters, measured along the
+    /// geodesics between consecutive points on the WGS 84 ellipsoid.
+    pub fn length_m(&self) -> f64 {
+        path_length(&self.points)
+    }
+
+    /// Gives the cumulative ascent and descent along the segment.
+    ///
+    /// Elevation changes smaller than `threshold` meters are treated as noise:
+    /// an ascent or descent is only counted once the elevation moved at least
+    /// `threshold` away from the last counted point. Pass `0.0` to count every
+    /// change. Points without elevation are skipped.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{TrackSegment, Waypoint};
+    ///
+    /// let mut segment = TrackSegment::new();
+    /// for elevation in [100.0, 101.0, 100.5, 110.0, 105.0] {
+    ///     let mut point = Waypoint::new(Point::new(0.0, 0.0));
+    ///     point.elevation = Some(elevation);
+    ///     segment.points.push(point);
+    /// }
+    ///
+    /// let change = segment.elevation_gain_loss(0.0);
+    /// assert_eq!((change.ascent, change.descent), (10.5, 5.5));
+    ///
+    /// let change = segment.elevation_gain_loss(2.0);
+    /// assert_eq!((change.ascent, change.descent), (10.0, 5.0));
+    /// ```
+    pub fn elevation_gain_loss(&self, threshold: f64) -> ElevationChange {
+        elevation_change(&self.points, threshold)
+    }
+
+    /// Gives the grade at each point of the segment, in percent.
+    ///
+    /// The grade at a point is the elevation change divided by the distance
+    /// travelled, measured from the earliest point at most `window` meters
+    /// back along the segment, or from the previous point if that one is
+    /// further away. A larger `window` smoothes out noisy elevations; pass
+    /// `0.0` to compare consecutive points only.
+    ///
+    /// The grade is `None` for the first point, and where either point
+    /// lacks an elevation or the points coincide.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{TrackSegment, Waypoint};
+    ///
+    /// let mut segment = TrackSegment::new();
+    /// // Points about 111 m apart, climbing 11.1 m each.
+    /// for i in 0..3 {
+    ///     let mut point = Waypoint::new(Point::new(0.0, i as f64 * 0.001));
+    ///     point.elevation = Some(i as f64 * 11.1);
+    ///     segment.points.push(point);
+    /// }
+    ///
+    /// let grades = segment.grades(0.0);
+    /// assert_eq!(grades[0], None);
+    /// assert!((grades[2].unwrap() - 10.0).abs() < 0.1);
+    /// ```
+    pub fn grades(&self, window: f64) -> Vec<Option<f64>> {
+        grades(&self.points, window)
+    }
+
+    /// Gives the time elapsed between the first and the last timestamped
+    /// point of the segment.
+    ///
+    /// Returns `None` if the segment has no timestamped points.
+    pub fn duration(&self) -> Option<Duration> {
+        let mut times = self.points.iter().filter_map(|point| point.time);
+        let first = times.next()?;
+        Some(elapsed(first, times.next_back().unwrap_or(first)))
+    }
+
+    /// Gives the time spent moving along the segment.
+    ///
+    /// Only the intervals between consecutive timestamped points where the
+    /// speed was at least `min_speed` meters per second are counted, so
+    /// pauses are left out. Points without a time are skipped.
+    ///
+    /// Returns `None` if the segment has no timestamped points.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Duration, Time, TrackSegment, Waypoint};
+    ///
+    /// let start = Time::UNIX_EPOCH;
+    /// let mut segment = TrackSegment::new();
+    /// // Roughly 11 m/s for ten seconds, then standing still for a minute.
+    /// for (latitude, seconds) in [(0.0, 0), (0.001, 10), (0.001, 70)] {
+    ///     let mut point = Waypoint::new(Point::new(0.0, latitude));
+    ///     point.time = Some(start + Duration::seconds(seconds));
+    ///     segment.points.push(point);
+    /// }
+    ///
+    /// assert_eq!(segment.duration(), Some(Duration::seconds(70)));
+    /// assert_eq!(segment.moving_time(0.5), Some(Duration::seconds(10)));
+    /// ```
+    pub fn moving_time(&self, min_speed: f64) -> Option<Duration> {
+        let mut points = self.points.iter().filter(|point| point.time.is_some());
+        let mut previous = points.next()?;
+        let mut moving = Duration::ZERO;
+        for point in points {
+            let interval = elapsed(previous.time?, point.time?);
+            let seconds = interval.as_seconds_f64();
+            if seconds > 0.0 && distance(previous, point) / seconds >= min_speed {
+                moving += interval;
+            }
+            previous = point;
+        }
+        Some(moving)
+    }
+}
+
+/// Time elapsed from `start` to `end`.
+pub(crate) fn elapsed(start: Time, end: Time) -> Duration {
+    end - start
+}
+
+/// Grows `bounds` so that it contains `point`.
+pub(crate) fn extend_bounds<T: CoordFloat>(bounds: Option<Rect<T>>, point: Point<T>) -> Rect<T> {
+    match bounds {
+        None => Rect::new(point.0, point.0),
+        Some(bounds) => Rect::new(
+            coord! {
+                x: bounds.min().x.min(point.x()),
+                y: bounds.min().y.min(point.y()),
+            },
+            coord! {
+                x: bounds.max().x.max(point.x()),
+                y: bounds.max().y.max(point.y()),
+            },
+        ),
+    }
+}
+
+/// Gives the smallest rectangle containing `points`.
+pub(crate) fn bounds<'a, T: CoordFloat + 'a>(
+    points: impl IntoIterator<Item = &'a Waypoint<T>>,
+) -> Option<Rect<T>> {
+    points.into_iter().fold(None, |bounds, point| {
+        Some(extend_bounds(bounds, point.point()))
+    })
+}
+
+/// Gives the bounds of all waypoints, route points and track points of
+/// `gpx`.
+pub(crate) fn content_bounds<T: CoordFloat>(gpx: &Gpx<T>) -> Option<Rect<T>> {
+    let routes = gpx.routes.iter().flat_map(|route| &route.points);
+    let tracks = gpx.tracks.iter().flat_map(|track| &track.segments);
+    bounds(
+        gpx.waypoints
+            .iter()
+            .chain(routes)
+            .chain(tracks.flat_map(|segment| &segment.points)),
+    )
+}
+
+/// Gives `value` as an `f64`, in which all measurements are made.
+pub(crate) fn to_f64<T: CoordFloat>(value: T) -> f64 {
+    value.to_f64().unwrap_or(f64::NAN)
+}
+
+/// Gives `value` as a `T`, e.g. to store an interpolated measurement.
+pub(crate) fn from_f64<T: CoordFloat>(value: f64) -> T {
+    T::from(value).unwrap_or_else(T::nan)
+}
+
+/// Gives the position of `point` in `f64` coordinates.
+pub(crate) fn position<T: CoordFloat>(point: &Waypoint<T>) -> Point {
+    let point = point.point();
+    Point::new(to_f64(point.x()), to_f64(point.y()))
+}
+
+/// Gives the elevation of `point` in meters, as an `f64`.
+pub(crate) fn elevation<T: CoordFloat>(point: &Waypoint<T>) -> Option<f64> {
+    point.elevation.map(to_f64)
+}
+
+/// Geodesic distance between two waypoints, in meters.
+pub(crate) fn distance<T: CoordFloat>(a: &Waypoint<T>, b: &Waypoint<T>) -> f64 {
+    position(a).geodesic_distance(&position(b))
+}
+
+// Folding from 0.0 rather than using `sum`, which starts at -0.0.
+fn path_length<T: CoordFloat>(points: &[Waypoint<T>]) -> f64 {
+    points
+        .windows(2)
+        .fold(0.0, |length, pair| length + distance(&pair[0], &pair[1]))
+}
+
+/// Grades at each of `points`, see [`TrackSegment::grades`].
+pub(crate) fn grades<T: CoordFloat>(points: &[Waypoint<T>], window: f64) -> Vec<Option<f64>> {
+    let mut travelled = Vec::with_capacity(points.len());
+    let mut total = 0.0;
+    for (index, point) in points.iter().enumerate() {
+        if index > 0 {
+            total += distance(&points[index - 1], point);
+        }
+        travelled.push(total);
+    }
+
+    let mut start = 0;
+    (0..points.len())
+        .map(|index| {
+            if index == 0 {
+                return None;
+            }
+            while start + 1 < index && travelled[index] - travelled[start] > window {
+                start += 1;
+            }
+            let run = travelled[index] - travelled[start];
+            let rise = elevation(&points[index])? - elevation(&points[start])?;
+            (run > 0.0).then(|| rise / run * 100.0)
+        })
+        .collect()
+}
+
+fn elevation_change<T: CoordFloat>(points: &[Waypoint<T>], threshold: f64) -> ElevationChange {
+    let mut counter = ElevationCounter::new(threshold);
+    for elevation in points.iter().filter_map(elevation) {
+        counter.add(elevation);
+    }
+    counter.change
+}
+
+/// Accumulates ascent and descent over a sequence of elevations, ignoring
+/// changes smaller than a threshold.
+pub(crate) struct ElevationCounter {
+    threshold: f64,
+    reference: Option<f64>,
+    pub(crate) change: ElevationChange,
+}
+
+impl ElevationCounter {
+    pub(crate) fn new(threshold: f64) -> ElevationCounter {
+        ElevationCounter {
+            threshold,
+            reference: None,
+            change: Default::default(),
+        }
+    }
+
+    pub(crate) fn add(&mut self, elevation: f64) {
+        let Some(reference) = self.reference else {
+            self.reference = Some(elevation);
+            return;
+        };
+        let difference = elevation - reference;
+        if difference.abs() >= self.threshold && difference != 0.0 {
+            if difference > 0.0 {
+                self.change.ascent += difference;
+            } else {
+                self.change.descent -= difference;
+            }
+            self.reference = Some(elevation);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::{coord, Point, Rect};
+
+    use crate::{Duration, Gpx, Metadata, Route, Time, Track, TrackSegment, Waypoint};
+
+    fn segment(coords: &[(f64, f64)]) -> TrackSegment {
+        TrackSegment {
+            points: coords
+                .iter()
+                .map(|&(x, y)| Waypoint::new(Point::new(x, y)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn segment_length() {
+        // One degree of latitude at the equator is about 110.6 km.
+        let segment = segment(&[(0.0, 0.0), (0.0, 0.5), (0.0, 1.0)]);
+        assert!((segment.length_m() - 110_574.4).abs() < 0.1);
+
+        assert_eq!(TrackSegment::<f64>::new().length_m(), 0.0);
+    }
+
+    #[test]
+    fn measurements_of_f32_documents() {
+        let mut segment = TrackSegment::<f32>::new();
+        for (latitude, elevation) in [(0.0, 10.0), (0.5, 30.0), (1.0, 20.0)] {
+            let mut point = Waypoint::new(Point::new(0.0, latitude));
+            point.elevation = Some(elevation);
+            segment.points.push(point);
+        }
+        let mut track = Track::new();
+        track.segments.push(segment);
+
+        assert!((track.length_m() - 110_574.4).abs() < 0.1);
+        let change = track.elevation_gain_loss(0.0);
+        assert_eq!((change.ascent, change.descent), (20.0, 10.0));
+        let bounds = track.recompute_bounds().unwrap();
+        assert_eq!(bounds.max(), coord! { x: 0.0f32, y: 1.0 });
+    }
+
+    #[test]
+    fn track_length_skips_gaps_between_segments() {
+        let mut track = Track::new();
+        track.segments.push(segment(&[(0.0, 0.0), (0.0, 1.0)]));
+        track.segments.push(segment(&[(10.0, 0.0), (10.0, 1.0)]));
+
+        assert!((track.length_m() - 2.0 * 110_574.4).abs() < 0.1);
+    }
+
+    #[test]
+    fn elevation_gain_loss_with_hysteresis() {
+        let mut track = Track::new();
+        let mut segment = segment(&[(0.0, 0.0); 6]);
+        let elevations = [
+            Some(10.0),
+            Some(11.0),
+            None,
+            Some(12.0),
+            Some(9.0),
+            Some(9.5),
+        ];
+        for (point, elevation) in segment.points.iter_mut().zip(elevations) {
+            point.elevation = elevation;
+        }
+        track.segments.push(segment.clone());
+        track.segments.push(segment);
+
+        let change = track.elevation_gain_loss(0.0);
+        assert_eq!(change.ascent, 2.0 * 2.5);
+        assert_eq!(change.descent, 2.0 * 3.0);
+
+        // The 1m steps only count once they add up to the threshold.
+        let change = track.segments[0].elevation_gain_loss(1.5);
+        assert_eq!(change.ascent, 2.0);
+        assert_eq!(change.descent, 3.0);
+
+        assert_eq!(
+            TrackSegment::<f64>::new().elevation_gain_loss(1.0),
+            Default::default()
+        );
+    }
+
+    #[test]
+    fn grades_with_window() {
+        // Points about 11.1 m apart.
+        let mut segment = segment(&[(0.0, 0.0), (0.0, 0.0001), (0.0, 0.0002), (0.0, 0.0003)]);
+        let elevations = [Some(0.0), Some(1.0), None, Some(1.0)];
+        for (point, elevation) in segment.points.iter_mut().zip(elevations) {
+            point.elevation = elevation;
+        }
+
+        let grades = segment.grades(0.0);
+        assert_eq!(grades[0], None);
+        assert!((grades[1].unwrap() - 9.04).abs() < 0.01);
+        assert_eq!(grades[2], None);
+        assert_eq!(grades[3], None);
+
+        // The window reaches back to the start of the segment.
+        let grades = segment.grades(40.0);
+        assert!((grades[3].unwrap() - 3.01).abs() < 0.01);
+
+        assert!(TrackSegment::<f64>::new().grades(10.0).is_empty());
+    }
+
+    #[test]
+    fn track_duration_and_moving_time() {
+        let start = Time::UNIX_EPOCH;
+        let timed_segment = |offset: i64| {
+            let mut segment = segment(&[(0.0, 0.0), (0.0, 0.001), (0.0, 0.001), (0.0, 0.002)]);
+            for (i, point) in segment.points.iter_mut().enumerate() {
+                point.time = Some(start + Duration::seconds(offset + 10 * i as i64));
+            }
+            segment
+        };
+        let mut track = Track::new();
+        track.segments.push(timed_segment(0));
+        track.segments.push(TrackSegment::new());
+        track.segments.push(timed_segment(100));
+
+        assert_eq!(track.duration(), Some(Duration::seconds(130)));
+        assert_eq!(track.moving_time(1.0), Some(Duration::seconds(40)));
+        assert_eq!(track.moving_time(100.0), Some(Duration::ZERO));
+
+        assert_eq!(Track::<f64>::new().duration(), None);
+        assert_eq!(TrackSegment::<f64>::new().moving_time(1.0), None);
+    }
+
+    #[test]
+    fn route_length() {
+        let route = Route::from(segment(&[(0.0, 0.0), (1.0, 0.0)]).linestring());
+
+        // One degree of longitude at the equator is about 111.3 km.
+        assert!((route.length_m() - 111_319.5).abs() < 0.1);
+    }
+
+    #[test]
+    fn track_and_document_bounds() {
+        let mut track = Track::new();
+        assert_eq!(track.recompute_bounds(), None);
+        track.segments.push(segment(&[(1.0, 2.0), (3.0, -1.0)]));
+        track.segments.push(segment(&[(-4.0, 0.0)]));
+        assert_eq!(track.bounds, None);
+        let bounds = Rect::new(coord! { x: -4.0, y: -1.0 }, coord! { x: 3.0, y: 2.0 });
+        assert_eq!(track.recompute_bounds(), Some(bounds));
+        assert_eq!(track.bounds, Some(bounds));
+
+        let mut gpx = Gpx::default();
+        gpx.update_bounds();
+        assert_eq!(gpx.metadata, None);
+        track.segments.push(segment(&[(0.0, 4.0)]));
+        gpx.tracks.push(track);
+        gpx.waypoints.push(Waypoint::new(Point::new(0.0, 5.0)));
+        gpx.update_bounds();
+        let track_bounds = Rect::new(coord! { x: -4.0, y: -1.0 }, coord! { x: 3.0, y: 4.0 });
+        assert_eq!(gpx.tracks[0].bounds, Some(track_bounds));
+        let bounds = Rect::new(coord! { x: -4.0, y: -1.0 }, coord! { x: 3.0, y: 5.0 });
+        assert_eq!(gpx.metadata.as_ref().unwrap().bounds, Some(bounds));
+
+        gpx.tracks.clear();
+        gpx.waypoints.clear();
+        gpx.metadata = Some(Metadata {
+            name: Some("Empty".to_string()),
+            bounds: Some(bounds),
+            ..Default::default()
+        });
+        gpx.update_bounds();
+        let metadata = gpx.metadata.unwrap();
+        assert_eq!(metadata.bounds, None);
+        assert_eq!(metadata.name.as_deref(), Some("Empty"));
+    }
+
+    #[test]
+    fn infer_metadata_time() {
+        let at = |seconds| Some(Time::UNIX_EPOCH + Duration::seconds(seconds));
+        let mut gpx = Gpx::default();
+        gpx.infer_metadata_time();
+        assert_eq!(gpx.metadata, None);
+
+        let mut track = Track::new();
+        track.segments.push(segment(&[(0.0, 0.0), (1.0, 1.0)]));
+        track.segments[0].points[1].time = at(30);
+        gpx.tracks.push(track);
+        let mut waypoint = Waypoint::new(Point::new(0.0, 0.0));
+        waypoint.time = at(60);
+        gpx.waypoints.push(waypoint);
+        gpx.infer_metadata_time();
+        assert_eq!(gpx.metadata.as_ref().unwrap().time, at(30));
+
+        gpx.metadata.as_mut().unwrap().time = at(90);
+        gpx.infer_metadata_time();
+        assert_eq!(gpx.metadata.unwrap().time, at(90));
+    }
+}
@@ -0,0 +1,289 @@
+//! nmea converts NMEA 0183 logs, as written by raw GPS modules, into tracks.
+
+use std::io::{BufRead, BufReader, Read};
+
+use geo_types::Point;
+use time::{Date, Duration, Month, PrimitiveDateTime, Time as TimeOfDay};
+
+use crate::errors::GpxResult;
+use crate::{Fix, Gpx, GpxVersion, Track, TrackSegment, Waypoint};
+
+/// Knots to meters per second.
+const KNOT: f64 = 1852.0 / 3600.0;
+
+/// Reads an NMEA 0183 log into a document with a single track.
+///
+/// `RMC` sentences give the date, time, position and speed, `GGA` sentences
+/// the time, position, elevation and fix details; sentences of both kinds
+/// for the same time are merged into one point. Sentences from any talker,
+/// like `$GP` or `$GN`, are read. Other sentences, sentences with a wrong
+/// checksum or without a valid fix, and lines that aren't NMEA at all are
+/// skipped, as logs of GPS modules are often noisy.
+///
+/// `GGA` sentences carry no date, so points get the date of the closest
+/// `RMC` sentence; without any, their time is left out.
+///
+/// ```
+/// use gpx::from_nmea;
+///
+/// let log = "\
+///     $GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47\n\
+///     $GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A\n";
+///
+/// let gpx = from_nmea(log.as_bytes()).unwrap();
+/// let point = &gpx.tracks[0].segments[0].points[0];
+/// assert_eq!(point.elevation, Some(545.4));
+/// assert_eq!(point.details().sat, Some(8));
+/// assert_eq!(point.time.unwrap().format().unwrap(), "1994-03-23T12:35:19.000000000Z");
+/// ```
+pub fn from_nmea<R: Read>(reader: R) -> GpxResult<Gpx> {
+    let mut epochs: Vec<Epoch> = Vec::new();
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        let Some(fields) = sentence_fields(line.trim()) else {
+            continue;
+        };
+        let epoch = match fields[0].get(2..) {
+            Some("RMC") => parse_rmc(&fields),
+            Some("GGA") => parse_gga(&fields),
+            _ => None,
+        };
+        let Some(epoch) = epoch else {
+            continue;
+        };
+        match epochs.last_mut() {
+            Some(last) if last.time_of_day == epoch.time_of_day => last.merge(epoch),
+            _ => epochs.push(epoch),
+        }
+    }
+    fill_dates(&mut epochs);
+
+    let mut track = Track::new();
+    track.segments.push(TrackSegment {
+        points: epochs.into_iter().map(Epoch::into_waypoint).collect(),
+    });
+    Ok(Gpx {
+        version: GpxVersion::Gpx11,
+        tracks: vec![track],
+        ..Default::default()
+    })
+}
+
+/// Verifies the checksum of a sentence and splits it into its fields, the
+/// first being the address, like `GPRMC`.
+fn sentence_fields(line: &str) -> Option<Vec<&str>> {
+    let (body, checksum) = line.strip_prefix('$')?.split_once('*')?;
+    let expected = u8::from_str_radix(checksum.get(..2)?, 16).ok()?;
+    let actual = body.bytes().fold(0, |checksum, byte| checksum ^ byte);
+    (actual == expected).then(|| body.split(',').collect())
+}
+
+/// A fix of the receiver, gathered from the sentences sharing a time.
+#[derive(Default)]
+struct Epoch {
+    time_of_day: Option<TimeOfDay>,
+    date: Option<Date>,
+    position: Option<Point<f64>>,
+    elevation: Option<f64>,
+    speed: Option<f64>,
+    fix: Option<Fix>,
+    sat: Option<u64>,
+    hdop: Option<f64>,
+    geoidheight: Option<f64>,
+    dgps_age: Option<f64>,
+    dgpsid: Option<u16>,
+}
+
+impl Epoch {
+    fn merge(&mut self, other: Epoch) {
+        self.date = self.date.or(other.date);
+        self.position = other.position.or(self.position);
+        self.elevation = self.elevation.or(other.elevation);
+        self.speed = self.speed.or(other.speed);
+        self.fix = self.fix.take().or(other.fix);
+        self.sat = self.sat.or(other.sat);
+        self.hdop = self.hdop.or(other.hdop);
+        self.geoidheight = self.geoidheight.or(other.geoidheight);
+        self.dgps_age = self.dgps_age.or(other.dgps_age);
+        self.dgpsid = self.dgpsid.or(other.dgpsid);
+    }
+
+    fn into_waypoint(self) -> Waypoint {
+        let mut waypoint = Waypoint::new(self.position.unwrap_or_default());
+        if let (Some(date), Some(time_of_day)) = (self.date, self.time_of_day) {
+            waypoint.time = Some(
+                PrimitiveDateTime::new(date, time_of_day)
+                    .assume_utc()
+                    .into(),
+            );
+        }
+        waypoint.elevation = self.elevation;
+        waypoint.speed = self.speed;
+        if self.fix.is_some() {
+            let details = waypoint.details_mut();
+            details.fix = self.fix;
+            details.sat = self.sat;
+            details.hdop = self.hdop;
+            details.geoidheight = self.geoidheight;
+            details.dgps_age = self.dgps_age;
+            details.dgpsid = self.dgpsid;
+        }
+        waypoint
+    }
+}
+
+/// Parses `$--RMC,time,status,lat,N/S,lon,E/W,knots,course,date,...`.
+fn parse_rmc(fields: &[&str]) -> Option<Epoch> {
+    if fields.get(2) != Some(&"A") {
+        return None;
+    }
+    Some(Epoch {
+        time_of_day: Some(parse_time_of_day(fields.get(1)?)?),
+        position: Some(parse_position(fields.get(3..7)?)?),
+        speed: fields.get(7)?.parse::<f64>().ok().map(|knots| knots * KNOT),
+        date: parse_date(fields.get(9)?),
+        ..Default::default()
+    })
+}
+
+/// Parses `$--GGA,time,lat,N/S,lon,E/W,quality,sats,hdop,alt,M,geoid,M,age,id`.
+fn parse_gga(fields: &[&str]) -> Option<Epoch> {
+    let elevation = fields.get(9)?.parse().ok();
+    let fix = match *fields.get(6)? {
+        "1" if elevation.is_some() => Fix::ThreeDimensional,
+        "1" => Fix::TwoDimensional,
+        "2" => Fix::DGPS,
+        "3" => Fix::PPS,
+        "4" => Fix::Other("rtk".to_string()),
+        "5" => Fix::Other("float rtk".to_string()),
+        "6" => Fix::Other("dead reckoning".to_string()),
+        _ => return None,
+    };
+    Some(Epoch {
+        time_of_day: Some(parse_time_of_day(fields.get(1)?)?),
+        position: Some(parse_position(fields.get(2..6)?)?),
+        elevation,
+        fix: Some(fix),
+        sat: fields.get(7)?.parse().ok(),
+        hdop: fields.get(8)?.parse().ok(),
+        geoidheight: fields.get(11).and_then(|value| value.parse().ok()),
+        dgps_age: fields.get(13).and_then(|value| value.parse().ok()),
+        dgpsid: fields.get(14).and_then(|value| value.parse().ok()),
+        ..Default::default()
+    })
+}
+
+/// Parses `hhmmss.sss`.
+fn parse_time_of_day(value: &str) -> Option<TimeOfDay> {
+    let hour = value.get(0..2)?.parse().ok()?;
+    let minute = value.get(2..4)?.parse().ok()?;
+    let seconds: f64 = value.get(4..)?.parse().ok()?;
+    let nanoseconds = (seconds.fract() * 1e9).round() as u32;
+    TimeOfDay::from_hms_nano(hour, minute, seconds as u8, nanoseconds).ok()
+}
+
+/// Parses `ddmmyy`, taking years before 80 to be in the 21st century.
+fn parse_date(value: &str) -> Option<Date> {
+    let day = value.get(0..2)?.parse().ok()?;
+    let month: u8 = value.get(2..4)?.parse().ok()?;
+    let year: i32 = value.get(4..6)?.parse().ok()?;
+    let year = if year < 80 { 2000 + year } else { 1900 + year };
+    Date::from_calendar_date(year, Month::try_from(month).ok()?, day).ok()
+}
+
+/// Parses `ddmm.mmmm,N/S,dddmm.mmmm,E/W`.
+fn parse_position(fields: &[&str]) -> Option<Point<f64>> {
+    let latitude = parse_angle(fields[0], 2, fields[1], "S")?;
+    let longitude = parse_angle(fields[2], 3, fields[3], "W")?;
+    ((-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude))
+        .then(|| Point::new(longitude, latitude))
+}
+
+fn parse_angle(value: &str, degree_digits: usize, hemisphere: &str, negative: &str) -> Option<f64> {
+    let degrees: f64 = value.get(..degree_digits)?.parse().ok()?;
+    let minutes: f64 = value.get(degree_digits..)?.parse().ok()?;
+    let angle = degrees + minutes / 60.0;
+    Some(if hemisphere == negative {
+        -angle
+    } else {
+        angle
+    })
+}
+
+/// Gives the epochs without a date the date of the previous epoch with one,
+/// moving to the next day when the time of day wraps around midnight.
+/// Leading epochs get the date of the first epoch with one.
+fn fill_dates(epochs: &mut [Epoch]) {
+    let Some(first) = epochs.iter().position(|epoch| epoch.date.is_some()) else {
+        return;
+    };
+    let date = epochs[first].date;
+    for epoch in &mut epochs[..first] {
+        epoch.date = date;
+    }
+    for i in first + 1..epochs.len() {
+        if epochs[i].date.is_some() {
+            continue;
+        }
+        let previous = &epochs[i - 1];
+        let mut date = previous.date;
+        if epochs[i].time_of_day < previous.time_of_day {
+            date = date.and_then(|date| date.checked_add(Duration::DAY));
+        }
+        epochs[i].date = date;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_nmea, sentence_fields};
+    use crate::Fix;
+
+    #[test]
+    fn nmea_log() {
+        let log = "\
+            $GPRMC,235959.50,A,4807.038,N,01131.000,E,010.0,084.4,311299,003.1,W*46\n\
+            garbage\n\
+            $GNGGA,235959.50,4807.038,N,01131.000,E,2,10,0.8,545.4,M,46.9,M,1.5,0012*5C\n\
+            $GPGGA,000000.50,4807.100,S,01131.100,W,1,05,1.2,,M,,M,,*59\n\
+            $GPGGA,000001.50,4807.200,S,01131.200,W,1,05,1.2,,M,,M,,*00\n\
+            $GPGGA,000002.50,4807.300,S,01131.300,W,0,00,,,M,,M,,*72\n\
+            $GPGSV,3,1,11,03,03,111,00,04,15,270,00,06,01,010,00,13,06,292,00*74\n";
+
+        let gpx = from_nmea(log.as_bytes()).unwrap();
+        let points = &gpx.tracks[0].segments[0].points;
+        assert_eq!(points.len(), 2);
+
+        let first = &points[0];
+        assert!((first.point().y() - 48.1173).abs() < 1e-9);
+        assert!((first.point().x() - 11.516_666_666_666_667).abs() < 1e-9);
+        assert!((first.speed.unwrap() - 5.144).abs() < 0.001);
+        assert_eq!(first.details().fix, Some(Fix::DGPS));
+        assert_eq!(first.details().sat, Some(10));
+        assert_eq!(first.details().hdop, Some(0.8));
+        assert_eq!(first.details().geoidheight, Some(46.9));
+        assert_eq!(first.details().dgps_age, Some(1.5));
+        assert_eq!(first.details().dgpsid, Some(12));
+        assert_eq!(
+            first.time.unwrap().format().unwrap(),
+            "1999-12-31T23:59:59.500000000Z"
+        );
+
+        let second = &points[1];
+        assert!(second.point().y() < 0.0 && second.point().x() < 0.0);
+        assert_eq!(second.details().fix, Some(Fix::TwoDimensional));
+        assert_eq!(second.elevation, None);
+        assert_eq!(
+            second.time.unwrap().format().unwrap(),
+            "2000-01-01T00:00:00.500000000Z"
+        );
+    }
+
+    #[test]
+    fn nmea_checksum() {
+        assert!(sentence_fields("$GPGGA,1*4C").is_none());
+        assert!(sentence_fields("GPGGA,1*5D").is_none());
+        assert!(sentence_fields("$GPGGA,1").is_none());
+        assert_eq!(sentence_fields("$GPGGA,1*4B"), Some(vec!["GPGGA", "1"]));
+    }
+}
@@ -0,0 +1,87 @@
+//! number converts floating point values from and to their text in GPX
+//! files.
+//!
+//! With the `fast-float` feature, numbers are parsed with `fast-float2` and
+//! formatted with `ryu`, which is several times faster than the standard
+//! library on dense tracks. Both give the same values and text as the
+//! standard library.
+
+use std::fmt::Display;
+use std::num::ParseFloatError;
+
+use geo_types::CoordFloat;
+
+/// Parses a floating point value, accepting what `str::parse` accepts.
+#[cfg(not(feature = "fast-float"))]
+pub(crate) fn parse_f64(value: &str) -> Result<f64, ParseFloatError> {
+    value.parse()
+}
+
+/// Parses a floating point value, accepting what `str::parse` accepts.
+#[cfg(feature = "fast-float")]
+pub(crate) fn parse_f64(value: &str) -> Result<f64, ParseFloatError> {
+    // The standard library gives the error, as fast-float2's can't be
+    // converted into one; invalid numbers are rare enough for this not to
+    // matter.
+    fast_float2::parse(value).or_else(|_| value.parse())
+}
+
+/// Formats a floating point value like its `Display` implementation.
+#[cfg(not(feature = "fast-float"))]
+pub(crate) fn format<T: CoordFloat + Display>(value: T) -> String {
+    value.to_string()
+}
+
+/// Formats a floating point value like its `Display` implementation.
+#[cfg(feature = "fast-float")]
+pub(crate) fn format<T: CoordFloat + Display>(value: T) -> String {
+    let mut buffer = ryu::Buffer::new();
+    // `CoordFloat` is only implemented by `f32` and `f64`; formatting an
+    // `f32` as `f64` would give its inexact binary digits.
+    let text = if std::mem::size_of::<T>() == std::mem::size_of::<f32>() {
+        value.to_f32().map(|value| buffer.format(value))
+    } else {
+        value.to_f64().map(|value| buffer.format(value))
+    };
+    match text {
+        // ryu uses exponents for very small and large values, which
+        // xsd:decimal doesn't allow.
+        Some(text) if value.is_finite() && !text.contains('e') => {
+            text.strip_suffix(".0").unwrap_or(text).to_string()
+        }
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format, parse_f64};
+
+    #[test]
+    fn numbers_match_std() {
+        for value in [
+            0.0,
+            -0.0,
+            1.0,
+            -121.97,
+            37.24,
+            0.1,
+            1e-7,
+            123456789.125,
+            1e21,
+            4.46,
+        ] {
+            assert_eq!(format(value), value.to_string());
+            assert_eq!(format(value as f32), (value as f32).to_string());
+            assert_eq!(parse_f64(&format(value)), Ok(value));
+        }
+        assert_eq!(format(f64::NAN), "NaN");
+        assert_eq!(format(f64::NEG_INFINITY), "-inf");
+
+        for text in [
+            "1.5", "+1.5", "-.5", "1e3", "inf", "", "1.5.", " 1", "0x1", "1,5",
+        ] {
+            assert_eq!(parse_f64(text), text.parse::<f64>());
+        }
+    }
+}
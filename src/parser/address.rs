@@ -0,0 +1,138 @@
+//! address handles parsing of the addresses and phone numbers of Garmin's
+//! extensions.
+
+use std::io::Read;
+
+use xml::reader::XmlEvent;
+
+use crate::errors::{GpxError, GpxResult};
+use crate::parser::{skip_element, string, verify_starting_tag, Context};
+use crate::{Address, PhoneNumber};
+
+/// consume consumes an `Address` element, skipping its extensions.
+pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<Address> {
+    verify_starting_tag(context, "Address")?;
+    let mut address = Address::default();
+
+    loop {
+        let next_event = match context.reader.peek() {
+            Some(Ok(event)) => event,
+            Some(Err(_)) => return Err(GpxError::EventParsingError("Address event")),
+            None => break,
+        };
+
+        match next_event {
+            XmlEvent::StartElement { ref name, .. } => match name.local_name.as_ref() {
+                "StreetAddress" => address
+                    .street_address
+                    .push(string::consume_value(context, "StreetAddress")?),
+                "City" => address.city = Some(string::consume_value(context, "City")?),
+                "State" => address.state = Some(string::consume_value(context, "State")?),
+                "Country" => address.country = Some(string::consume_value(context, "Country")?),
+                "PostalCode" => {
+                    address.postal_code = Some(string::consume_value(context, "PostalCode")?)
+                }
+                _ => skip_element(context)?,
+            },
+            XmlEvent::EndElement { ref name } => {
+                if name.local_name != "Address" {
+                    return Err(GpxError::InvalidClosingTag(
+                        name.local_name.clone(),
+                        "Address",
+                    ));
+                }
+                context.reader.next(); //consume the end tag
+                return Ok(address);
+            }
+            _ => {
+                context.reader.next(); //consume and ignore this event
+            }
+        }
+    }
+
+    Err(GpxError::MissingClosingTag("Address"))
+}
+
+/// consume_phone_number consumes a `PhoneNumber` element with its optional
+/// `Category` attribute.
+pub fn consume_phone_number<R: Read>(context: &mut Context<R>) -> GpxResult<PhoneNumber> {
+    let attributes = verify_starting_tag(context, "PhoneNumber")?;
+    let category = attributes
+        .into_iter()
+        .find(|attr| attr.name.local_name == "Category")
+        .map(|attr| attr.value);
+    let mut number = String::new();
+
+    for event in &mut context.reader {
+        match event? {
+            XmlEvent::StartElement { ref name, .. } => {
+                return Err(GpxError::InvalidChildElement(
+                    name.local_name.clone(),
+                    "PhoneNumber",
+                ));
+            }
+            XmlEvent::Characters(content) | XmlEvent::CData(content) => number.push_str(&content),
+            XmlEvent::EndElement { ref name } => {
+                if name.local_name != "PhoneNumber" {
+                    return Err(GpxError::InvalidClosingTag(
+                        name.local_name.clone(),
+                        "PhoneNumber",
+                    ));
+                }
+                let number = number.trim();
+                if number.is_empty() {
+                    return Err(GpxError::NoStringContent);
+                }
+                return Ok(PhoneNumber {
+                    number: number.to_string(),
+                    category,
+                });
+            }
+            _ => {} //consume and ignore other events
+        }
+    }
+    Err(GpxError::MissingClosingTag("PhoneNumber"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{consume, consume_phone_number};
+    use crate::parser::create_context;
+    use crate::GpxVersion;
+
+    #[test]
+    fn consume_address() {
+        let address = consume!(
+            "<Address>
+                <StreetAddress>1200 E 151st St</StreetAddress>
+                <StreetAddress>Building 3</StreetAddress>
+                <City>Olathe</City>
+                <State>KS</State>
+                <Country>USA</Country>
+                <PostalCode>66062</PostalCode>
+                <Extensions><Note>ignored</Note></Extensions>
+            </Address>",
+            GpxVersion::Gpx11
+        )
+        .unwrap();
+
+        assert_eq!(address.street_address, ["1200 E 151st St", "Building 3"]);
+        assert_eq!(address.city.as_deref(), Some("Olathe"));
+        assert_eq!(address.state.as_deref(), Some("KS"));
+        assert_eq!(address.country.as_deref(), Some("USA"));
+        assert_eq!(address.postal_code.as_deref(), Some("66062"));
+    }
+
+    #[test]
+    fn consume_phone_numbers() {
+        let xml = "<PhoneNumber Category=\"Fax\"> +1 913 397 8200 </PhoneNumber>";
+        let phone_number =
+            consume_phone_number(&mut create_context(xml.as_bytes(), GpxVersion::Gpx11)).unwrap();
+        assert_eq!(phone_number.number, "+1 913 397 8200");
+        assert_eq!(phone_number.category.as_deref(), Some("Fax"));
+
+        let xml = "<PhoneNumber></PhoneNumber>";
+        let result = consume_phone_number(&mut create_context(xml.as_bytes(), GpxVersion::Gpx11));
+        assert!(result.is_err());
+    }
+}
@@ -1,13 +1,13 @@
 use std::io::Read;
 
-use geo_types::{Coord, Rect};
+use geo_types::{Coord, CoordFloat, Rect};
 use xml::reader::XmlEvent;
 
 use crate::errors::{GpxError, GpxResult};
-use crate::parser::{verify_starting_tag, Context};
+use crate::parser::{parse_float, verify_starting_tag, Context};
 
 /// consume consumes a bounds element until it ends.
-pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<Rect<f64>> {
+pub fn consume<R: Read, T: CoordFloat>(context: &mut Context<R>) -> GpxResult<Rect<T>> {
     let attributes = verify_starting_tag(context, "bounds")?;
     // get required bounds
     let minlat = attributes
@@ -19,8 +19,8 @@ pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<Rect<f64>> {
         .find(|attr| attr.name.local_name == "maxlat")
         .ok_or(GpxError::InvalidElementLacksAttribute("maxlat", "bounds"))?;
 
-    let minlat: f64 = minlat.value.parse()?;
-    let maxlat: f64 = maxlat.value.parse()?;
+    let minlat: T = parse_float(context, &minlat.value)?;
+    let maxlat: T = parse_float(context, &maxlat.value)?;
 
     let minlon = attributes
         .iter()
@@ -31,8 +31,8 @@ pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<Rect<f64>> {
         .find(|attr| attr.name.local_name == "maxlon")
         .ok_or(GpxError::InvalidElementLacksAttribute("maxlon", "bounds"))?;
 
-    let minlon: f64 = minlon.value.parse()?;
-    let maxlon: f64 = maxlon.value.parse()?;
+    let minlon: T = parse_float(context, &minlon.value)?;
+    let maxlon: T = parse_float(context, &maxlon.value)?;
 
     // Verify bounding box first, since Rect::new will panic if these are wrong.
     if minlon > maxlon {
@@ -41,7 +41,7 @@ pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<Rect<f64>> {
         return Err(GpxError::OutOfBounds("latitude"));
     }
 
-    let bounds: Rect<f64> = Rect::new(
+    let bounds: Rect<T> = Rect::new(
         Coord {
             x: minlon,
             y: minlat,
@@ -72,7 +72,10 @@ pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<Rect<f64>> {
 
 #[cfg(test)]
 mod tests {
+    use geo_types::Rect;
+
     use super::consume;
+    use crate::errors::GpxResult;
     use crate::GpxVersion;
 
     #[test]
@@ -86,7 +89,7 @@ mod tests {
 
         assert!(bounds.is_ok());
 
-        let bounds = bounds.unwrap();
+        let bounds: Rect = bounds.unwrap();
         assert_eq!(bounds.min().x, -74.031837463);
         assert_eq!(bounds.min().y, 45.487064362);
         assert_eq!(bounds.max().x, -73.586273193);
@@ -95,7 +98,7 @@ mod tests {
 
     #[test]
     fn consume_bad_bounds() {
-        let bounds = consume!(
+        let bounds: GpxResult<Rect> = consume!(
             "<bounds minlat=\"32.4\" minlon=\"notanumber\"></wpt>",
             GpxVersion::Gpx11
         );
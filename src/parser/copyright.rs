@@ -8,6 +8,26 @@ use crate::errors::{GpxError, GpxResult};
 use crate::parser::{string, verify_starting_tag, Context};
 use crate::GpxCopyright;
 
+/// Parses the year of an xsd:gYear, `[-]CCYY[Z|(+|-)hh:mm]`, dropping the
+/// timezone.
+fn parse_year(value: &str) -> Option<i32> {
+    let value = value.trim();
+    let year = match value.strip_suffix('Z') {
+        Some(year) => year,
+        None => match value.len().checked_sub(6) {
+            Some(start) if is_offset(&value.as_bytes()[start..]) => &value[..start],
+            _ => value,
+        },
+    };
+    year.parse().ok()
+}
+
+/// Tells whether `value` is a timezone offset, `(+|-)hh:mm`.
+fn is_offset(value: &[u8]) -> bool {
+    matches!(value, [b'+' | b'-', h1, h2, b':', m1, m2]
+        if [h1, h2, m1, m2].iter().all(|digit| digit.is_ascii_digit()))
+}
+
 /// consume consumes a GPX copyright from the `reader` until it ends.
 /// When it returns, the reader will be at the element after the end GPX copyright tag.
 pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<GpxCopyright> {
@@ -34,7 +54,7 @@ pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<GpxCopyright> {
         match next_event {
             XmlEvent::StartElement { ref name, .. } => match name.local_name.as_ref() {
                 "license" => copyright.license = Some(string::consume(context, "license", false)?),
-                "year" => copyright.year = string::consume(context, "year", false)?.parse().ok(),
+                "year" => copyright.year = parse_year(&string::consume(context, "year", false)?),
                 child => {
                     return Err(GpxError::InvalidChildElement(
                         String::from(child),
@@ -102,4 +122,21 @@ mod tests {
 
         assert_eq!(copyright.author.unwrap(), "pelmers");
     }
+
+    #[test]
+    fn consume_year_with_timezone() {
+        let year = |value: &str| {
+            let xml = format!("<copyright author='a'><year>{value}</year></copyright>");
+            consume!(xml, GpxVersion::Gpx11).unwrap().year
+        };
+        assert_eq!(year("2020+01:00"), Some(2020));
+        assert_eq!(year("2020-05:30"), Some(2020));
+        assert_eq!(year("2020Z"), Some(2020));
+        assert_eq!(year("-0044"), Some(-44));
+        assert_eq!(year("-0044-01:00"), Some(-44));
+        assert_eq!(year(" 1999 "), Some(1999));
+        assert_eq!(year("2020+1:00"), None);
+        assert_eq!(year("twenty"), None);
+        assert_eq!(year("éé:00"), None);
+    }
 }
@@ -10,20 +10,28 @@ use crate::parser::{verify_starting_tag, Context};
 /// consume consumes a GPX email from the `reader` until it ends.
 /// When it returns, the reader will be at the element after the end GPX email
 /// tag.
+///
+/// Both the GPX 1.1 form, with `id` and `domain` attributes, and the GPX 1.0
+/// form, with the whole address as content, are accepted.
 pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<String> {
     let attributes = verify_starting_tag(context, "email")?;
-    // get required id and domain attributes
-    let id = attributes
-        .iter()
-        .find(|attr| attr.name.local_name == "id")
-        .ok_or(GpxError::InvalidElementLacksAttribute("id", "email"))?;
-
+    let id = attributes.iter().find(|attr| attr.name.local_name == "id");
     let domain = attributes
         .iter()
-        .find(|attr| attr.name.local_name == "domain")
-        .ok_or(GpxError::InvalidElementLacksAttribute("domain", "email"))?;
-
-    let email = format!("{id}@{domain}", id = &id.value, domain = &domain.value);
+        .find(|attr| attr.name.local_name == "domain");
+
+    // get required id and domain attributes, unless neither is given
+    let mut email = match (id, domain) {
+        (Some(id), Some(domain)) => Some(format!(
+            "{id}@{domain}",
+            id = &id.value,
+            domain = &domain.value
+        )),
+        (None, None) => None,
+        (None, Some(_)) => return Err(GpxError::InvalidElementLacksAttribute("id", "email")),
+        (Some(_), None) => return Err(GpxError::InvalidElementLacksAttribute("domain", "email")),
+    };
+    let has_attributes = email.is_some();
 
     for event in &mut context.reader {
         match event? {
@@ -34,7 +42,10 @@ pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<String> {
                 ));
             }
             XmlEvent::Characters(content) => {
-                return Err(GpxError::InvalidChildElement(content, "email"));
+                if has_attributes {
+                    return Err(GpxError::InvalidChildElement(content, "email"));
+                }
+                email = Some(content);
             }
             XmlEvent::EndElement { ref name } => {
                 if name.local_name != "email" {
@@ -43,7 +54,7 @@ pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<String> {
                         "email",
                     ));
                 }
-                return Ok(email);
+                return email.ok_or(GpxError::NoStringContent);
             }
             _ => {} //consume and ignore other events
         }
@@ -121,4 +132,13 @@ mod tests {
 
         assert_eq!(err.to_string(), "error while parsing XML");
     }
+
+    #[test]
+    fn consume_gpx10_email() {
+        let email = consume!("<email>me@example.com</email>", GpxVersion::Gpx10);
+        assert_eq!(email.unwrap(), "me@example.com");
+
+        let err = consume!("<email></email>", GpxVersion::Gpx10).unwrap_err();
+        assert_eq!(err.to_string(), "no string content");
+    }
 }
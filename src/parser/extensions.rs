@@ -1,16 +1,110 @@
 //! extensions handles parsing of GPX-spec extensions.
 
-// TODO: extensions are not implemented
-
 use std::io::Read;
 
+use geo_types::CoordFloat;
 use xml::reader::XmlEvent;
 
 use crate::errors::{GpxError, GpxResult};
-use crate::parser::Context;
+use crate::parser::{
+    linestyle, routepointextension, skip_element, string, trackpointextension, waypointextension,
+    Context,
+};
+use crate::{Track, Waypoint};
 
 use super::verify_starting_tag;
 
+/// consume_waypoint consumes the extensions of a waypoint, storing the known
+/// ones in `waypoint` and skipping all others.
+pub fn consume_waypoint<R: Read, T: CoordFloat>(
+    context: &mut Context<R>,
+    waypoint: &mut Waypoint<T>,
+) -> GpxResult<()> {
+    verify_starting_tag(context, "extensions")?;
+
+    loop {
+        let next_event = match context.reader.peek() {
+            Some(Ok(event)) => event,
+            Some(Err(_)) => return Err(GpxError::EventParsingError("extensions event")),
+            None => break,
+        };
+
+        match next_event {
+            XmlEvent::StartElement { ref name, .. } => match name.local_name.as_ref() {
+                "TrackPointExtension" => {
+                    waypoint.track_point_extension = Some(trackpointextension::consume(context)?)
+                }
+                "RoutePointExtension" => {
+                    waypoint.route_point_extension =
+                        Some(Box::new(routepointextension::consume(context)?))
+                }
+                "WaypointExtension" => {
+                    waypoint.details_mut().waypoint_extension =
+                        Some(waypointextension::consume(context)?)
+                }
+                _ => skip_element(context)?,
+            },
+            XmlEvent::EndElement { .. } => {
+                context.reader.next(); //consume the end tag
+                return Ok(());
+            }
+            _ => {
+                context.reader.next(); //consume and ignore this event
+            }
+        }
+    }
+
+    Err(GpxError::MissingClosingTag("extensions"))
+}
+
+/// consume_track consumes the extensions of a track, storing the display
+/// color of Garmin's `TrackExtension` and the `line` of gpx_style in
+/// `track` and skipping all others.
+pub fn consume_track<R: Read, T: CoordFloat>(
+    context: &mut Context<R>,
+    track: &mut Track<T>,
+) -> GpxResult<()> {
+    verify_starting_tag(context, "extensions")?;
+
+    // Garmin's track extension is entered rather than skipped, to find its
+    // display color.
+    let mut depth = 1;
+    loop {
+        let next_event = match context.reader.peek() {
+            Some(Ok(event)) => event,
+            Some(Err(_)) => return Err(GpxError::EventParsingError("extensions event")),
+            None => break,
+        };
+
+        match next_event {
+            XmlEvent::StartElement { ref name, .. } if name.local_name == "DisplayColor" => {
+                let color = string::consume_value(context, "DisplayColor")?;
+                track.display_color = Some(color.into());
+            }
+            XmlEvent::StartElement { ref name, .. } if name.local_name == "line" => {
+                track.line_style = Some(linestyle::consume(context)?);
+            }
+            XmlEvent::StartElement { ref name, .. } if name.local_name == "TrackExtension" => {
+                context.reader.next();
+                depth += 1;
+            }
+            XmlEvent::StartElement { .. } => skip_element(context)?,
+            XmlEvent::EndElement { .. } => {
+                context.reader.next(); //consume the end tag
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(());
+                }
+            }
+            _ => {
+                context.reader.next(); //consume and ignore this event
+            }
+        }
+    }
+
+    Err(GpxError::MissingClosingTag("extensions"))
+}
+
 /// consume consumes a single string as tag content.
 pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<()> {
     verify_starting_tag(context, "extensions")?;
@@ -18,21 +112,17 @@ pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<()> {
     let mut depth = 1;
     for event in &mut context.reader {
         match event? {
-            XmlEvent::StartElement { name, .. } => {
-                // I think its bad to hardcode the check on name == "extensions", because it is not a generic approach
-                // and treats inner tags that are called "extensions" differently from any other inner tags, like "a", "foo", "bar"
-                // It is correct, but feels wrong, maybe only a personal feeling
-                if name.local_name == "extensions" {
-                    depth += 1;
-                }
+            // I think its bad to hardcode the check on name == "extensions", because it is not a generic approach
+            // and treats inner tags that are called "extensions" differently from any other inner tags, like "a", "foo", "bar"
+            // It is correct, but feels wrong, maybe only a personal feeling
+            XmlEvent::StartElement { name, .. } if name.local_name == "extensions" => {
+                depth += 1;
             }
-            XmlEvent::EndElement { name } => {
-                if name.local_name == "extensions" {
-                    // pop one
-                    depth -= 1;
-                    if depth == 0 {
-                        return Ok(());
-                    }
+            XmlEvent::EndElement { name } if name.local_name == "extensions" => {
+                // pop one
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(());
                 }
             }
             _ => {}
@@ -8,7 +8,7 @@ use crate::types::Fix;
 
 /// consume consumes an element as a fix.
 pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<Fix> {
-    let fix_string = string::consume(context, "fix", false)?;
+    let fix_string = string::consume_value(context, "fix")?;
 
     let fix = match fix_string.as_ref() {
         "none" => Fix::None,
@@ -1,20 +1,23 @@
 //! gpx handles parsing of GPX elements.
 
-use geo_types::Rect;
+use geo_types::{CoordFloat, Rect};
 use std::io::Read;
+use xml::attribute::OwnedAttribute;
+use xml::name::OwnedName;
+use xml::namespace::{Namespace, NS_NO_PREFIX, NS_XMLNS_PREFIX, NS_XML_PREFIX};
 use xml::reader::XmlEvent;
 
-use crate::errors::{GpxError, GpxResult};
+use crate::errors::{GpxError, GpxResult, GpxWarning};
 use crate::parser::time::Time;
 use crate::parser::{
-    bounds, metadata, route, string, time, track, verify_starting_tag, waypoint, Context,
+    bounds, email, metadata, route, string, time, track, verify_starting_element, waypoint, Context,
 };
-use crate::{Gpx, GpxVersion, Link, Metadata, Person};
+use crate::{Comment, CommentPosition, Gpx, GpxVersion, Link, Metadata, Person};
 
 use super::extensions;
 
 /// Convert the version string to the version enum
-fn version_string_to_version(version_str: &str) -> GpxResult<GpxVersion> {
+pub(crate) fn version_string_to_version(version_str: &str) -> GpxResult<GpxVersion> {
     match version_str {
         "1.0" => Ok(GpxVersion::Gpx10),
         "1.1" => Ok(GpxVersion::Gpx11),
@@ -22,35 +25,202 @@ fn version_string_to_version(version_str: &str) -> GpxResult<GpxVersion> {
     }
 }
 
+/// Determines the version of a document from the `version` attribute and
+/// the namespace of its root element, which wins if they disagree or the
+/// attribute is missing or unknown.
+pub(crate) fn detect_version(
+    name: &OwnedName,
+    attributes: &[OwnedAttribute],
+) -> GpxResult<(GpxVersion, Option<GpxWarning>)> {
+    let namespace = match name.namespace.as_deref() {
+        Some("http://www.topografix.com/GPX/1/0") => Some(GpxVersion::Gpx10),
+        Some("http://www.topografix.com/GPX/1/1") => Some(GpxVersion::Gpx11),
+        _ => None,
+    };
+    let attribute = attributes
+        .iter()
+        .find(|attr| attr.name.local_name == "version");
+    match (attribute, namespace) {
+        (None, None) => Err(GpxError::InvalidElementLacksAttribute("version", "gpx")),
+        (None, Some(namespace)) => {
+            Ok((namespace, Some(GpxWarning::VersionFromNamespace(namespace))))
+        }
+        (Some(attribute), None) => Ok((version_string_to_version(&attribute.value)?, None)),
+        (Some(attribute), Some(namespace)) => match version_string_to_version(&attribute.value) {
+            Ok(version) if version == namespace => Ok((version, None)),
+            _ => Ok((
+                namespace,
+                Some(GpxWarning::VersionMismatch(
+                    attribute.value.clone(),
+                    namespace,
+                )),
+            )),
+        },
+    }
+}
+
+/// The metadata of GPX 1.0, whose fields are children of the root element.
+pub(crate) struct Gpx10Metadata<T: CoordFloat> {
+    author: Option<String>,
+    url: Option<String>,
+    urlname: Option<String>,
+    email: Option<String>,
+    time: Option<Time>,
+    bounds: Option<Rect<T>>,
+    name: Option<String>,
+    description: Option<String>,
+    keywords: Option<String>,
+}
+
+impl<T: CoordFloat> Default for Gpx10Metadata<T> {
+    fn default() -> Gpx10Metadata<T> {
+        Gpx10Metadata {
+            author: None,
+            url: None,
+            urlname: None,
+            email: None,
+            time: None,
+            bounds: None,
+            name: None,
+            description: None,
+            keywords: None,
+        }
+    }
+}
+
+impl<T: CoordFloat> Gpx10Metadata<T> {
+    /// Consumes the next element if it is one of the metadata fields,
+    /// telling whether it was.
+    pub(crate) fn consume<R: Read>(&mut self, context: &mut Context<R>) -> GpxResult<bool> {
+        let name = match context.reader.peek() {
+            Some(Ok(XmlEvent::StartElement { name, .. })) => name.local_name.as_str(),
+            _ => return Ok(false),
+        };
+        match name {
+            "time" => self.time = Some(time::consume(context)?),
+            "bounds" => self.bounds = Some(bounds::consume(context)?),
+            "author" => self.author = Some(string::consume(context, "author", false)?),
+            "email" => self.email = Some(email::consume(context)?),
+            "url" => self.url = Some(string::consume(context, "url", false)?),
+            "urlname" => self.urlname = Some(string::consume(context, "urlname", false)?),
+            "name" => self.name = Some(string::consume(context, "name", false)?),
+            "desc" => self.description = Some(string::consume(context, "desc", true)?),
+            "keywords" => self.keywords = Some(string::consume(context, "keywords", true)?),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Gives the metadata, or `None` if there was none.
+    pub(crate) fn into_metadata(self) -> Option<Metadata<T>> {
+        let link = self.url.map(|url| Link {
+            href: url,
+            text: self.urlname,
+            ..Default::default()
+        });
+        let person: Person = Person {
+            name: self.author,
+            email: self.email,
+            link,
+        };
+        let author = if person != Default::default() {
+            Some(person)
+        } else {
+            None
+        };
+        let metadata: Metadata<T> = Metadata {
+            name: self.name,
+            time: self.time,
+            bounds: self.bounds,
+            keywords: self.keywords,
+            description: self.description,
+            author,
+            ..Default::default()
+        };
+
+        if metadata != Default::default() {
+            Some(metadata)
+        } else {
+            None
+        }
+    }
+}
+
+/// Gives the attributes of the root element besides `version` and
+/// `creator`, with the namespace declarations first. Declarations of the
+/// GPX namespaces are left out, as they follow from the version.
+pub(crate) fn root_attributes(
+    attributes: &[OwnedAttribute],
+    namespace: &Namespace,
+) -> Vec<(String, String)> {
+    let declarations = namespace
+        .into_iter()
+        .filter(|(prefix, uri)| {
+            ![NS_NO_PREFIX, NS_XML_PREFIX, NS_XMLNS_PREFIX].contains(prefix)
+                && !uri.starts_with("http://www.topografix.com/GPX/1/")
+        })
+        .map(|(prefix, uri)| (format!("xmlns:{prefix}"), uri.to_string()));
+    let others = attributes
+        .iter()
+        .filter(|attr| {
+            attr.name.prefix.is_some()
+                || !["version", "creator"].contains(&attr.name.local_name.as_str())
+        })
+        .map(|attr| (attr.name.borrow().to_repr(), attr.value.clone()));
+    declarations.chain(others).collect()
+}
+
 /// consume consumes an entire GPX element.
-pub fn consume<R: Read>(context: &mut Context<R>) -> Result<Gpx, GpxError> {
-    let mut gpx: Gpx = Default::default();
-
-    let mut author: Option<String> = None;
-    let mut url: Option<String> = None;
-    let mut urlname: Option<String> = None;
-    let mut email: Option<String> = None;
-    let mut time: Option<Time> = None;
-    let mut bounds: Option<Rect<f64>> = None;
-    let mut gpx_name: Option<String> = None;
-    let mut description: Option<String> = None;
-    let mut keywords: Option<String> = None;
+pub fn consume<R: Read, T: CoordFloat>(context: &mut Context<R>) -> Result<Gpx<T>, GpxError> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("gpx").entered();
+    #[cfg(feature = "tracing")]
+    let start = std::time::Instant::now();
+
+    let result = consume_document(context);
+
+    #[cfg(feature = "tracing")]
+    match &result {
+        Ok(gpx) => tracing::debug!(
+            version = %gpx.version,
+            waypoints = gpx.waypoints.len(),
+            routes = gpx.routes.len(),
+            tracks = gpx.tracks.len(),
+            elapsed = ?start.elapsed(),
+            "read document"
+        ),
+        Err(error) => tracing::warn!(%error, elapsed = ?start.elapsed(), "failed to read document"),
+    }
+    result
+}
+
+fn consume_document<R: Read, T: CoordFloat>(context: &mut Context<R>) -> Result<Gpx<T>, GpxError> {
+    let mut gpx: Gpx<T> = Default::default();
+
+    let mut gpx10_metadata: Gpx10Metadata<T> = Default::default();
 
     // First we consume the gpx tag and its attributes
-    let attributes = verify_starting_tag(context, "gpx")?;
-    let version = attributes
-        .iter()
-        .find(|attr| attr.name.local_name == "version")
-        .ok_or(GpxError::InvalidElementLacksAttribute("version", "gpx"))?;
-    gpx.version = version_string_to_version(&version.value)?;
+    let (name, attributes, namespace) = verify_starting_element(context, "gpx")?;
+    let (version, warning) = detect_version(&name, &attributes)?;
+    if let Some(warning) = warning {
+        context.warn(warning);
+    }
+    gpx.version = version;
     context.version = gpx.version;
 
     let creator = attributes
         .iter()
         .find(|attr| attr.name.local_name == "creator");
     gpx.creator = creator.map(|c| c.value.to_owned());
+    gpx.root_attributes = root_attributes(&attributes, &namespace);
+    // Comments go before the next child, which isn't known yet.
+    let mut comments: Vec<String> = Vec::new();
 
     loop {
+        if context.version == GpxVersion::Gpx10 && gpx10_metadata.consume(context)? {
+            place_comments(&mut gpx, &mut comments, CommentPosition::Start);
+            continue;
+        }
         let next_event = {
             if let Some(next) = context.reader.peek() {
                 match next {
@@ -62,6 +232,17 @@ pub fn consume<R: Read>(context: &mut Context<R>) -> Result<Gpx, GpxError> {
             }
         };
 
+        if let XmlEvent::StartElement { name, .. } = next_event {
+            let position = match name.local_name.as_str() {
+                "trk" => CommentPosition::BeforeTrack(gpx.tracks.len()),
+                "rte" => CommentPosition::BeforeRoute(gpx.routes.len()),
+                "wpt" => CommentPosition::BeforeWaypoint(gpx.waypoints.len()),
+                "extensions" => CommentPosition::End,
+                _ => CommentPosition::Start,
+            };
+            place_comments(&mut gpx, &mut comments, position);
+        }
+
         match next_event {
             XmlEvent::StartElement { ref name, .. } => match name.local_name.as_ref() {
                 "metadata" if context.version != GpxVersion::Gpx10 => {
@@ -76,33 +257,6 @@ pub fn consume<R: Read>(context: &mut Context<R>) -> Result<Gpx, GpxError> {
                 "wpt" => {
                     gpx.waypoints.push(waypoint::consume(context, "wpt")?);
                 }
-                "time" if context.version == GpxVersion::Gpx10 => {
-                    time = Some(time::consume(context)?);
-                }
-                "bounds" if context.version == GpxVersion::Gpx10 => {
-                    bounds = Some(bounds::consume(context)?);
-                }
-                "author" if context.version == GpxVersion::Gpx10 => {
-                    author = Some(string::consume(context, "author", false)?);
-                }
-                "email" if context.version == GpxVersion::Gpx10 => {
-                    email = Some(string::consume(context, "email", false)?);
-                }
-                "url" if context.version == GpxVersion::Gpx10 => {
-                    url = Some(string::consume(context, "url", false)?);
-                }
-                "urlname" if context.version == GpxVersion::Gpx10 => {
-                    urlname = Some(string::consume(context, "urlname", false)?);
-                }
-                "name" if context.version == GpxVersion::Gpx10 => {
-                    gpx_name = Some(string::consume(context, "name", false)?);
-                }
-                "desc" if context.version == GpxVersion::Gpx10 => {
-                    description = Some(string::consume(context, "desc", true)?);
-                }
-                "keywords" if context.version == GpxVersion::Gpx10 => {
-                    keywords = Some(string::consume(context, "keywords", true)?);
-                }
                 "extensions" => {
                     extensions::consume(context)?;
                 }
@@ -115,39 +269,17 @@ pub fn consume<R: Read>(context: &mut Context<R>) -> Result<Gpx, GpxError> {
                     return Err(GpxError::InvalidClosingTag(name.local_name.clone(), "gpx"));
                 }
                 if gpx.version == GpxVersion::Gpx10 {
-                    let link = url.map(|url| Link {
-                        href: url,
-                        text: urlname,
-                        ..Default::default()
-                    });
-                    let person: Person = Person {
-                        name: author,
-                        email,
-                        link,
-                    };
-                    let author = if person != Default::default() {
-                        Some(person)
-                    } else {
-                        None
-                    };
-                    let metadata: Metadata = Metadata {
-                        name: gpx_name,
-                        time,
-                        bounds,
-                        keywords,
-                        description,
-                        author,
-                        ..Default::default()
-                    };
-
-                    if metadata != Default::default() {
-                        gpx.metadata = Some(metadata);
-                    }
+                    gpx.metadata = gpx10_metadata.into_metadata();
                 }
+                place_comments(&mut gpx, &mut comments, CommentPosition::End);
                 context.reader.next();
 
                 return Ok(gpx);
             }
+            XmlEvent::Comment(text) => {
+                comments.push(text.clone());
+                context.reader.next();
+            }
             _ => {
                 context.reader.next(); //consume and ignore this event
             }
@@ -157,37 +289,49 @@ pub fn consume<R: Read>(context: &mut Context<R>) -> Result<Gpx, GpxError> {
     Err(GpxError::MissingClosingTag("gpx"))
 }
 
+/// Adds the `comments` read since the last child to the document at
+/// `position`.
+fn place_comments<T: CoordFloat>(
+    gpx: &mut Gpx<T>,
+    comments: &mut Vec<String>,
+    position: CommentPosition,
+) {
+    gpx.comments
+        .extend(comments.drain(..).map(|text| Comment { text, position }));
+}
+
 #[cfg(test)]
 mod tests {
     use geo_types::Point;
 
     use super::consume;
-    use crate::{errors::GpxError, GpxVersion};
+    use crate::errors::{GpxError, GpxResult};
+    use crate::{Gpx, GpxVersion};
 
     #[test]
     fn consume_gpx() {
-        let gpx = consume!("<gpx version=\"1.1\"></gpx>", GpxVersion::Unknown);
+        let gpx: GpxResult<Gpx> = consume!("<gpx version=\"1.1\"></gpx>", GpxVersion::Unknown);
 
         assert!(gpx.is_ok());
     }
 
     #[test]
     fn consume_gpx_no_version() {
-        let gpx = consume!("<gpx></gpx>", GpxVersion::Unknown);
+        let gpx: GpxResult<Gpx> = consume!("<gpx></gpx>", GpxVersion::Unknown);
 
         assert!(gpx.is_err());
     }
 
     #[test]
     fn consume_gpx_version_error() {
-        let gpx = consume!("<gpx version=\"1.2\"></gpx>", GpxVersion::Unknown);
+        let gpx: GpxResult<Gpx> = consume!("<gpx version=\"1.2\"></gpx>", GpxVersion::Unknown);
 
         assert!(gpx.is_err());
     }
 
     #[test]
     fn consume_gpx_creator() {
-        let gpx = consume!(
+        let gpx: GpxResult<Gpx> = consume!(
             "<gpx version=\"1.1\" creator=\"unit test\"></gpx>",
             GpxVersion::Unknown
         );
@@ -251,7 +395,7 @@ mod tests {
 
     #[test]
     fn error_on_double_closing_tag() {
-        let gpx = consume!(
+        let gpx: GpxResult<Gpx> = consume!(
             "
             <gpx version=\"1.0\" xmlns:locus=\"http://www.locusmap.eu\">
                 <time>2016-03-27T18:57:55Z</time>
@@ -299,7 +443,7 @@ mod tests {
 
     #[test]
     fn fail_on_double_internal_closing_tag() {
-        let gpx = consume!(
+        let gpx: GpxResult<Gpx> = consume!(
             "
             <gpx version=\"1.0\" xmlns:locus=\"http://www.locusmap.eu\">
                 <time>2016-03-27T18:57:55Z</time>
@@ -344,4 +488,39 @@ mod tests {
             panic!("Expected different error.")
         }
     }
+
+    #[test]
+    fn consume_gpx_version_from_namespace() {
+        use crate::errors::GpxWarning;
+        use crate::parser::create_context;
+
+        let read = |data: &'static str| {
+            let mut context = create_context(data.as_bytes(), GpxVersion::Unknown);
+            let gpx: Gpx = consume(&mut context).unwrap();
+            (gpx.version, context.take_warnings())
+        };
+
+        assert_eq!(
+            read("<gpx xmlns=\"http://www.topografix.com/GPX/1/0\"></gpx>"),
+            (
+                GpxVersion::Gpx10,
+                vec![GpxWarning::VersionFromNamespace(GpxVersion::Gpx10)]
+            )
+        );
+        assert_eq!(
+            read("<gpx version=\"1.2\" xmlns=\"http://www.topografix.com/GPX/1/1\"></gpx>"),
+            (
+                GpxVersion::Gpx11,
+                vec![GpxWarning::VersionMismatch("1.2".into(), GpxVersion::Gpx11)]
+            )
+        );
+        assert_eq!(
+            read("<gpx version=\"1.1\" xmlns=\"http://www.topografix.com/GPX/1/1\"></gpx>"),
+            (GpxVersion::Gpx11, vec![])
+        );
+        assert_eq!(
+            read("<gpx version=\"1.0\" xmlns=\"http://example.com\"></gpx>"),
+            (GpxVersion::Gpx10, vec![])
+        );
+    }
 }
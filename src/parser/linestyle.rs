@@ -0,0 +1,93 @@
+//! linestyle handles parsing of the `line` element of the gpx_style
+//! extension.
+
+use std::io::Read;
+
+use xml::reader::XmlEvent;
+
+use crate::errors::{GpxError, GpxResult};
+use crate::parser::{
+    consume_extension_value, parse_float, skip_element, string, verify_starting_tag, Context,
+};
+use crate::LineStyle;
+
+/// consume consumes a `line` element, skipping unknown children like the
+/// dash pattern, and dropping invalid values with a warning.
+pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<LineStyle> {
+    verify_starting_tag(context, "line")?;
+    let mut style = LineStyle::default();
+
+    loop {
+        let next_event = match context.reader.peek() {
+            Some(Ok(event)) => event,
+            Some(Err(_)) => return Err(GpxError::EventParsingError("line event")),
+            None => break,
+        };
+
+        match next_event {
+            XmlEvent::StartElement { ref name, .. } => match name.local_name.as_ref() {
+                "color" => style.color = Some(string::consume_value(context, "color")?),
+                "opacity" => {
+                    style.opacity = consume_extension_value(context, "opacity", parse_float)?
+                }
+                "width" => style.width = consume_extension_value(context, "width", parse_float)?,
+                _ => skip_element(context)?,
+            },
+            XmlEvent::EndElement { ref name } => {
+                if name.local_name != "line" {
+                    return Err(GpxError::InvalidClosingTag(name.local_name.clone(), "line"));
+                }
+                context.reader.next(); //consume the end tag
+                return Ok(style);
+            }
+            _ => {
+                context.reader.next(); //consume and ignore this event
+            }
+        }
+    }
+
+    Err(GpxError::MissingClosingTag("line"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::consume;
+    use crate::errors::GpxWarning;
+    use crate::parser::create_context;
+    use crate::GpxVersion;
+
+    #[test]
+    fn consume_line_style() {
+        let style = consume!(
+            "<line xmlns=\"http://www.topografix.com/GPX/gpx_style/0/2\">
+                <color>00D7D7</color>
+                <opacity>0.59</opacity>
+                <width>6.0</width>
+                <extensions><locus:lsWidth xmlns:locus=\"http://www.locusmap.eu\">6.0</locus:lsWidth></extensions>
+            </line>",
+            GpxVersion::Gpx11
+        )
+        .unwrap();
+
+        assert_eq!(style.color.as_deref(), Some("00D7D7"));
+        assert_eq!(style.opacity, Some(0.59));
+        assert_eq!(style.width, Some(6.0));
+    }
+
+    #[test]
+    fn consume_invalid_opacity() {
+        let data = "<line><opacity>half</opacity><width>2</width></line>";
+        let mut context = create_context(data.as_bytes(), GpxVersion::Gpx11);
+        let style = consume(&mut context).unwrap();
+
+        assert_eq!(style.opacity, None);
+        assert_eq!(style.width, Some(2.0));
+        assert_eq!(
+            context.take_warnings(),
+            [GpxWarning::InvalidExtensionValue(
+                "opacity",
+                "half".to_string()
+            )]
+        );
+    }
+}
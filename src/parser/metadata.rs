@@ -2,19 +2,36 @@
 
 use std::io::Read;
 
+use geo_types::CoordFloat;
 use xml::reader::XmlEvent;
 
 use crate::errors::{GpxError, GpxResult};
 use crate::parser::{
-    bounds, copyright, extensions, link, person, string, time, verify_starting_tag, Context,
+    bounds, copyright, extensions, link, person, string, time, verify_starting_tag, Children,
+    Context,
 };
 use crate::Metadata;
 
-pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<Metadata> {
-    let mut metadata: Metadata = Default::default();
+/// The children of metadata, in the order of the GPX 1.1 schema.
+const ELEMENTS: &[&str] = &[
+    "name",
+    "desc",
+    "author",
+    "copyright",
+    "link",
+    "time",
+    "keywords",
+    "bounds",
+    "extensions",
+];
+
+pub fn consume<R: Read, T: CoordFloat>(context: &mut Context<R>) -> GpxResult<Metadata<T>> {
+    let mut metadata: Metadata<T> = Default::default();
     verify_starting_tag(context, "metadata")?;
+    let mut children = Children::new(context, "metadata", ELEMENTS);
 
     loop {
+        children.check(context)?;
         let next_event = {
             if let Some(next) = context.reader.peek() {
                 match next {
@@ -85,7 +102,7 @@ pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<Metadata> {
 mod tests {
 
     use super::consume;
-    use crate::GpxVersion;
+    use crate::{GpxVersion, Metadata};
     use time::{Date, Month, PrimitiveDateTime, Time};
 
     #[test]
@@ -94,7 +111,7 @@ mod tests {
 
         assert!(result.is_ok());
 
-        let result = result.unwrap();
+        let result: Metadata = result.unwrap();
         assert!(result.name.is_none());
         assert!(result.description.is_none());
         assert!(result.author.is_none());
@@ -127,7 +144,7 @@ mod tests {
 
         assert!(result.is_ok());
 
-        let result = result.unwrap();
+        let result: Metadata = result.unwrap();
 
         assert!(result.name.is_some());
         assert_eq!(result.name.unwrap(), "xxname");
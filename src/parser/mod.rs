@@ -13,16 +13,16 @@ macro_rules! consume {
         ))
     }};
     ($xml:expr, $version:expr, $tagname:expr) => {{
-        use crate::parser::create_context;
         use std::io::BufReader;
+        use $crate::parser::create_context;
         consume(
             &mut create_context(BufReader::new($xml.as_bytes()), $version),
             $tagname,
         )
     }};
     ($xml:expr, $version:expr, $tagname:expr, $allow_empty:expr) => {{
-        use crate::parser::create_context;
         use std::io::BufReader;
+        use $crate::parser::create_context;
         consume(
             &mut create_context(BufReader::new($xml.as_bytes()), $version),
             $tagname,
@@ -31,63 +31,144 @@ macro_rules! consume {
     }};
 }
 
+pub mod address;
 pub mod bounds;
 pub mod copyright;
 pub mod email;
 pub mod extensions;
 pub mod fix;
 pub mod gpx;
+pub mod linestyle;
 pub mod link;
 pub mod metadata;
 pub mod person;
 pub mod route;
+pub mod routepointextension;
 pub mod string;
 pub mod time;
 pub mod track;
+pub mod trackpointextension;
 pub mod tracksegment;
 pub mod waypoint;
+pub mod waypointextension;
 
+use std::collections::HashSet;
 use std::io::Read;
 use std::iter::Peekable;
+use std::num::ParseIntError;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use std::sync::Arc;
 
+use geo_types::CoordFloat;
 use xml::attribute::OwnedAttribute;
+use xml::name::OwnedName;
+use xml::namespace::Namespace;
 use xml::reader::{Events, XmlEvent};
 use xml::{EventReader, ParserConfig};
 
-use crate::errors::GpxError;
+use crate::errors::{GpxError, GpxResult, GpxWarning};
+use crate::number::parse_f64;
+use crate::reader::{DuplicatePolicy, ReadOptions};
 use crate::types::GpxVersion;
 
 pub struct Context<R: Read> {
     reader: Peekable<Events<R>>,
     version: GpxVersion,
+    warnings: Vec<GpxWarning>,
+    options: ReadOptions,
+    strings: HashSet<Arc<str>>,
 }
 
 impl<R: Read> Context<R> {
     pub fn new(reader: Peekable<Events<R>>, version: GpxVersion) -> Context<R> {
-        Context { reader, version }
+        Context {
+            reader,
+            version,
+            warnings: Vec::new(),
+            options: ReadOptions::default(),
+            strings: HashSet::new(),
+        }
+    }
+
+    pub fn set_options(&mut self, options: ReadOptions) {
+        self.options = options;
     }
 
     pub fn reader(&mut self) -> &mut Peekable<Events<R>> {
         &mut self.reader
     }
+
+    pub fn version(&self) -> GpxVersion {
+        self.version
+    }
+
+    pub fn set_version(&mut self, version: GpxVersion) {
+        self.version = version;
+    }
+
+    /// Records a problem that was worked around.
+    pub fn warn(&mut self, warning: GpxWarning) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(%warning, "worked around problem in document");
+        self.warnings.push(warning);
+    }
+
+    pub fn warnings(&self) -> &[GpxWarning] {
+        &self.warnings
+    }
+
+    /// Gives `string` as a shared string, the one read before if it is
+    /// repeated and [`ReadOptions::intern_strings`] is set.
+    pub fn share(&mut self, string: String) -> Arc<str> {
+        if !self.options.intern_strings {
+            return string.into();
+        }
+        if let Some(shared) = self.strings.get(string.as_str()) {
+            return shared.clone();
+        }
+        let shared: Arc<str> = string.into();
+        self.strings.insert(shared.clone());
+        shared
+    }
+
+    /// Records the warnings into `warnings`, reusing its allocation.
+    pub fn set_warnings(&mut self, warnings: Vec<GpxWarning>) {
+        self.warnings = warnings;
+    }
+
+    pub fn take_warnings(&mut self) -> Vec<GpxWarning> {
+        std::mem::take(&mut self.warnings)
+    }
 }
 
 pub fn verify_starting_tag<R: Read>(
     context: &mut Context<R>,
     local_name: &'static str,
 ) -> Result<Vec<OwnedAttribute>, GpxError> {
+    verify_starting_element(context, local_name).map(|(_, attributes, _)| attributes)
+}
+
+/// Like [`verify_starting_tag`], but also gives the name of the element,
+/// with its namespace, and the namespace declarations in scope.
+pub fn verify_starting_element<R: Read>(
+    context: &mut Context<R>,
+    local_name: &'static str,
+) -> Result<(OwnedName, Vec<OwnedAttribute>, Namespace), GpxError> {
     //makes sure the specified starting tag is the next tag on the stream
     //we ignore and skip all xmlevents except StartElement, Characters and EndElement
     loop {
         let next = context.reader.next();
         match next {
             Some(Ok(XmlEvent::StartElement {
-                name, attributes, ..
+                name,
+                attributes,
+                namespace,
             })) => {
                 if name.local_name != local_name {
                     return Err(GpxError::InvalidChildElement(name.local_name, local_name));
                 } else {
-                    return Ok(attributes);
+                    return Ok((name, attributes, namespace));
                 }
             }
             Some(Ok(XmlEvent::EndElement { name, .. })) => {
@@ -102,10 +183,218 @@ pub fn verify_starting_tag<R: Read>(
     }
 }
 
+/// Skips the next element on the stream, including all of its children.
+pub(crate) fn skip_element<R: Read>(context: &mut Context<R>) -> GpxResult<()> {
+    let mut depth = 0;
+    for event in &mut context.reader {
+        match event? {
+            XmlEvent::StartElement { .. } => depth += 1,
+            XmlEvent::EndElement { .. } => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(());
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(GpxError::EventParsingError("end of unknown element"))
+}
+
+/// Parses a floating point value and converts it to the coordinate type `T`.
+///
+/// With [`ReadOptions::lenient_numbers`], a comma is accepted as the decimal
+/// separator.
+pub(crate) fn parse_float<R: Read, T: CoordFloat>(
+    context: &Context<R>,
+    value: &str,
+) -> GpxResult<T> {
+    let value = match parse_f64(value.trim()) {
+        Ok(value) => value,
+        Err(error) if context.options.lenient_numbers => lenient_f64(value).ok_or(error)?,
+        Err(error) => return Err(error.into()),
+    };
+    T::from(value).ok_or(GpxError::CoordinateCastError(value))
+}
+
+/// Parses an integer value.
+///
+/// With [`ReadOptions::lenient_numbers`], integral decimals like `66.0` or
+/// `5,0` are accepted too.
+pub(crate) fn parse_integer<R: Read, I: FromStr<Err = ParseIntError>>(
+    context: &Context<R>,
+    value: &str,
+) -> GpxResult<I> {
+    match value.trim().parse() {
+        Ok(value) => Ok(value),
+        Err(error) if context.options.lenient_numbers => lenient_f64(value)
+            .filter(|value| value.fract() == 0.0)
+            .and_then(|value| format!("{value:.0}").parse().ok())
+            .ok_or(error.into()),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Consumes an element with an integer value of a field whose values must be
+/// in `range`, applying [`ReadOptions::out_of_range`] to values outside of
+/// it.
+pub(crate) fn consume_bounded<R: Read, I: TryFrom<i64>>(
+    context: &mut Context<R>,
+    tagname: &'static str,
+    range: RangeInclusive<i64>,
+) -> GpxResult<Option<I>> {
+    let value: i64 = consume_integer(context, tagname)?;
+    let kept = context.options.out_of_range.apply(tagname, value, range)?;
+    if kept != Some(value) {
+        context.warn(GpxWarning::ValueOutOfRange(tagname, value));
+    }
+    kept.map(|kept| I::try_from(kept).map_err(|_| GpxError::ValueOutOfRange(tagname, value)))
+        .transpose()
+}
+
+/// The children that may appear more than once in their parents.
+const REPEATABLE_ELEMENTS: &[&str] = &["link", "rtept", "trkseg"];
+
+/// Checks the children of an element against the GPX 1.1 schema: that they
+/// come in its order, if [`ReadOptions::strict_order`] is set, and that
+/// those allowed once aren't repeated, following
+/// [`ReadOptions::duplicates`]. Children that aren't in the schema, like
+/// those of GPX 1.0, are let through.
+pub(crate) struct Children {
+    parent: &'static str,
+    elements: &'static [&'static str],
+    check_order: bool,
+    duplicates: DuplicatePolicy,
+    position: Option<usize>,
+    /// The children seen so far, as bits by their index in `elements`.
+    seen: u64,
+}
+
+impl Children {
+    /// Creates a check of the children of `parent`, which the schema lists
+    /// in `elements`. Their order is only checked for GPX 1.1 documents.
+    pub(crate) fn new<R: Read>(
+        context: &Context<R>,
+        parent: &'static str,
+        elements: &'static [&'static str],
+    ) -> Children {
+        Children {
+            parent,
+            elements,
+            check_order: context.options.strict_order && context.version == GpxVersion::Gpx11,
+            duplicates: context.options.duplicates,
+            position: None,
+            seen: 0,
+        }
+    }
+
+    /// Checks the child at the start of the stream, if any, against the
+    /// children seen so far.
+    pub(crate) fn check<R: Read>(&mut self, context: &mut Context<R>) -> GpxResult<()> {
+        if !self.check_order && self.duplicates == DuplicatePolicy::Ignore {
+            return Ok(());
+        }
+        let index = match context.reader.peek() {
+            Some(Ok(XmlEvent::StartElement { name, .. })) => self
+                .elements
+                .iter()
+                .position(|element| *element == name.local_name),
+            _ => None,
+        };
+        let Some(index) = index else {
+            return Ok(());
+        };
+        let name = self.elements[index];
+        if self.check_order && self.position.map_or(false, |position| index < position) {
+            return Err(GpxError::ElementOutOfOrder(name.to_string(), self.parent));
+        }
+        self.position = Some(index);
+        if self.seen & (1 << index) != 0 && !REPEATABLE_ELEMENTS.contains(&name) {
+            match self.duplicates {
+                DuplicatePolicy::Ignore => {}
+                DuplicatePolicy::Warn => {
+                    context.warn(GpxWarning::DuplicateElement(name, self.parent));
+                }
+                DuplicatePolicy::Error => return Err(GpxError::TagOpenedTwice(name)),
+            }
+        }
+        self.seen |= 1 << index;
+        Ok(())
+    }
+}
+
+/// Parses a decimal number written by a broken exporter, which may use a
+/// comma as the decimal separator.
+fn lenient_f64(value: &str) -> Option<f64> {
+    let value = value.trim();
+    if let Ok(value) = parse_f64(value) {
+        return Some(value);
+    }
+    if value.contains('.') || value.matches(',').count() != 1 {
+        return None;
+    }
+    parse_f64(&value.replace(',', ".")).ok()
+}
+
+/// Consumes an element with a floating point value, see [`parse_float`].
+pub(crate) fn consume_float<R: Read, T: CoordFloat>(
+    context: &mut Context<R>,
+    tagname: &'static str,
+) -> GpxResult<T> {
+    let value = string::consume_value(context, tagname)?;
+    parse_float(context, &value)
+}
+
+/// Consumes an element of a vendor extension with a value read by `parse`,
+/// like [`parse_float`] or [`parse_integer`].
+///
+/// Invalid values are dropped with a warning instead of failing the whole
+/// document, giving `None`.
+pub(crate) fn consume_extension_value<R: Read, V>(
+    context: &mut Context<R>,
+    tagname: &'static str,
+    parse: fn(&Context<R>, &str) -> GpxResult<V>,
+) -> GpxResult<Option<V>> {
+    let value = string::consume_value(context, tagname)?;
+    match parse(context, &value) {
+        Ok(value) => Ok(Some(value)),
+        Err(_) => {
+            context.warn(GpxWarning::InvalidExtensionValue(tagname, value));
+            Ok(None)
+        }
+    }
+}
+
+/// Consumes an element with an integer value, see [`parse_integer`].
+pub(crate) fn consume_integer<R: Read, I: FromStr<Err = ParseIntError>>(
+    context: &mut Context<R>,
+    tagname: &'static str,
+) -> GpxResult<I> {
+    let value = string::consume_value(context, tagname)?;
+    parse_integer(context, &value)
+}
+
 pub(crate) fn create_context<R: Read>(reader: R, version: GpxVersion) -> Context<R> {
+    create_context_with_config(reader, version, true)
+}
+
+/// Creates a context reading a whole document with `options`, which keeps
+/// the comments if [`ReadOptions::comments`] is set.
+pub(crate) fn create_context_with_options<R: Read>(reader: R, options: &ReadOptions) -> Context<R> {
+    let mut context = create_context_with_config(reader, GpxVersion::Unknown, !options.comments);
+    context.set_options(options.clone());
+    context
+}
+
+fn create_context_with_config<R: Read>(
+    reader: R,
+    version: GpxVersion,
+    ignore_comments: bool,
+) -> Context<R> {
     let parser_config = ParserConfig {
         whitespace_to_characters: true, //convert Whitespace event to Characters
         cdata_to_characters: true,      //convert CData event to Characters
+        ignore_comments,
         ..ParserConfig::new()
     };
     let parser = EventReader::new_with_config(reader, parser_config);
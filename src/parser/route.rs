@@ -2,18 +2,36 @@
 
 use std::io::Read;
 
+use geo_types::CoordFloat;
 use xml::reader::XmlEvent;
 
 use crate::errors::{GpxError, GpxResult};
-use crate::parser::{extensions, link, string, verify_starting_tag, waypoint, Context};
+use crate::parser::{
+    consume_bounded, extensions, link, string, verify_starting_tag, waypoint, Children, Context,
+};
 use crate::Route;
 
+/// The children of a route, in the order of the GPX 1.1 schema.
+const ELEMENTS: &[&str] = &[
+    "name",
+    "cmt",
+    "desc",
+    "src",
+    "link",
+    "number",
+    "type",
+    "extensions",
+    "rtept",
+];
+
 /// consume consumes a GPX route from the `reader` until it ends.
-pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<Route> {
-    let mut route: Route = Default::default();
+pub fn consume<R: Read, T: CoordFloat>(context: &mut Context<R>) -> GpxResult<Route<T>> {
+    let mut route: Route<T> = Default::default();
     verify_starting_tag(context, "rte")?;
+    let mut children = Children::new(context, "route", ELEMENTS);
 
     loop {
+        children.check(context)?;
         let next_event = {
             if let Some(next) = context.reader.peek() {
                 match next {
@@ -39,9 +57,7 @@ pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<Route> {
                 "src" => {
                     route.source = Some(string::consume(context, "src", true)?);
                 }
-                "number" => {
-                    route.number = Some(string::consume(context, "number", false)?.parse()?)
-                }
+                "number" => route.number = consume_bounded(context, "number", 0..=i64::MAX)?,
                 "type" => {
                     route.type_ = Some(string::consume(context, "type", false)?);
                 }
@@ -80,7 +96,8 @@ pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<Route> {
 #[cfg(test)]
 mod tests {
     use super::consume;
-    use crate::GpxVersion;
+    use crate::errors::GpxResult;
+    use crate::{GpxVersion, Route};
 
     #[test]
     fn consume_full_route() {
@@ -100,7 +117,7 @@ mod tests {
 
         assert!(route.is_ok());
 
-        let route = route.unwrap();
+        let route: Route = route.unwrap();
 
         assert_eq!(route.name.unwrap(), "route name");
         assert_eq!(route.comment.unwrap(), "route comment");
@@ -112,7 +129,7 @@ mod tests {
 
     #[test]
     fn consume_empty() {
-        let route = consume!("<rte></rte>", GpxVersion::Gpx11);
+        let route: GpxResult<Route> = consume!("<rte></rte>", GpxVersion::Gpx11);
         assert!(route.is_ok());
     }
 }
@@ -0,0 +1,144 @@
+//! routepointextension handles parsing of Garmin's RoutePointExtension.
+
+use std::io::Read;
+
+use geo_types::{CoordFloat, Point};
+use xml::reader::XmlEvent;
+
+use crate::errors::{GpxError, GpxResult, GpxWarning};
+use crate::parser::{skip_element, string, verify_starting_tag, waypoint, Context};
+use crate::RoutePointExtension;
+
+/// consume consumes a `RoutePointExtension` element, skipping unknown
+/// children, and dropping points with invalid positions with a warning.
+pub fn consume<R: Read, T: CoordFloat>(
+    context: &mut Context<R>,
+) -> GpxResult<RoutePointExtension<T>> {
+    verify_starting_tag(context, "RoutePointExtension")?;
+    let mut extension = RoutePointExtension::default();
+
+    loop {
+        let next_event = match context.reader.peek() {
+            Some(Ok(event)) => event,
+            Some(Err(_)) => return Err(GpxError::EventParsingError("RoutePointExtension event")),
+            None => break,
+        };
+
+        match next_event {
+            XmlEvent::StartElement { ref name, .. } => match name.local_name.as_ref() {
+                "Subclass" => {
+                    extension.subclass = Some(string::consume_value(context, "Subclass")?)
+                }
+                "rpt" => {
+                    let attributes = verify_starting_tag(context, "rpt")?;
+                    match waypoint::position(&attributes) {
+                        Ok((latitude, longitude)) => extension.points.push(Point::new(
+                            T::from(longitude).ok_or(GpxError::CoordinateCastError(longitude))?,
+                            T::from(latitude).ok_or(GpxError::CoordinateCastError(latitude))?,
+                        )),
+                        Err(_) => {
+                            let position = attributes
+                                .iter()
+                                .map(|attribute| {
+                                    format!("{}=\"{}\"", attribute.name.local_name, attribute.value)
+                                })
+                                .collect::<Vec<_>>()
+                                .join(" ");
+                            context.warn(GpxWarning::InvalidExtensionValue("rpt", position));
+                        }
+                    }
+                    // The points have no children besides their own subclass.
+                    consume_rpt_end(context)?;
+                }
+                _ => skip_element(context)?,
+            },
+            XmlEvent::EndElement { ref name } => {
+                if name.local_name != "RoutePointExtension" {
+                    return Err(GpxError::InvalidClosingTag(
+                        name.local_name.clone(),
+                        "RoutePointExtension",
+                    ));
+                }
+                context.reader.next(); //consume the end tag
+                return Ok(extension);
+            }
+            _ => {
+                context.reader.next(); //consume and ignore this event
+            }
+        }
+    }
+
+    Err(GpxError::MissingClosingTag("RoutePointExtension"))
+}
+
+/// Consumes the children of an `rpt` element up to its end.
+fn consume_rpt_end<R: Read>(context: &mut Context<R>) -> GpxResult<()> {
+    loop {
+        match context.reader.peek() {
+            Some(Ok(XmlEvent::StartElement { .. })) => skip_element(context)?,
+            Some(Ok(XmlEvent::EndElement { .. })) => {
+                context.reader.next();
+                return Ok(());
+            }
+            Some(Ok(_)) => {
+                context.reader.next();
+            }
+            Some(Err(_)) => return Err(GpxError::EventParsingError("rpt event")),
+            None => return Err(GpxError::MissingClosingTag("rpt")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::Point;
+
+    use super::consume;
+    use crate::{GpxVersion, RoutePointExtension};
+
+    #[test]
+    fn consume_route_point_extension() {
+        let extension = consume!(
+            "<gpxx:RoutePointExtension xmlns:gpxx=\"http://www.garmin.com/xmlschemas/GpxExtensions/v3\">
+                <gpxx:Subclass>000000000000FFFFFFFFFFFFFFFFFFFFFFFF</gpxx:Subclass>
+                <gpxx:rpt lat=\"37.241\" lon=\"-121.971\"/>
+                <gpxx:rpt lat=\"37.242\" lon=\"-121.972\">
+                    <gpxx:Subclass>000000000000FFFFFFFFFFFFFFFFFFFFFFFF</gpxx:Subclass>
+                </gpxx:rpt>
+            </gpxx:RoutePointExtension>",
+            GpxVersion::Gpx11
+        )
+        .unwrap();
+
+        assert_eq!(
+            extension.subclass.as_deref(),
+            Some("000000000000FFFFFFFFFFFFFFFFFFFFFFFF")
+        );
+        assert_eq!(
+            extension.points,
+            [Point::new(-121.971, 37.241), Point::new(-121.972, 37.242)]
+        );
+    }
+
+    #[test]
+    fn consume_invalid_subpoint() {
+        let extension = consume!(
+            "<RoutePointExtension><rpt lat=\"91\" lon=\"0\"/><rpt lat=\"1\" lon=\"2\"/></RoutePointExtension>",
+            GpxVersion::Gpx11
+        )
+        .unwrap();
+
+        assert_eq!(extension.points, [Point::new(2.0, 1.0)]);
+    }
+
+    #[test]
+    fn consume_f32_subpoints() {
+        let extension: RoutePointExtension<f32> = consume!(
+            "<RoutePointExtension><rpt lat=\"37.241\" lon=\"-121.971\"/></RoutePointExtension>",
+            GpxVersion::Gpx11
+        )
+        .unwrap();
+
+        assert_eq!(extension.points, [Point::new(-121.971_f32, 37.241_f32)]);
+    }
+}
@@ -24,7 +24,9 @@ pub fn consume<R: Read>(
                     tagname,
                 ));
             }
-            XmlEvent::Characters(content) => string = content,
+            // Text is split around comments when they are kept.
+            XmlEvent::Characters(content) if string.is_empty() => string = content,
+            XmlEvent::Characters(content) => string.push_str(&content),
             XmlEvent::EndElement { ref name } => {
                 if name.local_name != tagname {
                     return Err(GpxError::InvalidClosingTag(
@@ -43,9 +45,28 @@ pub fn consume<R: Read>(
     Err(GpxError::MissingClosingTag(tagname))
 }
 
+/// consume_value consumes a value whose schema type collapses whitespace,
+/// like a number or a time, without the whitespace around it. Pretty-printed
+/// files put line breaks and indentation there.
+pub fn consume_value<R: Read>(
+    context: &mut Context<R>,
+    tagname: &'static str,
+) -> GpxResult<String> {
+    let mut value = consume(context, tagname, true)?;
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Err(GpxError::NoStringContent);
+    }
+    if trimmed.len() < value.len() {
+        value = trimmed.to_string();
+    }
+    Ok(value)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::consume;
+    use super::{consume, consume_value};
+    use crate::parser::create_context;
     use crate::GpxVersion;
 
     #[test]
@@ -114,4 +135,15 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn consume_value_trims() {
+        let data = "<value>\n  12.5\n</value>";
+        let mut context = create_context(data.as_bytes(), GpxVersion::Gpx11);
+        assert_eq!(consume_value(&mut context, "value").unwrap(), "12.5");
+
+        let mut context = create_context("<value>\n  </value>".as_bytes(), GpxVersion::Gpx11);
+        let err = consume_value(&mut context, "value").unwrap_err();
+        assert_eq!(err.to_string(), "no string content");
+    }
 }
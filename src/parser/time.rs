@@ -1,23 +1,239 @@
 //! time handles parsing of xsd:dateTime.
 
+use std::borrow::Cow;
 use std::io::Read;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
 
 /// format: [-]CCYY-MM-DDThh:mm:ss[Z|(+|-)hh:mm]
 #[cfg(feature = "use-serde")]
 use serde::{Deserialize, Serialize};
-use time::{format_description::well_known::Iso8601, OffsetDateTime, PrimitiveDateTime, UtcOffset};
+use time::format_description::well_known::iso8601::{Config, EncodedConfig};
+use time::{format_description::well_known::Iso8601, OffsetDateTime, PrimitiveDateTime};
 
-use crate::errors::GpxResult;
+use crate::errors::{GpxError, GpxResult, GpxWarning};
 use crate::parser::{string, Context};
 
+/// ISO 8601 with six-digit years and a sign, for years outside of 0000 to
+/// 9999.
+const EXPANDED: EncodedConfig = Config::DEFAULT.set_year_is_six_digits(true).encode();
+
+/// A point in time, in UTC unless moved to another offset with
+/// [`to_offset`](Time::to_offset).
+///
+/// Times are backed by `time`'s `OffsetDateTime`, but their API only uses
+/// this crate's own [`Duration`] and [`UtcOffset`], so callers don't need to
+/// depend on `time`. They convert from and to the types of `time` and, with
+/// the `chrono` feature, of `chrono`.
 #[derive(Debug, Clone, Copy, Eq, Ord, PartialOrd, PartialEq, Hash)]
 #[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
 pub struct Time(OffsetDateTime);
 
+/// Gives times between the years 0 and 9999 in UTC, which GPX files can
+/// hold.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Time {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Time> {
+        let seconds = u.int_in_range(-62_167_219_200..=253_402_300_799)?;
+        let nanoseconds: u32 = u.int_in_range(0..=999_999_999)?;
+        OffsetDateTime::from_unix_timestamp(seconds)
+            .map(|time| Time(time + time::Duration::nanoseconds(nanoseconds.into())))
+            .map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
 impl Time {
+    /// The Unix epoch, 1970-01-01T00:00:00Z.
+    pub const UNIX_EPOCH: Time = Time(OffsetDateTime::UNIX_EPOCH);
+
+    /// Gives the current time.
+    ///
+    /// On `wasm32-unknown-unknown`, this needs the `wasm` feature, which reads
+    /// the clock of the JavaScript host.
+    pub fn now() -> Time {
+        Time(OffsetDateTime::now_utc())
+    }
+
+    /// Gives the time `seconds` after the Unix epoch, 1970-01-01T00:00:00Z.
+    ///
+    /// ```
+    /// use gpx::Time;
+    ///
+    /// let time = Time::from_unix_seconds(1_685_620_800).unwrap();
+    /// assert_eq!((time.year(), time.month(), time.day()), (2023, 6, 1));
+    /// assert_eq!(time.hour(), 12);
+    /// assert!(Time::from_unix_seconds(i64::MAX).is_err());
+    /// ```
+    pub fn from_unix_seconds(seconds: i64) -> GpxResult<Time> {
+        OffsetDateTime::from_unix_timestamp(seconds)
+            .map(Time)
+            .map_err(|_| GpxError::ValueOutOfRange("seconds", seconds))
+    }
+
+    /// Gives the time `milliseconds` after the Unix epoch.
+    pub fn from_unix_millis(milliseconds: i64) -> GpxResult<Time> {
+        let nanoseconds = i128::from(milliseconds) * 1_000_000;
+        OffsetDateTime::from_unix_timestamp_nanos(nanoseconds)
+            .map(Time)
+            .map_err(|_| GpxError::ValueOutOfRange("milliseconds", milliseconds))
+    }
+
+    /// Gives the whole seconds since the Unix epoch, negative before it.
+    pub fn unix_seconds(&self) -> i64 {
+        self.0.unix_timestamp()
+    }
+
+    /// Gives the whole milliseconds since the Unix epoch, negative before it.
+    pub fn unix_millis(&self) -> i64 {
+        // Years up to 999999 are some 3e16 milliseconds, well within an i64.
+        self.0.unix_timestamp_nanos().div_euclid(1_000_000) as i64
+    }
+
+    /// Gives the year, where 0 is 1 BCE.
+    pub fn year(&self) -> i32 {
+        self.0.year()
+    }
+
+    /// Gives the month, from 1 to 12.
+    pub fn month(&self) -> u8 {
+        self.0.month().into()
+    }
+
+    /// Gives the day of the month, from 1 to 31.
+    pub fn day(&self) -> u8 {
+        self.0.day()
+    }
+
+    /// Gives the hour, from 0 to 23.
+    pub fn hour(&self) -> u8 {
+        self.0.hour()
+    }
+
+    /// Gives the minute, from 0 to 59.
+    pub fn minute(&self) -> u8 {
+        self.0.minute()
+    }
+
+    /// Gives the second, from 0 to 59.
+    pub fn second(&self) -> u8 {
+        self.0.second()
+    }
+
+    /// Gives the fraction of the second, in nanoseconds.
+    pub fn nanosecond(&self) -> u32 {
+        self.0.nanosecond()
+    }
+
+    /// Gives the offset from UTC of the calendar fields, like
+    /// [`hour`](Time::hour), which is UTC unless the time was moved with
+    /// [`to_offset`](Time::to_offset).
+    pub fn offset(&self) -> UtcOffset {
+        UtcOffset(self.0.offset())
+    }
+
     /// Render time in ISO 8601 format
+    ///
+    /// Years before 0000 or after 9999 are written like xsd:dateTime does,
+    /// e.g. `-0044-03-15T12:00:00.000000000Z` or
+    /// `12021-01-01T00:00:00.000000000Z`. Years are astronomical, so `0000`
+    /// is 1 BCE.
     pub fn format(&self) -> GpxResult<String> {
-        self.0.format(&Iso8601::DEFAULT).map_err(From::from)
+        if (0..=9999).contains(&self.0.year()) {
+            return self.0.format(&Iso8601::DEFAULT).map_err(From::from);
+        }
+        let expanded = self.0.format(&Iso8601::<EXPANDED>)?;
+        // Six digits after the sign, of which xsd:dateTime needs only four,
+        // and a sign only for negative years.
+        let (sign, rest) = expanded.split_at(1);
+        let zeros = rest[..2].bytes().take_while(|&digit| digit == b'0').count();
+        let sign = if sign == "-" { "-" } else { "" };
+        Ok(format!("{sign}{}", &rest[zeros..]))
+    }
+
+    /// Gives the same time at a fixed UTC offset, e.g. to present it as
+    /// local time. It is still equal to this time, but its calendar fields,
+    /// like [`hour`](Time::hour), and its format are local.
+    ///
+    /// ```
+    /// use gpx::{Time, UtcOffset};
+    ///
+    /// let time = Time::from_unix_seconds(1_685_620_800).unwrap();
+    /// let local = time.to_offset(UtcOffset::from_hms(2, 0, 0).unwrap());
+    /// assert_eq!(local.hour(), 14);
+    /// assert_eq!(local, time);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the local date is beyond the years -999999 to 999999.
+    pub fn to_offset(&self, offset: UtcOffset) -> Time {
+        Time(self.0.to_offset(offset.0))
+    }
+
+    /// Gives the same time in an IANA timezone, like `Europe/Berlin`,
+    /// taking daylight saving time into account, see
+    /// [`to_offset`](Time::to_offset).
+    ///
+    /// ```
+    /// use gpx::Time;
+    /// use time_tz::timezones;
+    ///
+    /// let time = Time::from_unix_seconds(1_685_620_800).unwrap();
+    /// let berlin = timezones::get_by_name("Europe/Berlin").unwrap();
+    /// assert_eq!(time.to_timezone(berlin).hour(), 14);
+    /// ```
+    #[cfg(feature = "time-tz")]
+    pub fn to_timezone<T: time_tz::TimeZone>(&self, timezone: &T) -> Time {
+        use time_tz::OffsetDateTimeExt;
+
+        Time(self.0.to_timezone(timezone))
+    }
+}
+
+/// Moves a time forward.
+///
+/// ```
+/// use gpx::{Duration, Time};
+///
+/// let start = Time::from_unix_seconds(0).unwrap();
+/// let end = start + Duration::minutes(90);
+/// assert_eq!(end.unix_millis(), 5_400_000);
+/// assert_eq!(end - start, Duration::minutes(90));
+/// ```
+impl Add<Duration> for Time {
+    type Output = Time;
+
+    fn add(self, duration: Duration) -> Time {
+        Time(self.0 + duration.0)
+    }
+}
+
+impl AddAssign<Duration> for Time {
+    fn add_assign(&mut self, duration: Duration) {
+        self.0 += duration.0;
+    }
+}
+
+impl Sub<Duration> for Time {
+    type Output = Time;
+
+    fn sub(self, duration: Duration) -> Time {
+        Time(self.0 - duration.0)
+    }
+}
+
+impl SubAssign<Duration> for Time {
+    fn sub_assign(&mut self, duration: Duration) {
+        self.0 -= duration.0;
+    }
+}
+
+/// Gives the duration between two times, negative if `other` is later.
+impl Sub for Time {
+    type Output = Duration;
+
+    fn sub(self, other: Time) -> Duration {
+        Duration(self.0 - other.0)
     }
 }
 
@@ -33,24 +249,454 @@ impl From<Time> for OffsetDateTime {
     }
 }
 
+/// Converts a `chrono` time, reading leap seconds as the last nanosecond
+/// before the next minute like [`read`](crate::read) does.
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use gpx::Time;
+///
+/// let time = Time::from(Utc.with_ymd_and_hms(2023, 6, 1, 12, 0, 0).unwrap());
+/// assert_eq!(time.format().unwrap(), "2023-06-01T12:00:00.000000000Z");
+/// ```
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for Time {
+    fn from(time: chrono::DateTime<Tz>) -> Time {
+        let nanoseconds = time.timestamp_subsec_nanos().min(999_999_999);
+        // `time` holds years up to 999999 and `chrono` up to 262143, so this
+        // can't fail.
+        let seconds = OffsetDateTime::from_unix_timestamp(time.timestamp())
+            .expect("chrono time out of range");
+        Time(seconds + time::Duration::nanoseconds(nanoseconds.into()))
+    }
+}
+
+/// Converts to a `chrono` time in UTC, failing for years `chrono` can't
+/// represent, beyond 262143.
+#[cfg(feature = "chrono")]
+impl TryFrom<Time> for chrono::DateTime<chrono::Utc> {
+    type Error = GpxError;
+
+    fn try_from(time: Time) -> GpxResult<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::from_timestamp(time.0.unix_timestamp(), time.0.nanosecond())
+            .ok_or(GpxError::ValueOutOfRange("year", time.0.year().into()))
+    }
+}
+
+/// A span of time, negative if it goes back, like the difference of two
+/// [`Time`]s.
+///
+/// Like [`Time`], it is backed by `time`, and converts from and to the
+/// durations of `time`, of the standard library and, with the `chrono`
+/// feature, of `chrono`.
+///
+/// ```
+/// use gpx::Duration;
+///
+/// let pace = Duration::minutes(5) + Duration::seconds(30);
+/// assert_eq!(pace.whole_seconds(), 330);
+/// assert_eq!((pace * 2).whole_minutes(), 11);
+/// assert!((-pace).is_negative());
+/// ```
+#[derive(Debug, Clone, Copy, Default, Eq, Ord, PartialOrd, PartialEq, Hash)]
+#[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
+pub struct Duration(time::Duration);
+
+impl Duration {
+    /// No time at all.
+    pub const ZERO: Duration = Duration(time::Duration::ZERO);
+
+    /// One second.
+    pub const SECOND: Duration = Duration(time::Duration::SECOND);
+
+    /// One day of 24 hours.
+    pub const DAY: Duration = Duration(time::Duration::DAY);
+
+    /// The longest duration there is.
+    pub const MAX: Duration = Duration(time::Duration::MAX);
+
+    /// Creates a duration of `days` days of 24 hours.
+    pub const fn days(days: i64) -> Duration {
+        Duration(time::Duration::days(days))
+    }
+
+    /// Creates a duration of `hours` hours.
+    pub const fn hours(hours: i64) -> Duration {
+        Duration(time::Duration::hours(hours))
+    }
+
+    /// Creates a duration of `minutes` minutes.
+    pub const fn minutes(minutes: i64) -> Duration {
+        Duration(time::Duration::minutes(minutes))
+    }
+
+    /// Creates a duration of `seconds` seconds.
+    pub const fn seconds(seconds: i64) -> Duration {
+        Duration(time::Duration::seconds(seconds))
+    }
+
+    /// Creates a duration of `seconds` seconds, rounded to nanoseconds.
+    pub fn seconds_f64(seconds: f64) -> Duration {
+        Duration(time::Duration::seconds_f64(seconds))
+    }
+
+    /// Creates a duration of `milliseconds` milliseconds.
+    pub const fn milliseconds(milliseconds: i64) -> Duration {
+        Duration(time::Duration::milliseconds(milliseconds))
+    }
+
+    /// Creates a duration of `microseconds` microseconds.
+    pub const fn microseconds(microseconds: i64) -> Duration {
+        Duration(time::Duration::microseconds(microseconds))
+    }
+
+    /// Creates a duration of `nanoseconds` nanoseconds.
+    pub const fn nanoseconds(nanoseconds: i64) -> Duration {
+        Duration(time::Duration::nanoseconds(nanoseconds))
+    }
+
+    /// Gives the whole minutes, rounded towards zero.
+    pub const fn whole_minutes(&self) -> i64 {
+        self.0.whole_minutes()
+    }
+
+    /// Gives the whole seconds, rounded towards zero.
+    pub const fn whole_seconds(&self) -> i64 {
+        self.0.whole_seconds()
+    }
+
+    /// Gives the whole milliseconds, rounded towards zero.
+    pub const fn whole_milliseconds(&self) -> i128 {
+        self.0.whole_milliseconds()
+    }
+
+    /// Gives the nanoseconds beyond the whole seconds, negative for negative
+    /// durations.
+    pub const fn subsec_nanoseconds(&self) -> i32 {
+        self.0.subsec_nanoseconds()
+    }
+
+    /// Gives the duration in seconds.
+    pub fn as_seconds_f64(&self) -> f64 {
+        self.0.as_seconds_f64()
+    }
+
+    /// Tells if the duration is longer than zero.
+    pub const fn is_positive(&self) -> bool {
+        self.0.is_positive()
+    }
+
+    /// Tells if the duration goes back in time.
+    pub const fn is_negative(&self) -> bool {
+        self.0.is_negative()
+    }
+
+    /// Tells if the duration is zero.
+    pub const fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// Gives the duration without its sign.
+    pub const fn abs(&self) -> Duration {
+        Duration(self.0.abs())
+    }
+}
+
+impl Add for Duration {
+    type Output = Duration;
+
+    fn add(self, other: Duration) -> Duration {
+        Duration(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Duration {
+    fn add_assign(&mut self, other: Duration) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for Duration {
+    type Output = Duration;
+
+    fn sub(self, other: Duration) -> Duration {
+        Duration(self.0 - other.0)
+    }
+}
+
+impl SubAssign for Duration {
+    fn sub_assign(&mut self, other: Duration) {
+        self.0 -= other.0;
+    }
+}
+
+impl Neg for Duration {
+    type Output = Duration;
+
+    fn neg(self) -> Duration {
+        Duration(-self.0)
+    }
+}
+
+impl Mul<i32> for Duration {
+    type Output = Duration;
+
+    fn mul(self, factor: i32) -> Duration {
+        Duration(self.0 * factor)
+    }
+}
+
+impl Mul<f64> for Duration {
+    type Output = Duration;
+
+    fn mul(self, factor: f64) -> Duration {
+        Duration(self.0 * factor)
+    }
+}
+
+impl Div<i32> for Duration {
+    type Output = Duration;
+
+    fn div(self, divisor: i32) -> Duration {
+        Duration(self.0 / divisor)
+    }
+}
+
+impl Div<f64> for Duration {
+    type Output = Duration;
+
+    fn div(self, divisor: f64) -> Duration {
+        Duration(self.0 / divisor)
+    }
+}
+
+impl Sum for Duration {
+    fn sum<I: Iterator<Item = Duration>>(durations: I) -> Duration {
+        durations.fold(Duration::ZERO, Add::add)
+    }
+}
+
+impl From<time::Duration> for Duration {
+    fn from(duration: time::Duration) -> Duration {
+        Duration(duration)
+    }
+}
+
+impl From<Duration> for time::Duration {
+    fn from(duration: Duration) -> time::Duration {
+        duration.0
+    }
+}
+
+/// Converts a duration of the standard library, failing for durations of
+/// more than `i64::MAX` seconds.
+impl TryFrom<std::time::Duration> for Duration {
+    type Error = GpxError;
+
+    fn try_from(duration: std::time::Duration) -> GpxResult<Duration> {
+        duration
+            .try_into()
+            .map(Duration)
+            .map_err(|_| GpxError::ValueOutOfRange("seconds", i64::MAX))
+    }
+}
+
+/// Converts to a duration of the standard library, failing for negative
+/// durations, which it can't hold.
+impl TryFrom<Duration> for std::time::Duration {
+    type Error = GpxError;
+
+    fn try_from(duration: Duration) -> GpxResult<std::time::Duration> {
+        duration
+            .0
+            .try_into()
+            .map_err(|_| GpxError::ValueOutOfRange("seconds", duration.whole_seconds()))
+    }
+}
+
+/// Converts a `chrono` duration, which is never longer than a [`Duration`]
+/// can be.
+#[cfg(feature = "chrono")]
+impl From<chrono::TimeDelta> for Duration {
+    fn from(duration: chrono::TimeDelta) -> Duration {
+        let nanoseconds = duration.subsec_nanos();
+        Duration(time::Duration::new(duration.num_seconds(), nanoseconds))
+    }
+}
+
+/// Converts to a `chrono` duration, failing for durations beyond the some
+/// 292 million years `chrono` can hold.
+#[cfg(feature = "chrono")]
+impl TryFrom<Duration> for chrono::TimeDelta {
+    type Error = GpxError;
+
+    fn try_from(duration: Duration) -> GpxResult<chrono::TimeDelta> {
+        let seconds = duration.whole_seconds();
+        let nanoseconds = duration.subsec_nanoseconds();
+        // `TimeDelta::new` takes nanoseconds from 0, so negative durations
+        // borrow a second.
+        let (seconds, nanoseconds) = if nanoseconds < 0 {
+            (seconds - 1, nanoseconds + 1_000_000_000)
+        } else {
+            (seconds, nanoseconds)
+        };
+        chrono::TimeDelta::new(seconds, nanoseconds as u32)
+            .ok_or(GpxError::ValueOutOfRange("seconds", seconds))
+    }
+}
+
+/// A fixed offset from UTC, like `+02:00`, at which to present a [`Time`],
+/// see [`Time::to_offset`].
+///
+/// Like [`Time`], it is backed by `time`, and converts from and to the
+/// offsets of `time` and, with the `chrono` feature, of `chrono`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct UtcOffset(time::UtcOffset);
+
+impl UtcOffset {
+    /// UTC itself, with no offset.
+    pub const UTC: UtcOffset = UtcOffset(time::UtcOffset::UTC);
+
+    /// Creates the offset of `hours`, `minutes` and `seconds` from UTC,
+    /// which must have the same sign. Offsets run from -25:59:59 to
+    /// +25:59:59.
+    ///
+    /// ```
+    /// use gpx::UtcOffset;
+    ///
+    /// let offset = UtcOffset::from_hms(-3, -30, 0).unwrap();
+    /// assert_eq!(offset.whole_seconds(), -12_600);
+    /// assert!(UtcOffset::from_hms(26, 0, 0).is_err());
+    /// ```
+    pub fn from_hms(hours: i8, minutes: i8, seconds: i8) -> GpxResult<UtcOffset> {
+        time::UtcOffset::from_hms(hours, minutes, seconds)
+            .map(UtcOffset)
+            .map_err(|_| GpxError::ValueOutOfRange("offset", hours.into()))
+    }
+
+    /// Creates the offset of `seconds` seconds from UTC.
+    pub fn from_whole_seconds(seconds: i32) -> GpxResult<UtcOffset> {
+        time::UtcOffset::from_whole_seconds(seconds)
+            .map(UtcOffset)
+            .map_err(|_| GpxError::ValueOutOfRange("offset", seconds.into()))
+    }
+
+    /// Gives the whole hours of the offset, rounded towards zero.
+    pub const fn whole_hours(&self) -> i8 {
+        self.0.whole_hours()
+    }
+
+    /// Gives the offset in seconds, negative west of Greenwich.
+    pub const fn whole_seconds(&self) -> i32 {
+        self.0.whole_seconds()
+    }
+}
+
+impl From<time::UtcOffset> for UtcOffset {
+    fn from(offset: time::UtcOffset) -> UtcOffset {
+        UtcOffset(offset)
+    }
+}
+
+impl From<UtcOffset> for time::UtcOffset {
+    fn from(offset: UtcOffset) -> time::UtcOffset {
+        offset.0
+    }
+}
+
+/// Converts a `chrono` offset, which is never larger than a [`UtcOffset`]
+/// can be.
+#[cfg(feature = "chrono")]
+impl From<chrono::FixedOffset> for UtcOffset {
+    fn from(offset: chrono::FixedOffset) -> UtcOffset {
+        UtcOffset::from_whole_seconds(offset.local_minus_utc()).expect("chrono offset out of range")
+    }
+}
+
+/// Converts to a `chrono` offset, failing for offsets of a day or more,
+/// which `chrono` can't hold.
+#[cfg(feature = "chrono")]
+impl TryFrom<UtcOffset> for chrono::FixedOffset {
+    type Error = GpxError;
+
+    fn try_from(offset: UtcOffset) -> GpxResult<chrono::FixedOffset> {
+        chrono::FixedOffset::east_opt(offset.whole_seconds()).ok_or(GpxError::ValueOutOfRange(
+            "offset",
+            offset.whole_seconds().into(),
+        ))
+    }
+}
+
 /// consume consumes an element as a time.
 pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<Time> {
-    let time_str = string::consume(context, "time", false)?;
+    let time_str = string::consume_value(context, "time")?;
+    let time = parse(&time_str)?;
+    if leap_second_index(&time_str).is_some() {
+        context.warn(GpxWarning::LeapSecond(time_str));
+    }
+    Ok(time)
+}
+
+/// parse parses an xsd:dateTime, assuming UTC if it has no offset.
+///
+/// Leap seconds, like `2016-12-31T23:59:60Z`, can't be represented, so they
+/// are read as the last nanosecond before the next minute.
+pub(crate) fn parse(time_str: &str) -> GpxResult<Time> {
+    let mut time_str = Cow::Borrowed(time_str);
+    if let Some(expanded) = expand_year(&time_str) {
+        time_str = Cow::Owned(expanded);
+    }
+    let leap_second = leap_second_index(&time_str);
+    if let Some(index) = leap_second {
+        time_str.to_mut().replace_range(index..index + 2, "59");
+    }
 
     // Try parsing as ISO 8601 with offset
-    let time = OffsetDateTime::parse(&time_str, &Iso8601::PARSING).or_else(|_| {
+    let mut time = OffsetDateTime::parse(&time_str, &Iso8601::PARSING).or_else(|_| {
         // Try parsing as ISO 8601 without offset, assuming UTC
         PrimitiveDateTime::parse(&time_str, &Iso8601::PARSING).map(PrimitiveDateTime::assume_utc)
     })?;
+    if leap_second.is_some() {
+        time += time::Duration::nanoseconds(999_999_999 - i64::from(time.nanosecond()));
+    }
 
-    Ok(time.to_offset(UtcOffset::UTC).into())
+    Ok(time.to_offset(time::UtcOffset::UTC).into())
+}
+
+/// Gives the index of the seconds of a time whose seconds are `60`.
+fn leap_second_index(time_str: &str) -> Option<usize> {
+    let start = time_str.find('T')?;
+    let leap_second = time_str.get(start + 3..start + 4) == Some(":")
+        && time_str.get(start + 6..start + 9) == Some(":60");
+    leap_second.then_some(start + 7)
+}
+
+/// Rewrites a year outside of 0000 to 9999, like `-0044` or `12021`, as the
+/// six digits with a sign that ISO 8601 uses for such years, or gives `None`
+/// for other years.
+fn expand_year(time_str: &str) -> Option<String> {
+    let (sign, rest) = match time_str.strip_prefix('-') {
+        Some(rest) => ('-', rest),
+        None => ('+', time_str),
+    };
+    let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+    if !(4..=6).contains(&digits) || sign == '+' && digits == 4 {
+        return None;
+    }
+    let (year, rest) = rest.split_at(digits);
+    Some(format!("{sign}{year:0>6}{rest}"))
 }
 
 #[cfg(test)]
 mod tests {
+    use time::macros::datetime;
+    use time::OffsetDateTime;
+
+    use crate::errors::GpxWarning;
+    use crate::parser::create_context;
     use crate::GpxVersion;
 
-    use super::consume;
+    use super::{consume, parse, Duration, Time};
 
     #[test]
     fn consume_time() {
@@ -67,6 +713,9 @@ mod tests {
         let result = consume!("<time>2001-10-26T19:32:52Z</time>", GpxVersion::Gpx11);
         assert!(result.is_ok());
 
+        let result = consume!("<time>\n  2001-10-26T19:32:52Z\n</time>", GpxVersion::Gpx11);
+        assert!(result.is_ok());
+
         let result = consume!("<time>2001-10-26T19:32:52+00:00</time>", GpxVersion::Gpx11);
         assert!(result.is_ok());
 
@@ -86,13 +735,113 @@ mod tests {
         let result = consume!("<time>01-10-26T21:32</time>", GpxVersion::Gpx11);
         assert!(result.is_err());
 
-        // TODO we currently don't allow for negative years although the standard demands it
-        //  see https://www.w3.org/TR/xmlschema-2/#dateTime
+        // Negative and five-digit years, see https://www.w3.org/TR/xmlschema-2/#dateTime
         let result = consume!("<time>-2001-10-26T21:32:52</time>", GpxVersion::Gpx11);
+        assert_eq!(OffsetDateTime::from(result.unwrap()).year(), -2001);
+
+        let result = consume!("<time>12001-10-26T21:32:52Z</time>", GpxVersion::Gpx11);
+        assert_eq!(OffsetDateTime::from(result.unwrap()).year(), 12001);
+
+        let result = consume!("<time>1234567-10-26T21:32:52Z</time>", GpxVersion::Gpx11);
         assert!(result.is_err());
 
         // https://github.com/georust/gpx/issues/77
         let result = consume!("<time>2021-10-10T09:55:20.952</time>", GpxVersion::Gpx11);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn format_extended_years() {
+        for time in [
+            "-0044-03-15T12:00:00.000000000Z",
+            "-123456-01-01T00:00:00.000000000Z",
+            "0000-02-29T00:00:00.000000000Z",
+            "12021-01-01T00:00:00.000000000Z",
+        ] {
+            assert_eq!(parse(time).unwrap().format().unwrap(), time);
+        }
+    }
+
+    #[test]
+    fn consume_leap_second() {
+        let data = "<time>2016-12-31T23:59:60.5Z</time>";
+        let mut context = create_context(data.as_bytes(), GpxVersion::Gpx11);
+        let time = consume(&mut context).unwrap();
+        assert_eq!(
+            time,
+            Time::from(datetime!(2016-12-31 23:59:59.999_999_999 UTC))
+        );
+        assert_eq!(
+            context.take_warnings(),
+            [GpxWarning::LeapSecond("2016-12-31T23:59:60.5Z".into())]
+        );
+
+        let time = parse("2017-01-01T00:59:60+01:00").unwrap();
+        assert_eq!(
+            time,
+            Time::from(datetime!(2016-12-31 23:59:59.999_999_999 UTC))
+        );
+        assert!(parse("2016-12-31T23:59:61Z").is_err());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_conversions() {
+        use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+
+        use super::UtcOffset;
+
+        let time = parse("2016-12-31T23:59:59.25+01:00").unwrap();
+        let converted: DateTime<Utc> = time.try_into().unwrap();
+        assert_eq!(Time::from(converted), time);
+        assert_eq!(converted.timestamp(), 1_483_225_199);
+        assert_eq!(converted.timestamp_subsec_millis(), 250);
+
+        let offset = FixedOffset::east_opt(3600).unwrap();
+        let local = offset.with_ymd_and_hms(2017, 1, 1, 0, 59, 59).unwrap();
+        assert_eq!(Time::from(local), parse("2016-12-31T23:59:59Z").unwrap());
+
+        let far = parse("300000-01-01T00:00:00Z").unwrap();
+        assert!(DateTime::<Utc>::try_from(far).is_err());
+        assert_eq!(
+            Time::from(Utc.timestamp_opt(0, 0).unwrap()),
+            Time::from(OffsetDateTime::UNIX_EPOCH)
+        );
+
+        let duration = -Duration::milliseconds(1_500);
+        let delta = chrono::TimeDelta::try_from(duration).unwrap();
+        assert_eq!(delta.num_milliseconds(), -1_500);
+        assert_eq!(Duration::from(delta), duration);
+        assert!(chrono::TimeDelta::try_from(Duration::MAX).is_err());
+
+        let offset = UtcOffset::from(FixedOffset::west_opt(12_600).unwrap());
+        assert_eq!(offset.whole_seconds(), -12_600);
+        assert_eq!(
+            FixedOffset::try_from(offset).unwrap().local_minus_utc(),
+            -12_600
+        );
+        let far = UtcOffset::from_hms(25, 0, 0).unwrap();
+        assert!(FixedOffset::try_from(far).is_err());
+    }
+
+    #[test]
+    fn time_api() {
+        let time = parse("-0001-12-31T23:59:59.999Z").unwrap();
+        assert_eq!(time.unix_millis(), -62_167_219_200_001);
+        assert_eq!(Time::from_unix_millis(time.unix_millis()).unwrap(), time);
+        assert_eq!(time.unix_seconds(), -62_167_219_201);
+        assert_eq!((time.year(), time.month(), time.day()), (-1, 12, 31));
+        assert_eq!((time.minute(), time.second()), (59, 59));
+        assert_eq!(time.nanosecond(), 999_000_000);
+
+        let mut later = time;
+        later += Duration::milliseconds(1);
+        assert_eq!(later.year(), 0);
+        later -= Duration::days(1);
+        assert_eq!(
+            later - time,
+            Duration::hours(-24) + Duration::milliseconds(1)
+        );
+        assert!(Time::now() > later);
+    }
 }
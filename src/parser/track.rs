@@ -2,18 +2,95 @@
 
 use std::io::Read;
 
+use geo_types::CoordFloat;
 use xml::reader::XmlEvent;
 
 use crate::errors::{GpxError, GpxResult};
-use crate::parser::{extensions, link, string, tracksegment, verify_starting_tag, Context};
+use crate::parser::{
+    consume_bounded, extensions, link, string, tracksegment, verify_starting_tag, Children, Context,
+};
 use crate::Track;
 
+/// The children of a track, in the order of the GPX 1.1 schema.
+const ELEMENTS: &[&str] = &[
+    "name",
+    "cmt",
+    "desc",
+    "src",
+    "link",
+    "number",
+    "type",
+    "extensions",
+    "trkseg",
+];
+
+/// consume_property consumes the next child of a track, which must not be a
+/// segment, into `track`.
+pub(crate) fn consume_property<R: Read, T: CoordFloat>(
+    context: &mut Context<R>,
+    track: &mut Track<T>,
+) -> GpxResult<()> {
+    let name = match context.reader.peek() {
+        Some(Ok(XmlEvent::StartElement { name, .. })) => name.local_name.as_str(),
+        _ => return Err(GpxError::EventParsingError("track event")),
+    };
+    match name {
+        "name" => {
+            track.name = Some(string::consume(context, "name", true)?);
+        }
+        "cmt" => {
+            track.comment = Some(string::consume(context, "cmt", true)?);
+        }
+        "desc" => {
+            track.description = Some(string::consume(context, "desc", true)?);
+        }
+        "src" => {
+            track.source = Some(string::consume(context, "src", true)?);
+        }
+        "type" => {
+            track.type_ = Some(string::consume(context, "type", false)?);
+        }
+        "link" => {
+            track.links.push(link::consume(context)?);
+        }
+        "number" => track.number = consume_bounded(context, "number", 0..=i64::MAX)?,
+        "extensions" => {
+            extensions::consume_track(context, track)?;
+        }
+        child => {
+            return Err(GpxError::InvalidChildElement(String::from(child), "track"));
+        }
+    }
+    Ok(())
+}
+
 /// consume consumes a GPX track from the `reader` until it ends.
-pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<Track> {
-    let mut track: Track = Default::default();
+pub fn consume<R: Read, T: CoordFloat>(context: &mut Context<R>) -> GpxResult<Track<T>> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("track").entered();
+    #[cfg(feature = "tracing")]
+    let start = std::time::Instant::now();
+
+    let track = consume_track(context)?;
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        name = track.name.as_deref(),
+        segments = track.segments.len(),
+        points = track.segments.iter().map(|segment| segment.points.len()).sum::<usize>(),
+        elapsed = ?start.elapsed(),
+        "read track"
+    );
+    Ok(track)
+}
+
+fn consume_track<R: Read, T: CoordFloat>(context: &mut Context<R>) -> GpxResult<Track<T>> {
+    let mut track: Track<T> = Default::default();
     verify_starting_tag(context, "trk")?;
+    let mut children = Children::new(context, "track", ELEMENTS);
 
     loop {
+        children.check(context)?;
         let next_event = {
             if let Some(next) = context.reader.peek() {
                 match next {
@@ -26,38 +103,10 @@ pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<Track> {
         };
 
         match next_event {
-            XmlEvent::StartElement { ref name, .. } => match name.local_name.as_ref() {
-                "name" => {
-                    track.name = Some(string::consume(context, "name", true)?);
-                }
-                "cmt" => {
-                    track.comment = Some(string::consume(context, "cmt", true)?);
-                }
-                "desc" => {
-                    track.description = Some(string::consume(context, "desc", true)?);
-                }
-                "src" => {
-                    track.source = Some(string::consume(context, "src", true)?);
-                }
-                "type" => {
-                    track.type_ = Some(string::consume(context, "type", false)?);
-                }
-                "trkseg" => {
-                    track.segments.push(tracksegment::consume(context)?);
-                }
-                "link" => {
-                    track.links.push(link::consume(context)?);
-                }
-                "number" => {
-                    track.number = Some(string::consume(context, "number", false)?.parse()?)
-                }
-                "extensions" => {
-                    extensions::consume(context)?;
-                }
-                child => {
-                    return Err(GpxError::InvalidChildElement(String::from(child), "track"));
-                }
-            },
+            XmlEvent::StartElement { ref name, .. } if name.local_name == "trkseg" => {
+                track.segments.push(tracksegment::consume(context)?);
+            }
+            XmlEvent::StartElement { .. } => consume_property(context, &mut track)?,
             XmlEvent::EndElement { ref name } => {
                 if name.local_name != "trk" {
                     return Err(GpxError::InvalidClosingTag(
@@ -80,7 +129,9 @@ pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<Track> {
 #[cfg(test)]
 mod tests {
     use super::consume;
-    use crate::GpxVersion;
+    use crate::errors::{GpxResult, GpxWarning};
+    use crate::parser::create_context;
+    use crate::{DisplayColor, DuplicatePolicy, GpxVersion, ReadOptions, Track};
 
     #[test]
     fn consume_full_track() {
@@ -92,6 +143,12 @@ mod tests {
                 <desc>track description</desc>
                 <src>track source</src>
                 <type>track type</type>
+                <extensions>
+                    <gpxx:TrackExtension xmlns:gpxx=\"http://www.garmin.com/xmlschemas/GpxExtensions/v3\">
+                        <gpxx:DisplayColor>DarkRed</gpxx:DisplayColor>
+                    </gpxx:TrackExtension>
+                    <other><DisplayColor>Red</DisplayColor></other>
+                </extensions>
             </trk>
             ",
             GpxVersion::Gpx11
@@ -99,18 +156,45 @@ mod tests {
 
         assert!(track.is_ok());
 
-        let track = track.unwrap();
+        let track: Track = track.unwrap();
 
         assert_eq!(track.name.unwrap(), "track name");
         assert_eq!(track.comment.unwrap(), "track comment");
         assert_eq!(track.description.unwrap(), "track description");
         assert_eq!(track.source.unwrap(), "track source");
         assert_eq!(track.type_.unwrap(), "track type");
+        assert_eq!(track.display_color, Some(DisplayColor::DarkRed));
     }
 
     #[test]
     fn consume_empty() {
-        let track = consume!("<trk></trk>", GpxVersion::Gpx11);
+        let track: GpxResult<Track> = consume!("<trk></trk>", GpxVersion::Gpx11);
         assert!(track.is_ok());
     }
+
+    #[test]
+    fn consume_duplicates() {
+        let data = "<trk><name>First</name><link href=\"a\"/><link href=\"b\"/>\
+            <name>Second</name><trkseg/><trkseg/></trk>";
+        let read = |duplicates| {
+            let mut context = create_context(data.as_bytes(), GpxVersion::Gpx11);
+            context.set_options(ReadOptions {
+                duplicates,
+                ..Default::default()
+            });
+            let track: GpxResult<Track> = consume(&mut context);
+            (track, context.take_warnings())
+        };
+
+        let (track, warnings) = read(DuplicatePolicy::Ignore);
+        assert_eq!(track.unwrap().name.as_deref(), Some("Second"));
+        assert!(warnings.is_empty());
+
+        let (track, warnings) = read(DuplicatePolicy::Warn);
+        assert_eq!(track.unwrap().name.as_deref(), Some("Second"));
+        assert_eq!(warnings, [GpxWarning::DuplicateElement("name", "track")]);
+
+        let (track, _) = read(DuplicatePolicy::Error);
+        assert_eq!(track.unwrap_err().to_string(), "tag opened twice: `name`");
+    }
 }
@@ -0,0 +1,102 @@
+//! trackpointextension handles parsing of Garmin's TrackPointExtension.
+
+use std::io::Read;
+
+use xml::reader::XmlEvent;
+
+use crate::errors::{GpxError, GpxResult};
+use crate::parser::{
+    consume_extension_value, parse_float, parse_integer, skip_element, verify_starting_tag, Context,
+};
+use crate::TrackPointExtension;
+
+/// consume consumes a `TrackPointExtension` element, skipping unknown
+/// children like the speed and course added in version 2, and dropping
+/// invalid values with a warning.
+pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<TrackPointExtension> {
+    verify_starting_tag(context, "TrackPointExtension")?;
+    let mut extension = TrackPointExtension::default();
+
+    loop {
+        let next_event = match context.reader.peek() {
+            Some(Ok(event)) => event,
+            Some(Err(_)) => return Err(GpxError::EventParsingError("TrackPointExtension event")),
+            None => break,
+        };
+
+        match next_event {
+            XmlEvent::StartElement { ref name, .. } => match name.local_name.as_ref() {
+                "atemp" => {
+                    extension.air_temperature =
+                        consume_extension_value(context, "atemp", parse_float)?
+                }
+                "wtemp" => {
+                    extension.water_temperature =
+                        consume_extension_value(context, "wtemp", parse_float)?
+                }
+                "depth" => {
+                    extension.depth = consume_extension_value(context, "depth", parse_float)?
+                }
+                "hr" => {
+                    extension.heart_rate = consume_extension_value(context, "hr", parse_integer)?
+                }
+                "cad" => {
+                    extension.cadence = consume_extension_value(context, "cad", parse_integer)?
+                }
+                _ => skip_element(context)?,
+            },
+            XmlEvent::EndElement { ref name } => {
+                if name.local_name != "TrackPointExtension" {
+                    return Err(GpxError::InvalidClosingTag(
+                        name.local_name.clone(),
+                        "TrackPointExtension",
+                    ));
+                }
+                context.reader.next(); //consume the end tag
+                return Ok(extension);
+            }
+            _ => {
+                context.reader.next(); //consume and ignore this event
+            }
+        }
+    }
+
+    Err(GpxError::MissingClosingTag("TrackPointExtension"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::consume;
+    use crate::GpxVersion;
+
+    #[test]
+    fn consume_track_point_extension() {
+        let extension = consume!(
+            "<gpxtpx:TrackPointExtension xmlns:gpxtpx=\"http://www.garmin.com/xmlschemas/TrackPointExtension/v2\">
+                <gpxtpx:atemp>21.5</gpxtpx:atemp>
+                <gpxtpx:hr>142</gpxtpx:hr>
+                <gpxtpx:cad>88</gpxtpx:cad>
+                <gpxtpx:speed>3.2</gpxtpx:speed>
+            </gpxtpx:TrackPointExtension>",
+            GpxVersion::Gpx11
+        )
+        .unwrap();
+
+        assert_eq!(extension.air_temperature, Some(21.5));
+        assert_eq!(extension.water_temperature, None);
+        assert_eq!(extension.heart_rate, Some(142));
+        assert_eq!(extension.cadence, Some(88));
+    }
+
+    #[test]
+    fn consume_invalid_heart_rate() {
+        let extension = consume!(
+            "<TrackPointExtension><hr>fast</hr><cad>88</cad></TrackPointExtension>",
+            GpxVersion::Gpx11
+        )
+        .unwrap();
+
+        assert_eq!(extension.heart_rate, None);
+        assert_eq!(extension.cadence, Some(88));
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::io::Read;
 
+use geo_types::CoordFloat;
 use xml::reader::XmlEvent;
 
 use crate::errors::{GpxError, GpxResult};
@@ -9,8 +10,25 @@ use crate::parser::{verify_starting_tag, waypoint, Context};
 use crate::TrackSegment;
 
 /// consume consumes a GPX track segment from the `reader` until it ends.
-pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<TrackSegment> {
-    let mut segment: TrackSegment = Default::default();
+pub fn consume<R: Read, T: CoordFloat>(context: &mut Context<R>) -> GpxResult<TrackSegment<T>> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::trace_span!("segment").entered();
+    #[cfg(feature = "tracing")]
+    let start = std::time::Instant::now();
+
+    let segment = consume_segment(context)?;
+
+    #[cfg(feature = "tracing")]
+    tracing::trace!(
+        points = segment.points.len(),
+        elapsed = ?start.elapsed(),
+        "read track segment"
+    );
+    Ok(segment)
+}
+
+fn consume_segment<R: Read, T: CoordFloat>(context: &mut Context<R>) -> GpxResult<TrackSegment<T>> {
+    let mut segment: TrackSegment<T> = Default::default();
     verify_starting_tag(context, "trkseg")?;
 
     loop {
@@ -60,7 +78,8 @@ mod tests {
     use geo::euclidean_length::EuclideanLength;
 
     use super::consume;
-    use crate::GpxVersion;
+    use crate::errors::GpxResult;
+    use crate::{GpxVersion, TrackSegment};
 
     #[test]
     fn consume_full_trkseg() {
@@ -81,7 +100,7 @@ mod tests {
         );
 
         assert!(segment.is_ok());
-        let segment = segment.unwrap();
+        let segment: TrackSegment = segment.unwrap();
 
         assert_eq!(segment.points.len(), 3);
 
@@ -91,7 +110,7 @@ mod tests {
 
     #[test]
     fn consume_empty() {
-        let segment = consume!("<trkseg></trkseg>", GpxVersion::Gpx11);
+        let segment: GpxResult<TrackSegment> = consume!("<trkseg></trkseg>", GpxVersion::Gpx11);
 
         assert!(segment.is_ok());
         let segment = segment.unwrap();
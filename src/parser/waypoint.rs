@@ -2,18 +2,20 @@
 
 use std::io::Read;
 
-use geo_types::Point;
+use geo_types::{CoordFloat, Point};
+use xml::attribute::OwnedAttribute;
 use xml::reader::XmlEvent;
 
 use crate::errors::{GpxError, GpxResult};
-use crate::parser::{extensions, fix, link, string, time, verify_starting_tag, Context};
+use crate::number::parse_f64;
+use crate::parser::{
+    consume_bounded, consume_float, extensions, fix, link, parse_float, string, time,
+    verify_starting_tag, Children, Context,
+};
 use crate::{GpxVersion, Waypoint};
 
-/// consume consumes a GPX waypoint from the `reader` until it ends.
-pub fn consume<R: Read>(context: &mut Context<R>, tagname: &'static str) -> GpxResult<Waypoint> {
-    let attributes = verify_starting_tag(context, tagname)?;
-
-    // get required latitude and longitude
+/// Parses the required latitude and longitude attributes of a waypoint.
+pub(crate) fn position(attributes: &[OwnedAttribute]) -> GpxResult<(f64, f64)> {
     let latitude = attributes
         .iter()
         .find(|attr| attr.name.local_name == "lat")
@@ -21,7 +23,7 @@ pub fn consume<R: Read>(context: &mut Context<R>, tagname: &'static str) -> GpxR
             "latitude", "waypoint",
         ))?;
 
-    let latitude: f64 = latitude.value.parse()?;
+    let latitude = parse_f64(latitude.value.trim())?;
 
     if !(-90.0..=90.0).contains(&latitude) {
         return Err(GpxError::LonLatOutOfBoundsError(
@@ -39,7 +41,7 @@ pub fn consume<R: Read>(context: &mut Context<R>, tagname: &'static str) -> GpxR
             "waypoint",
         ))?;
 
-    let longitude: f64 = longitude.value.parse()?;
+    let longitude = parse_f64(longitude.value.trim())?;
 
     if !(-180.0..180.0).contains(&longitude) {
         return Err(GpxError::LonLatOutOfBoundsError(
@@ -49,9 +51,49 @@ pub fn consume<R: Read>(context: &mut Context<R>, tagname: &'static str) -> GpxR
         ));
     };
 
-    let mut waypoint: Waypoint = Waypoint::new(Point::new(longitude, latitude));
+    Ok((latitude, longitude))
+}
+
+/// The children of a waypoint, in the order of the GPX 1.1 schema.
+const ELEMENTS: &[&str] = &[
+    "ele",
+    "time",
+    "magvar",
+    "geoidheight",
+    "name",
+    "cmt",
+    "desc",
+    "src",
+    "link",
+    "sym",
+    "type",
+    "fix",
+    "sat",
+    "hdop",
+    "vdop",
+    "pdop",
+    "ageofdgpsdata",
+    "dgpsid",
+    "extensions",
+];
+
+/// consume consumes a GPX waypoint from the `reader` until it ends.
+pub fn consume<R: Read, T: CoordFloat>(
+    context: &mut Context<R>,
+    tagname: &'static str,
+) -> GpxResult<Waypoint<T>> {
+    let attributes = verify_starting_tag(context, tagname)?;
+
+    let (latitude, longitude) = position(&attributes)?;
+
+    let mut waypoint: Waypoint<T> = Waypoint::new(Point::new(
+        T::from(longitude).ok_or(GpxError::CoordinateCastError(longitude))?,
+        T::from(latitude).ok_or(GpxError::CoordinateCastError(latitude))?,
+    ));
+    let mut children = Children::new(context, "waypoint", ELEMENTS);
 
     loop {
+        children.check(context)?;
         let next_event = {
             if let Some(next) = context.reader.peek() {
                 match next {
@@ -68,51 +110,64 @@ pub fn consume<R: Read>(context: &mut Context<R>, tagname: &'static str) -> GpxR
                 match name.local_name.as_ref() {
                     "ele" => {
                         // Cast the elevation to an f64, from a string.
-                        waypoint.elevation = match string::consume(context, "ele", false) {
-                            Ok(v) => Some(v.parse()?),
+                        waypoint.elevation = match string::consume_value(context, "ele") {
+                            Ok(v) => Some(parse_float(context, &v)?),
                             Err(GpxError::NoStringContent) => None,
                             Err(other_err) => return Err(other_err),
                         }
                     }
                     "speed" if context.version == GpxVersion::Gpx10 => {
                         // Speed is from GPX 1.0
-                        waypoint.speed = Some(string::consume(context, "speed", false)?.parse()?);
+                        waypoint.speed = Some(consume_float(context, "speed")?);
                     }
                     "time" => waypoint.time = Some(time::consume(context)?),
-                    "name" => waypoint.name = Some(string::consume(context, "name", true)?),
-                    "cmt" => waypoint.comment = Some(string::consume(context, "cmt", true)?),
-                    "desc" => waypoint.description = Some(string::consume(context, "desc", true)?),
-                    "src" => waypoint.source = Some(string::consume(context, "src", true)?),
-                    "link" => waypoint.links.push(link::consume(context)?),
-                    "sym" => waypoint.symbol = Some(string::consume(context, "sym", false)?),
-                    "type" => waypoint.type_ = Some(string::consume(context, "type", false)?),
+                    "name" => {
+                        waypoint.details_mut().name = Some(string::consume(context, "name", true)?)
+                    }
+                    "cmt" => {
+                        waypoint.details_mut().comment =
+                            Some(string::consume(context, "cmt", true)?)
+                    }
+                    "desc" => {
+                        waypoint.details_mut().description =
+                            Some(string::consume(context, "desc", true)?)
+                    }
+                    "src" => {
+                        waypoint.details_mut().source = Some(string::consume(context, "src", true)?)
+                    }
+                    "link" => waypoint.details_mut().links.push(link::consume(context)?),
+                    "sym" => {
+                        let symbol = string::consume(context, "sym", false)?;
+                        waypoint.details_mut().symbol = Some(context.share(symbol))
+                    }
+                    "type" => {
+                        let type_ = string::consume(context, "type", false)?;
+                        waypoint.details_mut().type_ = Some(context.share(type_))
+                    }
 
                     // Optional accuracy information
-                    "fix" => waypoint.fix = Some(fix::consume(context)?),
+                    "fix" => waypoint.details_mut().fix = Some(fix::consume(context)?),
                     "geoidheight" => {
-                        waypoint.geoidheight =
-                            Some(string::consume(context, "geoidheight", false)?.parse()?)
+                        waypoint.details_mut().geoidheight =
+                            Some(consume_float(context, "geoidheight")?)
                     }
-                    "sat" => waypoint.sat = Some(string::consume(context, "sat", false)?.parse()?),
-                    "hdop" => {
-                        waypoint.hdop = Some(string::consume(context, "hdop", false)?.parse()?)
-                    }
-                    "vdop" => {
-                        waypoint.vdop = Some(string::consume(context, "vdop", false)?.parse()?)
-                    }
-                    "pdop" => {
-                        waypoint.pdop = Some(string::consume(context, "pdop", false)?.parse()?)
+                    "sat" => {
+                        waypoint.details_mut().sat = consume_bounded(context, "sat", 0..=i64::MAX)?
                     }
+                    "hdop" => waypoint.details_mut().hdop = Some(consume_float(context, "hdop")?),
+                    "vdop" => waypoint.details_mut().vdop = Some(consume_float(context, "vdop")?),
+                    "pdop" => waypoint.details_mut().pdop = Some(consume_float(context, "pdop")?),
                     "ageofdgpsdata" => {
-                        waypoint.dgps_age =
-                            Some(string::consume(context, "ageofdgpsdata", false)?.parse()?)
+                        waypoint.details_mut().dgps_age =
+                            Some(consume_float(context, "ageofdgpsdata")?)
                     }
                     "dgpsid" => {
-                        waypoint.dgpsid = Some(string::consume(context, "dgpsid", false)?.parse()?)
+                        waypoint.details_mut().dgpsid =
+                            consume_bounded(context, "dgpsid", 0..=1023)?
                     }
 
                     // Finally the GPX 1.1 extensions
-                    "extensions" => extensions::consume(context)?,
+                    "extensions" => extensions::consume_waypoint(context, &mut waypoint)?,
                     child => {
                         return Err(GpxError::InvalidChildElement(
                             String::from(child),
@@ -145,7 +200,9 @@ mod tests {
     use geo_types::Point;
 
     use super::consume;
-    use crate::{Fix, GpxVersion};
+    use crate::errors::{GpxResult, GpxWarning};
+    use crate::parser::create_context;
+    use crate::{Fix, GpxVersion, RangePolicy, ReadOptions, Waypoint};
 
     #[test]
     fn consume_waypoint() {
@@ -173,21 +230,30 @@ mod tests {
         let waypoint = waypoint.unwrap();
 
         assert_eq!(waypoint.point(), Point::new(-77.0365, 38.8977));
-        assert_eq!(waypoint.name.unwrap(), "The White House");
         assert_eq!(
-            waypoint.comment.unwrap(),
+            waypoint.details().name.as_deref().unwrap(),
+            "The White House"
+        );
+        assert_eq!(
+            waypoint.details().comment.as_deref().unwrap(),
             "This is a comment about the white house."
         );
         assert_eq!(
-            waypoint.description.unwrap(),
+            waypoint.details().description.as_deref().unwrap(),
             "The white house is very nice!"
         );
-        assert_eq!(waypoint.source.unwrap(), "Garmin eTrex");
-        assert_eq!(waypoint.type_.unwrap(), "waypoint classification");
+        assert_eq!(
+            waypoint.details().source.as_deref().unwrap(),
+            "Garmin eTrex"
+        );
+        assert_eq!(
+            waypoint.details().type_.as_deref().unwrap(),
+            "waypoint classification"
+        );
         assert_eq!(waypoint.elevation.unwrap(), 4608.12);
-        assert_eq!(waypoint.fix.unwrap(), Fix::DGPS);
-        assert_eq!(waypoint.sat.unwrap(), 4);
-        assert_eq!(waypoint.hdop.unwrap(), 6.058);
+        assert_eq!(waypoint.details().fix, Some(Fix::DGPS));
+        assert_eq!(waypoint.details().sat.unwrap(), 4);
+        assert_eq!(waypoint.details().hdop.unwrap(), 6.058);
     }
 
     #[test]
@@ -226,7 +292,7 @@ mod tests {
 
     #[test]
     fn consume_bad_waypoint() {
-        let waypoint = consume!(
+        let waypoint: GpxResult<Waypoint> = consume!(
             "<wpt lat=\"32.4\" lon=\"notanumber\"></wpt>",
             GpxVersion::Gpx11,
             "wpt"
@@ -237,7 +303,7 @@ mod tests {
 
     #[test]
     fn consume_bad_latitude_1() {
-        let waypoint = consume!(
+        let waypoint: GpxResult<Waypoint> = consume!(
             "<trkpt lat=\"-90.1\" lon=\"1.234\"></trkpt>",
             GpxVersion::Gpx11,
             "trkpt"
@@ -248,7 +314,7 @@ mod tests {
 
     #[test]
     fn consume_bad_latitude_2() {
-        let waypoint = consume!(
+        let waypoint: GpxResult<Waypoint> = consume!(
             "<trkpt lat=\"90.1\" lon=\"1.234\"></trkpt>",
             GpxVersion::Gpx11,
             "trkpt"
@@ -259,7 +325,7 @@ mod tests {
 
     #[test]
     fn consume_bad_longitude_1() {
-        let waypoint = consume!(
+        let waypoint: GpxResult<Waypoint> = consume!(
             "<trkpt lat=\"-32.4\" lon=\"-180.1\"></trkpt>",
             GpxVersion::Gpx11,
             "trkpt"
@@ -270,7 +336,7 @@ mod tests {
 
     #[test]
     fn consume_bad_longitude_2() {
-        let waypoint = consume!(
+        let waypoint: GpxResult<Waypoint> = consume!(
             "<trkpt lat=\"32.4\" lon=\"180.0\"></trkpt>",
             GpxVersion::Gpx11,
             "trkpt"
@@ -278,4 +344,114 @@ mod tests {
 
         assert!(waypoint.is_err());
     }
+
+    #[test]
+    fn consume_pretty_printed_values() {
+        let waypoint: Waypoint = consume!(
+            "<wpt lat=\" 1.5\" lon=\"2\">\n  <ele>\n    12.5\n  </ele>\n  <sat> 4 </sat>\n  <fix>\n    3d\n  </fix>\n</wpt>",
+            GpxVersion::Gpx11,
+            "wpt"
+        )
+        .unwrap();
+        assert_eq!(waypoint.point(), Point::new(2.0, 1.5));
+        assert_eq!(waypoint.elevation, Some(12.5));
+        assert_eq!(waypoint.details().sat, Some(4));
+        assert_eq!(waypoint.details().fix, Some(Fix::ThreeDimensional));
+    }
+
+    #[test]
+    fn consume_lenient_numbers() {
+        let data = "<wpt lat=\"1\" lon=\"2\"><ele>12,5</ele><sat>05.0</sat>\
+            <hdop>1,2</hdop><dgpsid>7,0</dgpsid></wpt>";
+        let mut context = create_context(data.as_bytes(), GpxVersion::Gpx11);
+        assert!(consume::<_, f64>(&mut context, "wpt").is_err());
+
+        let mut context = create_context(data.as_bytes(), GpxVersion::Gpx11);
+        context.set_options(ReadOptions {
+            lenient_numbers: true,
+            ..Default::default()
+        });
+        let waypoint: Waypoint = consume(&mut context, "wpt").unwrap();
+        assert_eq!(waypoint.elevation, Some(12.5));
+        assert_eq!(waypoint.details().sat, Some(5));
+        assert_eq!(waypoint.details().hdop, Some(1.2));
+        assert_eq!(waypoint.details().dgpsid, Some(7));
+
+        for bad in ["<sat>5.5</sat>", "<sat>1,000.0</sat>", "<sat>-1.0</sat>"] {
+            let data = format!("<wpt lat=\"1\" lon=\"2\">{bad}</wpt>");
+            let mut context = create_context(data.as_bytes(), GpxVersion::Gpx11);
+            context.set_options(ReadOptions {
+                lenient_numbers: true,
+                ..Default::default()
+            });
+            assert!(consume::<_, f64>(&mut context, "wpt").is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn consume_out_of_range() {
+        let data = "<wpt lat=\"1\" lon=\"2\"><sat>-1</sat><dgpsid>2000</dgpsid></wpt>";
+        let read = |out_of_range| {
+            let mut context = create_context(data.as_bytes(), GpxVersion::Gpx11);
+            context.set_options(ReadOptions {
+                out_of_range,
+                ..Default::default()
+            });
+            let waypoint: GpxResult<Waypoint> = consume(&mut context, "wpt");
+            (waypoint, context.take_warnings())
+        };
+
+        let (waypoint, _) = read(RangePolicy::Keep);
+        assert_eq!(
+            waypoint.unwrap_err().to_string(),
+            "`sat` value `-1` is out of range"
+        );
+        assert!(read(RangePolicy::Error).0.is_err());
+
+        let (waypoint, warnings) = read(RangePolicy::Clamp);
+        let waypoint = waypoint.unwrap();
+        assert_eq!(
+            (waypoint.details().sat, waypoint.details().dgpsid),
+            (Some(0), Some(1023))
+        );
+        assert_eq!(
+            warnings,
+            [
+                GpxWarning::ValueOutOfRange("sat", -1),
+                GpxWarning::ValueOutOfRange("dgpsid", 2000)
+            ]
+        );
+
+        let (waypoint, _) = read(RangePolicy::Discard);
+        let waypoint = waypoint.unwrap();
+        assert_eq!(
+            (waypoint.details().sat, waypoint.details().dgpsid),
+            (None, None)
+        );
+    }
+
+    #[test]
+    fn consume_strict_order() {
+        let read = |data: &str, version| {
+            let mut context = create_context(data.as_bytes(), version);
+            context.set_options(ReadOptions {
+                strict_order: true,
+                ..Default::default()
+            });
+            consume::<_, f64>(&mut context, "wpt")
+        };
+
+        let ordered = "<wpt lat=\"1\" lon=\"2\"><ele>3</ele><name>A</name>\
+            <link href=\"a\"/><link href=\"b\"/><sym>Flag</sym></wpt>";
+        assert!(read(ordered, GpxVersion::Gpx11).is_ok());
+
+        let unordered = "<wpt lat=\"1\" lon=\"2\"><name>A</name><ele>3</ele></wpt>";
+        assert_eq!(
+            read(unordered, GpxVersion::Gpx11).unwrap_err().to_string(),
+            "element `ele` out of order in `waypoint`"
+        );
+        assert!(read(unordered, GpxVersion::Gpx10).is_ok());
+        let waypoint: GpxResult<Waypoint> = consume!(unordered, GpxVersion::Gpx11, "wpt");
+        assert!(waypoint.is_ok());
+    }
 }
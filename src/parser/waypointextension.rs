@@ -0,0 +1,124 @@
+//! waypointextension handles parsing of Garmin's WaypointExtension.
+
+use std::io::Read;
+
+use xml::reader::XmlEvent;
+
+use crate::errors::{GpxError, GpxResult};
+use crate::parser::{
+    address, consume_extension_value, parse_float, skip_element, string, verify_starting_tag,
+    Context,
+};
+use crate::{DisplayMode, WaypointExtension};
+
+/// consume consumes a `WaypointExtension` element, skipping unknown
+/// children like the categories, and dropping invalid values with a
+/// warning.
+pub fn consume<R: Read>(context: &mut Context<R>) -> GpxResult<WaypointExtension> {
+    verify_starting_tag(context, "WaypointExtension")?;
+    let mut extension = WaypointExtension::default();
+
+    loop {
+        let next_event = match context.reader.peek() {
+            Some(Ok(event)) => event,
+            Some(Err(_)) => return Err(GpxError::EventParsingError("WaypointExtension event")),
+            None => break,
+        };
+
+        match next_event {
+            XmlEvent::StartElement { ref name, .. } => match name.local_name.as_ref() {
+                "Proximity" => {
+                    extension.proximity =
+                        consume_extension_value(context, "Proximity", parse_float)?
+                }
+                "Temperature" => {
+                    extension.temperature =
+                        consume_extension_value(context, "Temperature", parse_float)?
+                }
+                "Depth" => {
+                    extension.depth = consume_extension_value(context, "Depth", parse_float)?
+                }
+                "DisplayMode" => {
+                    let mode = string::consume_value(context, "DisplayMode")?;
+                    extension.display_mode = Some(match mode.as_ref() {
+                        "SymbolOnly" => DisplayMode::SymbolOnly,
+                        "SymbolAndName" => DisplayMode::SymbolAndName,
+                        "SymbolAndDescription" => DisplayMode::SymbolAndDescription,
+                        _ => DisplayMode::Other(mode),
+                    });
+                }
+                "Address" => extension.address = Some(address::consume(context)?),
+                "PhoneNumber" => extension
+                    .phone_numbers
+                    .push(address::consume_phone_number(context)?),
+                _ => skip_element(context)?,
+            },
+            XmlEvent::EndElement { ref name } => {
+                if name.local_name != "WaypointExtension" {
+                    return Err(GpxError::InvalidClosingTag(
+                        name.local_name.clone(),
+                        "WaypointExtension",
+                    ));
+                }
+                context.reader.next(); //consume the end tag
+                return Ok(extension);
+            }
+            _ => {
+                context.reader.next(); //consume and ignore this event
+            }
+        }
+    }
+
+    Err(GpxError::MissingClosingTag("WaypointExtension"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::consume;
+    use crate::errors::GpxWarning;
+    use crate::parser::create_context;
+    use crate::{DisplayMode, GpxVersion};
+
+    #[test]
+    fn consume_waypoint_extension() {
+        let extension = consume!(
+            "<gpxx:WaypointExtension xmlns:gpxx=\"http://www.garmin.com/xmlschemas/GpxExtensions/v3\">
+                <gpxx:Proximity>50</gpxx:Proximity>
+                <gpxx:Depth>12.5</gpxx:Depth>
+                <gpxx:DisplayMode>SymbolAndName</gpxx:DisplayMode>
+                <gpxx:Categories><gpxx:Category>Fishing</gpxx:Category></gpxx:Categories>
+                <gpxx:Address><gpxx:City>Olathe</gpxx:City></gpxx:Address>
+                <gpxx:PhoneNumber>913 397 8200</gpxx:PhoneNumber>
+            </gpxx:WaypointExtension>",
+            GpxVersion::Gpx11
+        )
+        .unwrap();
+
+        assert_eq!(extension.proximity, Some(50.0));
+        assert_eq!(extension.temperature, None);
+        assert_eq!(extension.depth, Some(12.5));
+        assert_eq!(extension.display_mode, Some(DisplayMode::SymbolAndName));
+        let address = extension.address.unwrap();
+        assert_eq!(address.city.as_deref(), Some("Olathe"));
+        assert_eq!(extension.phone_numbers[0].number, "913 397 8200");
+        assert_eq!(extension.phone_numbers[0].category, None);
+    }
+
+    #[test]
+    fn consume_invalid_depth() {
+        let data =
+            "<WaypointExtension><Depth>deep</Depth><Proximity>50</Proximity></WaypointExtension>";
+        let mut context = create_context(data.as_bytes(), GpxVersion::Gpx11);
+        let extension = consume(&mut context).unwrap();
+
+        assert_eq!(extension.depth, None);
+        assert_eq!(extension.proximity, Some(50.0));
+        assert_eq!(
+            context.take_warnings(),
+            [GpxWarning::InvalidExtensionValue(
+                "Depth",
+                "deep".to_string()
+            )]
+        );
+    }
+}
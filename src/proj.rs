@@ -0,0 +1,112 @@
+//! proj reprojects the coordinates of documents to WGS 84 with `proj4rs`,
+//! a Rust port of Proj4 that doesn't need the PROJ C library.
+
+use geo_types::{CoordFloat, Point};
+use proj4rs::transform::transform;
+use proj4rs::Proj;
+
+use crate::errors::GpxResult;
+use crate::measure::{from_f64, to_f64};
+use crate::Gpx;
+
+impl<T: CoordFloat> Gpx<T> {
+    /// Reprojects all points of the document from the coordinate reference
+    /// system `from` to WGS 84 longitudes and latitudes, as GPX requires,
+    /// see [`map_points`](Gpx::map_points).
+    ///
+    /// Coordinates of geographic systems, like `+proj=longlat`, are read in
+    /// degrees. On error, the document is left unchanged.
+    ///
+    /// ```
+    /// use geo_types::{CoordFloat, Point};
+    /// use gpx::{Gpx, Waypoint};
+    /// use proj4rs::Proj;
+    ///
+    /// // Web Mercator, in meters.
+    /// let mercator = Proj::from_proj_string(concat!(
+    ///     "+proj=merc +a=6378137 +b=6378137 +lat_ts=0 +lon_0=0",
+    ///     " +x_0=0 +y_0=0 +k=1 +units=m +nadgrids=@null +no_defs",
+    /// ))
+    /// .unwrap();
+    ///
+    /// let mut gpx: Gpx = Default::default();
+    /// gpx.waypoints.push(Waypoint::new(Point::new(1_113_194.9, 0.0)));
+    /// gpx.reproject(&mercator).unwrap();
+    ///
+    /// let point = gpx.waypoints[0].point();
+    /// assert!((point.x() - 10.0).abs() < 1e-6);
+    /// assert!(point.y().abs() < 1e-6);
+    /// ```
+    pub fn reproject(&mut self, from: &Proj) -> GpxResult<()> {
+        let wgs84 = Proj::from_proj_string("+proj=longlat +datum=WGS84 +no_defs")?;
+        let mut coordinates = Vec::new();
+        self.map_points(|point| {
+            let (x, y) = (to_f64(point.x()), to_f64(point.y()));
+            coordinates.push(if from.is_latlong() {
+                (x.to_radians(), y.to_radians())
+            } else {
+                (x, y)
+            });
+            point
+        });
+        transform(from, &wgs84, coordinates.as_mut_slice())?;
+        // `map_points` visits the points in the same order both times.
+        let mut coordinates = coordinates.into_iter();
+        self.map_points(|point| match coordinates.next() {
+            Some((x, y)) => Point::new(from_f64(x.to_degrees()), from_f64(y.to_degrees())),
+            None => point,
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::Point;
+    use proj4rs::Proj;
+
+    use crate::{Gpx, Route, RoutePointExtension, Waypoint};
+
+    #[test]
+    fn reproject_all_points() {
+        let utm =
+            Proj::from_proj_string("+proj=utm +zone=32 +datum=WGS84 +units=m +no_defs").unwrap();
+        let mut gpx: Gpx = Default::default();
+        let mut waypoint = Waypoint::new(Point::new(500_000.0, 0.0));
+        waypoint.route_point_extension = Some(Box::new(RoutePointExtension {
+            points: vec![Point::new(500_000.0, 0.0)],
+            ..Default::default()
+        }));
+        let mut route = Route::new();
+        route.points.push(waypoint);
+        gpx.routes.push(route);
+        gpx.waypoints
+            .push(Waypoint::new(Point::new(500_000.0, 0.0)));
+
+        gpx.reproject(&utm).unwrap();
+        // The central meridian of zone 32 is 9°E.
+        let expected = Point::new(9.0, 0.0);
+        let route_point = &gpx.routes[0].points[0];
+        for point in [
+            gpx.waypoints[0].point(),
+            route_point.point(),
+            route_point.route_point_extension.as_ref().unwrap().points[0],
+        ] {
+            assert!((point.x() - expected.x()).abs() < 1e-9, "{point:?}");
+            assert!((point.y() - expected.y()).abs() < 1e-9, "{point:?}");
+        }
+    }
+
+    #[test]
+    fn reproject_from_latlong_in_degrees() {
+        let wgs84 = Proj::from_proj_string("+proj=longlat +datum=WGS84 +no_defs").unwrap();
+        let mut gpx: Gpx = Default::default();
+        gpx.waypoints
+            .push(Waypoint::new(Point::new(-121.97, 37.24)));
+
+        gpx.reproject(&wgs84).unwrap();
+        let point = gpx.waypoints[0].point();
+        assert!((point.x() + 121.97).abs() < 1e-9);
+        assert!((point.y() - 37.24).abs() < 1e-9);
+    }
+}
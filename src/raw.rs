@@ -0,0 +1,472 @@
+//! raw reads tracks with their points left undecoded, for workloads that
+//! only need coordinates.
+
+use std::fmt;
+use std::io::Read;
+use std::ops::Range;
+use std::sync::Arc;
+
+use geo_types::{LineString, Point};
+use xml::attribute::OwnedAttribute;
+use xml::name::OwnedName;
+use xml::reader::XmlEvent;
+use xml::EventReader;
+
+use crate::errors::{GpxError, GpxResult};
+use crate::parser::{create_context, gpx::detect_version, verify_starting_tag, waypoint};
+use crate::{GpxVersion, Waypoint};
+
+/// A track whose points haven't been decoded, see [`read_raw_tracks`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RawTrack {
+    /// The segments of the track.
+    pub segments: Vec<RawTrackSegment>,
+}
+
+/// A track segment whose points haven't been decoded.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RawTrackSegment {
+    /// The points of the segment.
+    pub points: Vec<RawPoint>,
+}
+
+impl RawTrackSegment {
+    /// Gives the linestring of the segment's points.
+    pub fn linestring(&self) -> LineString {
+        self.points.iter().map(RawPoint::point).collect()
+    }
+}
+
+/// The text of a document read by [`read_raw_tracks`], shared by its points.
+struct Source {
+    text: String,
+    version: GpxVersion,
+    /// The start tags of the `gpx`, `trk` and `trkseg` elements around the
+    /// points of each segment, which declare the namespaces they use.
+    scopes: Vec<[Range<usize>; 3]>,
+}
+
+/// A trackpoint with just its position decoded, keeping where the whole
+/// `<trkpt>` element is in the document to decode the rest on demand.
+#[derive(Clone)]
+pub struct RawPoint {
+    point: Point,
+    source: Arc<Source>,
+    scope: usize,
+    range: Range<usize>,
+}
+
+impl RawPoint {
+    /// Gives the position of the point.
+    pub fn point(&self) -> Point {
+        self.point
+    }
+
+    /// Gives the XML of the `<trkpt>` element, as it is in the document.
+    pub fn xml(&self) -> &str {
+        &self.source.text[self.range.clone()]
+    }
+
+    /// Decodes the whole point, as [`read`](crate::read) would have.
+    pub fn decode(&self) -> GpxResult<Waypoint> {
+        // The point is decoded inside the start tags of its parents, so that
+        // the prefixes of extensions are declared.
+        let mut xml = String::new();
+        for tag in &self.source.scopes[self.scope] {
+            xml.push_str(&self.source.text[tag.clone()]);
+        }
+        xml.push_str(self.xml());
+        let mut context = create_context(xml.as_bytes(), self.source.version);
+        for parent in ["gpx", "trk", "trkseg"] {
+            verify_starting_tag(&mut context, parent)?;
+        }
+        waypoint::consume(&mut context, "trkpt")
+    }
+}
+
+impl PartialEq for RawPoint {
+    fn eq(&self, other: &RawPoint) -> bool {
+        self.point == other.point
+            && self.source.version == other.source.version
+            && self.xml() == other.xml()
+    }
+}
+
+impl fmt::Debug for RawPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawPoint")
+            .field("point", &self.point)
+            .field("xml", &self.xml())
+            .finish()
+    }
+}
+
+/// Reads the tracks of a document, decoding only the position of each
+/// point.
+///
+/// The document is read into memory and only scanned for the tags of its
+/// tracks, segments and points, without running the XML parser over it,
+/// which is several times faster than [`read`](crate::read) when just the
+/// coordinates are needed. Elevations, times and all other children of the
+/// points are left as they are in the document, which all points share, and
+/// only parsed by [`RawPoint::decode`]. Everything besides the points, like
+/// waypoints, routes and the names of tracks, is skipped.
+///
+/// The scan doesn't check that the document is well-formed XML beyond the
+/// nesting of its elements, so some broken documents that [`read`] rejects
+/// are read, failing only when their points are decoded.
+///
+/// [`read`]: crate::read
+///
+/// ```
+/// use gpx::read_raw_tracks;
+///
+/// let data = "<gpx version=\"1.1\"><trk><trkseg>\
+///     <trkpt lat=\"37.24\" lon=\"-121.97\"><ele>61.5</ele></trkpt>\
+///     </trkseg></trk></gpx>";
+///
+/// let tracks = read_raw_tracks(data.as_bytes()).unwrap();
+/// let point = &tracks[0].segments[0].points[0];
+/// assert_eq!(point.point().y(), 37.24);
+/// assert_eq!(point.decode().unwrap().elevation, Some(61.5));
+/// ```
+pub fn read_raw_tracks<R: Read>(mut reader: R) -> GpxResult<Vec<RawTrack>> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    let text = decode_text(data);
+
+    let mut version = GpxVersion::Unknown;
+    let mut scopes = Vec::new();
+    let mut tracks: Vec<Vec<ScannedSegment>> = Vec::new();
+    // The open elements, with the range of their start tags.
+    let mut open: Vec<(&str, Range<usize>)> = Vec::new();
+    let mut position = 0;
+
+    while let Some(offset) = text[position..].find('<') {
+        let start = position + offset;
+        let tag = scan_tag(&text, start)?;
+        position = tag.end;
+        let (name, tag_attributes, empty) = match tag.kind {
+            TagKind::Other => continue,
+            TagKind::End(name) => {
+                match open.pop() {
+                    Some((open_name, _)) if open_name == name => {}
+                    _ => return Err(GpxError::InvalidClosingTag(name.to_string(), "gpx")),
+                }
+                continue;
+            }
+            TagKind::Start {
+                name,
+                attributes,
+                empty,
+            } => (name, attributes, empty),
+        };
+        let local_name = name.rsplit(':').next().unwrap_or(name);
+        let parents: Vec<&str> = open.iter().map(|(name, _)| local(name)).collect();
+        match (parents.as_slice(), local_name) {
+            ([], "gpx") => version = root_version(&text[start..tag.end])?,
+            ([], _) => return Err(GpxError::MissingOpeningTag("gpx")),
+            (["gpx"], "trk") => tracks.push(Vec::new()),
+            (["gpx", "trk"], "trkseg") => {
+                if let Some(track) = tracks.last_mut() {
+                    track.push(Vec::new());
+                }
+                let parents = [open[0].1.clone(), open[1].1.clone(), start..tag.end];
+                scopes.push(parents);
+            }
+            (["gpx", "trk", "trkseg"], "trkpt") => {
+                let (latitude, longitude) = waypoint::position(&attributes(tag_attributes)?)?;
+                let end = if empty {
+                    tag.end
+                } else {
+                    skip_children(&text, tag.end, name)?
+                };
+                position = end;
+                if let Some(segment) = tracks.last_mut().and_then(|track| track.last_mut()) {
+                    segment.push((Point::new(longitude, latitude), start..end));
+                }
+                continue;
+            }
+            _ => {}
+        }
+        if !empty {
+            open.push((name, start..tag.end));
+        }
+    }
+    if let Some((name, _)) = open.last() {
+        return Err(GpxError::MissingClosingTag(match local(name) {
+            "trk" => "trk",
+            "trkseg" => "trkseg",
+            _ => "gpx",
+        }));
+    }
+    if version == GpxVersion::Unknown {
+        return Err(GpxError::MissingOpeningTag("gpx"));
+    }
+
+    let source = Arc::new(Source {
+        text,
+        version,
+        scopes,
+    });
+    let mut scope = 0;
+    let tracks = tracks
+        .into_iter()
+        .map(|segments| RawTrack {
+            segments: segments
+                .into_iter()
+                .map(|points| {
+                    let segment = RawTrackSegment {
+                        points: points
+                            .into_iter()
+                            .map(|(point, range)| RawPoint {
+                                point,
+                                source: Arc::clone(&source),
+                                scope,
+                                range,
+                            })
+                            .collect(),
+                    };
+                    scope += 1;
+                    segment
+                })
+                .collect(),
+        })
+        .collect();
+    Ok(tracks)
+}
+
+/// The positions of the points of a segment with where they are in the
+/// document, before they share it as [`RawPoint`]s.
+type ScannedSegment = Vec<(Point, Range<usize>)>;
+
+/// Turns the bytes of a document into text: UTF-8, UTF-16 with a byte order
+/// mark, or else Latin-1, the encodings the XML parser reads.
+fn decode_text(data: Vec<u8>) -> String {
+    let utf16 = |to_unit: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = data[2..]
+            .chunks_exact(2)
+            .map(|pair| to_unit([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    };
+    match data.get(..2) {
+        Some([0xFF, 0xFE]) => return utf16(u16::from_le_bytes),
+        Some([0xFE, 0xFF]) => return utf16(u16::from_be_bytes),
+        _ => {}
+    }
+    String::from_utf8(data)
+        .unwrap_or_else(|error| error.into_bytes().into_iter().map(char::from).collect())
+}
+
+fn local(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+/// A tag found by [`scan_tag`], ending before `end`.
+struct Tag<'a> {
+    kind: TagKind<'a>,
+    end: usize,
+}
+
+enum TagKind<'a> {
+    /// A start tag, with the text of its attributes, which is empty if
+    /// the element is.
+    Start {
+        name: &'a str,
+        attributes: &'a str,
+        empty: bool,
+    },
+    End(&'a str),
+    /// A comment, CDATA section, processing instruction or declaration.
+    Other,
+}
+
+/// Scans the markup starting with the `<` at `start`.
+fn scan_tag(text: &str, start: usize) -> GpxResult<Tag<'_>> {
+    let rest = &text[start..];
+    let until = |delimiter: &str| {
+        rest.find(delimiter)
+            .map(|end| start + end + delimiter.len())
+            .ok_or(GpxError::EventParsingError("unclosed markup"))
+    };
+    let end = if rest.starts_with("<!--") {
+        until("-->")?
+    } else if rest.starts_with("<![CDATA[") {
+        until("]]>")?
+    } else if rest.starts_with("<?") {
+        until("?>")?
+    } else if rest.starts_with("<!") {
+        // A document type declaration, whose internal subset may hold `>`.
+        match (rest.find('['), rest.find('>')) {
+            (Some(bracket), Some(close)) if bracket < close => until("]").and_then(|end| {
+                text[end..]
+                    .find('>')
+                    .map(|close| end + close + 1)
+                    .ok_or(GpxError::EventParsingError("unclosed markup"))
+            })?,
+            _ => until(">")?,
+        }
+    } else {
+        // Start and end tags end at the first `>` outside of quotes.
+        let mut quote = None;
+        let close = rest.char_indices().find(|&(_, c)| match quote {
+            Some(q) if c == q => {
+                quote = None;
+                false
+            }
+            Some(_) => false,
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                false
+            }
+            None => c == '>',
+        });
+        let end = close
+            .map(|(close, _)| start + close + 1)
+            .ok_or(GpxError::EventParsingError("unclosed tag"))?;
+        let inner = &text[start + 1..end - 1];
+        let kind = match inner.strip_prefix('/') {
+            Some(name) => TagKind::End(name.trim_end()),
+            None => {
+                let (inner, empty) = match inner.strip_suffix('/') {
+                    Some(inner) => (inner, true),
+                    None => (inner, false),
+                };
+                let name_end = inner.find(char::is_whitespace).unwrap_or(inner.len());
+                TagKind::Start {
+                    name: &inner[..name_end],
+                    attributes: &inner[name_end..],
+                    empty,
+                }
+            }
+        };
+        return Ok(Tag { kind, end });
+    };
+    Ok(Tag {
+        kind: TagKind::Other,
+        end,
+    })
+}
+
+/// Parses the attributes of a start tag, given its text after the name.
+fn attributes(mut text: &str) -> GpxResult<Vec<OwnedAttribute>> {
+    let invalid = || GpxError::EventParsingError("attribute");
+    let mut attributes = Vec::new();
+    loop {
+        text = text.trim_start();
+        if text.is_empty() {
+            return Ok(attributes);
+        }
+        let (name, rest) = text.split_once('=').ok_or_else(invalid)?;
+        let rest = rest.trim_start();
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'');
+        let (value, rest) = rest[1..]
+            .split_once(quote.ok_or_else(invalid)?)
+            .ok_or_else(invalid)?;
+        let name = name.trim();
+        attributes.push(OwnedAttribute::new(
+            OwnedName::local(local(name)),
+            value.to_string(),
+        ));
+        text = rest;
+    }
+}
+
+/// Skips the children of the element `name` whose start tag ends at
+/// `position`, giving the end of its end tag.
+fn skip_children(text: &str, mut position: usize, name: &str) -> GpxResult<usize> {
+    let mut depth = 1;
+    while let Some(offset) = text[position..].find('<') {
+        let tag = scan_tag(text, position + offset)?;
+        position = tag.end;
+        match tag.kind {
+            TagKind::Start { empty: false, .. } => depth += 1,
+            TagKind::End(end_name) => {
+                depth -= 1;
+                if depth == 0 {
+                    if end_name != name {
+                        return Err(GpxError::InvalidClosingTag(end_name.to_string(), "trkpt"));
+                    }
+                    return Ok(position);
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(GpxError::MissingClosingTag("trkpt"))
+}
+
+/// Detects the version of a document from the start tag of its root,
+/// which is parsed on its own.
+fn root_version(tag: &str) -> GpxResult<GpxVersion> {
+    for event in EventReader::new(tag.as_bytes()) {
+        if let XmlEvent::StartElement {
+            name, attributes, ..
+        } = event?
+        {
+            return Ok(detect_version(&name, &attributes)?.0);
+        }
+    }
+    Err(GpxError::MissingOpeningTag("gpx"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::BufReader;
+
+    use super::read_raw_tracks;
+    use crate::read;
+
+    #[test]
+    fn raw_points_decode_like_read() {
+        for fixture in [
+            "tests/fixtures/garmin_with_extensions.gpx",
+            "tests/fixtures/wahoo_example.gpx",
+            "tests/fixtures/wikipedia_example.gpx",
+        ] {
+            let gpx = read(BufReader::new(File::open(fixture).unwrap())).unwrap();
+            let raw = read_raw_tracks(BufReader::new(File::open(fixture).unwrap())).unwrap();
+            assert_eq!(raw.len(), gpx.tracks.len());
+            for (raw, track) in raw.iter().zip(&gpx.tracks) {
+                assert_eq!(raw.segments.len(), track.segments.len());
+                for (raw, segment) in raw.segments.iter().zip(&track.segments) {
+                    assert_eq!(raw.linestring(), segment.linestring());
+                    for (raw, point) in raw.points.iter().zip(&segment.points) {
+                        assert_eq!(&raw.decode().unwrap(), point);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn raw_points_keep_their_xml() {
+        let data = "<?xml version=\"1.0\"?><!-- <trkpt lat=\"0\" lon=\"0\"/> -->\
+            <gpx version=\"1.1\"><wpt lat=\"1\" lon=\"1\"><name>a > b</name></wpt>\
+            <trk xmlns:tp=\"http://www.garmin.com/xmlschemas/TrackPointExtension/v1\"><trkseg>\
+            <trkpt lat='2' lon=\"3\"><extensions><tp:TrackPointExtension><tp:hr>120</tp:hr>\
+            </tp:TrackPointExtension></extensions></trkpt><trkpt lat=\"4\" lon=\"5\"/>\
+            </trkseg></trk></gpx>";
+        let tracks = read_raw_tracks(data.as_bytes()).unwrap();
+        let points = &tracks[0].segments[0].points;
+        assert_eq!(points.len(), 2);
+        assert!(points[0].xml().starts_with("<trkpt lat='2'"));
+        assert!(points[0].xml().ends_with("</trkpt>"));
+        assert_eq!(points[1].xml(), "<trkpt lat=\"4\" lon=\"5\"/>");
+        let extension = points[0].decode().unwrap().track_point_extension;
+        assert_eq!(extension.unwrap().heart_rate, Some(120));
+    }
+
+    #[test]
+    fn raw_tracks_errors() {
+        assert!(read_raw_tracks("<gpx></gpx>".as_bytes()).is_err());
+        assert!(read_raw_tracks("<kml></kml>".as_bytes()).is_err());
+        let bad_point =
+            "<gpx version=\"1.1\"><trk><trkseg><trkpt lat=\"91\" lon=\"0\"/></trkseg></trk></gpx>";
+        assert!(read_raw_tracks(bad_point.as_bytes()).is_err());
+        let unclosed = "<gpx version=\"1.1\"><trk><trkseg><trkpt lat=\"1\" lon=\"0\">";
+        assert!(read_raw_tracks(unclosed.as_bytes()).is_err());
+    }
+}
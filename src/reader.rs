@@ -1,9 +1,14 @@
 //! Reads an activity from GPX format.
 
 use std::io::Read;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
 
-use crate::errors::GpxResult;
-use crate::parser::{create_context, gpx};
+use geo_types::CoordFloat;
+
+use crate::errors::{GpxError, GpxResult, GpxWarning};
+use crate::parser::{create_context, create_context_with_options, gpx};
+use crate::salvage::salvage;
 use crate::{Gpx, GpxVersion};
 
 /// Reads an activity in GPX format.
@@ -33,5 +38,358 @@ use crate::{Gpx, GpxVersion};
 /// }
 /// ```
 pub fn read<R: Read>(reader: R) -> GpxResult<Gpx> {
+    read_as(reader)
+}
+
+/// Options used when reading a document with [`read_with_options`] or a
+/// [`Parser`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReadOptions {
+    /// Accepts numbers written by broken exporters: integers written as
+    /// decimals, like `<sat>05.0</sat>`, and a comma as the decimal
+    /// separator, like `<ele>12,5</ele>`.
+    pub lenient_numbers: bool,
+
+    /// What to do with values of bounded fields outside of their range, like
+    /// a `dgpsid` above 1023 or a negative `sat` or `number`.
+    pub out_of_range: RangePolicy,
+
+    /// Fails with [`GpxError::ElementOutOfOrder`] if the children of
+    /// `metadata`, `wpt`, `rtept`, `trkpt`, `rte` or `trk` are not in the
+    /// order of the GPX 1.1 schema, like a `<name>` after a `<time>`.
+    ///
+    /// Only GPX 1.1 documents are checked.
+    pub strict_order: bool,
+
+    /// What to do with children of `metadata`, `wpt`, `rtept`, `trkpt`,
+    /// `rte` or `trk` that the GPX 1.1 schema allows once but are repeated,
+    /// like two `<name>` of a track.
+    pub duplicates: DuplicatePolicy,
+
+    /// Reads documents that are cut off, like the files of loggers that lost
+    /// power, as far as they go instead of failing.
+    ///
+    /// Documents that aren't well-formed XML are read up to the last
+    /// complete waypoint, route point or track point, or the last complete
+    /// child of the document, a track or a route, and the elements open
+    /// there are closed. Trailing garbage, like the zeros some file systems
+    /// leave after a crash, is dropped the same way. A [`Parser`] reports
+    /// where the document was cut off as [`GpxWarning::Truncated`].
+    ///
+    /// ```
+    /// use gpx::errors::GpxWarning;
+    /// use gpx::{Parser, ReadOptions};
+    ///
+    /// let data = "<gpx version=\"1.1\"><trk><trkseg>\
+    ///     <trkpt lat=\"37.24\" lon=\"-121.97\"></trkpt>\
+    ///     <trkpt lat=\"37.25\" lon=\"-121.96\"><ele>6";
+    /// let mut parser = Parser::with_options(ReadOptions {
+    ///     salvage: true,
+    ///     ..Default::default()
+    /// });
+    /// let gpx = parser.read(data.as_bytes()).unwrap();
+    /// assert_eq!(gpx.tracks[0].segments[0].points.len(), 1);
+    /// assert!(matches!(parser.warnings(), [GpxWarning::Truncated(..)]));
+    /// ```
+    pub salvage: bool,
+
+    /// Keeps the XML comments between the children of the root element in
+    /// [`Gpx::comments`], to be written back where they were. Comments
+    /// elsewhere, like inside of points, are still dropped.
+    ///
+    /// ```
+    /// use gpx::{CommentPosition, ReadOptions};
+    ///
+    /// let data = "<gpx version=\"1.1\"><trk/><!-- Surveyed twice --><trk/></gpx>";
+    /// let options = ReadOptions {
+    ///     comments: true,
+    ///     ..Default::default()
+    /// };
+    /// let gpx = gpx::read_with_options(data.as_bytes(), &options).unwrap();
+    /// assert_eq!(gpx.comments[0].text, " Surveyed twice ");
+    /// assert_eq!(gpx.comments[0].position, CommentPosition::BeforeTrack(1));
+    /// ```
+    pub comments: bool,
+
+    /// Shares the `sym` and `type` of waypoints among the waypoints of a
+    /// document with the same value, so that each distinct symbol or type is
+    /// allocated once, rather than once per waypoint. Logs that tag thousands
+    /// of points with the same few symbols take much less memory this way.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use gpx::ReadOptions;
+    ///
+    /// let data = "<gpx version=\"1.1\">\
+    ///     <wpt lat=\"37.24\" lon=\"-121.97\"><sym>Flag, Blue</sym></wpt>\
+    ///     <wpt lat=\"37.25\" lon=\"-121.96\"><sym>Flag, Blue</sym></wpt>\
+    ///     </gpx>";
+    /// let options = ReadOptions {
+    ///     intern_strings: true,
+    ///     ..Default::default()
+    /// };
+    /// let gpx = gpx::read_with_options(data.as_bytes(), &options).unwrap();
+    /// let [first, second] = &gpx.waypoints[..] else { panic!() };
+    /// assert!(Arc::ptr_eq(
+    ///     first.details().symbol.as_ref().unwrap(),
+    ///     second.details().symbol.as_ref().unwrap(),
+    /// ));
+    /// ```
+    pub intern_strings: bool,
+}
+
+/// What to do with repeated elements that are allowed once, see
+/// [`ReadOptions::duplicates`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DuplicatePolicy {
+    /// Keeps the last of the elements.
+    #[default]
+    Ignore,
+    /// Keeps the last of the elements, reporting the others as
+    /// [`GpxWarning::DuplicateElement`].
+    Warn,
+    /// Fails with [`GpxError::TagOpenedTwice`].
+    Error,
+}
+
+/// What to do with values outside of the range the GPX schema allows for
+/// their field, see [`ReadOptions::out_of_range`] and
+/// [`WriteOptions::out_of_range`](crate::WriteOptions::out_of_range).
+///
+/// Values that were clamped or discarded on read are reported as
+/// [`GpxWarning::ValueOutOfRange`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RangePolicy {
+    /// Keeps values as they are, failing only for values their field can't
+    /// hold, like a negative `sat`.
+    #[default]
+    Keep,
+    /// Fails with [`GpxError::ValueOutOfRange`].
+    Error,
+    /// Replaces values with the nearest value in range.
+    Clamp,
+    /// Leaves the field empty.
+    Discard,
+}
+
+impl RangePolicy {
+    /// Applies the policy to `value` of the field `name`, giving the value to
+    /// keep, if any.
+    pub(crate) fn apply(
+        self,
+        name: &'static str,
+        value: i64,
+        range: RangeInclusive<i64>,
+    ) -> GpxResult<Option<i64>> {
+        if range.contains(&value) {
+            return Ok(Some(value));
+        }
+        match self {
+            RangePolicy::Keep => Ok(Some(value)),
+            RangePolicy::Error => Err(GpxError::ValueOutOfRange(name, value)),
+            RangePolicy::Clamp => Ok(Some(value.clamp(*range.start(), *range.end()))),
+            RangePolicy::Discard => Ok(None),
+        }
+    }
+}
+
+/// Reads an activity in GPX format, like [`read`] but with `options`.
+///
+/// ```
+/// use gpx::{read_with_options, ReadOptions};
+///
+/// let data = "<gpx version=\"1.1\"><wpt lat=\"1\" lon=\"2\"><ele>12,5</ele><sat>05.0</sat></wpt></gpx>";
+/// assert!(gpx::read(data.as_bytes()).is_err());
+///
+/// let options = ReadOptions {
+///     lenient_numbers: true,
+///     ..Default::default()
+/// };
+/// let gpx = read_with_options(data.as_bytes(), &options).unwrap();
+/// assert_eq!(gpx.waypoints[0].elevation, Some(12.5));
+/// assert_eq!(gpx.waypoints[0].details().sat, Some(5));
+/// ```
+pub fn read_with_options<R: Read>(reader: R, options: &ReadOptions) -> GpxResult<Gpx> {
+    if options.salvage {
+        return Parser::with_options(options.clone()).read(reader);
+    }
+    gpx::consume(&mut create_context_with_options(reader, options))
+}
+
+/// Reads an activity in GPX format from bytes in memory, e.g. a file
+/// uploaded in a browser.
+///
+/// This is a convenience wrapper around [`read`] with `data` as its reader,
+/// parsing the same way and no faster than `read(data)`. The XML parser
+/// reads its input byte by byte, which is slow from an unbuffered `File`, so
+/// wrap files in a `BufReader` rather than handing them to [`read`] as they
+/// are.
+///
+/// ```
+/// use gpx::read_from_slice;
+///
+/// let gpx = read_from_slice(b"<gpx version=\"1.1\"><wpt lat=\"37.24\" lon=\"-121.97\"/></gpx>").unwrap();
+/// assert_eq!(gpx.waypoints.len(), 1);
+/// ```
+pub fn read_from_slice(data: &[u8]) -> GpxResult<Gpx> {
+    read_from_slice_as(data)
+}
+
+/// Reads an activity in GPX format from bytes in memory, storing
+/// coordinates as `T`, see [`read_from_slice`] and [`read_as`].
+pub fn read_from_slice_as<T: CoordFloat>(data: &[u8]) -> GpxResult<Gpx<T>> {
+    gpx::consume(&mut create_context(data, GpxVersion::Unknown))
+}
+
+/// Reads an activity in GPX format, storing coordinates as `T`.
+///
+/// This behaves like [`read`], but lets the caller pick the scalar type used
+/// for coordinates, elevations and bounds, e.g. `f32` to save memory on very
+/// large files.
+///
+/// ```
+/// use gpx::{read_as, Gpx};
+///
+/// let data = "<gpx version=\"1.1\"><wpt lat=\"37.24\" lon=\"-121.97\"/></gpx>";
+///
+/// let gpx: Gpx<f32> = read_as(data.as_bytes()).unwrap();
+/// assert_eq!(gpx.waypoints[0].point().y(), 37.24_f32);
+/// ```
+pub fn read_as<T: CoordFloat, R: Read>(reader: R) -> GpxResult<Gpx<T>> {
     gpx::consume(&mut create_context(reader, GpxVersion::Unknown))
 }
+
+impl<T: CoordFloat> Gpx<T> {
+    /// Reads an activity in GPX format from bytes in memory, see
+    /// [`read_from_slice`].
+    pub fn from_bytes(data: &[u8]) -> GpxResult<Gpx<T>> {
+        read_from_slice_as(data)
+    }
+}
+
+/// Reads an activity in GPX format from a string.
+///
+/// ```
+/// use gpx::Gpx;
+///
+/// let gpx: Gpx = "<gpx version=\"1.1\"><wpt lat=\"37.24\" lon=\"-121.97\"/></gpx>"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(gpx.waypoints.len(), 1);
+/// assert!("<kml></kml>".parse::<Gpx>().is_err());
+/// ```
+impl<T: CoordFloat> FromStr for Gpx<T> {
+    type Err = GpxError;
+
+    fn from_str(data: &str) -> GpxResult<Gpx<T>> {
+        Gpx::from_bytes(data.as_bytes())
+    }
+}
+
+/// A reader for many documents with the same [`ReadOptions`], like the
+/// uploads a server parses, which reports the problems worked around in each
+/// through [`warnings`](Parser::warnings).
+///
+/// Each input is read into a buffer owned by the parser and parsed from
+/// there, and that buffer and the list of warnings are kept between reads,
+/// so a parser that has seen the largest input reads the next ones without
+/// growing them. The strings of names, descriptions and other text are
+/// allocated by the XML parser for each document, as it can't be reset to a
+/// new input and reused.
+///
+/// ```
+/// use gpx::Parser;
+///
+/// let mut parser = Parser::new();
+/// for upload in ["<gpx version=\"1.1\"></gpx>", "<gpx version=\"1.0\"></gpx>"] {
+///     let gpx = parser.read(upload.as_bytes()).unwrap();
+///     assert!(gpx.waypoints.is_empty());
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Parser {
+    buffer: Vec<u8>,
+    warnings: Vec<GpxWarning>,
+    options: ReadOptions,
+}
+
+impl Parser {
+    /// Creates a parser with the default options.
+    pub fn new() -> Parser {
+        Default::default()
+    }
+
+    /// Creates a parser whose input buffer fits inputs of `capacity` bytes
+    /// without growing.
+    pub fn with_capacity(capacity: usize) -> Parser {
+        Parser {
+            buffer: Vec::with_capacity(capacity),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a parser reading with `options`.
+    pub fn with_options(options: ReadOptions) -> Parser {
+        Parser {
+            options,
+            ..Default::default()
+        }
+    }
+
+    /// Reads an activity in GPX format, see [`read`].
+    pub fn read<R: Read>(&mut self, reader: R) -> GpxResult<Gpx> {
+        self.read_as(reader)
+    }
+
+    /// Reads an activity in GPX format, storing coordinates as `T`, see
+    /// [`read_as`].
+    pub fn read_as<T: CoordFloat, R: Read>(&mut self, mut reader: R) -> GpxResult<Gpx<T>> {
+        self.warnings.clear();
+        self.buffer.clear();
+        reader.read_to_end(&mut self.buffer)?;
+        let result = parse(self.buffer.as_slice(), &self.options, &mut self.warnings);
+        if result.is_err() && self.options.salvage {
+            if let Some((repaired, warning)) = salvage(&self.buffer) {
+                self.warnings.clear();
+                let result = parse(repaired.as_slice(), &self.options, &mut self.warnings);
+                self.warnings.push(warning);
+                return result;
+            }
+        }
+        result
+    }
+
+    /// Gives the problems worked around in the last read, like a `version`
+    /// attribute disagreeing with the namespace of the document.
+    ///
+    /// ```
+    /// use gpx::errors::GpxWarning;
+    /// use gpx::{GpxVersion, Parser};
+    ///
+    /// let mut parser = Parser::new();
+    /// let data = "<gpx version=\"1.0\" xmlns=\"http://www.topografix.com/GPX/1/1\"></gpx>";
+    /// let gpx = parser.read(data.as_bytes()).unwrap();
+    /// assert_eq!(gpx.version, GpxVersion::Gpx11);
+    /// assert_eq!(
+    ///     parser.warnings(),
+    ///     [GpxWarning::VersionMismatch("1.0".into(), GpxVersion::Gpx11)]
+    /// );
+    /// ```
+    pub fn warnings(&self) -> &[GpxWarning] {
+        &self.warnings
+    }
+}
+
+/// Parses the document of `reader` with `options`, adding the warnings to
+/// `warnings`.
+fn parse<T: CoordFloat, R: Read>(
+    reader: R,
+    options: &ReadOptions,
+    warnings: &mut Vec<GpxWarning>,
+) -> GpxResult<Gpx<T>> {
+    let mut context = create_context_with_options(reader, options);
+    context.set_warnings(std::mem::take(warnings));
+    let result = gpx::consume(&mut context);
+    *warnings = context.take_warnings();
+    result
+}
@@ -0,0 +1,127 @@
+//! record records tracks from live positioning sources, like gpsd or a
+//! serial GPS receiver, which the crate doesn't talk to itself.
+
+use std::fmt::Display;
+use std::io::Write;
+
+use geo_types::CoordFloat;
+
+use crate::errors::GpxResult;
+use crate::{GpxLogger, Track, TrackSegment, Waypoint};
+
+/// A source of timestamped positions, like a gpsd connection or a serial
+/// GPS receiver, see [`Track::record`] and [`GpxLogger::record`].
+///
+/// Iterators of waypoints are sources too, which is handy for replaying
+/// recordings.
+pub trait GpsSource<T: CoordFloat = f64> {
+    /// Waits for the next fix, giving the position with its time and
+    /// whatever else the receiver reports, like the elevation or the number
+    /// of satellites. Gives `None` once the source has ended.
+    fn next_fix(&mut self) -> GpxResult<Option<Waypoint<T>>>;
+}
+
+impl<T: CoordFloat, I: Iterator<Item = Waypoint<T>>> GpsSource<T> for I {
+    fn next_fix(&mut self) -> GpxResult<Option<Waypoint<T>>> {
+        Ok(self.next())
+    }
+}
+
+impl<T: CoordFloat> Track<T> {
+    /// Records the fixes of `source` until it ends, appending them to the
+    /// last segment of the track, or to a new one if the track has none.
+    ///
+    /// Gives the number of recorded fixes. On errors of the source, the
+    /// fixes recorded so far are kept.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Track, Waypoint};
+    ///
+    /// let mut fixes = (0..5).map(|i| Waypoint::new(Point::new(f64::from(i) * 1e-4, 47.0)));
+    ///
+    /// let mut track = Track::new();
+    /// assert_eq!(track.record(&mut fixes).unwrap(), 5);
+    /// assert_eq!(track.segments[0].points.len(), 5);
+    /// ```
+    pub fn record(&mut self, source: &mut dyn GpsSource<T>) -> GpxResult<usize> {
+        if self.segments.is_empty() {
+            self.segments.push(TrackSegment::new());
+        }
+        let segment = self.segments.last_mut().unwrap();
+        let mut count = 0;
+        while let Some(fix) = source.next_fix()? {
+            segment.points.push(fix);
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+impl<W: Write> GpxLogger<W> {
+    /// Records the fixes of `source` until it ends, writing each to the
+    /// current segment as it comes.
+    ///
+    /// Gives the number of recorded fixes. On errors of the source, the
+    /// fixes recorded so far have been written, and logging can go on.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{GpxLogger, Track, Waypoint};
+    ///
+    /// let mut fixes = (0..5).map(|i| Waypoint::new(Point::new(f64::from(i) * 1e-4, 47.0)));
+    ///
+    /// let mut logger = GpxLogger::new(Vec::new(), &Track::<f64>::new()).unwrap();
+    /// assert_eq!(logger.record(&mut fixes).unwrap(), 5);
+    /// let gpx = gpx::read_from_slice(&logger.finish().unwrap()).unwrap();
+    /// assert_eq!(gpx.tracks[0].segments[0].points.len(), 5);
+    /// ```
+    pub fn record<T: CoordFloat + Display>(
+        &mut self,
+        source: &mut dyn GpsSource<T>,
+    ) -> GpxResult<usize> {
+        let mut count = 0;
+        while let Some(fix) = source.next_fix()? {
+            self.push(&fix)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::Point;
+
+    use super::GpsSource;
+    use crate::errors::{GpxError, GpxResult};
+    use crate::{Track, Waypoint};
+
+    /// A receiver that loses its connection after `fixes` fixes.
+    struct Flaky {
+        fixes: usize,
+    }
+
+    impl GpsSource for Flaky {
+        fn next_fix(&mut self) -> GpxResult<Option<Waypoint>> {
+            if self.fixes == 0 {
+                return Err(GpxError::IoError(std::io::ErrorKind::BrokenPipe.into()));
+            }
+            self.fixes -= 1;
+            Ok(Some(Waypoint::new(Point::new(1.0, 2.0))))
+        }
+    }
+
+    #[test]
+    fn record_keeps_fixes_on_errors() {
+        let mut track = Track::new();
+        track.segments.push(Default::default());
+        track.segments[0]
+            .points
+            .push(Waypoint::new(Point::new(0.0, 0.0)));
+
+        assert!(track.record(&mut Flaky { fixes: 3 }).is_err());
+        assert_eq!(track.segments.len(), 1);
+        assert_eq!(track.segments[0].points.len(), 4);
+    }
+}
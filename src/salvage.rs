@@ -0,0 +1,107 @@
+//! salvage repairs documents that were cut off, like the files of loggers
+//! that lost power, see [`ReadOptions::salvage`](crate::ReadOptions::salvage).
+
+use xml::common::Position;
+use xml::name::OwnedName;
+use xml::reader::{EventReader, XmlEvent};
+use xml::writer::EmitterConfig;
+
+use crate::errors::GpxWarning;
+
+/// The elements whose children can be dropped as a whole: an unfinished
+/// point, route or track is left out, but the complete ones before it in
+/// these elements are kept.
+const CONTAINERS: [&str; 4] = ["gpx", "trk", "trkseg", "rte"];
+
+/// Repairs the document in `data` if it isn't well-formed XML, like a
+/// document cut off in the middle of a point.
+///
+/// The document is kept up to the last point where only [`CONTAINERS`] were
+/// open, and those are closed. Gives `None` for well-formed documents, or
+/// the repaired document with a warning telling where it was cut off.
+pub(crate) fn salvage(data: &[u8]) -> Option<(Vec<u8>, GpxWarning)> {
+    // Empty elements are written with separate closing tags, so that the
+    // whole of every event is written out and the document can be cut
+    // after any of them.
+    let mut writer = EmitterConfig::new()
+        .write_document_declaration(false)
+        .normalize_empty_elements(false)
+        .create_writer(Vec::new());
+    let mut open: Vec<String> = Vec::new();
+    // The length of the document and the number of open elements at the
+    // last point where only containers were open.
+    let mut safe = (0, 0);
+
+    for event in EventReader::new(data) {
+        let event = match event {
+            Ok(event) => event,
+            Err(error) => {
+                let position = error.position();
+                let mut repaired = writer.into_inner();
+                repaired.truncate(safe.0);
+                for name in open[..safe.1].iter().rev() {
+                    repaired.extend_from_slice(format!("</{name}>").as_bytes());
+                }
+                let warning = GpxWarning::Truncated(position.row + 1, position.column + 1);
+                return Some((repaired, warning));
+            }
+        };
+        match &event {
+            XmlEvent::StartDocument { .. } | XmlEvent::EndDocument => continue,
+            XmlEvent::StartElement { name, .. } => open.push(qualified(name)),
+            XmlEvent::EndElement { .. } => {
+                open.pop();
+            }
+            _ => {}
+        }
+        if let Some(event) = event.as_writer_event() {
+            writer.write(event).ok()?;
+        }
+        if open.iter().all(|name| is_container(name)) {
+            safe = (writer.inner_ref().len(), open.len());
+        }
+    }
+    None
+}
+
+/// Gives the name of an element as written in the document, with its
+/// prefix.
+fn qualified(name: &OwnedName) -> String {
+    match &name.prefix {
+        Some(prefix) => format!("{prefix}:{}", name.local_name),
+        None => name.local_name.clone(),
+    }
+}
+
+fn is_container(name: &str) -> bool {
+    let local_name = name.rsplit(':').next().unwrap_or(name);
+    CONTAINERS.contains(&local_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::salvage;
+    use crate::errors::GpxWarning;
+
+    #[test]
+    fn salvage_cuts_unfinished_points() {
+        let data = "<gpx version=\"1.1\"><trk><name>Ride</name><trkseg>\
+            <trkpt lat=\"1\" lon=\"2\"><ele>3</ele></trkpt>\
+            <trkpt lat=\"4\" lon=\"5\"><ele>6";
+        let (repaired, warning) = salvage(data.as_bytes()).unwrap();
+        assert_eq!(
+            String::from_utf8(repaired).unwrap(),
+            "<gpx version=\"1.1\"><trk><name>Ride</name><trkseg>\
+            <trkpt lat=\"1\" lon=\"2\"><ele>3</ele></trkpt></trkseg></trk></gpx>"
+        );
+        assert!(matches!(warning, GpxWarning::Truncated(1, _)));
+
+        let nul_padded = "<gpx version=\"1.1\"><rte><rtept lat=\"1\" lon=\"2\"/></rte>\0\0\0";
+        let (repaired, _) = salvage(nul_padded.as_bytes()).unwrap();
+        assert!(String::from_utf8(repaired)
+            .unwrap()
+            .ends_with("</rtept></rte></gpx>"));
+
+        assert!(salvage(b"<gpx version=\"1.1\"></gpx>").is_none());
+    }
+}
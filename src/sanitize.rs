@@ -0,0 +1,300 @@
+//! sanitize strips the vendor extensions from documents, for consumers that
+//! only take plain GPX or to make files smaller.
+
+use geo_types::CoordFloat;
+
+use crate::writer::{
+    GPX_EXTENSIONS_NAMESPACE, GPX_STYLE_NAMESPACE, TRACK_POINT_EXTENSION_NAMESPACE,
+};
+use crate::{Gpx, Waypoint, WaypointDetails};
+
+/// The namespace of XML Schema's attributes, like `schemaLocation`.
+const SCHEMA_INSTANCE_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+/// A vendor extension the crate reads and writes, see
+/// [`Gpx::strip_extension`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Extension {
+    /// Garmin's GpxExtensions: the [`WaypointExtension`](crate::WaypointExtension)
+    /// and [`RoutePointExtension`](crate::RoutePointExtension) of points and
+    /// the display colors of tracks.
+    GpxExtensions,
+    /// Garmin's [`TrackPointExtension`](crate::TrackPointExtension) of track
+    /// points.
+    TrackPointExtension,
+    /// The [`LineStyle`](crate::LineStyle)s of tracks, from gpx_style.
+    GpxStyle,
+}
+
+impl Extension {
+    /// Gives the namespace URI of the extension.
+    pub fn namespace(self) -> &'static str {
+        match self {
+            Extension::GpxExtensions => GPX_EXTENSIONS_NAMESPACE,
+            Extension::TrackPointExtension => TRACK_POINT_EXTENSION_NAMESPACE,
+            Extension::GpxStyle => GPX_STYLE_NAMESPACE,
+        }
+    }
+}
+
+impl<T: CoordFloat> Gpx<T> {
+    /// Removes all vendor data from the document, leaving plain GPX as the
+    /// specification defines it: the data of all [`Extension`]s, and the
+    /// attributes of the root element other than the schema locations of
+    /// GPX itself.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Gpx, TrackPointExtension, Waypoint};
+    ///
+    /// let mut point = Waypoint::new(Point::new(0.0, 0.0));
+    /// point.track_point_extension = Some(TrackPointExtension {
+    ///     heart_rate: Some(150),
+    ///     ..Default::default()
+    /// });
+    /// let mut gpx: Gpx = Default::default();
+    /// gpx.waypoints.push(point);
+    /// gpx.root_attributes.push(("xmlns:acme".to_string(), "https://acme.example".to_string()));
+    ///
+    /// gpx.strip_extensions();
+    /// assert_eq!(gpx.waypoints[0].track_point_extension, None);
+    /// assert!(gpx.root_attributes.is_empty());
+    /// ```
+    pub fn strip_extensions(&mut self) {
+        self.strip_extension(Extension::GpxExtensions);
+        self.strip_extension(Extension::TrackPointExtension);
+        self.strip_extension(Extension::GpxStyle);
+
+        // Keep just the schema locations of GPX, with the declaration of
+        // their namespace.
+        let schema_instance = self.namespace_prefix(SCHEMA_INSTANCE_NAMESPACE);
+        let schema_location = schema_instance
+            .as_ref()
+            .map(|prefix| format!("{prefix}:schemaLocation"));
+        self.root_attributes.retain_mut(|(name, value)| {
+            if Some(&*name) != schema_location.as_ref() {
+                return false;
+            }
+            *value = retain_locations(value, |namespace| {
+                namespace.starts_with("http://www.topografix.com/GPX/1/")
+            });
+            !value.is_empty()
+        });
+        if let (Some(prefix), false) = (schema_instance, self.root_attributes.is_empty()) {
+            self.root_attributes.insert(
+                0,
+                (
+                    format!("xmlns:{prefix}"),
+                    SCHEMA_INSTANCE_NAMESPACE.to_string(),
+                ),
+            );
+        }
+    }
+
+    /// Removes the data of one vendor `extension` from the document, along
+    /// with the declaration and schema location of its namespace.
+    pub fn strip_extension(&mut self, extension: Extension) {
+        let route_points = self.routes.iter_mut().flat_map(|route| &mut route.points);
+        let track_points = self
+            .tracks
+            .iter_mut()
+            .flat_map(|track| &mut track.segments)
+            .flat_map(|segment| &mut segment.points);
+        for point in self
+            .waypoints
+            .iter_mut()
+            .chain(route_points)
+            .chain(track_points)
+        {
+            strip_point(point, extension);
+        }
+        for track in &mut self.tracks {
+            match extension {
+                Extension::GpxExtensions => track.display_color = None,
+                Extension::GpxStyle => track.line_style = None,
+                Extension::TrackPointExtension => {}
+            }
+        }
+
+        let namespace = extension.namespace();
+        if let Some(prefix) = self.namespace_prefix(SCHEMA_INSTANCE_NAMESPACE) {
+            let schema_location = format!("{prefix}:schemaLocation");
+            for (_, value) in self
+                .root_attributes
+                .iter_mut()
+                .filter(|(name, _)| *name == schema_location)
+            {
+                *value = retain_locations(value, |other| other != namespace);
+            }
+        }
+        self.root_attributes
+            .retain(|(name, value)| !(name.starts_with("xmlns:") && value == namespace));
+    }
+
+    /// Gives the prefix the root element declares for `namespace`.
+    fn namespace_prefix(&self, namespace: &str) -> Option<String> {
+        self.root_attributes.iter().find_map(|(name, value)| {
+            let prefix = name.strip_prefix("xmlns:")?;
+            (value == namespace).then(|| prefix.to_string())
+        })
+    }
+}
+
+/// Removes the data of `extension` from `point`, dropping its details if
+/// nothing else is left of them.
+fn strip_point<T: CoordFloat>(point: &mut Waypoint<T>, extension: Extension) {
+    match extension {
+        Extension::TrackPointExtension => point.track_point_extension = None,
+        Extension::GpxExtensions => {
+            point.route_point_extension = None;
+            if point.has_details() {
+                point.details_mut().waypoint_extension = None;
+            }
+        }
+        Extension::GpxStyle => {}
+    }
+    if point.has_details() && *point.details() == WaypointDetails::default() {
+        point.clear_details();
+    }
+}
+
+/// Keeps the pairs of namespace and schema URIs in the `xsi:schemaLocation`
+/// `value` whose namespaces satisfy `keep`.
+fn retain_locations(value: &str, keep: impl Fn(&str) -> bool) -> String {
+    let uris: Vec<&str> = value.split_whitespace().collect();
+    uris.chunks(2)
+        .filter(|pair| keep(pair[0]))
+        .map(|pair| pair.join(" "))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::Point;
+
+    use super::Extension;
+    use crate::{
+        DisplayColor, Gpx, LineStyle, Route, Track, TrackPointExtension, TrackSegment, Waypoint,
+        WaypointExtension,
+    };
+
+    fn document() -> Gpx {
+        let mut waypoint = Waypoint::new(Point::new(1.0, 2.0));
+        waypoint.details_mut().waypoint_extension = Some(WaypointExtension {
+            depth: Some(3.0),
+            ..Default::default()
+        });
+        waypoint.details_mut().comment = Some("kept".to_string());
+        let mut route_point = Waypoint::new(Point::new(3.0, 4.0));
+        route_point.route_point_extension = Some(Default::default());
+        let mut route = Route::new();
+        route.points.push(route_point);
+        let mut track_point = Waypoint::new(Point::new(5.0, 6.0));
+        track_point.track_point_extension = Some(TrackPointExtension {
+            cadence: Some(90),
+            ..Default::default()
+        });
+        let mut track = Track::new();
+        track.display_color = Some(DisplayColor::Red);
+        track.line_style = Some(LineStyle {
+            width: Some(2.0),
+            ..Default::default()
+        });
+        track.segments.push(TrackSegment {
+            points: vec![track_point],
+        });
+        let attribute = |name: &str, value: &str| (name.to_string(), value.to_string());
+        Gpx {
+            waypoints: vec![waypoint],
+            routes: vec![route],
+            tracks: vec![track],
+            root_attributes: vec![
+                attribute("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance"),
+                attribute(
+                    "xmlns:gpxx",
+                    "http://www.garmin.com/xmlschemas/GpxExtensions/v3",
+                ),
+                attribute(
+                    "xmlns:gpxtpx",
+                    "http://www.garmin.com/xmlschemas/TrackPointExtension/v2",
+                ),
+                attribute(
+                    "xsi:schemaLocation",
+                    "http://www.topografix.com/GPX/1/1 http://www.topografix.com/GPX/1/1/gpx.xsd \
+                     http://www.garmin.com/xmlschemas/GpxExtensions/v3 \
+                     http://www.garmin.com/xmlschemas/GpxExtensionsv3.xsd \
+                     http://www.garmin.com/xmlschemas/TrackPointExtension/v2 \
+                     http://www.garmin.com/xmlschemas/TrackPointExtensionv2.xsd",
+                ),
+                attribute("acme:device", "Tracker 3000"),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn strip_one_extension() {
+        let mut gpx = document();
+        gpx.strip_extension(Extension::GpxExtensions);
+
+        assert_eq!(gpx.waypoints[0].details().waypoint_extension, None);
+        assert_eq!(gpx.waypoints[0].details().comment.as_deref(), Some("kept"));
+        assert!(!gpx.routes[0].points[0].has_details());
+        assert_eq!(gpx.tracks[0].display_color, None);
+        assert!(gpx.tracks[0].line_style.is_some());
+        let track_point = &gpx.tracks[0].segments[0].points[0];
+        assert!(track_point.track_point_extension.is_some());
+        let names: Vec<&str> = gpx
+            .root_attributes
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "xmlns:xsi",
+                "xmlns:gpxtpx",
+                "xsi:schemaLocation",
+                "acme:device"
+            ]
+        );
+        assert_eq!(
+            gpx.root_attributes[2].1,
+            "http://www.topografix.com/GPX/1/1 http://www.topografix.com/GPX/1/1/gpx.xsd \
+             http://www.garmin.com/xmlschemas/TrackPointExtension/v2 \
+             http://www.garmin.com/xmlschemas/TrackPointExtensionv2.xsd"
+        );
+    }
+
+    #[test]
+    fn strip_all_extensions() {
+        let mut gpx = document();
+        gpx.strip_extensions();
+
+        assert_eq!(
+            gpx.tracks[0].segments[0].points[0].track_point_extension,
+            None
+        );
+        assert_eq!(gpx.tracks[0].line_style, None);
+        assert_eq!(
+            gpx.root_attributes,
+            [
+                (
+                    "xmlns:xsi".to_string(),
+                    "http://www.w3.org/2001/XMLSchema-instance".to_string()
+                ),
+                (
+                    "xsi:schemaLocation".to_string(),
+                    "http://www.topografix.com/GPX/1/1 http://www.topografix.com/GPX/1/1/gpx.xsd"
+                        .to_string()
+                ),
+            ]
+        );
+
+        let mut gpx = document();
+        gpx.root_attributes.remove(3);
+        gpx.strip_extensions();
+        assert!(gpx.root_attributes.is_empty());
+    }
+}
@@ -0,0 +1,225 @@
+//! simplify reduces the number of points of tracks and routes while keeping
+//! the retained waypoints intact.
+
+use std::collections::BinaryHeap;
+
+use geo::SimplifyIdx;
+use geo_types::{CoordFloat, LineString, Point};
+
+use crate::measure::position;
+use crate::{Route, TrackSegment, Waypoint};
+
+impl<T: CoordFloat> TrackSegment<T> {
+    /// Gives a copy of the segment simplified with the Ramer–Douglas–Peucker
+    /// algorithm.
+    ///
+    /// Points closer than `epsilon` to the simplified line are dropped.
+    /// `epsilon` is measured in coordinate units, i.e. degrees. Unlike
+    /// [`geo::Simplify`], the retained points keep all of their data, like
+    /// time and elevation. The first and last points are always kept.
+    ///
+    /// ```
+    /// use geo_types::line_string;
+    /// use gpx::TrackSegment;
+    ///
+    /// let segment = TrackSegment::from(line_string![
+    ///     (x: 0.0, y: 0.0),
+    ///     (x: 1.0, y: 0.001),
+    ///     (x: 2.0, y: 0.0),
+    /// ]);
+    ///
+    /// assert_eq!(segment.simplified(0.01).points.len(), 2);
+    /// assert_eq!(segment.simplified(0.0001).points.len(), 3);
+    /// ```
+    pub fn simplified(&self, epsilon: f64) -> TrackSegment<T> {
+        TrackSegment {
+            points: simplify(&self.points, epsilon),
+        }
+    }
+
+    /// Gives a copy of the segment with at most `count` points, e.g. for
+    /// devices or services that only take tracks up to a number of points.
+    ///
+    /// Starting from the first and last points, the point farthest from the
+    /// line through the points kept so far is added until there are `count`
+    /// points, which keeps corners and spikes as well as possible. Like
+    /// [`simplified`](TrackSegment::simplified), the retained points keep
+    /// all of their data.
+    ///
+    /// ```
+    /// use geo_types::line_string;
+    /// use gpx::TrackSegment;
+    ///
+    /// let segment = TrackSegment::from(line_string![
+    ///     (x: 0.0, y: 0.0),
+    ///     (x: 1.0, y: 0.001),
+    ///     (x: 2.0, y: 1.0),
+    ///     (x: 3.0, y: 0.0),
+    /// ]);
+    ///
+    /// let decimated = segment.decimate_to(3);
+    /// assert_eq!(decimated.points.len(), 3);
+    /// assert_eq!(decimated.points[1], segment.points[2]);
+    /// ```
+    pub fn decimate_to(&self, count: usize) -> TrackSegment<T> {
+        TrackSegment {
+            points: decimate(&self.points, count),
+        }
+    }
+}
+
+impl<T: CoordFloat> Route<T> {
+    /// Gives a copy of the route with its points simplified, see
+    /// [`TrackSegment::simplified`].
+    pub fn simplified(&self, epsilon: f64) -> Route<T> {
+        self.with_points(simplify(&self.points, epsilon))
+    }
+
+    /// Gives a copy of the route with at most `count` points, see
+    /// [`TrackSegment::decimate_to`].
+    pub fn decimate_to(&self, count: usize) -> Route<T> {
+        self.with_points(decimate(&self.points, count))
+    }
+}
+
+fn simplify<T: CoordFloat>(points: &[Waypoint<T>], epsilon: f64) -> Vec<Waypoint<T>> {
+    let line: LineString<f64> = points.iter().map(|point| position(point).0).collect();
+    line.simplify_idx(&epsilon)
+        .into_iter()
+        .map(|index| points[index].clone())
+        .collect()
+}
+
+fn decimate<T: CoordFloat>(points: &[Waypoint<T>], count: usize) -> Vec<Waypoint<T>> {
+    if points.len() <= count {
+        return points.to_vec();
+    }
+    if count < 2 {
+        return points.iter().take(count).cloned().collect();
+    }
+
+    let mut kept = vec![false; points.len()];
+    kept[0] = true;
+    kept[points.len() - 1] = true;
+    // Distances aren't negative, so their bits order like the distances.
+    let mut spans = BinaryHeap::new();
+    spans.extend(farthest(points, 0, points.len() - 1));
+    for _ in 2..count {
+        let Some((_, index, start, end)) = spans.pop() else {
+            break;
+        };
+        kept[index] = true;
+        spans.extend(farthest(points, start, index));
+        spans.extend(farthest(points, index, end));
+    }
+    points
+        .iter()
+        .zip(kept)
+        .filter(|(_, kept)| *kept)
+        .map(|(point, _)| point.clone())
+        .collect()
+}
+
+/// Finds the point between `start` and `end` farthest from the line
+/// between them, giving the bits of its distance, its index, `start` and
+/// `end`.
+fn farthest<T: CoordFloat>(
+    points: &[Waypoint<T>],
+    start: usize,
+    end: usize,
+) -> Option<(u64, usize, usize, usize)> {
+    let (a, b) = (position(&points[start]), position(&points[end]));
+    (start + 1..end)
+        .map(|index| {
+            let distance = distance_to_line(position(&points[index]), a, b);
+            (distance.to_bits(), index, start, end)
+        })
+        .max()
+}
+
+fn distance_to_line(point: Point, a: Point, b: Point) -> f64 {
+    let (dx, dy) = (b.x() - a.x(), b.y() - a.y());
+    let length = dx.hypot(dy);
+    if length == 0.0 {
+        return (point.x() - a.x()).hypot(point.y() - a.y());
+    }
+    (dx * (point.y() - a.y()) - dy * (point.x() - a.x())).abs() / length
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::{line_string, LineString};
+
+    use crate::{Route, TrackSegment};
+
+    #[test]
+    fn simplified_keeps_waypoint_data() {
+        let mut segment = TrackSegment::from(line_string![
+            (x: 0.0, y: 0.0),
+            (x: 1.0, y: 0.26),
+            (x: 2.0, y: 0.5),
+            (x: 3.0, y: 0.0),
+        ]);
+        for (i, point) in segment.points.iter_mut().enumerate() {
+            point.details_mut().name = Some(i.to_string());
+            point.elevation = Some(i as f64);
+        }
+
+        let simplified = segment.simplified(0.1);
+        let names: Vec<_> = simplified
+            .points
+            .iter()
+            .map(|point| point.details().name.as_deref().unwrap())
+            .collect();
+        assert_eq!(names, ["0", "2", "3"]);
+        assert_eq!(simplified.points[1], segment.points[2]);
+
+        assert!(TrackSegment::<f64>::new().simplified(1.0).points.is_empty());
+    }
+
+    #[test]
+    fn route_simplified_keeps_metadata() {
+        let mut route = Route::from(line_string![
+            (x: 0.0, y: 0.0),
+            (x: 1.0, y: 0.0),
+            (x: 2.0, y: 0.0),
+        ]);
+        route.name = Some("route".into());
+
+        let simplified = route.simplified(0.1);
+        assert_eq!(simplified.points.len(), 2);
+        assert_eq!(simplified.name, route.name);
+    }
+
+    #[test]
+    fn decimate_to_keeps_shape() {
+        let segment = TrackSegment::from(
+            (0..100)
+                .map(|x| {
+                    let y = if x == 50 {
+                        10.0
+                    } else {
+                        (x as f64 * 0.1).sin() * 0.01
+                    };
+                    (x as f64, y)
+                })
+                .collect::<LineString>(),
+        );
+
+        for count in [2, 3, 10, 99] {
+            let decimated = segment.decimate_to(count);
+            assert_eq!(decimated.points.len(), count);
+            assert_eq!(decimated.points[0], segment.points[0]);
+            assert_eq!(decimated.points[count - 1], segment.points[99]);
+            if count > 2 {
+                assert!(decimated.points.contains(&segment.points[50]));
+            }
+        }
+        assert_eq!(segment.decimate_to(100).points, segment.points);
+        assert_eq!(segment.decimate_to(1).points, [segment.points[0].clone()]);
+        assert!(segment.decimate_to(0).points.is_empty());
+
+        let route = Route::from(segment.linestring());
+        assert_eq!(route.decimate_to(5).points.len(), 5);
+    }
+}
@@ -0,0 +1,284 @@
+//! smooth reduces the jitter of recorded positions, which phones in
+//! particular record a lot of.
+
+use geo_types::CoordFloat;
+
+use crate::geometry::EARTH_RADIUS;
+use crate::measure::{elevation, from_f64, position};
+use crate::{Time, Track, TrackSegment, Waypoint};
+
+/// A way to smooth the points of a track segment, see
+/// [`TrackSegment::smooth`].
+pub trait Smoother<T: CoordFloat = f64> {
+    /// Smoothes the positions and elevations of `points` in place.
+    fn smooth(&self, points: &mut [Waypoint<T>]);
+}
+
+/// Smoothes tracks with a Kalman filter assuming constant velocity,
+/// followed by a Rauch–Tung–Striebel smoother, so that the smoothed track
+/// doesn't lag behind the recorded one.
+///
+/// Easting, northing and elevation are filtered separately. The time
+/// between points is taken from their times; points without a time are
+/// taken as recorded at the same time as the point before.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KalmanSmoother {
+    /// Standard deviation of the recorded horizontal positions, in meters.
+    pub position_noise: f64,
+
+    /// Standard deviation of the recorded elevations, in meters.
+    pub elevation_noise: f64,
+
+    /// Standard deviation of the accelerations, in meters per second
+    /// squared. Smaller values give smoother tracks, larger ones follow
+    /// quick turns and stops more closely.
+    pub acceleration_noise: f64,
+}
+
+impl Default for KalmanSmoother {
+    /// Suits phones recording walks, runs or rides, with 5 m horizontal and
+    /// 10 m vertical noise and accelerations of about 1 m/s².
+    fn default() -> KalmanSmoother {
+        KalmanSmoother {
+            position_noise: 5.0,
+            elevation_noise: 10.0,
+            acceleration_noise: 1.0,
+        }
+    }
+}
+
+impl<T: CoordFloat> Smoother<T> for KalmanSmoother {
+    fn smooth(&self, points: &mut [Waypoint<T>]) {
+        let Some(origin) = points.first().map(position) else {
+            return;
+        };
+        let mut start: Option<Time> = None;
+        let mut seconds = 0.0;
+        let times: Vec<f64> = points
+            .iter()
+            .map(|point| {
+                if let Some(time) = point.time {
+                    let start = *start.get_or_insert(time);
+                    seconds = (time - start).as_seconds_f64();
+                }
+                seconds
+            })
+            .collect();
+
+        let scale = EARTH_RADIUS.to_radians();
+        let x_scale = scale * origin.y().to_radians().cos();
+        let eastings: Vec<(f64, f64)> = points
+            .iter()
+            .zip(&times)
+            .map(|(point, &time)| (time, (position(point).x() - origin.x()) * x_scale))
+            .collect();
+        let northings: Vec<(f64, f64)> = points
+            .iter()
+            .zip(&times)
+            .map(|(point, &time)| (time, (position(point).y() - origin.y()) * scale))
+            .collect();
+        let eastings = filter(&eastings, self.position_noise, self.acceleration_noise);
+        let northings = filter(&northings, self.position_noise, self.acceleration_noise);
+        for ((point, easting), northing) in points.iter_mut().zip(eastings).zip(northings) {
+            point.set_point(geo_types::Point::new(
+                from_f64(origin.x() + easting / x_scale),
+                from_f64(origin.y() + northing / scale),
+            ));
+        }
+
+        let elevations: Vec<(f64, f64)> = points
+            .iter()
+            .zip(&times)
+            .filter_map(|(point, &time)| Some((time, elevation(point)?)))
+            .collect();
+        let mut elevations =
+            filter(&elevations, self.elevation_noise, self.acceleration_noise).into_iter();
+        for point in points.iter_mut().filter(|point| point.elevation.is_some()) {
+            point.elevation = elevations.next().map(from_f64);
+        }
+    }
+}
+
+impl<T: CoordFloat> Track<T> {
+    /// Smoothes all segments of the track, see [`TrackSegment::smooth`].
+    pub fn smooth(&mut self, smoother: &dyn Smoother<T>) {
+        for segment in &mut self.segments {
+            segment.smooth(smoother);
+        }
+    }
+}
+
+impl<T: CoordFloat> TrackSegment<T> {
+    /// Smoothes the positions and elevations of the points with
+    /// `smoother`, like a [`KalmanSmoother`], removing the jitter of GPS
+    /// receivers. All other data of the points is kept.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Duration, KalmanSmoother, Time, TrackSegment, Waypoint};
+    ///
+    /// let mut segment = TrackSegment::new();
+    /// // Walking east along the equator with the position jumping around.
+    /// for (seconds, latitude) in [(0, 0.0), (1, 0.0001), (2, -0.0001), (3, 0.0001), (4, 0.0)] {
+    ///     let mut point = Waypoint::new(Point::new(seconds as f64 * 0.00001, latitude));
+    ///     point.time = Some(Time::UNIX_EPOCH + Duration::seconds(seconds));
+    ///     segment.points.push(point);
+    /// }
+    ///
+    /// let jitter = |segment: &TrackSegment| {
+    ///     let latitudes = segment.points.iter().map(|point| point.point().y());
+    ///     latitudes.fold(0.0, |jitter: f64, latitude| jitter.max(latitude.abs()))
+    /// };
+    /// let before = jitter(&segment);
+    /// segment.smooth(&KalmanSmoother::default());
+    /// assert!(jitter(&segment) < before / 2.0);
+    /// ```
+    pub fn smooth(&mut self, smoother: &dyn Smoother<T>) {
+        smoother.smooth(&mut self.points);
+    }
+}
+
+/// A 2×2 matrix, in rows.
+type Matrix = [[f64; 2]; 2];
+
+/// Filters the `(time, value)` measurements of one coordinate, giving the
+/// smoothed values.
+fn filter(measurements: &[(f64, f64)], noise: f64, acceleration_noise: f64) -> Vec<f64> {
+    let Some(&(mut previous_time, first)) = measurements.first() else {
+        return Vec::new();
+    };
+    let variance = noise * noise;
+    let acceleration_variance = acceleration_noise * acceleration_noise;
+
+    // The state is the value and its rate of change, starting at rest with
+    // the velocity unknown.
+    let mut state = [first, 0.0];
+    let mut covariance: Matrix = [[variance, 0.0], [0.0, 1e4]];
+    let mut filtered = vec![(state, covariance)];
+    let mut predicted = vec![(state, covariance)];
+    for &(time, value) in &measurements[1..] {
+        let dt = time - previous_time;
+        previous_time = time;
+
+        let predicted_state = [state[0] + dt * state[1], state[1]];
+        let [[p00, p01], [p10, p11]] = covariance;
+        let q = acceleration_variance;
+        let predicted_covariance = [
+            [
+                p00 + dt * (p01 + p10) + dt * dt * p11 + q * dt.powi(4) / 4.0,
+                p01 + dt * p11 + q * dt.powi(3) / 2.0,
+            ],
+            [p10 + dt * p11 + q * dt.powi(3) / 2.0, p11 + q * dt * dt],
+        ];
+        predicted.push((predicted_state, predicted_covariance));
+
+        let [[p00, p01], [p10, p11]] = predicted_covariance;
+        let gain = [p00 / (p00 + variance), p10 / (p00 + variance)];
+        let residual = value - predicted_state[0];
+        state = [
+            predicted_state[0] + gain[0] * residual,
+            predicted_state[1] + gain[1] * residual,
+        ];
+        covariance = [
+            [(1.0 - gain[0]) * p00, (1.0 - gain[0]) * p01],
+            [p10 - gain[1] * p00, p11 - gain[1] * p01],
+        ];
+        filtered.push((state, covariance));
+    }
+
+    // Rauch–Tung–Striebel: correct each state with the smoothed state after
+    // it, going backwards.
+    let mut smoothed = vec![0.0; measurements.len()];
+    let mut next = state;
+    smoothed[measurements.len() - 1] = next[0];
+    for index in (0..measurements.len() - 1).rev() {
+        let (state, [[p00, p01], [p10, p11]]) = filtered[index];
+        let (predicted_state, predicted_covariance) = predicted[index + 1];
+        let dt = measurements[index + 1].0 - measurements[index].0;
+        // The covariance of this state with the next one, P Fᵀ.
+        let cross: Matrix = [[p00 + dt * p01, p01], [p10 + dt * p11, p11]];
+        let Some(inverse) = invert(predicted_covariance) else {
+            smoothed[index] = state[0];
+            next = state;
+            continue;
+        };
+        let gain = multiply(cross, inverse);
+        let difference = [next[0] - predicted_state[0], next[1] - predicted_state[1]];
+        next = [
+            state[0] + gain[0][0] * difference[0] + gain[0][1] * difference[1],
+            state[1] + gain[1][0] * difference[0] + gain[1][1] * difference[1],
+        ];
+        smoothed[index] = next[0];
+    }
+    smoothed
+}
+
+fn multiply(a: Matrix, b: Matrix) -> Matrix {
+    [
+        [
+            a[0][0] * b[0][0] + a[0][1] * b[1][0],
+            a[0][0] * b[0][1] + a[0][1] * b[1][1],
+        ],
+        [
+            a[1][0] * b[0][0] + a[1][1] * b[1][0],
+            a[1][0] * b[0][1] + a[1][1] * b[1][1],
+        ],
+    ]
+}
+
+fn invert([[a, b], [c, d]]: Matrix) -> Option<Matrix> {
+    let determinant = a * d - b * c;
+    if determinant.abs() < f64::MIN_POSITIVE {
+        return None;
+    }
+    Some([
+        [d / determinant, -b / determinant],
+        [-c / determinant, a / determinant],
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::Point;
+
+    use super::KalmanSmoother;
+    use crate::{Duration, Time, Track, TrackSegment, Waypoint};
+
+    #[test]
+    fn kalman_smoother_reduces_noise() {
+        // Walking north at 1.5 m/s with up to 8 m of noise across the way
+        // and on the elevation.
+        let noise = |index: usize| [0.0, 8.0, -5.0, 3.0, -8.0, 6.0, -2.0][index % 7];
+        let meters = 1.0 / 111_195.0;
+        let mut segment = TrackSegment::new();
+        for index in 0..60 {
+            let mut point = Waypoint::new(Point::new(
+                noise(index) * meters,
+                index as f64 * 1.5 * meters,
+            ));
+            point.time = Some(Time::UNIX_EPOCH + Duration::seconds(index as i64));
+            point.elevation = (index != 10).then(|| 100.0 + noise(index + 3));
+            point.details_mut().name = Some(index.to_string());
+            segment.points.push(point);
+        }
+        let mut track = Track::new();
+        track.segments.push(segment.clone());
+        track.segments.push(TrackSegment::new());
+
+        track.smooth(&KalmanSmoother::default());
+        let smoothed = &track.segments[0].points;
+        assert_eq!(smoothed.len(), 60);
+        let error = |points: &[Waypoint], of: &dyn Fn(&Waypoint) -> f64| {
+            points.iter().map(|point| of(point).abs()).sum::<f64>() / points.len() as f64
+        };
+        let across = |point: &Waypoint| point.point().x() / meters;
+        assert!(error(smoothed, &across) < error(&segment.points, &across) / 3.0);
+        let elevation =
+            |point: &Waypoint| point.elevation.map_or(0.0, |elevation| elevation - 100.0);
+        assert!(error(smoothed, &elevation) < error(&segment.points, &elevation) / 3.0);
+        assert_eq!(smoothed[10].elevation, None);
+        assert_eq!(smoothed[59].details().name.as_deref(), Some("59"));
+        let along = smoothed[59].point().y() / meters;
+        assert!((along - 59.0 * 1.5).abs() < 5.0, "{along}");
+    }
+}
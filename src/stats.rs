@@ -0,0 +1,302 @@
+//! stats computes the usual statistics of a recorded activity, like distance,
+//! moving time, speeds and elevation, in a single pass over the points.
+
+use geo_types::{CoordFloat, Rect};
+
+use crate::measure::{
+    distance, elapsed, elevation, extend_bounds, grades, position, ElevationCounter,
+};
+use crate::{Duration, ElevationChange, Gpx, Route, Time, Track, Waypoint};
+
+/// Options used when computing [`TrackStats`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StatsOptions {
+    /// Minimum speed, in meters per second, for an interval between two
+    /// points to count as moving.
+    pub min_moving_speed: f64,
+
+    /// Elevation changes smaller than this, in meters, are ignored when
+    /// computing the ascent and descent, see
+    /// [`TrackSegment::elevation_gain_loss`](crate::TrackSegment::elevation_gain_loss).
+    pub elevation_threshold: f64,
+
+    /// Distance in meters over which grades are smoothed, see
+    /// [`TrackSegment::grades`](crate::TrackSegment::grades).
+    pub grade_window: f64,
+}
+
+impl Default for StatsOptions {
+    /// Counts speeds of at least 0.5 m/s as moving, ignores elevation
+    /// changes below 2 m and smoothes grades over 50 m.
+    fn default() -> StatsOptions {
+        StatsOptions {
+            min_moving_speed: 0.5,
+            elevation_threshold: 2.0,
+            grade_window: 50.0,
+        }
+    }
+}
+
+/// Statistics of a track, route or whole document.
+///
+/// Gaps between track segments are not counted towards the distance, the
+/// speeds or the elevation change, but they are part of the duration.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TrackStats {
+    /// Length of the path, in meters.
+    pub distance: f64,
+
+    /// Time elapsed between the first and the last timestamped point.
+    pub duration: Option<Duration>,
+
+    /// Time spent moving at least [`StatsOptions::min_moving_speed`].
+    pub moving_time: Option<Duration>,
+
+    /// Lowest speed while moving, in meters per second.
+    pub min_speed: Option<f64>,
+
+    /// Highest speed between two consecutive timestamped points, in meters
+    /// per second.
+    pub max_speed: Option<f64>,
+
+    /// Average speed while moving, in meters per second: the distance divided
+    /// by the moving time.
+    pub avg_speed: Option<f64>,
+
+    /// Cumulative ascent and descent.
+    pub elevation: ElevationChange,
+
+    /// Lowest elevation of any point, in meters.
+    pub min_elevation: Option<f64>,
+
+    /// Highest elevation of any point, in meters.
+    pub max_elevation: Option<f64>,
+
+    /// Steepest uphill grade, in percent.
+    pub max_grade: Option<f64>,
+
+    /// Average grade, in percent, weighted by the distance over which each
+    /// grade was measured.
+    pub avg_grade: Option<f64>,
+
+    /// Bounding box of all points.
+    pub bounds: Option<Rect<f64>>,
+
+    /// Timestamp of the first timestamped point.
+    pub start_time: Option<Time>,
+
+    /// Timestamp of the last timestamped point.
+    pub end_time: Option<Time>,
+}
+
+impl TrackStats {
+    /// Computes the statistics of a track.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::stats::{StatsOptions, TrackStats};
+    /// use gpx::{Duration, Time, Track, TrackSegment, Waypoint};
+    ///
+    /// let mut segment = TrackSegment::new();
+    /// for (latitude, seconds) in [(0.0, 0), (0.001, 10), (0.001, 70)] {
+    ///     let mut point = Waypoint::new(Point::new(0.0, latitude));
+    ///     point.time = Some(Time::UNIX_EPOCH + Duration::seconds(seconds));
+    ///     segment.points.push(point);
+    /// }
+    /// let mut track = Track::new();
+    /// track.segments.push(segment);
+    ///
+    /// let stats = TrackStats::from_track(&track, &StatsOptions::default());
+    /// assert_eq!(stats.duration, Some(Duration::seconds(70)));
+    /// assert_eq!(stats.moving_time, Some(Duration::seconds(10)));
+    /// assert!((stats.avg_speed.unwrap() - 11.06).abs() < 0.01);
+    /// ```
+    pub fn from_track<T: CoordFloat>(track: &Track<T>, options: &StatsOptions) -> TrackStats {
+        let mut accumulator = Accumulator::new(options);
+        accumulator.add_track(track);
+        accumulator.finish()
+    }
+
+    /// Computes the statistics of a route.
+    pub fn from_route<T: CoordFloat>(route: &Route<T>, options: &StatsOptions) -> TrackStats {
+        let mut accumulator = Accumulator::new(options);
+        accumulator.add_path(&route.points);
+        accumulator.finish()
+    }
+
+    /// Computes the statistics of all tracks of a document, as if they were
+    /// a single track. Routes and waypoints are not included.
+    pub fn from_gpx<T: CoordFloat>(gpx: &Gpx<T>, options: &StatsOptions) -> TrackStats {
+        let mut accumulator = Accumulator::new(options);
+        for track in &gpx.tracks {
+            accumulator.add_track(track);
+        }
+        accumulator.finish()
+    }
+}
+
+struct Accumulator<'a> {
+    options: &'a StatsOptions,
+    stats: TrackStats,
+    grade_sum: f64,
+    grade_distance: f64,
+}
+
+impl<'a> Accumulator<'a> {
+    fn new(options: &'a StatsOptions) -> Accumulator<'a> {
+        Accumulator {
+            options,
+            stats: Default::default(),
+            grade_sum: 0.0,
+            grade_distance: 0.0,
+        }
+    }
+
+    fn add_track<T: CoordFloat>(&mut self, track: &Track<T>) {
+        for segment in &track.segments {
+            self.add_path(&segment.points);
+        }
+    }
+
+    fn add_path<T: CoordFloat>(&mut self, points: &[Waypoint<T>]) {
+        let stats = &mut self.stats;
+        let mut elevation_change = ElevationCounter::new(self.options.elevation_threshold);
+        let mut previous: Option<&Waypoint<T>> = None;
+        let mut previous_timed: Option<(&Waypoint<T>, Time)> = None;
+
+        let grades = grades(points, self.options.grade_window);
+        for (point, grade) in points.iter().zip(grades) {
+            if let Some(previous) = previous {
+                let step = distance(previous, point);
+                stats.distance += step;
+                if let Some(grade) = grade {
+                    stats.max_grade = Some(stats.max_grade.map_or(grade, |max| max.max(grade)));
+                    self.grade_sum += grade * step;
+                    self.grade_distance += step;
+                }
+            }
+            previous = Some(point);
+
+            stats.bounds = Some(extend_bounds(stats.bounds, position(point)));
+            if let Some(value) = elevation(point) {
+                elevation_change.add(value);
+                stats.min_elevation = Some(stats.min_elevation.map_or(value, |min| min.min(value)));
+                stats.max_elevation = Some(stats.max_elevation.map_or(value, |max| max.max(value)));
+            }
+
+            let Some(time) = point.time else {
+                continue;
+            };
+            stats.start_time.get_or_insert(time);
+            stats.end_time = Some(time);
+            let moving_time = stats.moving_time.get_or_insert(Duration::ZERO);
+            if let Some((previous, previous_time)) = previous_timed {
+                let interval = elapsed(previous_time, time);
+                let seconds = interval.as_seconds_f64();
+                if seconds > 0.0 {
+                    let speed = distance(previous, point) / seconds;
+                    stats.max_speed = Some(stats.max_speed.map_or(speed, |max| max.max(speed)));
+                    if speed >= self.options.min_moving_speed {
+                        *moving_time += interval;
+                        stats.min_speed = Some(stats.min_speed.map_or(speed, |min| min.min(speed)));
+                    }
+                }
+            }
+            previous_timed = Some((point, time));
+        }
+
+        stats.elevation = stats.elevation + elevation_change.change;
+    }
+
+    fn finish(self) -> TrackStats {
+        let mut stats = self.stats;
+        if self.grade_distance > 0.0 {
+            stats.avg_grade = Some(self.grade_sum / self.grade_distance);
+        }
+        if let (Some(start), Some(end)) = (stats.start_time, stats.end_time) {
+            stats.duration = Some(elapsed(start, end));
+        }
+        stats.avg_speed = stats
+            .moving_time
+            .map(|moving_time| moving_time.as_seconds_f64())
+            .filter(|&seconds| seconds > 0.0)
+            .map(|seconds| stats.distance / seconds);
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::Point;
+
+    use super::{StatsOptions, TrackStats};
+    use crate::{Duration, Gpx, Route, Time, Track, TrackSegment, Waypoint};
+
+    fn point(latitude: f64, elevation: f64, seconds: i64) -> Waypoint {
+        let mut point = Waypoint::new(Point::new(0.0, latitude));
+        point.elevation = Some(elevation);
+        point.time = Some(Time::UNIX_EPOCH + Duration::seconds(seconds));
+        point
+    }
+
+    #[test]
+    fn track_stats_over_segments() {
+        let mut track = Track::new();
+        track.segments.push(TrackSegment {
+            points: vec![
+                point(0.0, 100.0, 0),
+                point(0.001, 110.0, 10),
+                point(0.001, 110.0, 70),
+            ],
+        });
+        track.segments.push(TrackSegment {
+            points: vec![point(1.0, 90.0, 100), point(1.002, 95.0, 110)],
+        });
+
+        let stats = TrackStats::from_track(&track, &StatsOptions::default());
+        assert!((stats.distance - 331.7).abs() < 0.1);
+        assert_eq!(stats.duration, Some(Duration::seconds(110)));
+        assert_eq!(stats.moving_time, Some(Duration::seconds(20)));
+        assert!((stats.min_speed.unwrap() - 11.06).abs() < 0.01);
+        assert!((stats.max_speed.unwrap() - 22.11).abs() < 0.01);
+        assert!((stats.avg_speed.unwrap() - 16.59).abs() < 0.01);
+        assert_eq!(stats.elevation.ascent, 15.0);
+        assert_eq!(stats.elevation.descent, 0.0);
+        assert_eq!(stats.min_elevation, Some(90.0));
+        assert_eq!(stats.max_elevation, Some(110.0));
+        assert!((stats.max_grade.unwrap() - 9.04).abs() < 0.01);
+        assert!((stats.avg_grade.unwrap() - 4.52).abs() < 0.01);
+        assert_eq!(stats.bounds.unwrap().max().y, 1.002);
+        assert_eq!(stats.start_time, track.segments[0].points[0].time);
+        assert_eq!(stats.end_time, track.segments[1].points[1].time);
+
+        let gpx = Gpx {
+            tracks: vec![track.clone(), track],
+            ..Default::default()
+        };
+        let stats = TrackStats::from_gpx(&gpx, &StatsOptions::default());
+        assert!((stats.distance - 2.0 * 331.7).abs() < 0.1);
+        assert_eq!(stats.moving_time, Some(Duration::seconds(40)));
+    }
+
+    #[test]
+    fn route_stats_without_times() {
+        let route = Route {
+            points: vec![
+                Waypoint::new(Point::new(0.0, 0.0)),
+                Waypoint::new(Point::new(1.0, 0.0)),
+            ],
+            ..Default::default()
+        };
+
+        let stats = TrackStats::from_route(&route, &StatsOptions::default());
+        assert!((stats.distance - 111_319.5).abs() < 0.1);
+        assert_eq!(stats.duration, None);
+        assert_eq!(stats.moving_time, None);
+        assert_eq!(stats.avg_speed, None);
+        assert_eq!(stats.min_elevation, None);
+
+        let stats = TrackStats::from_track(&Track::<f64>::new(), &StatsOptions::default());
+        assert_eq!(stats, TrackStats::default());
+    }
+}
@@ -0,0 +1,87 @@
+//! strategies generates valid documents for property tests with
+//! [`proptest`](https://docs.rs/proptest), like checking that reading what
+//! was written gives the same document:
+//!
+//! ```
+//! use gpx::strategies::gpx;
+//! use proptest::prelude::*;
+//!
+//! proptest!(|(document in gpx())| {
+//!     let written = gpx::write_to_vec(&document).unwrap();
+//!     prop_assert_eq!(gpx::read_from_slice(&written).unwrap(), document);
+//! });
+//! ```
+//!
+//! Everything generated survives writing and reading unchanged: positions
+//! are within range, text is alphanumeric and times are in UTC.
+
+use geo_types::Point;
+use proptest::collection::vec;
+use proptest::option;
+use proptest::prelude::*;
+use time::OffsetDateTime;
+
+use crate::{Gpx, GpxVersion, Time, Track, TrackSegment, Waypoint};
+
+/// Generates a short alphanumeric text.
+fn text() -> impl Strategy<Value = String> {
+    "[A-Za-z0-9]{1,16}"
+}
+
+/// Generates times between the years 1970 and 2100 in UTC, with
+/// millisecond precision.
+pub fn time() -> impl Strategy<Value = Time> {
+    (0..4_102_444_800_000_i64).prop_map(|milliseconds| {
+        let nanoseconds = i128::from(milliseconds) * 1_000_000;
+        Time::from(OffsetDateTime::from_unix_timestamp_nanos(nanoseconds).unwrap())
+    })
+}
+
+/// Generates waypoints with a valid position, and maybe an elevation, a
+/// time and a name.
+pub fn waypoint() -> impl Strategy<Value = Waypoint> {
+    (
+        -90.0..=90.0,
+        -180.0..180.0,
+        option::of(-500.0..9000.0),
+        option::of(time()),
+        option::of(text()),
+    )
+        .prop_map(|(latitude, longitude, elevation, time, name)| {
+            let mut waypoint = Waypoint::new(Point::new(longitude, latitude));
+            waypoint.elevation = elevation;
+            waypoint.time = time;
+            if name.is_some() {
+                waypoint.details_mut().name = name;
+            }
+            waypoint
+        })
+}
+
+/// Generates track segments of up to 50 points.
+pub fn track_segment() -> impl Strategy<Value = TrackSegment> {
+    vec(waypoint(), 0..50).prop_map(|points| TrackSegment { points })
+}
+
+/// Generates tracks of up to 3 segments, maybe with a name.
+pub fn track() -> impl Strategy<Value = Track> {
+    (option::of(text()), vec(track_segment(), 0..3)).prop_map(|(name, segments)| {
+        let mut track = Track::new();
+        track.name = name;
+        track.segments = segments;
+        track
+    })
+}
+
+/// Generates GPX 1.1 documents with up to 5 waypoints and 3 tracks.
+pub fn gpx() -> impl Strategy<Value = Gpx> {
+    (text(), vec(waypoint(), 0..5), vec(track(), 0..3)).prop_map(|(creator, waypoints, tracks)| {
+        Gpx {
+            version: GpxVersion::Gpx11,
+            creator: Some(creator),
+            waypoints,
+            tracks,
+            ..Default::default()
+        }
+    })
+}
@@ -0,0 +1,192 @@
+//! summary gives a short, human-readable description of a GPX document.
+
+use std::fmt;
+
+use geo_types::{CoordFloat, Rect};
+
+use crate::measure::{extend_bounds, position};
+use crate::{Gpx, Time, Waypoint};
+
+/// Summary describes a GPX document at a glance, see [`Gpx::summary`].
+///
+/// Its `Display` implementation renders the summary as a few lines of text,
+/// suitable for command line tools and logs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Summary {
+    /// Number of top-level waypoints.
+    pub waypoints: usize,
+
+    /// Number of tracks.
+    pub tracks: usize,
+
+    /// Number of segments over all tracks.
+    pub segments: usize,
+
+    /// Number of track points over all tracks.
+    pub track_points: usize,
+
+    /// Number of routes.
+    pub routes: usize,
+
+    /// Number of route points over all routes.
+    pub route_points: usize,
+
+    /// Length of all tracks, in meters.
+    pub distance: f64,
+
+    /// Earliest timestamp of any point in the document.
+    pub start_time: Option<Time>,
+
+    /// Latest timestamp of any point in the document.
+    pub end_time: Option<Time>,
+
+    /// Bounding box of all points in the document.
+    pub bounds: Option<Rect<f64>>,
+}
+
+impl<T: CoordFloat> Gpx<T> {
+    /// Summarizes the document: how many points it holds, how long its
+    /// tracks are, and where and when it was recorded.
+    ///
+    /// ```
+    /// use std::fs::File;
+    /// use std::io::BufReader;
+    ///
+    /// let file = File::open("tests/fixtures/wikipedia_example.gpx").unwrap();
+    /// let gpx = gpx::read(BufReader::new(file)).unwrap();
+    ///
+    /// let summary = gpx.summary();
+    /// assert_eq!(summary.tracks, 1);
+    /// assert_eq!(summary.track_points, 3);
+    /// println!("{}", summary);
+    /// ```
+    pub fn summary(&self) -> Summary {
+        let segments = self.tracks.iter().flat_map(|track| &track.segments);
+        let track_points = segments.clone().flat_map(|segment| &segment.points);
+        let route_points = self.routes.iter().flat_map(|route| &route.points);
+        let all_points = self
+            .waypoints
+            .iter()
+            .chain(track_points.clone())
+            .chain(route_points.clone());
+
+        let mut summary = Summary {
+            waypoints: self.waypoints.len(),
+            tracks: self.tracks.len(),
+            segments: segments.clone().count(),
+            track_points: track_points.count(),
+            routes: self.routes.len(),
+            route_points: route_points.count(),
+            distance: segments.fold(0.0, |distance, segment| distance + segment.length_m()),
+            start_time: None,
+            end_time: None,
+            bounds: None,
+        };
+        for point in all_points {
+            summary.add_point(point);
+        }
+        summary
+    }
+}
+
+impl Summary {
+    fn add_point<T: CoordFloat>(&mut self, waypoint: &Waypoint<T>) {
+        if let Some(time) = waypoint.time {
+            self.start_time = Some(self.start_time.map_or(time, |start| start.min(time)));
+            self.end_time = Some(self.end_time.map_or(time, |end| end.max(time)));
+        }
+        self.bounds = Some(extend_bounds(self.bounds, position(waypoint)));
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} waypoints, {} tracks ({} segments, {} points), {} routes ({} points)",
+            self.waypoints,
+            self.tracks,
+            self.segments,
+            self.track_points,
+            self.routes,
+            self.route_points
+        )?;
+        write!(f, "distance: {:.3} km", self.distance / 1000.0)?;
+        if let (Some(start), Some(end)) = (self.start_time, self.end_time) {
+            let elapsed = end - start;
+            let seconds = elapsed.whole_seconds();
+            write!(
+                f,
+                "\ntime: {} to {} ({}:{:02}:{:02})",
+                start.format().map_err(|_| fmt::Error)?,
+                end.format().map_err(|_| fmt::Error)?,
+                seconds / 3600,
+                seconds % 3600 / 60,
+                seconds % 60
+            )?;
+        }
+        if let Some(bounds) = self.bounds {
+            write!(
+                f,
+                "\nbounds: lat {} to {}, lon {} to {}",
+                bounds.min().y,
+                bounds.max().y,
+                bounds.min().x,
+                bounds.max().x
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::Point;
+
+    use crate::{Duration, Gpx, Time, Track, TrackSegment, Waypoint};
+
+    #[test]
+    fn summary_counts_and_spans() {
+        let start = Time::UNIX_EPOCH;
+        let mut first = Waypoint::new(Point::new(0.0, 0.0));
+        first.time = Some(start + Duration::seconds(3725));
+        let mut second = Waypoint::new(Point::new(0.0, 1.0));
+        second.time = Some(start);
+
+        let mut track = Track::new();
+        track.segments.push(TrackSegment {
+            points: vec![first, second],
+        });
+        let gpx = Gpx {
+            waypoints: vec![Waypoint::new(Point::new(2.0, -1.0))],
+            tracks: vec![track],
+            ..Default::default()
+        };
+
+        let summary = gpx.summary();
+        assert_eq!(summary.waypoints, 1);
+        assert_eq!(summary.segments, 1);
+        assert_eq!(summary.track_points, 2);
+        assert!((summary.distance - 110_574.4).abs() < 0.1);
+        assert_eq!(summary.start_time, Some(start));
+        assert_eq!(summary.bounds.unwrap().min().y, -1.0);
+        assert_eq!(summary.bounds.unwrap().max().x, 2.0);
+
+        let text = summary.to_string();
+        assert!(text.contains("1 waypoints, 1 tracks (1 segments, 2 points)"));
+        assert!(text.contains("(1:02:05)"));
+    }
+
+    #[test]
+    fn summary_of_empty_document() {
+        let summary = Gpx::<f64>::default().summary();
+
+        assert_eq!(summary.track_points, 0);
+        assert_eq!(summary.distance, 0.0);
+        assert!(summary.bounds.is_none());
+        assert_eq!(
+            summary.to_string(),
+            "0 waypoints, 0 tracks (0 segments, 0 points), 0 routes (0 points)\ndistance: 0.000 km"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! timezone derives a rough local time offset of points from their
+//! longitude, for when their IANA timezone isn't known.
+
+use geo_types::CoordFloat;
+
+use crate::measure::position;
+use crate::{Track, UtcOffset, Waypoint};
+
+impl<T: CoordFloat> Waypoint<T> {
+    /// Approximates the time offset at the waypoint's longitude with the
+    /// nautical time zones: whole hours from UTC, in 15° wide bands centered
+    /// on multiples of 15°.
+    ///
+    /// This is no timezone lookup. It ignores political borders and daylight
+    /// saving time, so on land it is often off by an hour or more. Prefer
+    /// [`Time::to_timezone`](crate::Time::to_timezone) whenever the IANA
+    /// timezone is known.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Time, UtcOffset, Waypoint};
+    ///
+    /// let mut waypoint = Waypoint::new(Point::new(-122.42, 37.77));
+    /// // 2023-01-15T20:00:00Z
+    /// waypoint.time = Some(Time::from_unix_seconds(1_673_812_800).unwrap());
+    ///
+    /// let offset = waypoint.approximate_offset_from_longitude();
+    /// assert_eq!(offset, UtcOffset::from_hms(-8, 0, 0).unwrap());
+    /// assert_eq!(waypoint.time.unwrap().to_offset(offset).hour(), 12);
+    /// ```
+    pub fn approximate_offset_from_longitude(&self) -> UtcOffset {
+        let hours = (position(self).x() / 15.0).round().clamp(-12.0, 12.0);
+        UtcOffset::from_hms(hours as i8, 0, 0).unwrap_or(UtcOffset::UTC)
+    }
+}
+
+impl<T: CoordFloat> Track<T> {
+    /// Approximates the time offset where the track starts, see
+    /// [`Waypoint::approximate_offset_from_longitude`].
+    pub fn approximate_offset_from_longitude(&self) -> Option<UtcOffset> {
+        let first = self
+            .segments
+            .iter()
+            .flat_map(|segment| &segment.points)
+            .next()?;
+        Some(first.approximate_offset_from_longitude())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::Point;
+
+    use crate::{Track, TrackSegment, UtcOffset, Waypoint};
+
+    #[test]
+    fn approximate_offsets_from_longitude() {
+        let offset_at = |longitude| {
+            let waypoint = Waypoint::new(Point::new(longitude, 0.0));
+            waypoint.approximate_offset_from_longitude().whole_hours()
+        };
+        assert_eq!(offset_at(0.0), 0);
+        assert_eq!(offset_at(7.4), 0);
+        assert_eq!(offset_at(7.6), 1);
+        assert_eq!(offset_at(13.4), 1);
+        assert_eq!(offset_at(-179.9), -12);
+        assert_eq!(offset_at(180.0), 12);
+
+        let mut track = Track::new();
+        assert_eq!(track.approximate_offset_from_longitude(), None);
+        track.segments.push(TrackSegment::new());
+        track.segments.push(TrackSegment {
+            points: vec![Waypoint::new(Point::new(139.7, 35.7))],
+        });
+        let offset = track.approximate_offset_from_longitude();
+        assert_eq!(offset, Some(UtcOffset::from_hms(9, 0, 0).unwrap()));
+    }
+}
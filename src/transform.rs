@@ -0,0 +1,1059 @@
+//! transform derives new tracks and segments from existing ones, e.g. by
+//! resampling, cropping, splitting, joining or reversing them.
+
+use geo_types::{CoordFloat, Point};
+
+use crate::geometry::nearest_spot;
+use crate::measure::{distance, elapsed, from_f64};
+use crate::{Duration, Gpx, Route, Time, Track, TrackSegment, Waypoint};
+
+/// How [`Track::append`] joins two tracks.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AppendOptions {
+    /// Shifts the times of the appended track so that its first timed point
+    /// follows the last timed point of the track it is appended to by this
+    /// gap, e.g. when a logger restarted with a wrong clock. `None` keeps
+    /// the times.
+    pub rebase_time: Option<Duration>,
+
+    /// Keeps the segments of the appended track apart from the last segment
+    /// of the track it is appended to, rather than continuing that segment.
+    pub segment_break: bool,
+}
+
+impl<T: CoordFloat> Gpx<T> {
+    /// Gives a copy of the document with its tracks cropped to the time
+    /// window from `start` to `end`, see [`TrackSegment::crop_time`].
+    ///
+    /// Tracks left without points are dropped. Waypoints, routes and the
+    /// metadata are copied unchanged.
+    pub fn crop_time(&self, start: Time, end: Time) -> Gpx<T> {
+        Gpx {
+            version: self.version,
+            creator: self.creator.clone(),
+            metadata: self.metadata.clone(),
+            waypoints: self.waypoints.clone(),
+            tracks: self
+                .tracks
+                .iter()
+                .map(|track| track.crop_time(start, end))
+                .filter(|track| !track.segments.is_empty())
+                .collect(),
+            routes: self.routes.clone(),
+            root_attributes: self.root_attributes.clone(),
+            comments: self.comments.clone(),
+        }
+    }
+
+    /// Shifts all times of the document, of the metadata and of all points,
+    /// by `offset`, e.g. to fix a clock set to the wrong time zone.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a shifted time is out of the range of [`Time`].
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Duration, Gpx, Time, Waypoint};
+    ///
+    /// let mut point = Waypoint::new(Point::new(-121.97, 37.24));
+    /// point.time = Some(Time::UNIX_EPOCH);
+    /// let mut gpx: Gpx = Default::default();
+    /// gpx.waypoints.push(point);
+    ///
+    /// gpx.shift_time(Duration::hours(-2));
+    /// assert_eq!(gpx.waypoints[0].time.unwrap().unix_seconds(), -7200);
+    /// ```
+    pub fn shift_time(&mut self, offset: Duration) {
+        if let Some(time) = self
+            .metadata
+            .as_mut()
+            .and_then(|metadata| metadata.time.as_mut())
+        {
+            *time += offset;
+        }
+        let route_points = self.routes.iter_mut().flat_map(|route| &mut route.points);
+        let track_points = self
+            .tracks
+            .iter_mut()
+            .flat_map(|track| &mut track.segments)
+            .flat_map(|segment| &mut segment.points);
+        for point in self
+            .waypoints
+            .iter_mut()
+            .chain(route_points)
+            .chain(track_points)
+        {
+            if let Some(time) = &mut point.time {
+                *time += offset;
+            }
+        }
+    }
+
+    /// Copies the track points that have a name or a symbol to the
+    /// waypoints of the document, as some devices record points of
+    /// interest as named track points. With `remove`, the points are taken
+    /// out of their tracks.
+    ///
+    /// Returns the number of promoted points.
+    ///
+    /// ```
+    /// use geo_types::{line_string, MultiLineString};
+    /// use gpx::{Gpx, Track};
+    ///
+    /// let mut track = Track::from(MultiLineString::new(vec![
+    ///     line_string![(x: 0.0, y: 0.0), (x: 1.0, y: 0.0), (x: 2.0, y: 0.0)],
+    /// ]));
+    /// track.segments[0].points[1].details_mut().name = Some("Summit".to_string());
+    /// let mut gpx: Gpx = Default::default();
+    /// gpx.tracks.push(track);
+    ///
+    /// assert_eq!(gpx.promote_named_points(true), 1);
+    /// assert_eq!(gpx.waypoints[0].details().name.as_deref(), Some("Summit"));
+    /// assert_eq!(gpx.tracks[0].segments[0].points.len(), 2);
+    /// ```
+    pub fn promote_named_points(&mut self, remove: bool) -> usize {
+        let is_named = |point: &Waypoint<T>| {
+            let details = point.details();
+            details.name.is_some() || details.symbol.is_some()
+        };
+        let count = self.waypoints.len();
+        for segment in self.tracks.iter_mut().flat_map(|track| &mut track.segments) {
+            if remove {
+                let (named, rest): (Vec<Waypoint<T>>, Vec<Waypoint<T>>) =
+                    std::mem::take(&mut segment.points)
+                        .into_iter()
+                        .partition(|point| is_named(point));
+                segment.points = rest;
+                self.waypoints.extend(named);
+            } else {
+                let named = segment.points.iter().filter(|point| is_named(point));
+                self.waypoints.extend(named.cloned());
+            }
+        }
+        self.waypoints.len() - count
+    }
+}
+
+impl<T: CoordFloat> Track<T> {
+    /// Gives a copy of the track with its segments cropped to the time
+    /// window from `start` to `end`, see [`TrackSegment::crop_time`].
+    pub fn crop_time(&self, start: Time, end: Time) -> Track<T> {
+        self.with_segments(
+            self.segments
+                .iter()
+                .flat_map(|segment| segment.crop_time(start, end))
+                .collect(),
+        )
+    }
+
+    /// Gives the interpolated position of the track at `time`, see
+    /// [`TrackSegment::position_at`].
+    ///
+    /// Returns `None` if `time` falls outside of all segments, including
+    /// the gaps between segments, where the position is unknown.
+    pub fn position_at(&self, time: Time) -> Option<Waypoint<T>> {
+        self.segments
+            .iter()
+            .find_map(|segment| segment.position_at(time))
+    }
+
+    /// Gives a copy of the track with all of its segments joined into a
+    /// single continuous segment.
+    ///
+    /// ```
+    /// use geo_types::{line_string, MultiLineString};
+    /// use gpx::Track;
+    ///
+    /// let track = Track::from(MultiLineString::new(vec![
+    ///     line_string![(x: 0.0, y: 0.0), (x: 0.0, y: 1.0)],
+    ///     line_string![(x: 5.0, y: 0.0), (x: 5.0, y: 1.0)],
+    /// ]));
+    ///
+    /// let joined = track.join_segments();
+    /// assert_eq!(joined.segments.len(), 1);
+    /// assert_eq!(joined.segments[0].points.len(), 4);
+    /// ```
+    pub fn join_segments(&self) -> Track<T> {
+        self.join_segments_within(Duration::MAX, f64::INFINITY)
+    }
+
+    /// Gives a copy of the track with consecutive segments joined where the
+    /// gap between them is small: at most `max_time_gap` in time and at most
+    /// `max_distance_gap` meters. The time gap is only checked if both
+    /// points around the gap have a time. Empty segments are dropped.
+    pub fn join_segments_within(&self, max_time_gap: Duration, max_distance_gap: f64) -> Track<T> {
+        let mut segments: Vec<TrackSegment<T>> = Vec::new();
+        for segment in self.segments.iter().filter(|s| !s.points.is_empty()) {
+            match segments.last_mut() {
+                Some(previous)
+                    if !is_gap(
+                        &previous.points[previous.points.len() - 1],
+                        &segment.points[0],
+                        max_time_gap,
+                        max_distance_gap,
+                    ) =>
+                {
+                    previous.points.extend_from_slice(&segment.points);
+                }
+                _ => segments.push(segment.clone()),
+            }
+        }
+        self.with_segments(segments)
+    }
+
+    /// Gives a copy of the track with its segments and their points in
+    /// reverse order. The points themselves, including their timestamps, are
+    /// unchanged.
+    pub fn reversed(&self) -> Track<T> {
+        self.with_segments(
+            self.segments
+                .iter()
+                .rev()
+                .map(|segment| TrackSegment {
+                    points: segment.points.iter().rev().cloned().collect(),
+                })
+                .collect(),
+        )
+    }
+
+    /// Gives a copy of the track in reverse order, with timestamps mirrored
+    /// so that they increase again: the reversed track starts at the time the
+    /// original started, and the time between any two points is preserved.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Duration, Time, Track, TrackSegment, Waypoint};
+    ///
+    /// let start = Time::UNIX_EPOCH;
+    /// let mut segment = TrackSegment::new();
+    /// for (x, seconds) in [(0.0, 0), (1.0, 10), (2.0, 15)] {
+    ///     let mut point = Waypoint::new(Point::new(x, 0.0));
+    ///     point.time = Some(start + Duration::seconds(seconds));
+    ///     segment.points.push(point);
+    /// }
+    /// let mut track = Track::new();
+    /// track.segments.push(segment);
+    ///
+    /// let reversed = track.reversed_retimed();
+    /// let point = &reversed.segments[0].points[1];
+    /// assert_eq!(point.point(), Point::new(1.0, 0.0));
+    /// assert_eq!(point.time, Some(start + Duration::seconds(5)));
+    /// ```
+    pub fn reversed_retimed(&self) -> Track<T> {
+        let mut track = self.reversed();
+        retime(
+            track
+                .segments
+                .iter_mut()
+                .flat_map(|segment| &mut segment.points),
+        );
+        track
+    }
+
+    /// Appends the segments of `other` to the track, e.g. to put an activity
+    /// back together after the logger restarted in the middle of it. The
+    /// first segment of `other` continues the last segment of the track
+    /// unless [`AppendOptions::segment_break`] is set. The name and other
+    /// data of `other` are dropped.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{AppendOptions, Duration, Time, Track, TrackSegment, Waypoint};
+    ///
+    /// let track = |seconds: i64| {
+    ///     let mut point = Waypoint::new(Point::new(0.0, 0.0));
+    ///     point.time = Some(Time::UNIX_EPOCH + Duration::seconds(seconds));
+    ///     let mut track = Track::new();
+    ///     track.segments.push(TrackSegment { points: vec![point] });
+    ///     track
+    /// };
+    /// let mut morning = track(100);
+    /// // Recorded after a restart that reset the clock.
+    /// let restarted = track(0);
+    ///
+    /// morning.append(
+    ///     restarted,
+    ///     AppendOptions {
+    ///         rebase_time: Some(Duration::seconds(1)),
+    ///         segment_break: true,
+    ///     },
+    /// );
+    /// assert_eq!(morning.segments.len(), 2);
+    /// let time = morning.segments[1].points[0].time.unwrap();
+    /// assert_eq!(time.unix_seconds(), 101);
+    /// ```
+    pub fn append(&mut self, mut other: Track<T>, options: AppendOptions) {
+        let times = |track: &Track<T>| -> Vec<Time> {
+            track
+                .segments
+                .iter()
+                .flat_map(|segment| &segment.points)
+                .filter_map(|point| point.time)
+                .collect()
+        };
+        if let Some(gap) = options.rebase_time {
+            if let (Some(&last), Some(&first)) = (times(self).last(), times(&other).first()) {
+                let offset = elapsed(first, last) + gap;
+                for point in other
+                    .segments
+                    .iter_mut()
+                    .flat_map(|segment| &mut segment.points)
+                {
+                    if let Some(time) = &mut point.time {
+                        *time += offset;
+                    }
+                }
+            }
+        }
+
+        let mut segments = other.segments.into_iter();
+        if let Some(last) = self.segments.last_mut() {
+            if !options.segment_break {
+                if let Some(first) = segments.next() {
+                    last.points.extend(first.points);
+                }
+            }
+        }
+        self.segments.extend(segments);
+    }
+
+    /// Cuts the track in two after `distance_m` meters, measured along its
+    /// segments without the gaps between them, e.g. to break an activity
+    /// into legs.
+    ///
+    /// The segment the cut falls into is divided between both tracks, each
+    /// getting a point at the cut: a copy of the point there if there is
+    /// one, or else a point with the position, elevation and time
+    /// interpolated from the points around the cut. Parts of the divided
+    /// segment that would be a lone point are left out. Both tracks keep
+    /// the name and other data of this one. If `distance_m` is beyond the
+    /// end, the second track has no segments.
+    ///
+    /// ```
+    /// use geo_types::{line_string, MultiLineString, Point};
+    /// use gpx::Track;
+    ///
+    /// // About 1.1 km north, then as far east.
+    /// let track: Track = Track::from(MultiLineString::new(vec![
+    ///     line_string![(x: 0.0, y: 0.0), (x: 0.0, y: 0.01), (x: 0.01, y: 0.01)],
+    /// ]));
+    ///
+    /// // About halfway up north.
+    /// let (first, second) = track.split_at_distance(track.length_m() / 4.0);
+    /// let cut = first.segments[0].points[1].point();
+    /// assert!((cut.y() - 0.005).abs() < 1e-4);
+    /// assert_eq!(second.segments[0].points.len(), 3);
+    /// assert_eq!(second.segments[0].points[0].point(), cut);
+    /// ```
+    pub fn split_at_distance(&self, distance_m: f64) -> (Track<T>, Track<T>) {
+        let mut covered = 0.0;
+        for (index, segment) in self.segments.iter().enumerate() {
+            for (step, pair) in segment.points.windows(2).enumerate() {
+                let length = distance(&pair[0], &pair[1]);
+                if covered + length >= distance_m {
+                    let fraction = if length > 0.0 {
+                        ((distance_m - covered) / length).clamp(0.0, 1.0)
+                    } else {
+                        0.0
+                    };
+                    return self.split_at(index, step, fraction);
+                }
+                covered += length;
+            }
+        }
+        (self.clone(), self.with_segments(Vec::new()))
+    }
+
+    /// Cuts the track in two at the spot nearest to `point`, see
+    /// [`Track::split_at_distance`]. Returns `None` if the track doesn't
+    /// pass within `tolerance` meters of `point`, see
+    /// [`Track::min_distance_to`].
+    ///
+    /// If the track passes the point more than once, it is cut where it
+    /// comes closest.
+    pub fn split_at_point(
+        &self,
+        point: Point<f64>,
+        tolerance: f64,
+    ) -> Option<(Track<T>, Track<T>)> {
+        let (index, step, fraction, distance) = self
+            .segments
+            .iter()
+            .enumerate()
+            .filter_map(|(index, segment)| {
+                let (step, fraction, distance) = nearest_spot(&segment.points, point)?;
+                Some((index, step, fraction, distance))
+            })
+            .min_by(|a, b| a.3.total_cmp(&b.3))?;
+        (distance <= tolerance).then(|| self.split_at(index, step, fraction))
+    }
+
+    /// Cuts the track in two at `fraction` of the way from the point `step`
+    /// of the segment `index` to the next point.
+    fn split_at(&self, index: usize, step: usize, fraction: f64) -> (Track<T>, Track<T>) {
+        let points = &self.segments[index].points;
+        let (mut first, mut second) = match points.get(step + 1) {
+            None => (points.to_vec(), Vec::new()),
+            Some(_) if fraction == 0.0 => (points[..=step].to_vec(), points[step..].to_vec()),
+            Some(_) if fraction == 1.0 => {
+                (points[..=step + 1].to_vec(), points[step + 1..].to_vec())
+            }
+            Some(next) => {
+                let a = &points[step];
+                let time = match (a.time, next.time) {
+                    (Some(a), Some(b)) => Some(a + elapsed(a, b) * fraction),
+                    _ => None,
+                };
+                let cut = interpolate(a, next, fraction, time);
+                let mut first = points[..=step].to_vec();
+                first.push(cut.clone());
+                let mut second = vec![cut];
+                second.extend_from_slice(&points[step + 1..]);
+                (first, second)
+            }
+        };
+        if first.len() < 2 && points.len() > 1 {
+            first.clear();
+        }
+        if second.len() < 2 {
+            second.clear();
+        }
+
+        let mut before = self.segments[..index].to_vec();
+        if !first.is_empty() {
+            before.push(TrackSegment { points: first });
+        }
+        let mut after = Vec::new();
+        if !second.is_empty() {
+            after.push(TrackSegment { points: second });
+        }
+        after.extend_from_slice(&self.segments[index + 1..]);
+        (self.with_segments(before), self.with_segments(after))
+    }
+
+    /// Gives a copy of the track with its segments split wherever recording
+    /// stopped, see [`TrackSegment::split_on_gaps`].
+    pub fn split_on_gaps(&self, max_time_gap: Duration, max_distance_gap: f64) -> Track<T> {
+        self.with_segments(
+            self.segments
+                .iter()
+                .flat_map(|segment| segment.split_on_gaps(max_time_gap, max_distance_gap))
+                .collect(),
+        )
+    }
+}
+
+impl<T: CoordFloat> Route<T> {
+    /// Gives a copy of the route with its points in reverse order. The
+    /// points themselves, including their timestamps, are unchanged.
+    pub fn reversed(&self) -> Route<T> {
+        self.with_points(self.points.iter().rev().cloned().collect())
+    }
+
+    /// Gives a copy of the route with its points in reverse order and their
+    /// timestamps mirrored, see [`Track::reversed_retimed`].
+    pub fn reversed_retimed(&self) -> Route<T> {
+        let mut route = self.reversed();
+        retime(route.points.iter_mut());
+        route
+    }
+}
+
+impl<T: CoordFloat> TrackSegment<T> {
+    /// Gives the position of the segment at `time`, e.g. to geotag a photo.
+    ///
+    /// The position, elevation and time are interpolated linearly between
+    /// the two consecutive timestamped points surrounding `time`; all other
+    /// data is left empty. Returns `None` if `time` is before the first or
+    /// after the last timestamped point.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Duration, Time, TrackSegment, Waypoint};
+    ///
+    /// let start = Time::UNIX_EPOCH;
+    /// let mut segment = TrackSegment::new();
+    /// for (x, seconds) in [(0.0, 0), (4.0, 4)] {
+    ///     let mut point = Waypoint::new(Point::new(x, 0.0));
+    ///     point.time = Some(start + Duration::seconds(seconds));
+    ///     segment.points.push(point);
+    /// }
+    ///
+    /// let point = segment.position_at(start + Duration::seconds(1));
+    /// assert_eq!(point.unwrap().point(), Point::new(1.0, 0.0));
+    /// assert!(segment.position_at(start - Duration::seconds(1)).is_none());
+    /// ```
+    pub fn position_at(&self, time: Time) -> Option<Waypoint<T>> {
+        let mut timed = self
+            .points
+            .iter()
+            .filter_map(|point| Some((point, point.time?)));
+        let mut previous = timed.next()?;
+        if previous.1 == time {
+            return Some(interpolate(previous.0, previous.0, 0.0, Some(time)));
+        }
+        for current in timed {
+            if previous.1 <= time && time <= current.1 {
+                let span = elapsed(previous.1, current.1).as_seconds_f64();
+                let fraction = elapsed(previous.1, time).as_seconds_f64() / span;
+                return Some(interpolate(previous.0, current.0, fraction, Some(time)));
+            }
+            previous = current;
+        }
+        None
+    }
+
+    /// Gives the parts of the segment recorded within the time window from
+    /// `start` to `end`, inclusive.
+    ///
+    /// Points outside of the window and points without a time are dropped.
+    /// If the segment leaves the window and comes back, each stretch within
+    /// the window becomes a segment of its own, so no segment spans a
+    /// stretch that was cropped out.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Duration, Time, TrackSegment, Waypoint};
+    ///
+    /// let start = Time::UNIX_EPOCH;
+    /// let mut segment = TrackSegment::new();
+    /// for seconds in 0..10 {
+    ///     let mut point = Waypoint::new(Point::new(0.0, 0.0));
+    ///     point.time = Some(start + Duration::seconds(seconds));
+    ///     segment.points.push(point);
+    /// }
+    ///
+    /// let cropped = segment.crop_time(
+    ///     start + Duration::seconds(2),
+    ///     start + Duration::seconds(7),
+    /// );
+    /// assert_eq!(cropped.len(), 1);
+    /// assert_eq!(cropped[0].points.len(), 6);
+    /// ```
+    pub fn crop_time(&self, start: Time, end: Time) -> Vec<TrackSegment<T>> {
+        let mut segments = Vec::new();
+        let mut current = TrackSegment::new();
+        for point in &self.points {
+            match point.time {
+                Some(time) if start <= time && time <= end => current.points.push(point.clone()),
+                Some(_) if !current.points.is_empty() => {
+                    segments.push(std::mem::take(&mut current));
+                }
+                _ => {}
+            }
+        }
+        if !current.points.is_empty() {
+            segments.push(current);
+        }
+        segments
+    }
+
+    /// Splits the segment wherever the GPS stopped recording.
+    ///
+    /// A new segment is started between two consecutive points that are more
+    /// than `max_time_gap` apart in time, or more than `max_distance_gap`
+    /// meters apart. The time gap is only checked if both points have a time.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Duration, Time, TrackSegment, Waypoint};
+    ///
+    /// let start = Time::UNIX_EPOCH;
+    /// let mut segment = TrackSegment::new();
+    /// for seconds in [0, 1, 2, 600, 601] {
+    ///     let mut point = Waypoint::new(Point::new(0.0, 0.0));
+    ///     point.time = Some(start + Duration::seconds(seconds));
+    ///     segment.points.push(point);
+    /// }
+    ///
+    /// let segments = segment.split_on_gaps(Duration::minutes(1), 100.0);
+    /// assert_eq!(segments.len(), 2);
+    /// assert_eq!(segments[1].points.len(), 2);
+    /// ```
+    pub fn split_on_gaps(
+        &self,
+        max_time_gap: Duration,
+        max_distance_gap: f64,
+    ) -> Vec<TrackSegment<T>> {
+        let mut segments = Vec::new();
+        let mut current = TrackSegment::new();
+        for point in &self.points {
+            if let Some(previous) = current.points.last() {
+                if is_gap(previous, point, max_time_gap, max_distance_gap) {
+                    segments.push(std::mem::take(&mut current));
+                }
+            }
+            current.points.push(point.clone());
+        }
+        if !current.points.is_empty() {
+            segments.push(current);
+        }
+        segments
+    }
+
+    /// Gives a copy of the segment resampled at a fixed time `interval`,
+    /// e.g. one second for 1 Hz.
+    ///
+    /// The samples start at the first timestamped point and end at or before
+    /// the last one. Position, elevation and time are interpolated linearly
+    /// between the surrounding points; all other data of the original points
+    /// is dropped. Points without a time are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is not positive.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Duration, Time, TrackSegment, Waypoint};
+    ///
+    /// let start = Time::UNIX_EPOCH;
+    /// let mut segment = TrackSegment::new();
+    /// for (x, seconds) in [(0.0, 0), (4.0, 4)] {
+    ///     let mut point = Waypoint::new(Point::new(x, 0.0));
+    ///     point.time = Some(start + Duration::seconds(seconds));
+    ///     segment.points.push(point);
+    /// }
+    ///
+    /// let resampled = segment.resampled(Duration::seconds(1));
+    /// assert_eq!(resampled.points.len(), 5);
+    /// assert_eq!(resampled.points[1].point(), Point::new(1.0, 0.0));
+    /// ```
+    pub fn resampled(&self, interval: Duration) -> TrackSegment<T> {
+        assert!(interval.is_positive(), "interval must be positive");
+
+        let timed: Vec<(&Waypoint<T>, Time)> = self
+            .points
+            .iter()
+            .filter_map(|point| Some((point, point.time?)))
+            .collect();
+        let (Some(&(_, start)), Some(&(_, end))) = (timed.first(), timed.last()) else {
+            return TrackSegment::new();
+        };
+
+        let mut points = Vec::new();
+        let mut index = 0;
+        let mut time = start;
+        while time <= end {
+            while index + 1 < timed.len() && timed[index + 1].1 < time {
+                index += 1;
+            }
+            let (a, a_time) = timed[index];
+            let (b, b_time) = timed.get(index + 1).copied().unwrap_or(timed[index]);
+            let span = (b_time - a_time).as_seconds_f64();
+            let fraction = if span > 0.0 {
+                (time - a_time).as_seconds_f64() / span
+            } else {
+                0.0
+            };
+            points.push(interpolate(a, b, fraction, Some(time)));
+            time += interval;
+        }
+        TrackSegment { points }
+    }
+}
+
+/// Whether recording stopped between the consecutive points `a` and `b`.
+fn is_gap<T: CoordFloat>(
+    a: &Waypoint<T>,
+    b: &Waypoint<T>,
+    max_time_gap: Duration,
+    max_distance_gap: f64,
+) -> bool {
+    let time_gap = match (a.time, b.time) {
+        (Some(a), Some(b)) => elapsed(a, b) > max_time_gap,
+        _ => false,
+    };
+    time_gap || distance(a, b) > max_distance_gap
+}
+
+/// Mirrors the timestamps of reversed points, mapping each time `t` to
+/// `first + last - t`, where `first` and `last` are the earliest and latest
+/// timestamp.
+fn retime<'a, T: CoordFloat + 'a>(points: impl Iterator<Item = &'a mut Waypoint<T>>) {
+    let points: Vec<&mut Waypoint<T>> = points.collect();
+    let times = points.iter().filter_map(|point| point.time);
+    let (Some(first), Some(last)) = (times.clone().min(), times.max()) else {
+        return;
+    };
+    for point in points {
+        if let Some(time) = point.time {
+            point.time = Some(first + elapsed(time, last));
+        }
+    }
+}
+
+/// Creates a waypoint `fraction` of the way from `a` to `b`, at `time`.
+fn interpolate<T: CoordFloat>(
+    a: &Waypoint<T>,
+    b: &Waypoint<T>,
+    fraction: f64,
+    time: Option<Time>,
+) -> Waypoint<T> {
+    let fraction: T = from_f64(fraction);
+    let lerp = |a: T, b: T| a + (b - a) * fraction;
+    let (a_point, b_point) = (a.point(), b.point());
+    let mut point = Waypoint::new(Point::new(
+        lerp(a_point.x(), b_point.x()),
+        lerp(a_point.y(), b_point.y()),
+    ));
+    point.elevation = match (a.elevation, b.elevation) {
+        (Some(a), Some(b)) => Some(lerp(a, b)),
+        (elevation, None) | (None, elevation) => elevation,
+    };
+    point.time = time;
+    point
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::Point;
+
+    use super::AppendOptions;
+    use crate::{Duration, Gpx, Metadata, Route, Time, Track, TrackSegment, Waypoint};
+
+    fn timed_segment(seconds: &[i64]) -> TrackSegment {
+        TrackSegment {
+            points: seconds
+                .iter()
+                .map(|&seconds| {
+                    let mut point = Waypoint::new(Point::new(seconds as f64, 0.0));
+                    point.time = Some(Time::UNIX_EPOCH + Duration::seconds(seconds));
+                    point
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn crop_time_splits_segments() {
+        let at = |seconds| Time::UNIX_EPOCH + Duration::seconds(seconds);
+        let mut segment = timed_segment(&[0, 5, 10, 20, 6, 7, 30]);
+        segment
+            .points
+            .insert(2, Waypoint::new(Point::new(-1.0, 0.0)));
+
+        let cropped = segment.crop_time(at(5), at(10));
+        let xs: Vec<Vec<f64>> = cropped
+            .iter()
+            .map(|segment| segment.points.iter().map(|p| p.point().x()).collect())
+            .collect();
+        assert_eq!(xs, [vec![5.0, 10.0], vec![6.0, 7.0]]);
+
+        let mut track = Track::new();
+        track.name = Some("track".into());
+        track.segments.push(segment);
+        track.segments.push(timed_segment(&[100, 200]));
+        let gpx = Gpx {
+            tracks: vec![track.clone(), Track::new()],
+            ..Default::default()
+        };
+
+        let cropped = track.crop_time(at(5), at(10));
+        assert_eq!(cropped.name, track.name);
+        assert_eq!(cropped.segments.len(), 2);
+
+        let cropped = gpx.crop_time(at(5), at(10));
+        assert_eq!(cropped.tracks.len(), 1);
+        assert!(gpx.crop_time(at(40), at(50)).tracks.is_empty());
+    }
+
+    #[test]
+    fn shift_time() {
+        let mut track = Track::new();
+        track.segments.push(timed_segment(&[0, 10]));
+        let mut route = Route::new();
+        route.points = timed_segment(&[20]).points;
+        route.points.push(Waypoint::new(Point::new(0.0, 0.0)));
+        let mut gpx = Gpx {
+            metadata: Some(Metadata {
+                time: Some(Time::UNIX_EPOCH),
+                ..Default::default()
+            }),
+            tracks: vec![track],
+            routes: vec![route],
+            ..Default::default()
+        };
+
+        gpx.shift_time(Duration::hours(1));
+        let seconds = |point: &Waypoint| point.time.map(|time| time.unix_seconds());
+        assert_eq!(gpx.metadata.unwrap().time.unwrap().unix_seconds(), 3600);
+        let points = &gpx.tracks[0].segments[0].points;
+        assert_eq!(seconds(&points[1]), Some(3610));
+        assert_eq!(seconds(&gpx.routes[0].points[0]), Some(3620));
+        assert_eq!(seconds(&gpx.routes[0].points[1]), None);
+    }
+
+    #[test]
+    fn split_on_gaps() {
+        let mut segment = timed_segment(&[0, 1, 2, 100, 101]);
+        segment.points[4] = Waypoint::new(Point::new(101.0, 0.0));
+        segment
+            .points
+            .push(Waypoint::new(Point::new(101.0, 0.0001)));
+
+        let mut track = Track::new();
+        track.segments.push(segment);
+        track.segments.push(TrackSegment::new());
+
+        // 1 degree of longitude is far beyond the distance limit.
+        let split = track.split_on_gaps(Duration::seconds(10), 1e6);
+        let lengths: Vec<_> = split.segments.iter().map(|s| s.points.len()).collect();
+        assert_eq!(lengths, [3, 3]);
+
+        let split = track.split_on_gaps(Duration::seconds(10), 100.0);
+        let lengths: Vec<_> = split.segments.iter().map(|s| s.points.len()).collect();
+        assert_eq!(lengths, [1, 1, 1, 1, 2]);
+    }
+
+    #[test]
+    fn join_segments_within_gap() {
+        let mut track = Track::new();
+        track.segments.push(timed_segment(&[0, 1]));
+        track.segments.push(TrackSegment::new());
+        track.segments.push(timed_segment(&[2, 3]));
+        track.segments.push(timed_segment(&[100, 101]));
+
+        let joined = track.join_segments_within(Duration::seconds(10), f64::INFINITY);
+        let lengths: Vec<_> = joined.segments.iter().map(|s| s.points.len()).collect();
+        assert_eq!(lengths, [4, 2]);
+
+        let joined = track.join_segments();
+        assert_eq!(joined.segments.len(), 1);
+        assert_eq!(joined.segments[0].points.len(), 6);
+
+        assert!(Track::<f64>::new().join_segments().segments.is_empty());
+    }
+
+    #[test]
+    fn reversed_tracks_and_routes() {
+        let mut track = Track::new();
+        track.segments.push(timed_segment(&[0, 1]));
+        track.segments.push(timed_segment(&[5, 10]));
+
+        let reversed = track.reversed();
+        assert_eq!(reversed.segments[0].points[0], track.segments[1].points[1]);
+        assert_eq!(reversed.segments[1].points[1], track.segments[0].points[0]);
+
+        let reversed = track.reversed_retimed();
+        let times: Vec<_> = reversed
+            .segments
+            .iter()
+            .flat_map(|segment| &segment.points)
+            .map(|point| (point.point().x(), point.time.unwrap()))
+            .collect();
+        let at = |seconds| Time::UNIX_EPOCH + Duration::seconds(seconds);
+        assert_eq!(
+            times,
+            [(10.0, at(0)), (5.0, at(5)), (1.0, at(9)), (0.0, at(10))]
+        );
+
+        let mut route = Route::from(track.segments[1].clone().linestring());
+        route.points[1].time = Some(at(3));
+        let reversed = route.reversed_retimed();
+        assert_eq!(reversed.points[0].point().x(), 10.0);
+        assert_eq!(reversed.points[0].time, route.points[1].time);
+        assert_eq!(reversed.points[1].time, None);
+    }
+
+    #[test]
+    fn position_at_interpolates_within_segments() {
+        let at = |seconds| Time::UNIX_EPOCH + Duration::seconds(seconds);
+        let mut segment = timed_segment(&[0, 10, 10, 20]);
+        segment.points[1].elevation = Some(100.0);
+        segment.points[2].elevation = Some(200.0);
+        segment.points[3].elevation = Some(300.0);
+        let mut track = Track::new();
+        track.segments.push(segment);
+        track.segments.push(timed_segment(&[100, 110]));
+
+        let position = |seconds| {
+            track
+                .position_at(at(seconds))
+                .map(|point| (point.point().x(), point.elevation))
+        };
+        assert_eq!(position(0), Some((0.0, None)));
+        assert_eq!(position(5), Some((5.0, Some(100.0))));
+        assert_eq!(position(10), Some((10.0, Some(100.0))));
+        assert_eq!(position(15), Some((15.0, Some(250.0))));
+        assert_eq!(position(105), Some((105.0, None)));
+        assert_eq!(position(50), None);
+        assert_eq!(position(200), None);
+        assert_eq!(track.position_at(at(105)).unwrap().time, Some(at(105)));
+    }
+
+    #[test]
+    fn resampled_interpolates() {
+        let start = Time::UNIX_EPOCH;
+        let mut segment = TrackSegment::new();
+        for (x, elevation, seconds) in [
+            (0.0, Some(10.0), Some(0)),
+            (5.0, None, None),
+            (2.0, Some(20.0), Some(2)),
+            (2.0, Some(20.0), Some(2)),
+            (5.0, Some(50.0), Some(5)),
+        ] {
+            let mut point = Waypoint::new(Point::new(x, 0.0));
+            point.elevation = elevation;
+            point.time = seconds.map(|seconds| start + Duration::seconds(seconds));
+            segment.points.push(point);
+        }
+
+        let resampled = segment.resampled(Duration::seconds(2));
+        let samples: Vec<_> = resampled
+            .points
+            .iter()
+            .map(|point| (point.point().x(), point.elevation.unwrap()))
+            .collect();
+        assert_eq!(samples, [(0.0, 10.0), (2.0, 20.0), (4.0, 40.0)]);
+        assert_eq!(resampled.points[2].time, Some(start + Duration::seconds(4)));
+
+        let resampled = segment.resampled(Duration::milliseconds(500));
+        assert_eq!(resampled.points.len(), 11);
+        assert_eq!(resampled.points[10].point(), Point::new(5.0, 0.0));
+    }
+
+    #[test]
+    fn resampled_f32_segment() {
+        let mut segment = TrackSegment::<f32>::new();
+        for (x, elevation, seconds) in [(0.0, 10.0, 0), (4.0, 30.0, 4)] {
+            let mut point = Waypoint::new(Point::new(x, 0.0));
+            point.elevation = Some(elevation);
+            point.time = Some(Time::UNIX_EPOCH + Duration::seconds(seconds));
+            segment.points.push(point);
+        }
+
+        let resampled = segment.resampled(Duration::seconds(1));
+        assert_eq!(resampled.points.len(), 5);
+        assert_eq!(resampled.points[1].point(), Point::new(1.0f32, 0.0));
+        assert_eq!(resampled.points[1].elevation, Some(15.0f32));
+    }
+
+    #[test]
+    fn resampled_without_times() {
+        let segment = TrackSegment {
+            points: vec![Waypoint::new(Point::new(1.0, 2.0))],
+        };
+        assert!(segment.resampled(Duration::SECOND).points.is_empty());
+    }
+
+    #[test]
+    fn split_at_distance_and_point() {
+        let mut track = Track::new();
+        track.name = Some("Legs".to_string());
+        track.segments.push(timed_segment(&[0, 1, 2]));
+        track.segments.push(timed_segment(&[5, 6]));
+        let degree = track.segments[0].length_m() / 2.0;
+        let at = |seconds: f64| Some(Time::UNIX_EPOCH + Duration::seconds_f64(seconds));
+
+        // Within the first segment, interpolating the cut.
+        let (first, second) = track.split_at_distance(degree * 1.5);
+        assert_eq!(first.name.as_deref(), Some("Legs"));
+        assert_eq!(first.segments.len(), 1);
+        let cut = &first.segments[0].points[2];
+        assert!((cut.point().x() - 1.5).abs() < 1e-9);
+        assert_eq!(cut.time, at(1.5));
+        assert_eq!(second.segments.len(), 2);
+        assert_eq!(&second.segments[0].points[0], cut);
+        assert_eq!(second.segments[0].points.len(), 2);
+
+        // At the end of the first segment, leaving no lone points.
+        let (first, second) = track.split_at_distance(degree * 2.0);
+        assert_eq!(first.segments, track.segments[..1]);
+        assert_eq!(second.segments, track.segments[1..]);
+
+        // At the start or beyond the end.
+        let (first, second) = track.split_at_distance(0.0);
+        assert!(first.segments.is_empty());
+        assert_eq!(second.segments, track.segments);
+        let (first, second) = track.split_at_distance(degree * 10.0);
+        assert_eq!(first, track);
+        assert!(second.segments.is_empty());
+
+        // Near a point of the second segment.
+        let (first, second) = track
+            .split_at_point(Point::new(5.25, 0.0001), 20.0)
+            .unwrap();
+        assert_eq!(first.segments.len(), 2);
+        assert_eq!(first.segments[1].points[1].time, at(5.25));
+        assert_eq!(second.segments.len(), 1);
+        assert!(track
+            .split_at_point(Point::new(5.25, 0.001), 20.0)
+            .is_none());
+        assert!(Track::<f64>::new()
+            .split_at_point(Point::new(0.0, 0.0), 20.0)
+            .is_none());
+    }
+
+    #[test]
+    fn append_tracks() {
+        let track = |seconds: &[i64]| {
+            let mut track = Track::new();
+            track.segments.push(timed_segment(seconds));
+            track
+        };
+        let at = |seconds| Some(Time::UNIX_EPOCH + Duration::seconds(seconds));
+
+        let mut joined = track(&[0, 1]);
+        joined.name = Some("Ride".to_string());
+        joined.append(track(&[10, 11]), AppendOptions::default());
+        assert_eq!(joined.name.as_deref(), Some("Ride"));
+        assert_eq!(joined.segments.len(), 1);
+        let times: Vec<_> = joined.segments[0].points.iter().map(|p| p.time).collect();
+        assert_eq!(times, [at(0), at(1), at(10), at(11)]);
+
+        let mut rebased = track(&[100, 101]);
+        let mut other = track(&[0, 1]);
+        other.segments[0].points[0].time = None;
+        other.segments.push(timed_segment(&[5]));
+        rebased.append(
+            other,
+            AppendOptions {
+                rebase_time: Some(Duration::seconds(10)),
+                segment_break: true,
+            },
+        );
+        assert_eq!(rebased.segments.len(), 3);
+        assert_eq!(rebased.segments[1].points[0].time, None);
+        assert_eq!(rebased.segments[1].points[1].time, at(111));
+        assert_eq!(rebased.segments[2].points[0].time, at(115));
+
+        let mut empty = Track::new();
+        empty.append(track(&[0]), AppendOptions::default());
+        assert_eq!(empty.segments, track(&[0]).segments);
+    }
+
+    #[test]
+    fn promote_named_points() {
+        let mut segment = timed_segment(&[0, 1, 2, 3]);
+        segment.points[1].details_mut().name = Some("Hut".to_string());
+        segment.points[3].details_mut().symbol = Some("Flag, Blue".into());
+        let mut track = Track::new();
+        track.segments.push(segment);
+        let mut gpx = Gpx {
+            waypoints: vec![Waypoint::new(Point::new(9.0, 9.0))],
+            tracks: vec![track],
+            ..Default::default()
+        };
+
+        let mut kept = gpx.clone();
+        assert_eq!(kept.promote_named_points(false), 2);
+        assert_eq!(kept.waypoints.len(), 3);
+        assert_eq!(kept.tracks, gpx.tracks);
+
+        assert_eq!(gpx.promote_named_points(true), 2);
+        assert_eq!(gpx.waypoints, kept.waypoints);
+        assert_eq!(
+            gpx.waypoints[1].time,
+            kept.tracks[0].segments[0].points[1].time
+        );
+        let remaining: Vec<_> = gpx.tracks[0].segments[0]
+            .points
+            .iter()
+            .map(|point| point.point().x())
+            .collect();
+        assert_eq!(remaining, [0.0, 2.0]);
+        assert_eq!(gpx.promote_named_points(true), 0);
+    }
+}
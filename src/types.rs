@@ -1,13 +1,18 @@
 //! generic types for GPX
 
-pub use crate::parser::time::Time;
-use geo_types::{Geometry, LineString, MultiLineString, Point, Rect};
+use std::sync::Arc;
+
+use crate::errors::GpxError;
+pub use crate::parser::time::{Duration, Time, UtcOffset};
+
+use geo_types::{CoordFloat, Geometry, LineString, MultiLineString, Point, Rect};
 #[cfg(feature = "use-serde")]
 use serde::{Deserialize, Serialize};
 
 /// Allowable GPX versions. Currently, only GPX 1.0 and GPX 1.1 are accepted.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[derive(Default)]
 pub enum GpxVersion {
     #[default]
@@ -23,9 +28,13 @@ impl std::fmt::Display for GpxVersion {
 }
 
 /// Gpx is the root element in the XML file.
-#[derive(Clone, Default, Debug, PartialEq)]
+///
+/// Coordinates and elevations are stored as `T`, which defaults to `f64`. Use
+/// `f32` to roughly halve the memory used by the points of large files.
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
-pub struct Gpx {
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Gpx<T: CoordFloat = f64> {
     /// Version of the Gpx file.
     pub version: GpxVersion,
 
@@ -33,24 +42,171 @@ pub struct Gpx {
     pub creator: Option<String>,
 
     /// Metadata about the file.
-    pub metadata: Option<Metadata>,
+    pub metadata: Option<Metadata<T>>,
 
     /// A list of waypoints.
-    pub waypoints: Vec<Waypoint>,
+    pub waypoints: Vec<Waypoint<T>>,
 
     /// A list of tracks.
-    pub tracks: Vec<Track>,
+    pub tracks: Vec<Track<T>>,
 
     /// A list of routes with a list of point-by-point directions
-    pub routes: Vec<Route>,
+    pub routes: Vec<Route<T>>,
+
+    /// Other attributes of the root element, by qualified name, like
+    /// `xsi:schemaLocation` or the `xmlns:*` declarations of vendor
+    /// namespaces. They are written back as they are, except for those the
+    /// writer sets itself.
+    pub root_attributes: Vec<(String, String)>,
+
+    /// The XML comments between the children of the root element, kept
+    /// when reading with
+    /// [`ReadOptions::comments`](crate::ReadOptions::comments) and written
+    /// back where they were.
+    pub comments: Vec<Comment>,
+}
+
+impl<T: CoordFloat> Default for Gpx<T> {
+    fn default() -> Gpx<T> {
+        Gpx {
+            version: Default::default(),
+            creator: None,
+            metadata: None,
+            waypoints: Vec::new(),
+            tracks: Vec::new(),
+            routes: Vec::new(),
+            root_attributes: Vec::new(),
+            comments: Vec::new(),
+        }
+    }
+}
+
+/// An XML comment between the children of the root element, like the notes
+/// of a survey.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Comment {
+    /// The text of the comment, without `<!--` and `-->`.
+    pub text: String,
+
+    /// Where the comment goes.
+    pub position: CommentPosition,
+}
+
+/// Where a [`Comment`] goes in the document.
+///
+/// Comments before a waypoint, track or route are written with it, and
+/// left out if it isn't written or doesn't exist.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum CommentPosition {
+    /// At the start, before the metadata.
+    Start,
+    /// Before the waypoint at the index of [`Gpx::waypoints`].
+    BeforeWaypoint(usize),
+    /// Before the track at the index of [`Gpx::tracks`].
+    BeforeTrack(usize),
+    /// Before the route at the index of [`Gpx::routes`].
+    BeforeRoute(usize),
+    /// At the end, after all waypoints, tracks and routes.
+    End,
+}
+
+impl<T: CoordFloat> Gpx<T> {
+    /// Retains only the waypoints, track points and route points for which
+    /// `f` returns `true`.
+    ///
+    /// Track segments, tracks and routes that become empty because all of
+    /// their points were removed are dropped as well. Ones that were already
+    /// empty are left untouched.
+    ///
+    /// ```
+    /// use gpx::{Gpx, Track, TrackSegment, Waypoint};
+    /// use geo_types::Point;
+    ///
+    /// let mut segment = TrackSegment::new();
+    /// segment.points.push(Waypoint::new(Point::new(-121.97, 37.24)));
+    /// segment.points.push(Waypoint::new(Point::new(0.0, 0.0)));
+    /// let mut track = Track::new();
+    /// track.segments.push(segment);
+    /// let mut gpx = Gpx::default();
+    /// gpx.tracks.push(track);
+    ///
+    /// // Drop points sitting on "null island".
+    /// gpx.retain_points(|wpt| wpt.point() != Point::new(0.0, 0.0));
+    /// assert_eq!(gpx.tracks[0].segments[0].points.len(), 1);
+    /// ```
+    pub fn retain_points<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Waypoint<T>) -> bool,
+    {
+        self.waypoints.retain(&mut f);
+        self.tracks.retain_mut(|track| {
+            let was_empty = track.segments.is_empty();
+            track.retain_points(&mut f);
+            was_empty || !track.segments.is_empty()
+        });
+        self.routes.retain_mut(|route| {
+            let was_empty = route.points.is_empty();
+            route.retain_points(&mut f);
+            was_empty || !route.points.is_empty()
+        });
+    }
+
+    /// Moves all points of the document to where `f` maps them, e.g. to
+    /// reproject coordinates surveyed in another datum or projection to
+    /// WGS 84, like `Gpx::reproject` of the `proj` feature does.
+    ///
+    /// Waypoints, route points, track points and the shape points of
+    /// Garmin's `RoutePointExtension` are mapped alike. The bounds of the
+    /// metadata are left as they are.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{Gpx, Waypoint};
+    ///
+    /// let mut gpx: Gpx = Default::default();
+    /// gpx.waypoints.push(Waypoint::new(Point::new(10.0, 50.0)));
+    ///
+    /// // Moves the points by a fixed datum shift.
+    /// gpx.map_points(|point| Point::new(point.x() + 0.5, point.y() - 0.25));
+    /// assert_eq!(gpx.waypoints[0].point(), Point::new(10.5, 49.75));
+    /// ```
+    pub fn map_points<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Point<T>) -> Point<T>,
+    {
+        let route_points = self.routes.iter_mut().flat_map(|route| &mut route.points);
+        let track_points = self
+            .tracks
+            .iter_mut()
+            .flat_map(|track| &mut track.segments)
+            .flat_map(|segment| &mut segment.points);
+        for waypoint in self
+            .waypoints
+            .iter_mut()
+            .chain(route_points)
+            .chain(track_points)
+        {
+            waypoint.set_point(f(waypoint.point()));
+            if let Some(extension) = &mut waypoint.route_point_extension {
+                for point in &mut extension.points {
+                    *point = f(*point);
+                }
+            }
+        }
+    }
 }
 
 /// Information about the copyright holder and any license governing use of this file.
 ///
 /// By linking to an appropriate license, you may place your data into the
 /// public domain or grant additional usage rights.
-#[derive(Clone, Default, Debug, PartialEq)]
+#[derive(Clone, Default, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct GpxCopyright {
     pub author: Option<String>,
     pub year: Option<i32>,
@@ -61,9 +217,10 @@ pub struct GpxCopyright {
 ///
 /// Providing rich, meaningful information about your GPX files allows others to
 /// search for and use your GPS data.
-#[derive(Clone, Default, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
-pub struct Metadata {
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Metadata<T: CoordFloat = f64> {
     /// The name of the GPX file.
     pub name: Option<String>,
 
@@ -87,14 +244,30 @@ pub struct Metadata {
     pub copyright: Option<GpxCopyright>,
 
     /// Bounds for the tracks in the GPX.
-    pub bounds: Option<Rect<f64>>,
+    pub bounds: Option<Rect<T>>,
     /*extensions: GpxExtensionsType,*/
 }
 
+impl<T: CoordFloat> Default for Metadata<T> {
+    fn default() -> Metadata<T> {
+        Metadata {
+            name: None,
+            description: None,
+            author: None,
+            links: Vec::new(),
+            time: None,
+            keywords: None,
+            copyright: None,
+            bounds: None,
+        }
+    }
+}
+
 /// Route represents an ordered list of waypoints representing a series of turn points leading to a destination.
-#[derive(Clone, Default, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
-pub struct Route {
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Route<T: CoordFloat = f64> {
     /// GPS name of route.
     pub name: Option<String>,
 
@@ -119,16 +292,65 @@ pub struct Route {
 
     /// Each Waypoint holds the coordinates, elevation, timestamp, and metadata
     /// for a single point in a track.
-    pub points: Vec<Waypoint>,
+    pub points: Vec<Waypoint<T>>,
+}
+
+impl<T: CoordFloat> Default for Route<T> {
+    fn default() -> Route<T> {
+        Route {
+            name: None,
+            comment: None,
+            description: None,
+            source: None,
+            links: Vec::new(),
+            number: None,
+            type_: None,
+            points: Vec::new(),
+        }
+    }
 }
 
-impl Route {
+impl<T: CoordFloat> Route<T> {
     /// Gives the linestring of the segment's points, the sequence of points that
     /// comprises the track segment.
-    pub fn linestring(&self) -> LineString<f64> {
+    pub fn linestring(&self) -> LineString<T> {
         self.points.iter().map(|wpt| wpt.point()).collect()
     }
 
+    /// Gives the linestring of the route's points along with the shape points
+    /// of their [`RoutePointExtension`]s, which follows the roads rather than
+    /// going straight from turn to turn.
+    ///
+    /// ```
+    /// use geo_types::{line_string, Point};
+    /// use gpx::{Route, RoutePointExtension, Waypoint};
+    ///
+    /// let mut start = Waypoint::new(Point::new(0.0, 0.0));
+    /// start.route_point_extension = Some(Box::new(RoutePointExtension {
+    ///     points: vec![Point::new(0.5, 0.1)],
+    ///     ..Default::default()
+    /// }));
+    /// let mut route: Route = Route::new();
+    /// route.points = vec![start, Waypoint::new(Point::new(1.0, 0.0))];
+    ///
+    /// assert_eq!(
+    ///     route.detailed_linestring(),
+    ///     line_string![(x: 0.0, y: 0.0), (x: 0.5, y: 0.1), (x: 1.0, y: 0.0)]
+    /// );
+    /// ```
+    pub fn detailed_linestring(&self) -> LineString<T> {
+        let mut coords = Vec::new();
+        for waypoint in &self.points {
+            coords.push(waypoint.point().0);
+            let shape = waypoint
+                .route_point_extension
+                .iter()
+                .flat_map(|extension| &extension.points);
+            coords.extend(shape.map(|point| point.0));
+        }
+        LineString::new(coords)
+    }
+
     /// Creates a new Route with default values.
     ///
     /// ```
@@ -145,21 +367,64 @@ impl Route {
     ///     route.points.push(point);
     /// }
     ///
-    pub fn new() -> Route {
+    pub fn new() -> Route<T> {
         Default::default()
     }
+
+    /// Retains only the route points for which `f` returns `true`.
+    pub fn retain_points<F>(&mut self, f: F)
+    where
+        F: FnMut(&Waypoint<T>) -> bool,
+    {
+        self.points.retain(f);
+    }
+
+    /// Copies the route's metadata onto a new route with the given points.
+    pub(crate) fn with_points(&self, points: Vec<Waypoint<T>>) -> Route<T> {
+        Route {
+            name: self.name.clone(),
+            comment: self.comment.clone(),
+            description: self.description.clone(),
+            source: self.source.clone(),
+            links: self.links.clone(),
+            number: self.number,
+            type_: self.type_.clone(),
+            points,
+        }
+    }
 }
 
-impl From<Route> for Geometry<f64> {
-    fn from(route: Route) -> Geometry<f64> {
+impl<T: CoordFloat> From<Route<T>> for Geometry<T> {
+    fn from(route: Route<T>) -> Geometry<T> {
         Geometry::LineString(route.linestring())
     }
 }
 
+impl<T: CoordFloat> TryFrom<Geometry<T>> for Route<T> {
+    type Error = GpxError;
+
+    /// Converts a `Geometry::LineString` into a Route, failing for any other
+    /// kind of geometry.
+    fn try_from(geometry: Geometry<T>) -> Result<Route<T>, GpxError> {
+        Ok(LineString::try_from(geometry)?.into())
+    }
+}
+
+impl<T: CoordFloat> From<LineString<T>> for Route<T> {
+    /// Creates a Route whose points are the coordinates of the linestring.
+    fn from(linestring: LineString<T>) -> Route<T> {
+        Route {
+            points: linestring.points().map(Waypoint::new).collect(),
+            ..Default::default()
+        }
+    }
+}
+
 /// Track represents an ordered list of points describing a path.
-#[derive(Clone, Default, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
-pub struct Track {
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Track<T: CoordFloat = f64> {
     /// GPS name of track.
     pub name: Option<String>,
 
@@ -182,19 +447,50 @@ pub struct Track {
     /// GPS number of track
     pub number: Option<u32>,
 
+    /// Color of the track on Garmin devices, from the `DisplayColor` of
+    /// Garmin's `TrackExtension`.
+    pub display_color: Option<DisplayColor>,
+
+    /// How the track is drawn, from the `line` of the `gpx_style`
+    /// extension.
+    pub line_style: Option<LineStyle>,
+
     /// A Track Segment holds a list of Track Points which are logically
     /// connected in order. To represent a single GPS track where GPS reception
     /// was lost, or the GPS receiver was turned off, start a new Track Segment
     /// for each continuous span of track data.
-    pub segments: Vec<TrackSegment>,
+    pub segments: Vec<TrackSegment<T>>,
+
+    /// Bounds of the points of all segments, as last stored by
+    /// [`recompute_bounds`](Track::recompute_bounds). GPX has no bounds
+    /// for tracks, so they are neither read nor written.
+    pub bounds: Option<Rect<T>>,
     /* extensions */
     /* trkSeg */
 }
 
-impl Track {
+impl<T: CoordFloat> Default for Track<T> {
+    fn default() -> Track<T> {
+        Track {
+            name: None,
+            comment: None,
+            description: None,
+            source: None,
+            links: Vec::new(),
+            type_: None,
+            number: None,
+            display_color: None,
+            line_style: None,
+            segments: Vec::new(),
+            bounds: None,
+        }
+    }
+}
+
+impl<T: CoordFloat> Track<T> {
     /// Gives the multi-linestring that this track represents, which is multiple
     /// linestrings.
-    pub fn multilinestring(&self) -> MultiLineString<f64> {
+    pub fn multilinestring(&self) -> MultiLineString<T> {
         self.segments.iter().map(|seg| seg.linestring()).collect()
     }
 
@@ -207,36 +503,113 @@ impl Track {
     ///
     /// let segment = TrackSegment::new();
     /// track.segments.push(segment);
-    pub fn new() -> Track {
+    pub fn new() -> Track<T> {
         Default::default()
     }
+
+    /// Retains only the track points for which `f` returns `true`.
+    ///
+    /// Segments that become empty because all of their points were removed
+    /// are dropped from the track.
+    pub fn retain_points<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Waypoint<T>) -> bool,
+    {
+        self.segments.retain_mut(|segment| {
+            let was_empty = segment.points.is_empty();
+            segment.retain_points(&mut f);
+            was_empty || !segment.points.is_empty()
+        });
+    }
+
+    /// Copies the track's metadata onto a new track with the given segments.
+    /// The bounds aren't copied, as they no longer match.
+    pub(crate) fn with_segments(&self, segments: Vec<TrackSegment<T>>) -> Track<T> {
+        Track {
+            name: self.name.clone(),
+            comment: self.comment.clone(),
+            description: self.description.clone(),
+            source: self.source.clone(),
+            links: self.links.clone(),
+            type_: self.type_.clone(),
+            number: self.number,
+            display_color: self.display_color.clone(),
+            line_style: self.line_style.clone(),
+            segments,
+            bounds: None,
+        }
+    }
 }
 
-impl From<Track> for Geometry<f64> {
-    fn from(track: Track) -> Geometry<f64> {
+impl<T: CoordFloat> From<Track<T>> for Geometry<T> {
+    fn from(track: Track<T>) -> Geometry<T> {
         Geometry::MultiLineString(track.multilinestring())
     }
 }
 
+impl<T: CoordFloat> TryFrom<Geometry<T>> for Track<T> {
+    type Error = GpxError;
+
+    /// Converts a `Geometry::MultiLineString` into a Track, failing for any
+    /// other kind of geometry.
+    fn try_from(geometry: Geometry<T>) -> Result<Track<T>, GpxError> {
+        Ok(MultiLineString::try_from(geometry)?.into())
+    }
+}
+
+impl<T: CoordFloat> From<MultiLineString<T>> for Track<T> {
+    /// Creates a Track with one segment per linestring.
+    ///
+    /// ```
+    /// use geo_types::{line_string, MultiLineString};
+    /// use gpx::Track;
+    ///
+    /// let lines = MultiLineString::new(vec![
+    ///     line_string![(x: -121.97, y: 37.24), (x: -121.96, y: 37.25)],
+    ///     line_string![(x: -121.95, y: 37.26)],
+    /// ]);
+    ///
+    /// let track = Track::from(lines);
+    /// assert_eq!(track.segments.len(), 2);
+    /// assert_eq!(track.segments[0].points.len(), 2);
+    /// ```
+    fn from(multilinestring: MultiLineString<T>) -> Track<T> {
+        Track {
+            segments: multilinestring
+                .into_iter()
+                .map(TrackSegment::from)
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
 /// TrackSegment represents a list of track points.
 ///
 /// This TrackSegment holds a list of Track Points which are logically
 /// connected in order. To represent a single GPS track where GPS reception
 /// was lost, or the GPS receiver was turned off, start a new Track Segment
 /// for each continuous span of track data.
-#[derive(Clone, Default, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
-pub struct TrackSegment {
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct TrackSegment<T: CoordFloat = f64> {
     /// Each Waypoint holds the coordinates, elevation, timestamp, and metadata
     /// for a single point in a track.
-    pub points: Vec<Waypoint>,
+    pub points: Vec<Waypoint<T>>,
     /* extensions */
 }
 
-impl TrackSegment {
+impl<T: CoordFloat> Default for TrackSegment<T> {
+    fn default() -> TrackSegment<T> {
+        TrackSegment { points: Vec::new() }
+    }
+}
+
+impl<T: CoordFloat> TrackSegment<T> {
     /// Gives the linestring of the segment's points, the sequence of points that
     /// comprises the track segment.
-    pub fn linestring(&self) -> LineString<f64> {
+    pub fn linestring(&self) -> LineString<T> {
         self.points.iter().map(|wpt| wpt.point()).collect()
     }
 
@@ -255,43 +628,108 @@ impl TrackSegment {
     ///     let point = Waypoint::new(Point::new(-121.97, 37.24));
     ///     trkseg.points.push(point);
     /// }
-    pub fn new() -> TrackSegment {
+    pub fn new() -> TrackSegment<T> {
         Default::default()
     }
+
+    /// Retains only the track points for which `f` returns `true`.
+    pub fn retain_points<F>(&mut self, f: F)
+    where
+        F: FnMut(&Waypoint<T>) -> bool,
+    {
+        self.points.retain(f);
+    }
 }
 
-impl From<TrackSegment> for Geometry<f64> {
-    fn from(track_segment: TrackSegment) -> Geometry<f64> {
+impl<T: CoordFloat> From<TrackSegment<T>> for Geometry<T> {
+    fn from(track_segment: TrackSegment<T>) -> Geometry<T> {
         Geometry::LineString(track_segment.linestring())
     }
 }
 
+impl<T: CoordFloat> TryFrom<Geometry<T>> for TrackSegment<T> {
+    type Error = GpxError;
+
+    /// Converts a `Geometry::LineString` into a TrackSegment, failing for any
+    /// other kind of geometry.
+    fn try_from(geometry: Geometry<T>) -> Result<TrackSegment<T>, GpxError> {
+        Ok(LineString::try_from(geometry)?.into())
+    }
+}
+
+impl<T: CoordFloat> From<LineString<T>> for TrackSegment<T> {
+    /// Creates a TrackSegment whose points are the coordinates of the
+    /// linestring.
+    fn from(linestring: LineString<T>) -> TrackSegment<T> {
+        TrackSegment {
+            points: linestring.points().map(Waypoint::new).collect(),
+        }
+    }
+}
+
 // A Version of geo_types::Point that has the Default trait implemented, which
 // allows us to initialise the GpxPoint with default values compactly
 // in the Waypoint::new function below
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
-struct GpxPoint(Point<f64>);
+struct GpxPoint<T: CoordFloat>(Point<T>);
+
+impl<T: CoordFloat> Default for GpxPoint<T> {
+    fn default() -> GpxPoint<T> {
+        GpxPoint(Point::new(T::zero(), T::zero()))
+    }
+}
 
-impl Default for GpxPoint {
-    fn default() -> GpxPoint {
-        GpxPoint(Point::new(0 as f64, 0 as f64))
+/// Gives points within the valid ranges of latitude and longitude, so that
+/// generated documents can be written.
+#[cfg(feature = "arbitrary")]
+impl<'a, T: CoordFloat> arbitrary::Arbitrary<'a> for GpxPoint<T> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<GpxPoint<T>> {
+        let fraction = |value: u32| f64::from(value) / f64::from(u32::MAX);
+        let latitude = fraction(u.arbitrary()?) * 180.0 - 90.0;
+        let longitude = (fraction(u.arbitrary()?) * 360.0 - 180.0).min(179.999_999);
+        let cast = |value: f64| T::from(value).ok_or(arbitrary::Error::IncorrectFormat);
+        Ok(GpxPoint(Point::new(cast(longitude)?, cast(latitude)?)))
     }
 }
 
 /// Waypoint represents a waypoint, point of interest, or named feature on a
 /// map.
-#[derive(Clone, Default, Debug, PartialEq)]
+///
+/// Only the position, elevation, speed, time and Garmin's track point and
+/// route point extensions are stored in the waypoint itself. The rarely
+/// used descriptive and GPS fields, like [`name`](WaypointDetails::name) or
+/// [`hdop`](WaypointDetails::hdop), live in a separately allocated
+/// [`WaypointDetails`], which is only created once they are changed through
+/// [`details_mut`](Waypoint::details_mut), so that tracks of millions of
+/// points need little memory:
+///
+/// ```
+/// use geo_types::Point;
+/// use gpx::Waypoint;
+///
+/// let mut waypoint = Waypoint::new(Point::new(-121.97, 37.24));
+/// assert_eq!(waypoint.details().name, None);
+/// assert!(!waypoint.has_details());
+///
+/// waypoint.details_mut().name = Some("Summit".into());
+/// assert_eq!(waypoint.details().name.as_deref(), Some("Summit"));
+/// ```
+///
+/// With the `use-serde` feature, the details are serialized along with the
+/// other fields of the waypoint, as if they were its own.
+#[derive(Clone, Debug)]
 #[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
-pub struct Waypoint {
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Waypoint<T: CoordFloat = f64> {
     /// The geographical point.
-    point: GpxPoint,
+    point: GpxPoint<T>,
 
     /// Elevation (in meters) of the point.
-    pub elevation: Option<f64>,
+    pub elevation: Option<T>,
 
     /// Speed (in meters per second) (only in GPX 1.0)
-    pub speed: Option<f64>,
+    pub speed: Option<T>,
 
     /// Creation/modification timestamp for element. Date and time in are in
     /// Univeral Coordinated Time (UTC), not local time! Conforms to ISO 8601
@@ -299,6 +737,26 @@ pub struct Waypoint {
     /// allowed for millisecond timing in tracklogs.
     pub time: Option<Time>,
 
+    /// Garmin's `TrackPointExtension` from the <extensions> element, holding
+    /// e.g. heart rate and cadence recorded by fitness devices.
+    pub track_point_extension: Option<TrackPointExtension>,
+
+    /// Garmin's `RoutePointExtension` from the <extensions> element of route
+    /// points, holding the road geometry up to the next route point. It is
+    /// boxed, as few points have one.
+    pub route_point_extension: Option<Box<RoutePointExtension<T>>>,
+
+    /// The rarely used fields, see [`Waypoint::details`].
+    #[cfg_attr(feature = "use-serde", serde(flatten, with = "flat_details"))]
+    details: Option<Box<WaypointDetails>>,
+}
+
+/// The descriptive and GPS fields of a [`Waypoint`], which most trackpoints
+/// lack.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct WaypointDetails {
     /// The GPS name of the waypoint. This field will be transferred to and
     /// from the GPS. GPX does not place restrictions on the length of this
     /// field or the characters contained in it. It is up to the receiving
@@ -322,10 +780,14 @@ pub struct Waypoint {
     /// Text of GPS symbol name. For interchange with other programs, use the
     /// exact spelling of the symbol as displayed on the GPS. If the GPS
     /// abbreviates words, spell them out.
-    pub symbol: Option<String>,
+    ///
+    /// Symbols repeat across many waypoints, so they are shared strings, see
+    /// [`ReadOptions::intern_strings`](crate::ReadOptions::intern_strings).
+    pub symbol: Option<Arc<str>>,
 
-    /// Type (classification) of the waypoint.
-    pub type_: Option<String>,
+    /// Type (classification) of the waypoint, shared like the
+    /// [`symbol`](WaypointDetails::symbol).
+    pub type_: Option<Arc<str>>,
 
     // <magvar> degreesType </magvar> [0..1] ?
     /// Height of geoid in meters above WGS 84. This correspond to the sea level.
@@ -357,10 +819,89 @@ pub struct Waypoint {
 
     /// ID of DGPS station used in differential correction, in the range [0, 1023].
     pub dgpsid: Option<u16>,
-    // <extensions> extensionsType </extensions> [0..1] ?
+
+    /// Garmin's `WaypointExtension` from the <extensions> element, holding
+    /// e.g. the depth and proximity alarm set on marine and handheld units.
+    pub waypoint_extension: Option<WaypointExtension>,
+}
+
+/// The details of waypoints that have none.
+#[allow(deprecated)]
+static NO_DETAILS: WaypointDetails = WaypointDetails {
+    name: None,
+    comment: None,
+    description: None,
+    source: None,
+    links: Vec::new(),
+    symbol: None,
+    type_: None,
+    geoidheight: None,
+    fix: None,
+    sat: None,
+    hdop: None,
+    vdop: None,
+    pdop: None,
+    age: None,
+    dgps_age: None,
+    dgpsid: None,
+    waypoint_extension: None,
+};
+
+/// Serializes the details of a waypoint as fields of the waypoint itself,
+/// the way waypoints were serialized before the details were split off.
+#[cfg(feature = "use-serde")]
+mod flat_details {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{WaypointDetails, NO_DETAILS};
+
+    pub fn serialize<S: Serializer>(
+        details: &Option<Box<WaypointDetails>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        details
+            .as_deref()
+            .unwrap_or(&NO_DETAILS)
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Box<WaypointDetails>>, D::Error> {
+        let details = WaypointDetails::deserialize(deserializer)?;
+        Ok((details != NO_DETAILS).then(|| Box::new(details)))
+    }
+}
+
+impl<T: CoordFloat> Default for Waypoint<T> {
+    fn default() -> Waypoint<T> {
+        Waypoint {
+            point: Default::default(),
+            elevation: None,
+            speed: None,
+            time: None,
+            track_point_extension: None,
+            route_point_extension: None,
+            details: None,
+        }
+    }
+}
+
+impl<T: CoordFloat> PartialEq for Waypoint<T> {
+    /// Compares all fields, where waypoints without details equal those
+    /// whose details are all empty.
+    fn eq(&self, other: &Waypoint<T>) -> bool {
+        self.point == other.point
+            && self.elevation == other.elevation
+            && self.speed == other.speed
+            && self.time == other.time
+            && self.track_point_extension == other.track_point_extension
+            && self.route_point_extension == other.route_point_extension
+            && self.details() == other.details()
+    }
 }
 
-impl Waypoint {
+impl<T: CoordFloat> Waypoint<T> {
     /// Gives the geographical point of the waypoint.
     ///
     /// ```
@@ -378,10 +919,44 @@ impl Waypoint {
     ///     println!("waypoint latitude: {}, longitude: {}", point.x(), point.y());
     /// }
     /// ```
-    pub fn point(&self) -> Point<f64> {
+    pub fn point(&self) -> Point<T> {
         self.point.0 //.0 to extract the geo_types::Point from the tuple struct GpxPoint
     }
 
+    /// Moves the waypoint to `point`.
+    pub fn set_point(&mut self, point: Point<T>) {
+        self.point = GpxPoint(point);
+    }
+
+    /// Gives the rarely used fields of the waypoint, which are all empty if
+    /// none of them was ever set.
+    pub fn details(&self) -> &WaypointDetails {
+        self.details.as_deref().unwrap_or(&NO_DETAILS)
+    }
+
+    /// Gives the rarely used fields of the waypoint to change them, creating
+    /// them if the waypoint has none.
+    pub fn details_mut(&mut self) -> &mut WaypointDetails {
+        self.details.get_or_insert_with(Default::default)
+    }
+
+    /// Replaces the rarely used fields of the waypoint, dropping them if they
+    /// are all empty.
+    pub fn set_details(&mut self, details: WaypointDetails) {
+        self.details = (details != NO_DETAILS).then(|| Box::new(details));
+    }
+
+    /// Tells whether the rarely used fields of the waypoint were created,
+    /// which takes memory even if all of them are empty.
+    pub fn has_details(&self) -> bool {
+        self.details.is_some()
+    }
+
+    /// Drops the rarely used fields of the waypoint, freeing their memory.
+    pub fn clear_details(&mut self) {
+        self.details = None;
+    }
+
     /// Creates a new Waypoint from a given geographical point.
     ///
     /// ```
@@ -398,23 +973,97 @@ impl Waypoint {
     ///     wpt.elevation = Some(553.21);
     /// }
     /// ```
-    pub fn new(point: Point<f64>) -> Waypoint {
+    pub fn new(point: Point<T>) -> Waypoint<T> {
         Waypoint {
             point: GpxPoint(point),
             ..Default::default()
         }
     }
+
+    /// Gives a hashable key made of the waypoint's position, elevation and
+    /// time.
+    ///
+    /// Waypoints can't implement `Eq` and `Hash` themselves because of their
+    /// floating point fields, but their keys can be stored in sets and maps,
+    /// e.g. to find duplicate points.
+    ///
+    /// ```
+    /// use std::collections::HashSet;
+    ///
+    /// use geo_types::Point;
+    /// use gpx::Waypoint;
+    ///
+    /// let points = vec![
+    ///     Waypoint::new(Point::new(-121.97, 37.24)),
+    ///     Waypoint::new(Point::new(-121.97, 37.24)),
+    ///     Waypoint::new(Point::new(-121.96, 37.25)),
+    /// ];
+    ///
+    /// let unique: HashSet<_> = points.iter().map(Waypoint::key).collect();
+    /// assert_eq!(unique.len(), 2);
+    /// ```
+    pub fn key(&self) -> WaypointKey {
+        WaypointKey {
+            x: float_bits(self.point().x()),
+            y: float_bits(self.point().y()),
+            elevation: self.elevation.map(float_bits),
+            time: self.time,
+        }
+    }
 }
 
-impl From<Waypoint> for Geometry<f64> {
-    fn from(waypoint: Waypoint) -> Geometry<f64> {
+/// A hashable identity for a [`Waypoint`], see [`Waypoint::key`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WaypointKey {
+    x: u64,
+    y: u64,
+    elevation: Option<u64>,
+    time: Option<Time>,
+}
+
+// Bit pattern of a float, with both zeros mapped to the same value so that
+// keys agree with `PartialEq` on the coordinates.
+fn float_bits<T: CoordFloat>(value: T) -> u64 {
+    let value = value.to_f64().unwrap_or(f64::NAN);
+    if value == 0.0 {
+        0.0f64.to_bits()
+    } else {
+        value.to_bits()
+    }
+}
+
+impl<T: CoordFloat> From<Waypoint<T>> for Geometry<T> {
+    fn from(waypoint: Waypoint<T>) -> Geometry<T> {
         Geometry::Point(waypoint.point())
     }
 }
 
+impl<T: CoordFloat> TryFrom<Geometry<T>> for Waypoint<T> {
+    type Error = GpxError;
+
+    /// Converts a `Geometry::Point` into a Waypoint, failing for any other
+    /// kind of geometry.
+    ///
+    /// ```
+    /// use geo_types::{Geometry, LineString, Point};
+    /// use gpx::Waypoint;
+    ///
+    /// let geometry = Geometry::Point(Point::new(-121.97, 37.24));
+    /// let wpt = Waypoint::try_from(geometry).unwrap();
+    /// assert_eq!(wpt.point(), Point::new(-121.97, 37.24));
+    ///
+    /// let geometry = Geometry::LineString(LineString::from(vec![(0.0, 0.0)]));
+    /// assert!(Waypoint::try_from(geometry).is_err());
+    /// ```
+    fn try_from(geometry: Geometry<T>) -> Result<Waypoint<T>, GpxError> {
+        Ok(Waypoint::new(Point::try_from(geometry)?))
+    }
+}
+
 /// Person represents a person or organization.
-#[derive(Clone, Default, Debug, PartialEq)]
+#[derive(Clone, Default, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Person {
     /// Name of person or organization.
     pub name: Option<String>,
@@ -430,8 +1079,9 @@ pub struct Person {
 ///
 /// An external resource could be a web page, digital photo,
 /// video clip, etc., with additional information.
-#[derive(Clone, Default, Debug, PartialEq)]
+#[derive(Clone, Default, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Link {
     /// URL of hyperlink.
     pub href: String,
@@ -444,8 +1094,9 @@ pub struct Link {
 }
 
 /// Type of the GPS fix.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Fix {
     /// The GPS had no fix. To signify "the fix info is unknown", leave out the Fix entirely.
     None,
@@ -460,3 +1111,397 @@ pub enum Fix {
     /// Other values that are not in the specification.
     Other(String),
 }
+
+/// Garmin's `TrackPointExtension`, recorded by fitness devices for each
+/// track point. See
+/// <https://www8.garmin.com/xmlschemas/TrackPointExtensionv2.xsd>.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct TrackPointExtension {
+    /// Air temperature in degrees Celsius, from <atemp>.
+    pub air_temperature: Option<f64>,
+
+    /// Water temperature in degrees Celsius, from <wtemp>.
+    pub water_temperature: Option<f64>,
+
+    /// Depth in meters, from <depth>.
+    pub depth: Option<f64>,
+
+    /// Heart rate in beats per minute, from <hr>.
+    pub heart_rate: Option<u16>,
+
+    /// Cadence in revolutions per minute, from <cad>.
+    pub cadence: Option<u16>,
+}
+
+/// Garmin's `WaypointExtension` of waypoints, from the GPX extensions of
+/// Garmin devices and BaseCamp. See
+/// <https://www8.garmin.com/xmlschemas/GpxExtensionsv3.xsd>.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct WaypointExtension {
+    /// Distance in meters at which the device alerts of the waypoint, from
+    /// <Proximity>.
+    pub proximity: Option<f64>,
+
+    /// Temperature in degrees Celsius, from <Temperature>.
+    pub temperature: Option<f64>,
+
+    /// Water depth in meters, from <Depth>.
+    pub depth: Option<f64>,
+
+    /// How the waypoint is shown on the map, from <DisplayMode>.
+    pub display_mode: Option<DisplayMode>,
+
+    /// Postal address of the place, from <Address>.
+    pub address: Option<Address>,
+
+    /// Phone numbers of the place, from the <PhoneNumber> elements.
+    pub phone_numbers: Vec<PhoneNumber>,
+}
+
+/// A postal address of Garmin's extensions, as found in points of interest.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Address {
+    /// Lines of the street address, from the <StreetAddress> elements, of
+    /// which the specification allows two.
+    pub street_address: Vec<String>,
+
+    /// City, from <City>.
+    pub city: Option<String>,
+
+    /// State or province, from <State>.
+    pub state: Option<String>,
+
+    /// Country, from <Country>.
+    pub country: Option<String>,
+
+    /// Postal code, from <PostalCode>.
+    pub postal_code: Option<String>,
+}
+
+/// A phone number of Garmin's extensions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct PhoneNumber {
+    /// The number, as written.
+    pub number: String,
+
+    /// What the number is for, like "Fax", from the `Category` attribute.
+    pub category: Option<String>,
+}
+
+/// Garmin's `RoutePointExtension` of route points, written by devices and
+/// BaseCamp for routes calculated along roads. See
+/// <https://www8.garmin.com/xmlschemas/GpxExtensionsv3.xsd>.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct RoutePointExtension<T: CoordFloat = f64> {
+    /// Identifies the map feature of the route point, as hexadecimal digits,
+    /// from <Subclass>.
+    pub subclass: Option<String>,
+
+    /// The shape points between this route point and the next, following
+    /// the road, from the <rpt> elements.
+    pub points: Vec<Point<T>>,
+}
+
+impl<T: CoordFloat> Default for RoutePointExtension<T> {
+    fn default() -> RoutePointExtension<T> {
+        RoutePointExtension {
+            subclass: None,
+            points: Vec::new(),
+        }
+    }
+}
+
+/// How a device shows a waypoint on the map.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum DisplayMode {
+    /// Just the symbol.
+    SymbolOnly,
+    /// The symbol and the name.
+    SymbolAndName,
+    /// The symbol and the description.
+    SymbolAndDescription,
+    /// Other values that are not in the specification.
+    Other(String),
+}
+
+/// A color of Garmin devices, used for tracks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum DisplayColor {
+    Black,
+    DarkRed,
+    DarkGreen,
+    DarkYellow,
+    DarkBlue,
+    DarkMagenta,
+    DarkCyan,
+    LightGray,
+    DarkGray,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Transparent,
+    /// Other values that are not in the specification.
+    Other(String),
+}
+
+impl DisplayColor {
+    const NAMED: [(DisplayColor, &'static str); 17] = [
+        (DisplayColor::Black, "Black"),
+        (DisplayColor::DarkRed, "DarkRed"),
+        (DisplayColor::DarkGreen, "DarkGreen"),
+        (DisplayColor::DarkYellow, "DarkYellow"),
+        (DisplayColor::DarkBlue, "DarkBlue"),
+        (DisplayColor::DarkMagenta, "DarkMagenta"),
+        (DisplayColor::DarkCyan, "DarkCyan"),
+        (DisplayColor::LightGray, "LightGray"),
+        (DisplayColor::DarkGray, "DarkGray"),
+        (DisplayColor::Red, "Red"),
+        (DisplayColor::Green, "Green"),
+        (DisplayColor::Yellow, "Yellow"),
+        (DisplayColor::Blue, "Blue"),
+        (DisplayColor::Magenta, "Magenta"),
+        (DisplayColor::Cyan, "Cyan"),
+        (DisplayColor::White, "White"),
+        (DisplayColor::Transparent, "Transparent"),
+    ];
+
+    /// Gives the name of the color in GPX files, like `DarkRed`.
+    ///
+    /// ```
+    /// use gpx::DisplayColor;
+    ///
+    /// assert_eq!(DisplayColor::DarkRed.name(), "DarkRed");
+    /// assert_eq!(DisplayColor::from("Red".to_string()), DisplayColor::Red);
+    /// ```
+    pub fn name(&self) -> &str {
+        match self {
+            DisplayColor::Other(name) => name,
+            color => {
+                let (_, name) = DisplayColor::NAMED
+                    .iter()
+                    .find(|(named, _)| named == color)
+                    .expect("all colors are named");
+                name
+            }
+        }
+    }
+}
+
+impl From<String> for DisplayColor {
+    /// Gives the color with the name `name`, or [`DisplayColor::Other`].
+    fn from(name: String) -> DisplayColor {
+        DisplayColor::NAMED
+            .into_iter()
+            .find(|(_, named)| *named == name)
+            .map_or(DisplayColor::Other(name), |(color, _)| color)
+    }
+}
+
+/// How a track is drawn, from the `line` element of the `gpx_style`
+/// extension. See <https://www.topografix.com/GPX/gpx_style/0/2/gpx_style.xsd>.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "use-serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct LineStyle {
+    /// Color of the line as hexadecimal RGB, like `00D7D7`, from <color>.
+    pub color: Option<String>,
+
+    /// Opacity of the line from 0 to 1, from <opacity>.
+    pub opacity: Option<f64>,
+
+    /// Width of the line in millimeters, from <width>.
+    pub width: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::Point;
+
+    use super::*;
+
+    fn segment(coords: &[(f64, f64)]) -> TrackSegment {
+        TrackSegment {
+            points: coords
+                .iter()
+                .map(|&(x, y)| Waypoint::new(Point::new(x, y)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn linestring_round_trip() {
+        let linestring: LineString<f64> = vec![(1.0, 2.0), (3.0, 4.0)].into();
+
+        let segment = TrackSegment::from(linestring.clone());
+        assert_eq!(segment.points[1].point(), Point::new(3.0, 4.0));
+        assert_eq!(segment.linestring(), linestring);
+
+        let route = Route::from(linestring.clone());
+        assert_eq!(route.linestring(), linestring);
+    }
+
+    #[test]
+    fn try_from_geometry() {
+        let linestring: LineString<f64> = vec![(1.0, 2.0), (3.0, 4.0)].into();
+        let multilinestring = MultiLineString::new(vec![linestring.clone()]);
+
+        let track = Track::try_from(Geometry::from(multilinestring)).unwrap();
+        assert_eq!(track.segments.len(), 1);
+        let segment = TrackSegment::try_from(Geometry::from(linestring.clone())).unwrap();
+        assert_eq!(segment.points.len(), 2);
+        let route = Route::try_from(Geometry::from(linestring.clone())).unwrap();
+        assert_eq!(route.points.len(), 2);
+
+        assert!(Track::try_from(Geometry::from(linestring)).is_err());
+        assert!(Route::try_from(Geometry::from(Point::new(1.0, 2.0))).is_err());
+    }
+
+    #[test]
+    fn track_retain_points_prunes_emptied_segments() {
+        let mut track = Track::new();
+        track.segments.push(segment(&[(1.0, 1.0), (0.0, 0.0)]));
+        track.segments.push(segment(&[(0.0, 0.0)]));
+        track.segments.push(TrackSegment::new());
+
+        track.retain_points(|wpt| wpt.point() != Point::new(0.0, 0.0));
+
+        assert_eq!(track.segments.len(), 2);
+        assert_eq!(track.segments[0].points.len(), 1);
+        assert!(track.segments[1].points.is_empty());
+    }
+
+    #[test]
+    fn gpx_retain_points_prunes_emptied_tracks_and_routes() {
+        let mut track = Track::new();
+        track.segments.push(segment(&[(0.0, 0.0)]));
+        let route = Route {
+            points: segment(&[(0.0, 0.0), (2.0, 2.0)]).points,
+            ..Default::default()
+        };
+        let mut gpx = Gpx {
+            waypoints: segment(&[(0.0, 0.0), (3.0, 3.0)]).points,
+            tracks: vec![track, Track::new()],
+            routes: vec![route],
+            ..Default::default()
+        };
+
+        gpx.retain_points(|wpt| wpt.point() != Point::new(0.0, 0.0));
+
+        assert_eq!(gpx.waypoints.len(), 1);
+        assert_eq!(gpx.tracks.len(), 1);
+        assert!(gpx.tracks[0].segments.is_empty());
+        assert_eq!(gpx.routes.len(), 1);
+        assert_eq!(gpx.routes[0].points.len(), 1);
+    }
+
+    #[test]
+    fn gpx_map_points() {
+        let mut route_point = Waypoint::new(Point::new(1.0, 1.0));
+        route_point.route_point_extension = Some(Box::new(RoutePointExtension {
+            subclass: None,
+            points: vec![Point::new(2.0, 2.0)],
+        }));
+        let mut route = Route::new();
+        route.points.push(route_point);
+        let mut track = Track::new();
+        track.segments.push(segment(&[(3.0, 3.0)]));
+        let mut gpx = Gpx {
+            waypoints: vec![Waypoint::new(Point::new(4.0, 4.0))],
+            tracks: vec![track],
+            routes: vec![route],
+            ..Default::default()
+        };
+
+        gpx.map_points(|point| Point::new(point.x() * 10.0, -point.y()));
+        assert_eq!(gpx.waypoints[0].point(), Point::new(40.0, -4.0));
+        assert_eq!(
+            gpx.tracks[0].segments[0].points[0].point(),
+            Point::new(30.0, -3.0)
+        );
+        let route_point = &gpx.routes[0].points[0];
+        assert_eq!(route_point.point(), Point::new(10.0, -1.0));
+        let extension = route_point.route_point_extension.as_ref().unwrap();
+        assert_eq!(extension.points, [Point::new(20.0, -2.0)]);
+        assert!(!gpx.waypoints[0].has_details());
+    }
+
+    #[test]
+    fn waypoint_details() {
+        assert!(std::mem::size_of::<Waypoint>() <= 160);
+
+        let mut waypoint = Waypoint::new(Point::new(1.0, 2.0));
+        let plain = waypoint.clone();
+        assert_eq!(waypoint.details().hdop, None);
+        assert!(!waypoint.has_details());
+
+        waypoint.details_mut().links.clear();
+        assert!(waypoint.has_details());
+        assert_eq!(waypoint, plain);
+
+        waypoint.details_mut().hdop = Some(1.5);
+        assert_ne!(waypoint, plain);
+        waypoint.clear_details();
+        assert!(!waypoint.has_details());
+    }
+
+    #[cfg(all(feature = "use-serde", feature = "json"))]
+    #[test]
+    fn waypoint_details_serde() {
+        let mut waypoint = Waypoint::new(Point::new(1.0, 2.0));
+        waypoint.details_mut().name = Some("Summit".into());
+        let value = serde_json::to_value(&waypoint).unwrap();
+        assert_eq!(value["name"], "Summit");
+        assert!(value.get("details").is_none());
+        let read: Waypoint = serde_json::from_value(value).unwrap();
+        assert_eq!(read, waypoint);
+
+        let plain = serde_json::to_value(Waypoint::new(Point::new(1.0, 2.0))).unwrap();
+        assert_eq!(plain["name"], serde_json::Value::Null);
+        let read: Waypoint = serde_json::from_value(plain).unwrap();
+        assert!(!read.has_details());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_documents() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let bytes: Vec<u8> = (0..4096)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1);
+                (state >> 56) as u8
+            })
+            .collect();
+        let mut u = Unstructured::new(&bytes);
+        for _ in 0..20 {
+            let waypoint = Waypoint::<f64>::arbitrary(&mut u).unwrap();
+            assert!((-90.0..=90.0).contains(&waypoint.point().y()));
+            assert!((-180.0..180.0).contains(&waypoint.point().x()));
+            if let Some(time) = waypoint.time {
+                assert!(time.format().is_ok());
+            }
+        }
+        assert!(Gpx::<f32>::arbitrary(&mut u).is_ok());
+    }
+}
@@ -0,0 +1,44 @@
+//! wasm exposes reading and writing to JavaScript, for browser-based GPX
+//! editors built with `wasm-bindgen` for `wasm32-unknown-unknown`.
+//!
+//! Documents cross the boundary in the stable JSON representation of
+//! [`crate::json`]. Errors are thrown as JavaScript `Error`s named
+//! `GpxError`, with a `code` property holding the
+//! [`code`](crate::errors::GpxErrorKind::code) of the error's kind, so
+//! callers can tell bad input from bugs:
+//!
+//! ```js
+//! try {
+//!   const gpx = JSON.parse(gpxToJson(new Uint8Array(await file.arrayBuffer())));
+//! } catch (error) {
+//!   if (error.code === "structure") { /* not a valid GPX file */ }
+//! }
+//! ```
+
+use js_sys::{Error, Reflect};
+use wasm_bindgen::prelude::*;
+
+use crate::errors::GpxError;
+use crate::{json, read_from_slice, write_to_vec};
+
+impl From<GpxError> for JsValue {
+    fn from(error: GpxError) -> JsValue {
+        let js_error = Error::new(&error.to_string());
+        js_error.set_name("GpxError");
+        // Setting a property on a fresh `Error` can't fail.
+        let _ = Reflect::set(&js_error, &"code".into(), &error.kind().code().into());
+        js_error.into()
+    }
+}
+
+/// Reads a GPX file and gives its JSON representation.
+#[wasm_bindgen(js_name = gpxToJson)]
+pub fn gpx_to_json(data: &[u8]) -> Result<String, JsValue> {
+    Ok(json::to_string(&read_from_slice(data)?)?)
+}
+
+/// Writes the JSON representation of a document as a GPX file.
+#[wasm_bindgen(js_name = jsonToGpx)]
+pub fn json_to_gpx(json: &str) -> Result<Vec<u8>, JsValue> {
+    Ok(write_to_vec(&json::from_str(json)?)?)
+}
@@ -1,14 +1,170 @@
 //! Writes an activity to GPX format.
 
+use std::borrow::Cow;
+use std::fmt::Display;
 use std::io::Write;
 
-use geo_types::Rect;
+use geo_types::{CoordFloat, Rect};
+use xml::attribute::OwnedAttribute;
+use xml::name::Name;
+use xml::namespace::{Namespace, NS_NO_PREFIX};
+use xml::reader::XmlEvent as ReaderEvent;
+use xml::writer::events::StartElementBuilder;
 use xml::writer::{EmitterConfig, EventWriter, XmlEvent};
+use xml::{EventReader, ParserConfig};
 
 use crate::errors::{GpxError, GpxResult};
+use crate::measure::bounds;
+use crate::number;
 use crate::parser::time::Time;
 use crate::types::*;
-use crate::{Gpx, GpxVersion};
+use crate::{CommentPosition, Gpx, GpxVersion, RangePolicy};
+
+pub(crate) const TRACK_POINT_EXTENSION_NAMESPACE: &str =
+    "http://www.garmin.com/xmlschemas/TrackPointExtension/v2";
+pub(crate) const GPX_EXTENSIONS_NAMESPACE: &str =
+    "http://www.garmin.com/xmlschemas/GpxExtensions/v3";
+pub(crate) const GPX_STYLE_NAMESPACE: &str = "http://www.topografix.com/GPX/gpx_style/0/2";
+
+/// The namespaces of the extensions a document uses, declared once on its
+/// root element so that all extensions share the same prefixes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Namespaces {
+    /// Garmin's GpxExtensions, written with the `gpxx` prefix.
+    gpx_extensions: bool,
+    /// Garmin's TrackPointExtension, written with the `gpxtpx` prefix.
+    track_point_extension: bool,
+    /// The styles of gpx_style, written with the `gpx_style` prefix.
+    gpx_style: bool,
+}
+
+impl Namespaces {
+    /// Collects the namespaces of the extensions that will be written for
+    /// the `selection` of `gpx`. GPX 1.0 documents don't get any extensions.
+    fn of<T: CoordFloat>(gpx: &Gpx<T>, selection: &Selection) -> Namespaces {
+        let mut namespaces = Namespaces::default();
+        if gpx.version == GpxVersion::Gpx10 {
+            return namespaces;
+        }
+        for waypoint in selection.points(gpx) {
+            namespaces.gpx_extensions |= waypoint.details().waypoint_extension.is_some()
+                || waypoint.route_point_extension.is_some();
+            namespaces.track_point_extension |= waypoint.track_point_extension.is_some();
+        }
+        for &index in &selection.tracks {
+            let track = &gpx.tracks[index];
+            namespaces.gpx_extensions |= track.display_color.is_some();
+            namespaces.gpx_style |= track.line_style.is_some();
+        }
+        namespaces
+    }
+
+    /// Checks whether the namespace with `prefix` is declared by
+    /// [`declare`](Namespaces::declare).
+    fn declares(self, prefix: &str) -> bool {
+        match prefix {
+            "gpxx" => self.gpx_extensions,
+            "gpxtpx" => self.track_point_extension,
+            "gpx_style" => self.gpx_style,
+            _ => false,
+        }
+    }
+
+    /// Adds the declarations of the namespaces to the root `element`.
+    fn declare(self, mut element: StartElementBuilder<'_>) -> StartElementBuilder<'_> {
+        if self.gpx_extensions {
+            element = element.ns("gpxx", GPX_EXTENSIONS_NAMESPACE);
+        }
+        if self.track_point_extension {
+            element = element.ns("gpxtpx", TRACK_POINT_EXTENSION_NAMESPACE);
+        }
+        if self.gpx_style {
+            element = element.ns("gpx_style", GPX_STYLE_NAMESPACE);
+        }
+        element
+    }
+}
+
+/// The indices of the waypoints, tracks and routes of a document that are
+/// written, see [`Gpx::write_selection`].
+struct Selection {
+    waypoints: Vec<usize>,
+    tracks: Vec<usize>,
+    routes: Vec<usize>,
+    /// Whether every item is selected.
+    all: bool,
+}
+
+impl Selection {
+    /// Asks `select` about each item of `gpx`, once.
+    fn new<T: CoordFloat>(
+        gpx: &Gpx<T>,
+        select: &mut dyn FnMut(DocumentItem<'_, T>) -> bool,
+    ) -> Selection {
+        let waypoints: Vec<usize> = (0..gpx.waypoints.len())
+            .filter(|&index| {
+                select(DocumentItem::Waypoint {
+                    index,
+                    waypoint: &gpx.waypoints[index],
+                })
+            })
+            .collect();
+        let tracks: Vec<usize> = (0..gpx.tracks.len())
+            .filter(|&index| {
+                select(DocumentItem::Track {
+                    index,
+                    track: &gpx.tracks[index],
+                })
+            })
+            .collect();
+        let routes: Vec<usize> = (0..gpx.routes.len())
+            .filter(|&index| {
+                select(DocumentItem::Route {
+                    index,
+                    route: &gpx.routes[index],
+                })
+            })
+            .collect();
+        let all = waypoints.len() == gpx.waypoints.len()
+            && tracks.len() == gpx.tracks.len()
+            && routes.len() == gpx.routes.len();
+        Selection {
+            waypoints,
+            tracks,
+            routes,
+            all,
+        }
+    }
+
+    /// The selected waypoints, route points and track points of `gpx`.
+    fn points<'a, T: CoordFloat>(
+        &'a self,
+        gpx: &'a Gpx<T>,
+    ) -> impl Iterator<Item = &'a Waypoint<T>> {
+        let waypoints = self.waypoints.iter().map(|&index| &gpx.waypoints[index]);
+        let route_points = self
+            .routes
+            .iter()
+            .flat_map(|&index| &gpx.routes[index].points);
+        let track_points = self
+            .tracks
+            .iter()
+            .flat_map(|&index| &gpx.tracks[index].segments)
+            .flat_map(|segment| &segment.points);
+        waypoints.chain(route_points).chain(track_points)
+    }
+
+    /// The bounds to write in the metadata of `gpx`: those of the metadata
+    /// when everything is selected, else those of the selected points, so
+    /// that they don't cover items left out.
+    fn bounds<T: CoordFloat>(&self, gpx: &Gpx<T>) -> Option<Rect<T>> {
+        let metadata_bounds = gpx.metadata.as_ref()?.bounds;
+        if self.all || metadata_bounds.is_none() {
+            return metadata_bounds;
+        }
+        bounds(self.points(gpx))
+    }
+}
 
 /// Writes an activity to GPX format.
 ///
@@ -28,11 +184,118 @@ use crate::{Gpx, GpxVersion};
 /// // You can give it anything that implements `std::io::Write`.
 /// write(&data, std::io::stdout()).unwrap();
 /// ```
-pub fn write<W: Write>(gpx: &Gpx, writer: W) -> GpxResult<()> {
+pub fn write<W: Write, T: CoordFloat + Display>(gpx: &Gpx<T>, writer: W) -> GpxResult<()> {
+    write_with_options(gpx, writer, &WriteOptions::default())
+}
+
+/// Options used when writing a document with [`write_with_options`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WriteOptions {
+    /// Writes emails that can't be split into an id and a domain, like
+    /// `info` or `a@b@c`, as the text of `<email>` instead of failing with
+    /// [`GpxError::MissingEmailPartError`] or [`GpxError::TooManyAtsError`].
+    ///
+    /// Such emails aren't valid GPX 1.1, but are read back unchanged.
+    pub raw_emails: bool,
+
+    /// What to do with values of bounded fields outside of their range,
+    /// like a `dgpsid` above 1023.
+    pub out_of_range: RangePolicy,
+
+    /// Writes the elements of GPX with this namespace prefix, like
+    /// `<g:gpx xmlns:g="…"><g:trk>`, instead of declaring GPX as the
+    /// default namespace.
+    ///
+    /// The document is first written to memory, then rewritten with the
+    /// prefix, which takes about twice the time and memory.
+    pub prefix: Option<&'static str>,
+
+    /// What to do with tracks, segments and routes without points, which
+    /// some validators reject.
+    pub empty: EmptyPolicy,
+}
+
+/// What to do with empty tracks, segments and routes when writing, see
+/// [`WriteOptions::empty`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EmptyPolicy {
+    /// Writes them as they are, like `<trkseg/>`.
+    #[default]
+    Write,
+    /// Leaves them out. Tracks whose segments are all empty are left out
+    /// as well.
+    Skip,
+    /// Fails with [`GpxError::EmptyElement`].
+    Error,
+}
+
+impl EmptyPolicy {
+    /// Tells whether the element `name` should be written, given whether it
+    /// is `empty`.
+    fn keep(self, name: &'static str, empty: bool) -> GpxResult<bool> {
+        match self {
+            _ if !empty => Ok(true),
+            EmptyPolicy::Write => Ok(true),
+            EmptyPolicy::Skip => Ok(false),
+            EmptyPolicy::Error => Err(GpxError::EmptyElement(name)),
+        }
+    }
+}
+
+/// Writes an activity to GPX format, like [`write`] but with `options`.
+///
+/// ```
+/// use gpx::{write_with_options, Gpx, GpxVersion, Metadata, Person, WriteOptions};
+///
+/// let data: Gpx = Gpx {
+///     version: GpxVersion::Gpx11,
+///     metadata: Some(Metadata {
+///         author: Some(Person {
+///             email: Some("info".to_string()),
+///             ..Default::default()
+///         }),
+///         ..Default::default()
+///     }),
+///     ..Default::default()
+/// };
+/// assert!(gpx::write_to_vec(&data).is_err());
+///
+/// let options = WriteOptions {
+///     raw_emails: true,
+///     ..Default::default()
+/// };
+/// let mut bytes = Vec::new();
+/// write_with_options(&data, &mut bytes, &options).unwrap();
+/// let read = gpx::read_from_slice(&bytes).unwrap();
+/// assert_eq!(read.metadata.unwrap().author.unwrap().email.as_deref(), Some("info"));
+/// ```
+pub fn write_with_options<W: Write, T: CoordFloat + Display>(
+    gpx: &Gpx<T>,
+    writer: W,
+    options: &WriteOptions,
+) -> GpxResult<()> {
     let mut writer = EmitterConfig::new()
         .perform_indent(true)
         .create_writer(writer);
-    write_with_event_writer(gpx, &mut writer)
+    write_document(gpx, &mut writer, options, &mut |_| true, &mut NoHook)
+}
+
+/// Writes an activity to GPX format into a new buffer.
+///
+/// ```
+/// use gpx::{write_to_vec, Gpx, GpxVersion};
+///
+/// let data: Gpx = Gpx {
+///     version: GpxVersion::Gpx11,
+///     ..Default::default()
+/// };
+/// let bytes = write_to_vec(&data).unwrap();
+/// assert_eq!(gpx::read_from_slice(&bytes).unwrap().version, GpxVersion::Gpx11);
+/// ```
+pub fn write_to_vec<T: CoordFloat + Display>(gpx: &Gpx<T>) -> GpxResult<Vec<u8>> {
+    let mut bytes = Vec::new();
+    write(gpx, &mut bytes)?;
+    Ok(bytes)
 }
 
 /// Writes an activity to GPX format.
@@ -56,33 +319,526 @@ pub fn write<W: Write>(gpx: &Gpx, writer: W) -> GpxResult<()> {
 ///
 /// write_with_event_writer(&data, &mut writer).unwrap();
 /// ```
-pub fn write_with_event_writer<W: Write>(gpx: &Gpx, writer: &mut EventWriter<W>) -> GpxResult<()> {
+pub fn write_with_event_writer<W: Write, T: CoordFloat + Display>(
+    gpx: &Gpx<T>,
+    writer: &mut EventWriter<W>,
+) -> GpxResult<()> {
+    write_document(
+        gpx,
+        writer,
+        &WriteOptions::default(),
+        &mut |_| true,
+        &mut NoHook,
+    )
+}
+
+/// A waypoint, track or route of a document, see [`Gpx::write_selection`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DocumentItem<'a, T: CoordFloat = f64> {
+    /// The waypoint at `index` of [`Gpx::waypoints`].
+    Waypoint {
+        index: usize,
+        waypoint: &'a Waypoint<T>,
+    },
+    /// The track at `index` of [`Gpx::tracks`].
+    Track { index: usize, track: &'a Track<T> },
+    /// The route at `index` of [`Gpx::routes`].
+    Route { index: usize, route: &'a Route<T> },
+}
+
+impl<T: CoordFloat + Display> Gpx<T> {
+    /// Writes the document with just the waypoints, tracks and routes for
+    /// which `select` returns `true`, e.g. those picked in an export dialog,
+    /// without copying them into a new document. The namespaces of the root
+    /// element and the bounds of the metadata only cover the selected items;
+    /// the rest of the metadata is written as it is.
+    ///
+    /// ```
+    /// use geo_types::Point;
+    /// use gpx::{DocumentItem, Gpx, GpxVersion, Track, Waypoint, WriteOptions};
+    ///
+    /// let mut gpx: Gpx = Gpx {
+    ///     version: GpxVersion::Gpx11,
+    ///     ..Default::default()
+    /// };
+    /// gpx.waypoints.push(Waypoint::new(Point::new(-121.97, 37.24)));
+    /// for name in ["Morning", "Evening"] {
+    ///     let mut track = Track::new();
+    ///     track.name = Some(name.to_string());
+    ///     gpx.tracks.push(track);
+    /// }
+    ///
+    /// let mut bytes = Vec::new();
+    /// gpx.write_selection(&mut bytes, &WriteOptions::default(), |item| match item {
+    ///     DocumentItem::Track { track, .. } => track.name.as_deref() == Some("Evening"),
+    ///     _ => false,
+    /// })
+    /// .unwrap();
+    /// let written = gpx::read_from_slice(&bytes).unwrap();
+    /// assert!(written.waypoints.is_empty());
+    /// assert_eq!(written.tracks.len(), 1);
+    /// assert_eq!(written.tracks[0].name.as_deref(), Some("Evening"));
+    /// ```
+    pub fn write_selection<W: Write>(
+        &self,
+        writer: W,
+        options: &WriteOptions,
+        mut select: impl FnMut(DocumentItem<'_, T>) -> bool,
+    ) -> GpxResult<()> {
+        let mut writer = EmitterConfig::new()
+            .perform_indent(true)
+            .create_writer(writer);
+        write_document(self, &mut writer, options, &mut select, &mut NoHook)
+    }
+}
+
+/// An element a [`WriteHook`] is called for, with the data it is written
+/// from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HookElement<'a, T: CoordFloat = f64> {
+    /// The `metadata` element, or the metadata fields of GPX 1.0.
+    Metadata(&'a Metadata<T>),
+    /// A `wpt` element.
+    Waypoint(&'a Waypoint<T>),
+    /// A `rtept` element.
+    RoutePoint(&'a Waypoint<T>),
+    /// A `trkpt` element.
+    TrackPoint(&'a Waypoint<T>),
+    /// A `rte` element, before its points.
+    Route(&'a Route<T>),
+    /// A `trk` element, before its segments.
+    Track(&'a Track<T>),
+}
+
+/// Adds custom XML, like vendor extensions, to the elements of a document
+/// while it is written, see [`write_with_hook`].
+///
+/// Closures taking a [`HookElement`] and an [`ExtensionWriter`] are hooks
+/// too.
+pub trait WriteHook<T: CoordFloat = f64> {
+    /// Writes the extra content of `element` with `writer`, if any.
+    fn write_extensions(
+        &mut self,
+        element: HookElement<'_, T>,
+        writer: &mut ExtensionWriter<'_>,
+    ) -> GpxResult<()>;
+}
+
+impl<T, F> WriteHook<T> for F
+where
+    T: CoordFloat,
+    F: FnMut(HookElement<'_, T>, &mut ExtensionWriter<'_>) -> GpxResult<()>,
+{
+    fn write_extensions(
+        &mut self,
+        element: HookElement<'_, T>,
+        writer: &mut ExtensionWriter<'_>,
+    ) -> GpxResult<()> {
+        self(element, writer)
+    }
+}
+
+/// The hook of writers without one.
+pub(crate) struct NoHook;
+
+impl<T: CoordFloat> WriteHook<T> for NoHook {
+    fn write_extensions(
+        &mut self,
+        _: HookElement<'_, T>,
+        _: &mut ExtensionWriter<'_>,
+    ) -> GpxResult<()> {
+        Ok(())
+    }
+}
+
+/// Something XML events can be written to, to hand an [`EventWriter`] of
+/// any sink to hooks.
+trait EventSink {
+    fn write_event(&mut self, event: XmlEvent<'_>) -> GpxResult<()>;
+}
+
+impl<W: Write> EventSink for EventWriter<W> {
+    fn write_event(&mut self, event: XmlEvent<'_>) -> GpxResult<()> {
+        write_xml_event(event, self)
+    }
+}
+
+/// Writes the content a [`WriteHook`] adds to an element.
+///
+/// In GPX 1.1 documents, the content goes into the `extensions` element,
+/// which is started before the first event if the element has no other
+/// extensions. In GPX 1.0 documents, it is written as the last children of
+/// the element, or of the root element for the metadata.
+pub struct ExtensionWriter<'a> {
+    sink: &'a mut dyn EventSink,
+    /// Whether an `extensions` element still has to be started before the
+    /// first event.
+    start: bool,
+    /// Whether an `extensions` element was started, to be ended after the
+    /// hook.
+    started: bool,
+}
+
+impl ExtensionWriter<'_> {
+    /// Writes an XML event, like the start of an element. Prefixes have to
+    /// be declared, e.g. on the root element through
+    /// [`Gpx::root_attributes`], or with
+    /// [`ns`](xml::writer::events::StartElementBuilder::ns) on the element
+    /// using them.
+    pub fn write<'e>(&mut self, event: impl Into<XmlEvent<'e>>) -> GpxResult<()> {
+        if self.start {
+            self.start = false;
+            self.started = true;
+            self.sink
+                .write_event(XmlEvent::start_element("extensions").into())?;
+        }
+        self.sink.write_event(event.into())
+    }
+
+    /// Writes an element `name` with the text `value`, like
+    /// `<ql:rating>4</ql:rating>`.
+    pub fn write_text(&mut self, name: &str, value: &str) -> GpxResult<()> {
+        self.write(XmlEvent::start_element(name))?;
+        self.write(XmlEvent::characters(value))?;
+        self.write(XmlEvent::end_element())
+    }
+}
+
+/// Calls `hook` for `element` of a document of `version`, whose
+/// `extensions` element is already started if `open`.
+fn call_hook<W: Write, T: CoordFloat>(
+    hook: &mut dyn WriteHook<T>,
+    element: HookElement<'_, T>,
+    version: GpxVersion,
+    open: bool,
+    writer: &mut EventWriter<W>,
+) -> GpxResult<()> {
+    let mut extensions = ExtensionWriter {
+        sink: writer,
+        start: version != GpxVersion::Gpx10 && !open,
+        started: false,
+    };
+    hook.write_extensions(element, &mut extensions)?;
+    if extensions.started {
+        write_xml_event(XmlEvent::end_element(), writer)?;
+    }
+    Ok(())
+}
+
+/// Writes an activity to GPX format like [`write_with_options`], letting
+/// `hook` add custom XML, like vendor extensions, to the metadata,
+/// waypoints, routes, route points, tracks and track points.
+///
+/// ```
+/// use geo_types::Point;
+/// use gpx::{write_with_hook, ExtensionWriter, Gpx, GpxVersion, HookElement, Waypoint};
+///
+/// let mut gpx: Gpx = Gpx {
+///     version: GpxVersion::Gpx11,
+///     root_attributes: vec![("xmlns:ql".to_string(), "https://example.com/ql".to_string())],
+///     ..Default::default()
+/// };
+/// gpx.waypoints.push(Waypoint::new(Point::new(-121.97, 37.24)));
+///
+/// let mut bytes = Vec::new();
+/// let mut rate = |element: HookElement<'_>, writer: &mut ExtensionWriter<'_>| match element {
+///     HookElement::Waypoint(_) => writer.write_text("ql:rating", "4"),
+///     _ => Ok(()),
+/// };
+/// write_with_hook(&gpx, &mut bytes, &Default::default(), &mut rate).unwrap();
+///
+/// let written = String::from_utf8(bytes).unwrap();
+/// assert!(written.contains("<extensions>\n      <ql:rating>4</ql:rating>"));
+/// ```
+pub fn write_with_hook<W: Write, T: CoordFloat + Display>(
+    gpx: &Gpx<T>,
+    writer: W,
+    options: &WriteOptions,
+    hook: &mut dyn WriteHook<T>,
+) -> GpxResult<()> {
+    let mut writer = EmitterConfig::new()
+        .perform_indent(true)
+        .create_writer(writer);
+    write_document(gpx, &mut writer, options, &mut |_| true, hook)
+}
+
+/// Writes a GPX 1.1 document with a single track whose points come from
+/// `points`, so that generated or database-backed points can be written
+/// without collecting them into a [`Gpx`] first.
+///
+/// The name, description and other fields of the track are taken from
+/// `meta`, whose segments are ignored. All points go into one segment.
+///
+/// ```
+/// use geo_types::Point;
+/// use gpx::{write_track_from_iter, Track, Waypoint};
+///
+/// let mut meta: Track = Track::new();
+/// meta.name = Some("Generated".to_string());
+/// let points = (0..1000).map(|i| Waypoint::new(Point::new(f64::from(i) * 1e-4, 47.0)));
+///
+/// let mut bytes = Vec::new();
+/// write_track_from_iter(&meta, points, &mut bytes).unwrap();
+/// let gpx = gpx::read_from_slice(&bytes).unwrap();
+/// assert_eq!(gpx.tracks[0].name.as_deref(), Some("Generated"));
+/// assert_eq!(gpx.tracks[0].segments[0].points.len(), 1000);
+/// ```
+pub fn write_track_from_iter<W, T, I>(meta: &Track<T>, points: I, writer: W) -> GpxResult<()>
+where
+    W: Write,
+    T: CoordFloat + Display,
+    I: IntoIterator<Item = Waypoint<T>>,
+{
+    let options = WriteOptions::default();
+    let mut writer = EmitterConfig::new()
+        .perform_indent(true)
+        .create_writer(writer);
+    start_track_document(meta, &mut writer)?;
+    for point in points {
+        write_waypoint(
+            GpxVersion::Gpx11,
+            "trkpt",
+            &point,
+            &mut writer,
+            &options,
+            &mut NoHook,
+        )?;
+    }
+    // Closes the segment, the track and the document.
+    for _ in 0..3 {
+        write_xml_event(XmlEvent::end_element(), &mut writer)?;
+    }
+    Ok(())
+}
+
+/// Starts a GPX 1.1 document with a single track with the fields of
+/// `meta`, up to the opening tag of its first segment. The segment, the
+/// track and the document are left for the caller to close.
+pub(crate) fn start_track_document<W: Write, T: CoordFloat + Display>(
+    meta: &Track<T>,
+    writer: &mut EventWriter<W>,
+) -> GpxResult<()> {
+    let version = GpxVersion::Gpx11;
+    // The points aren't known up front, so both extension namespaces of
+    // points are declared in case they are used.
+    let namespaces = Namespaces {
+        gpx_extensions: true,
+        track_point_extension: true,
+        gpx_style: meta.line_style.is_some(),
+    };
+    let root = XmlEvent::start_element("gpx")
+        .attr("version", version_to_version_string(version)?)
+        .attr("xmlns", version_to_xml_url(version)?)
+        .attr("creator", "https://github.com/georust/gpx");
+    write_xml_event(namespaces.declare(root), writer)?;
+    write_xml_event(XmlEvent::start_element("trk"), writer)?;
+    write_track_fields(version, meta, writer, &mut NoHook)?;
+    write_xml_event(XmlEvent::start_element("trkseg"), writer)?;
+    Ok(())
+}
+
+fn write_document<W: Write, T: CoordFloat + Display>(
+    gpx: &Gpx<T>,
+    writer: &mut EventWriter<W>,
+    options: &WriteOptions,
+    select: &mut dyn FnMut(DocumentItem<'_, T>) -> bool,
+    hook: &mut dyn WriteHook<T>,
+) -> GpxResult<()> {
+    if let Some(prefix) = options.prefix {
+        let mut buffer = EmitterConfig::new()
+            .write_document_declaration(false)
+            .create_writer(Vec::new());
+        let options = WriteOptions {
+            prefix: None,
+            ..*options
+        };
+        write_document(gpx, &mut buffer, &options, select, hook)?;
+        return write_prefixed(
+            &buffer.into_inner(),
+            version_to_xml_url(gpx.version)?,
+            prefix,
+            writer,
+        );
+    }
     let creator: &str = gpx
         .creator
         .as_deref()
         .unwrap_or("https://github.com/georust/gpx");
+    let selection = Selection::new(gpx, select);
+    let namespaces = Namespaces::of(gpx, &selection);
+    let mut root = XmlEvent::start_element("gpx")
+        .attr("version", version_to_version_string(gpx.version)?)
+        .attr("xmlns", version_to_xml_url(gpx.version)?)
+        .attr("creator", creator);
+    for (name, value) in &gpx.root_attributes {
+        match name.strip_prefix("xmlns:") {
+            Some(prefix) if !namespaces.declares(prefix) => root = root.ns(prefix, value),
+            Some(_) => {}
+            None if !["version", "xmlns", "creator"].contains(&name.as_str()) => {
+                root = root.attr(name.as_str(), value)
+            }
+            None => {}
+        }
+    }
+    write_xml_event(namespaces.declare(root), writer)?;
+    write_comments(gpx, CommentPosition::Start, writer)?;
+    write_metadata(gpx, &selection.bounds(gpx), writer, options, hook)?;
+    for &index in &selection.waypoints {
+        write_comments(gpx, CommentPosition::BeforeWaypoint(index), writer)?;
+        write_waypoint(
+            gpx.version,
+            "wpt",
+            &gpx.waypoints[index],
+            writer,
+            options,
+            hook,
+        )?;
+    }
+    for &index in &selection.tracks {
+        write_comments(gpx, CommentPosition::BeforeTrack(index), writer)?;
+        write_track(gpx.version, &gpx.tracks[index], writer, options, hook)?;
+    }
+    for &index in &selection.routes {
+        write_comments(gpx, CommentPosition::BeforeRoute(index), writer)?;
+        write_route(gpx.version, &gpx.routes[index], writer, options, hook)?;
+    }
+    write_comments(gpx, CommentPosition::End, writer)?;
+    write_xml_event(XmlEvent::end_element(), writer)?;
+    Ok(())
+}
+
+/// Writes the waypoints, tracks and routes of `gpx`, and its comments, as
+/// children of the root element of a document of `version` whose root
+/// element declares the namespaces of `scope`, see
+/// [`append_to_path`](crate::append_to_path).
+///
+/// Namespaces that `scope` lacks, like those of extensions, are declared on
+/// each child that uses them.
+pub(crate) fn write_children<T: CoordFloat + Display>(
+    gpx: &Gpx<T>,
+    version: GpxVersion,
+    scope: &Namespace,
+) -> GpxResult<Vec<u8>> {
+    let children = Gpx {
+        version,
+        creator: None,
+        metadata: None,
+        waypoints: gpx.waypoints.clone(),
+        tracks: gpx.tracks.clone(),
+        routes: gpx.routes.clone(),
+        root_attributes: Vec::new(),
+        comments: gpx.comments.clone(),
+    };
+    let mut document = EmitterConfig::new()
+        .write_document_declaration(false)
+        .create_writer(Vec::new());
+    write_document(
+        &children,
+        &mut document,
+        &WriteOptions::default(),
+        &mut |_| true,
+        &mut NoHook,
+    )?;
+    let document = document.into_inner();
+
+    // The children are written below a root element declaring `scope`, which
+    // is cut off again, so that the declarations of the document aren't
+    // repeated on every child.
+    let mut writer = EmitterConfig::new()
+        .perform_indent(true)
+        .write_document_declaration(false)
+        .normalize_empty_elements(false)
+        .create_writer(Vec::new());
     write_xml_event(
-        XmlEvent::start_element("gpx")
-            .attr("version", version_to_version_string(gpx.version)?)
-            .attr("xmlns", version_to_xml_url(gpx.version)?)
-            .attr("creator", creator),
-        writer,
+        XmlEvent::StartElement {
+            name: Name::local("gpx"),
+            attributes: Cow::Owned(Vec::new()),
+            namespace: Cow::Borrowed(scope),
+        },
+        &mut writer,
     )?;
-    write_metadata(gpx, writer)?;
-    for point in &gpx.waypoints {
-        write_waypoint(gpx.version, "wpt", point, writer)?;
+    let start = writer.inner_ref().len();
+    let mut depth = 0;
+    let config = ParserConfig::new().ignore_comments(false);
+    for event in EventReader::new_with_config(document.as_slice(), config) {
+        let event = event?;
+        let inside = match event {
+            ReaderEvent::StartElement { .. } => {
+                depth += 1;
+                depth > 1
+            }
+            ReaderEvent::EndElement { .. } => {
+                depth -= 1;
+                depth > 0
+            }
+            _ => depth > 0,
+        };
+        if let Some(event) = event.as_writer_event().filter(|_| inside) {
+            write_xml_event(event, &mut writer)?;
+        }
     }
-    for track in &gpx.tracks {
-        write_track(gpx.version, track, writer)?;
+    Ok(writer.into_inner().split_off(start))
+}
+
+/// Writes the comments of `gpx` at `position`.
+fn write_comments<W: Write, T: CoordFloat>(
+    gpx: &Gpx<T>,
+    position: CommentPosition,
+    writer: &mut EventWriter<W>,
+) -> GpxResult<()> {
+    for comment in gpx.comments.iter().filter(|c| c.position == position) {
+        write_xml_event(XmlEvent::comment(&comment.text), writer)?;
     }
-    for route in &gpx.routes {
-        write_route(gpx.version, route, writer)?;
+    Ok(())
+}
+
+/// Rewrites the document in `data` with the elements of the namespace `uri`
+/// prefixed by `prefix`.
+fn write_prefixed<W: Write>(
+    data: &[u8],
+    uri: &str,
+    prefix: &str,
+    writer: &mut EventWriter<W>,
+) -> GpxResult<()> {
+    let config = ParserConfig::new().ignore_comments(false);
+    for event in EventReader::new_with_config(data, config) {
+        match event? {
+            ReaderEvent::StartElement {
+                name,
+                attributes,
+                mut namespace,
+            } => {
+                if namespace.get(NS_NO_PREFIX) == Some(uri) {
+                    namespace.0.remove(NS_NO_PREFIX);
+                    namespace.put(prefix, uri);
+                }
+                let name = if name.namespace_ref() == Some(uri) {
+                    Name::prefixed(&name.local_name, prefix)
+                } else {
+                    name.borrow()
+                };
+                write_xml_event(
+                    XmlEvent::StartElement {
+                        name,
+                        attributes: attributes.iter().map(OwnedAttribute::borrow).collect(),
+                        namespace: Cow::Owned(namespace),
+                    },
+                    writer,
+                )?;
+            }
+            ReaderEvent::EndElement { .. } => write_xml_event(XmlEvent::end_element(), writer)?,
+            ReaderEvent::StartDocument { .. } => {}
+            event => {
+                if let Some(event) = event.as_writer_event() {
+                    write_xml_event(event, writer)?;
+                }
+            }
+        }
     }
-    write_xml_event(XmlEvent::end_element(), writer)?;
     Ok(())
 }
 
-fn write_xml_event<'a, W, E>(event: E, writer: &mut EventWriter<W>) -> GpxResult<()>
+pub(crate) fn write_xml_event<'a, W, E>(event: E, writer: &mut EventWriter<W>) -> GpxResult<()>
 where
     W: Write,
     E: Into<XmlEvent<'a>>,
@@ -106,15 +862,26 @@ fn version_to_xml_url(version: GpxVersion) -> GpxResult<&'static str> {
     }
 }
 
-fn write_metadata<W: Write>(gpx: &Gpx, writer: &mut EventWriter<W>) -> GpxResult<()> {
+fn write_metadata<W: Write, T: CoordFloat + Display>(
+    gpx: &Gpx<T>,
+    bounds: &Option<Rect<T>>,
+    writer: &mut EventWriter<W>,
+    options: &WriteOptions,
+    hook: &mut dyn WriteHook<T>,
+) -> GpxResult<()> {
     match gpx.version {
-        GpxVersion::Gpx10 => write_gpx10_metadata(gpx, writer),
-        GpxVersion::Gpx11 => write_gpx11_metadata(gpx, writer),
+        GpxVersion::Gpx10 => write_gpx10_metadata(gpx, bounds, writer, hook),
+        GpxVersion::Gpx11 => write_gpx11_metadata(gpx, bounds, writer, options, hook),
         version => Err(GpxError::UnknownVersionError(version)),
     }
 }
 
-fn write_gpx10_metadata<W: Write>(gpx: &Gpx, writer: &mut EventWriter<W>) -> GpxResult<()> {
+fn write_gpx10_metadata<W: Write, T: CoordFloat + Display>(
+    gpx: &Gpx<T>,
+    bounds: &Option<Rect<T>>,
+    writer: &mut EventWriter<W>,
+    hook: &mut dyn WriteHook<T>,
+) -> GpxResult<()> {
     if gpx.metadata.is_none() {
         return Ok(());
     }
@@ -123,7 +890,7 @@ fn write_gpx10_metadata<W: Write>(gpx: &Gpx, writer: &mut EventWriter<W>) -> Gpx
     write_string_if_exists("desc", &metadata.description, writer)?;
     if let Some(author) = metadata.author.as_ref() {
         write_string_if_exists("author", &author.name, writer)?;
-        write_email_if_exists(&author.email, writer)?;
+        write_string_if_exists("email", &author.email, writer)?;
         if let Some(link) = author.link.as_ref() {
             write_string("url", &link.href, writer)?;
             write_string_if_exists("urlname", &link.text, writer)?;
@@ -131,11 +898,24 @@ fn write_gpx10_metadata<W: Write>(gpx: &Gpx, writer: &mut EventWriter<W>) -> Gpx
     }
     write_string_if_exists("keywords", &metadata.keywords, writer)?;
     write_time_if_exists(&metadata.time, writer)?;
-    write_bounds_if_exists(&metadata.bounds, writer)?;
+    write_bounds_if_exists(bounds, writer)?;
+    call_hook(
+        hook,
+        HookElement::Metadata(metadata),
+        GpxVersion::Gpx10,
+        false,
+        writer,
+    )?;
     Ok(())
 }
 
-fn write_gpx11_metadata<W: Write>(gpx: &Gpx, writer: &mut EventWriter<W>) -> GpxResult<()> {
+fn write_gpx11_metadata<W: Write, T: CoordFloat + Display>(
+    gpx: &Gpx<T>,
+    bounds: &Option<Rect<T>>,
+    writer: &mut EventWriter<W>,
+    options: &WriteOptions,
+    hook: &mut dyn WriteHook<T>,
+) -> GpxResult<()> {
     if gpx.metadata.is_none() {
         return Ok(());
     }
@@ -143,38 +923,49 @@ fn write_gpx11_metadata<W: Write>(gpx: &Gpx, writer: &mut EventWriter<W>) -> Gpx
     write_xml_event(XmlEvent::start_element("metadata"), writer)?;
     write_string_if_exists("name", &metadata.name, writer)?;
     write_string_if_exists("desc", &metadata.description, writer)?;
-    write_person_if_exists("author", &metadata.author, writer)?;
+    write_person_if_exists("author", &metadata.author, writer, options)?;
     write_string_if_exists("keywords", &metadata.keywords, writer)?;
     write_time_if_exists(&metadata.time, writer)?;
     for link in &metadata.links {
         write_link(link, writer)?;
     }
-    write_bounds_if_exists(&metadata.bounds, writer)?;
+    write_bounds_if_exists(bounds, writer)?;
+    call_hook(
+        hook,
+        HookElement::Metadata(metadata),
+        GpxVersion::Gpx11,
+        false,
+        writer,
+    )?;
     write_xml_event(XmlEvent::end_element(), writer)?;
     Ok(())
 }
 
-fn write_string<W: Write>(key: &str, value: &str, writer: &mut EventWriter<W>) -> GpxResult<()> {
+pub(crate) fn write_string<W: Write>(
+    key: &str,
+    value: &str,
+    writer: &mut EventWriter<W>,
+) -> GpxResult<()> {
     write_xml_event(XmlEvent::start_element(key), writer)?;
     write_xml_event(XmlEvent::characters(value), writer)?;
     write_xml_event(XmlEvent::end_element(), writer)?;
     Ok(())
 }
 
-fn write_string_if_exists<W: Write>(
+pub(crate) fn write_string_if_exists<W: Write>(
     key: &str,
-    value: &Option<String>,
+    value: &Option<impl AsRef<str>>,
     writer: &mut EventWriter<W>,
 ) -> GpxResult<()> {
     if let Some(ref value) = value {
-        write_string(key, value, writer)?;
+        write_string(key, value.as_ref(), writer)?;
     }
     Ok(())
 }
 
-fn write_value_if_exists<W: Write, T: ToString>(
+fn write_value_if_exists<W: Write, V: ToString>(
     key: &str,
-    value: &Option<T>,
+    value: &Option<V>,
     writer: &mut EventWriter<W>,
 ) -> GpxResult<()> {
     if let Some(ref value) = value {
@@ -186,19 +977,33 @@ fn write_value_if_exists<W: Write, T: ToString>(
     Ok(())
 }
 
+fn write_float_if_exists<W: Write, T: CoordFloat + Display>(
+    key: &str,
+    value: Option<T>,
+    writer: &mut EventWriter<W>,
+) -> GpxResult<()> {
+    if let Some(value) = value {
+        write_xml_event(XmlEvent::start_element(key), writer)?;
+        write_xml_event(XmlEvent::characters(&number::format(value)), writer)?;
+        write_xml_event(XmlEvent::end_element(), writer)?;
+    }
+    Ok(())
+}
+
 fn write_email_if_exists<W: Write>(
     email: &Option<String>,
     writer: &mut EventWriter<W>,
+    options: &WriteOptions,
 ) -> GpxResult<()> {
     if let Some(ref email) = email {
         let mut parts = email.split('@');
         let id = parts.next().ok_or(GpxError::MissingEmailPartError("id"))?;
-        let domain = parts
-            .next()
-            .ok_or(GpxError::MissingEmailPartError("domain"))?;
-        if parts.next().is_some() {
-            return Err(GpxError::TooManyAtsError);
-        }
+        let domain = match (parts.next(), parts.next()) {
+            (Some(domain), None) => domain,
+            _ if options.raw_emails => return write_string("email", email, writer),
+            (None, _) => return Err(GpxError::MissingEmailPartError("domain")),
+            (Some(_), Some(_)) => return Err(GpxError::TooManyAtsError),
+        };
         write_xml_event(
             XmlEvent::start_element("email")
                 .attr("id", id)
@@ -235,11 +1040,12 @@ fn write_person_if_exists<W: Write>(
     key: &str,
     value: &Option<Person>,
     writer: &mut EventWriter<W>,
+    options: &WriteOptions,
 ) -> GpxResult<()> {
     if let Some(ref value) = value {
         write_xml_event(XmlEvent::start_element(key), writer)?;
         write_string_if_exists("name", &value.name, writer)?;
-        write_email_if_exists(&value.email, writer)?;
+        write_email_if_exists(&value.email, writer, options)?;
         write_link_if_exists(&value.link, writer)?;
         write_xml_event(XmlEvent::end_element(), writer)?;
     }
@@ -258,17 +1064,17 @@ fn write_time_if_exists<W: Write>(
     Ok(())
 }
 
-fn write_bounds_if_exists<W: Write>(
-    bounds: &Option<Rect<f64>>,
+fn write_bounds_if_exists<W: Write, T: CoordFloat + Display>(
+    bounds: &Option<Rect<T>>,
     writer: &mut EventWriter<W>,
 ) -> GpxResult<()> {
     if let Some(ref bounds) = bounds {
         write_xml_event(
             XmlEvent::start_element("bounds")
-                .attr("minlat", &bounds.min().y.to_string())
-                .attr("maxlat", &bounds.max().y.to_string())
-                .attr("minlon", &bounds.min().x.to_string())
-                .attr("maxlon", &bounds.max().x.to_string()),
+                .attr("minlat", &number::format(bounds.min().y))
+                .attr("maxlat", &number::format(bounds.max().y))
+                .attr("minlon", &number::format(bounds.min().x))
+                .attr("maxlon", &number::format(bounds.max().x)),
             writer,
         )?;
         write_xml_event(XmlEvent::end_element(), writer)?;
@@ -293,8 +1099,37 @@ fn write_fix_if_exists<W: Write>(fix: &Option<Fix>, writer: &mut EventWriter<W>)
     Ok(())
 }
 
-fn write_track<W: Write>(version: GpxVersion, track: &Track, writer: &mut EventWriter<W>) -> GpxResult<()> {
+fn write_track<W: Write, T: CoordFloat + Display>(
+    version: GpxVersion,
+    track: &Track<T>,
+    writer: &mut EventWriter<W>,
+    options: &WriteOptions,
+    hook: &mut dyn WriteHook<T>,
+) -> GpxResult<()> {
+    let empty = match options.empty {
+        EmptyPolicy::Skip => track.segments.iter().all(|s| s.points.is_empty()),
+        _ => track.segments.is_empty(),
+    };
+    if !options.empty.keep("trk", empty)? {
+        return Ok(());
+    }
     write_xml_event(XmlEvent::start_element("trk"), writer)?;
+    write_track_fields(version, track, writer, hook)?;
+    for segment in &track.segments {
+        write_track_segment(version, segment, writer, options, hook)?;
+    }
+    write_xml_event(XmlEvent::end_element(), writer)?;
+    Ok(())
+}
+
+/// Writes the children of the `trk` element of `track` that come before its
+/// segments.
+fn write_track_fields<W: Write, T: CoordFloat + Display>(
+    version: GpxVersion,
+    track: &Track<T>,
+    writer: &mut EventWriter<W>,
+    hook: &mut dyn WriteHook<T>,
+) -> GpxResult<()> {
     write_string_if_exists("name", &track.name, writer)?;
     write_string_if_exists("cmt", &track.comment, writer)?;
     write_string_if_exists("desc", &track.description, writer)?;
@@ -303,14 +1138,42 @@ fn write_track<W: Write>(version: GpxVersion, track: &Track, writer: &mut EventW
         write_link(link, writer)?;
     }
     write_string_if_exists("type", &track.type_, writer)?;
-    for segment in &track.segments {
-        write_track_segment(version, segment, writer)?;
+    let gpx10 = version == GpxVersion::Gpx10;
+    let display_color = track.display_color.as_ref().filter(|_| !gpx10);
+    let line_style = track.line_style.as_ref().filter(|_| !gpx10);
+    let open = display_color.is_some() || line_style.is_some();
+    if open {
+        write_xml_event(XmlEvent::start_element("extensions"), writer)?;
+    }
+    if let Some(style) = line_style {
+        write_xml_event(XmlEvent::start_element("gpx_style:line"), writer)?;
+        write_string_if_exists("gpx_style:color", &style.color, writer)?;
+        write_float_if_exists("gpx_style:opacity", style.opacity, writer)?;
+        write_float_if_exists("gpx_style:width", style.width, writer)?;
+        write_xml_event(XmlEvent::end_element(), writer)?;
+    }
+    if let Some(display_color) = display_color {
+        write_xml_event(XmlEvent::start_element("gpxx:TrackExtension"), writer)?;
+        write_string("gpxx:DisplayColor", display_color.name(), writer)?;
+        write_xml_event(XmlEvent::end_element(), writer)?;
+    }
+    call_hook(hook, HookElement::Track(track), version, open, writer)?;
+    if open {
+        write_xml_event(XmlEvent::end_element(), writer)?;
     }
-    write_xml_event(XmlEvent::end_element(), writer)?;
     Ok(())
 }
 
-fn write_route<W: Write>(version: GpxVersion, route: &Route, writer: &mut EventWriter<W>) -> GpxResult<()> {
+fn write_route<W: Write, T: CoordFloat + Display>(
+    version: GpxVersion,
+    route: &Route<T>,
+    writer: &mut EventWriter<W>,
+    options: &WriteOptions,
+    hook: &mut dyn WriteHook<T>,
+) -> GpxResult<()> {
+    if !options.empty.keep("rte", route.points.is_empty())? {
+        return Ok(());
+    }
     write_xml_event(XmlEvent::start_element("rte"), writer)?;
     write_string_if_exists("name", &route.name, writer)?;
     write_string_if_exists("cmt", &route.comment, writer)?;
@@ -321,63 +1184,182 @@ fn write_route<W: Write>(version: GpxVersion, route: &Route, writer: &mut EventW
     }
     write_value_if_exists("number", &route.number, writer)?;
     write_string_if_exists("type", &route.type_, writer)?;
+    call_hook(hook, HookElement::Route(route), version, false, writer)?;
     for point in &route.points {
-        write_waypoint(version, "rtept", point, writer)?;
+        write_waypoint(version, "rtept", point, writer, options, hook)?;
     }
     write_xml_event(XmlEvent::end_element(), writer)?;
     Ok(())
 }
 
-fn write_track_segment<W: Write>(
+fn write_track_segment<W: Write, T: CoordFloat + Display>(
     version: GpxVersion,
-    segment: &TrackSegment,
+    segment: &TrackSegment<T>,
     writer: &mut EventWriter<W>,
+    options: &WriteOptions,
+    hook: &mut dyn WriteHook<T>,
 ) -> GpxResult<()> {
+    if !options.empty.keep("trkseg", segment.points.is_empty())? {
+        return Ok(());
+    }
     write_xml_event(XmlEvent::start_element("trkseg"), writer)?;
     for point in &segment.points {
-        write_waypoint(version, "trkpt", point, writer)?;
+        write_waypoint(version, "trkpt", point, writer, options, hook)?;
     }
     write_xml_event(XmlEvent::end_element(), writer)?;
     Ok(())
 }
 
-fn write_waypoint<W: Write>(
+pub(crate) fn write_waypoint<W: Write, T: CoordFloat + Display>(
     version: GpxVersion,
     tagname: &str,
-    waypoint: &Waypoint,
+    waypoint: &Waypoint<T>,
     writer: &mut EventWriter<W>,
+    options: &WriteOptions,
+    hook: &mut dyn WriteHook<T>,
 ) -> GpxResult<()> {
     write_xml_event(
         XmlEvent::start_element(tagname)
-            .attr("lat", &waypoint.point().y().to_string())
-            .attr("lon", &waypoint.point().x().to_string()),
+            .attr("lat", &number::format(waypoint.point().y()))
+            .attr("lon", &number::format(waypoint.point().x())),
         writer,
     )?;
-    write_value_if_exists("ele", &waypoint.elevation, writer)?;
-    match version {
-        GpxVersion::Gpx10 => {
-            write_value_if_exists("speed", &waypoint.speed, writer)?;
-        }
-        _ => {}
+    write_float_if_exists("ele", waypoint.elevation, writer)?;
+    if version == GpxVersion::Gpx10 {
+        write_float_if_exists("speed", waypoint.speed, writer)?;
     }
     write_time_if_exists(&waypoint.time, writer)?;
-    write_value_if_exists("geoidheight", &waypoint.geoidheight, writer)?;
-    write_string_if_exists("name", &waypoint.name, writer)?;
-    write_string_if_exists("cmt", &waypoint.comment, writer)?;
-    write_string_if_exists("desc", &waypoint.description, writer)?;
-    write_string_if_exists("src", &waypoint.source, writer)?;
-    for link in &waypoint.links {
+    write_float_if_exists("geoidheight", waypoint.details().geoidheight, writer)?;
+    write_string_if_exists("name", &waypoint.details().name, writer)?;
+    write_string_if_exists("cmt", &waypoint.details().comment, writer)?;
+    write_string_if_exists("desc", &waypoint.details().description, writer)?;
+    write_string_if_exists("src", &waypoint.details().source, writer)?;
+    for link in &waypoint.details().links {
         write_link(link, writer)?;
     }
-    write_string_if_exists("sym", &waypoint.symbol, writer)?;
-    write_string_if_exists("type", &waypoint.type_, writer)?;
-    write_fix_if_exists(&waypoint.fix, writer)?;
-    write_value_if_exists("sat", &waypoint.sat, writer)?;
-    write_value_if_exists("hdop", &waypoint.hdop, writer)?;
-    write_value_if_exists("vdop", &waypoint.vdop, writer)?;
-    write_value_if_exists("pdop", &waypoint.pdop, writer)?;
-    write_value_if_exists("ageofdgpsdata", &waypoint.dgps_age, writer)?;
-    write_value_if_exists("dgpsid", &waypoint.dgpsid, writer)?;
+    write_string_if_exists("sym", &waypoint.details().symbol, writer)?;
+    write_string_if_exists("type", &waypoint.details().type_, writer)?;
+    write_fix_if_exists(&waypoint.details().fix, writer)?;
+    write_value_if_exists("sat", &waypoint.details().sat, writer)?;
+    write_float_if_exists("hdop", waypoint.details().hdop, writer)?;
+    write_float_if_exists("vdop", waypoint.details().vdop, writer)?;
+    write_float_if_exists("pdop", waypoint.details().pdop, writer)?;
+    write_float_if_exists("ageofdgpsdata", waypoint.details().dgps_age, writer)?;
+    let dgpsid = waypoint
+        .details()
+        .dgpsid
+        .map(|dgpsid| {
+            options
+                .out_of_range
+                .apply("dgpsid", dgpsid.into(), 0..=1023)
+        })
+        .transpose()?
+        .flatten();
+    write_value_if_exists("dgpsid", &dgpsid, writer)?;
+    let waypoint_extension = &waypoint.details().waypoint_extension;
+    let route_point_extension = &waypoint.route_point_extension;
+    let open = version != GpxVersion::Gpx10
+        && (waypoint.track_point_extension.is_some()
+            || waypoint_extension.is_some()
+            || route_point_extension.is_some());
+    if open {
+        write_xml_event(XmlEvent::start_element("extensions"), writer)?;
+        if let Some(extension) = waypoint_extension {
+            write_waypoint_extension(extension, writer)?;
+        }
+        if let Some(extension) = route_point_extension {
+            write_route_point_extension(extension, writer)?;
+        }
+        if let Some(extension) = &waypoint.track_point_extension {
+            write_track_point_extension(extension, writer)?;
+        }
+    }
+    let element = match tagname {
+        "rtept" => HookElement::RoutePoint(waypoint),
+        "trkpt" => HookElement::TrackPoint(waypoint),
+        _ => HookElement::Waypoint(waypoint),
+    };
+    call_hook(hook, element, version, open, writer)?;
+    if open {
+        write_xml_event(XmlEvent::end_element(), writer)?;
+    }
+    write_xml_event(XmlEvent::end_element(), writer)?;
+    Ok(())
+}
+
+fn write_waypoint_extension<W: Write>(
+    extension: &WaypointExtension,
+    writer: &mut EventWriter<W>,
+) -> GpxResult<()> {
+    write_xml_event(XmlEvent::start_element("gpxx:WaypointExtension"), writer)?;
+    write_float_if_exists("gpxx:Proximity", extension.proximity, writer)?;
+    write_float_if_exists("gpxx:Temperature", extension.temperature, writer)?;
+    write_float_if_exists("gpxx:Depth", extension.depth, writer)?;
+    if let Some(display_mode) = &extension.display_mode {
+        let display_mode = match display_mode {
+            DisplayMode::SymbolOnly => "SymbolOnly",
+            DisplayMode::SymbolAndName => "SymbolAndName",
+            DisplayMode::SymbolAndDescription => "SymbolAndDescription",
+            DisplayMode::Other(string) => string,
+        };
+        write_string("gpxx:DisplayMode", display_mode, writer)?;
+    }
+    if let Some(address) = &extension.address {
+        write_xml_event(XmlEvent::start_element("gpxx:Address"), writer)?;
+        for street_address in &address.street_address {
+            write_string("gpxx:StreetAddress", street_address, writer)?;
+        }
+        write_string_if_exists("gpxx:City", &address.city, writer)?;
+        write_string_if_exists("gpxx:State", &address.state, writer)?;
+        write_string_if_exists("gpxx:Country", &address.country, writer)?;
+        write_string_if_exists("gpxx:PostalCode", &address.postal_code, writer)?;
+        write_xml_event(XmlEvent::end_element(), writer)?;
+    }
+    for phone_number in &extension.phone_numbers {
+        let mut start = XmlEvent::start_element("gpxx:PhoneNumber");
+        if let Some(category) = &phone_number.category {
+            start = start.attr("Category", category);
+        }
+        write_xml_event(start, writer)?;
+        write_xml_event(XmlEvent::characters(&phone_number.number), writer)?;
+        write_xml_event(XmlEvent::end_element(), writer)?;
+    }
+    write_xml_event(XmlEvent::end_element(), writer)?;
+    Ok(())
+}
+
+fn write_route_point_extension<W: Write, T: CoordFloat + Display>(
+    extension: &RoutePointExtension<T>,
+    writer: &mut EventWriter<W>,
+) -> GpxResult<()> {
+    write_xml_event(XmlEvent::start_element("gpxx:RoutePointExtension"), writer)?;
+    write_string_if_exists("gpxx:Subclass", &extension.subclass, writer)?;
+    for point in &extension.points {
+        write_xml_event(
+            XmlEvent::start_element("gpxx:rpt")
+                .attr("lat", &number::format(point.y()))
+                .attr("lon", &number::format(point.x())),
+            writer,
+        )?;
+        write_xml_event(XmlEvent::end_element(), writer)?;
+    }
+    write_xml_event(XmlEvent::end_element(), writer)?;
+    Ok(())
+}
+
+fn write_track_point_extension<W: Write>(
+    extension: &TrackPointExtension,
+    writer: &mut EventWriter<W>,
+) -> GpxResult<()> {
+    write_xml_event(
+        XmlEvent::start_element("gpxtpx:TrackPointExtension"),
+        writer,
+    )?;
+    write_value_if_exists("gpxtpx:atemp", &extension.air_temperature, writer)?;
+    write_value_if_exists("gpxtpx:wtemp", &extension.water_temperature, writer)?;
+    write_value_if_exists("gpxtpx:depth", &extension.depth, writer)?;
+    write_value_if_exists("gpxtpx:hr", &extension.heart_rate, writer)?;
+    write_value_if_exists("gpxtpx:cad", &extension.cadence, writer)?;
     write_xml_event(XmlEvent::end_element(), writer)?;
     Ok(())
 }
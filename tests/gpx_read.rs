@@ -183,6 +183,11 @@ fn gpx_reader_read_test_garmin_activity() {
     assert_eq!(track.segments.len(), 1);
     let segment = &track.segments[0];
 
+    // Heart rate and cadence come from the Garmin TrackPointExtension.
+    let extension = segment.points[0].track_point_extension.as_ref().unwrap();
+    assert_eq!(extension.heart_rate, Some(74));
+    assert_eq!(extension.cadence, Some(79));
+
     // Test for every single point in the file.
     for point in segment.points.iter() {
         // Elevation is between 90 and 220.
@@ -222,13 +227,13 @@ fn gpx_reader_read_test_garmin_activity() {
         }
 
         // It's missing almost all fields, actually.
-        assert!(point.name.is_none());
-        assert!(point.comment.is_none());
-        assert!(point.description.is_none());
-        assert!(point.source.is_none());
-        assert!(point.symbol.is_none());
-        assert!(point.type_.is_none());
-        assert_eq!(point.links.len(), 0);
+        assert!(point.details().name.is_none());
+        assert!(point.details().comment.is_none());
+        assert!(point.details().description.is_none());
+        assert!(point.details().source.is_none());
+        assert!(point.details().symbol.is_none());
+        assert!(point.details().type_.is_none());
+        assert_eq!(point.details().links.len(), 0);
     }
 }
 
@@ -275,13 +280,13 @@ fn gpx_reader_read_test_lovers_lane() {
         }
 
         // It's missing almost all fields, actually.
-        assert!(point.name.is_none());
-        assert!(point.comment.is_none());
-        assert!(point.description.is_none());
-        assert!(point.source.is_none());
-        assert!(point.symbol.is_none());
-        assert!(point.type_.is_none());
-        assert_eq!(point.links.len(), 0);
+        assert!(point.details().name.is_none());
+        assert!(point.details().comment.is_none());
+        assert!(point.details().description.is_none());
+        assert!(point.details().source.is_none());
+        assert!(point.details().symbol.is_none());
+        assert!(point.details().type_.is_none());
+        assert_eq!(point.details().links.len(), 0);
     }
 }
 
@@ -314,24 +319,24 @@ fn gpx_reader_read_test_with_accuracy() {
     assert_eq!(segment.points.len(), 3);
     let points = &segment.points;
 
-    assert_eq!(points[0].fix, Some(Fix::DGPS));
-    assert_eq!(points[0].sat.unwrap(), 4);
-    assert_eq!(points[0].hdop.unwrap(), 5.);
-    assert_eq!(points[0].vdop.unwrap(), 6.2);
-    assert_eq!(points[0].pdop.unwrap(), 728.);
-    assert_eq!(points[0].dgps_age.unwrap(), 1.);
-    assert_eq!(points[0].dgpsid.unwrap(), 3);
-
-    assert_eq!(points[1].fix, Some(Fix::ThreeDimensional));
-    assert_eq!(points[1].sat.unwrap(), 5);
-    assert_eq!(points[1].hdop.unwrap(), 3.6);
-    assert_eq!(points[1].vdop.unwrap(), 5.);
-    assert_eq!(points[1].pdop.unwrap(), 619.1);
-    assert_eq!(points[1].dgps_age.unwrap(), 2.01);
-    assert_eq!(points[1].dgpsid.unwrap(), 4);
+    assert_eq!(points[0].details().fix, Some(Fix::DGPS));
+    assert_eq!(points[0].details().sat.unwrap(), 4);
+    assert_eq!(points[0].details().hdop.unwrap(), 5.);
+    assert_eq!(points[0].details().vdop.unwrap(), 6.2);
+    assert_eq!(points[0].details().pdop.unwrap(), 728.);
+    assert_eq!(points[0].details().dgps_age.unwrap(), 1.);
+    assert_eq!(points[0].details().dgpsid.unwrap(), 3);
+
+    assert_eq!(points[1].details().fix, Some(Fix::ThreeDimensional));
+    assert_eq!(points[1].details().sat.unwrap(), 5);
+    assert_eq!(points[1].details().hdop.unwrap(), 3.6);
+    assert_eq!(points[1].details().vdop.unwrap(), 5.);
+    assert_eq!(points[1].details().pdop.unwrap(), 619.1);
+    assert_eq!(points[1].details().dgps_age.unwrap(), 2.01);
+    assert_eq!(points[1].details().dgpsid.unwrap(), 4);
 
     assert_eq!(
-        points[2].fix,
+        points[2].details().fix,
         Some(Fix::Other("something_not_in_the_spec".to_string()))
     );
 }
@@ -534,3 +539,46 @@ fn outdooractive_export() {
     assert_eq!(points.len(), 9);
     assert_eq!(points[0].point().y(), -3.173433);
 }
+
+#[test]
+fn parser_reuse() {
+    let mut parser = gpx::Parser::with_capacity(16);
+    for fixture in [
+        "tests/fixtures/wikipedia_example.gpx",
+        "tests/fixtures/badcharacter.xml",
+        "tests/fixtures/garmin-activity.gpx",
+    ] {
+        let reused = parser.read(BufReader::new(File::open(fixture).unwrap()));
+        let fresh = read(BufReader::new(File::open(fixture).unwrap()));
+        match (reused, fresh) {
+            (Ok(reused), Ok(fresh)) => assert_eq!(reused, fresh),
+            (Err(_), Err(_)) => {}
+            (reused, fresh) => panic!("{fixture}: {reused:?} vs {fresh:?}"),
+        }
+    }
+}
+
+#[test]
+fn salvage_truncated_file() {
+    let data = std::fs::read("tests/fixtures/garmin-activity.gpx").unwrap();
+    let complete = read(data.as_slice()).unwrap();
+    let truncated = &data[..data.len() / 2];
+    assert!(read(truncated).is_err());
+
+    let mut parser = gpx::Parser::with_options(gpx::ReadOptions {
+        salvage: true,
+        ..Default::default()
+    });
+    let salvaged = parser.read(truncated).unwrap();
+    assert!(matches!(
+        parser.warnings(),
+        [gpx::errors::GpxWarning::Truncated(..)]
+    ));
+    let points = &salvaged.tracks[0].segments[0].points;
+    let complete_points = &complete.tracks[0].segments[0].points;
+    assert!(!points.is_empty() && points.len() < complete_points.len());
+    assert_eq!(points[..], complete_points[..points.len()]);
+
+    assert_eq!(parser.read(data.as_slice()).unwrap(), complete);
+    assert!(parser.warnings().is_empty());
+}
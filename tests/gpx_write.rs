@@ -1,8 +1,17 @@
 use std::fs::File;
 use std::io::BufReader;
 
-use gpx::{read, write};
-use gpx::{Gpx, Link, Waypoint};
+use geo_types::{coord, Point, Rect};
+use gpx::errors::GpxError;
+use gpx::{
+    read, read_as, read_with_options, write, write_track_from_iter, write_with_hook,
+    write_with_options, ExtensionWriter, HookElement, ReadOptions,
+};
+use gpx::{
+    Address, Comment, CommentPosition, DisplayColor, DisplayMode, DocumentItem, EmptyPolicy, Gpx,
+    GpxVersion, LineStyle, Link, Metadata, Person, PhoneNumber, RangePolicy, Route,
+    RoutePointExtension, Tolerance, Track, TrackSegment, Waypoint, WaypointExtension, WriteOptions,
+};
 
 #[test]
 fn gpx_writer_write_unknown_gpx_version() {
@@ -35,12 +44,406 @@ fn gpx_writer_write_test_outdooractive_export() {
     check_write_for_example_file("tests/fixtures/outdooractive-export.gpx");
 }
 
+#[test]
+fn gpx_writer_write_f32_round_trip() {
+    let file = File::open("tests/fixtures/wikipedia_example.gpx").unwrap();
+    let reference_gpx: Gpx<f32> = read_as(BufReader::new(file)).unwrap();
+
+    let mut buffer: Vec<u8> = Vec::new();
+    write(&reference_gpx, &mut buffer).unwrap();
+    let written_gpx: Gpx<f32> = read_as(buffer.as_slice()).unwrap();
+
+    assert_eq!(reference_gpx, written_gpx);
+    let points = &written_gpx.tracks[0].segments[0].points;
+    assert_eq!(points[0].elevation, Some(4.46_f32));
+}
+
+#[test]
+fn gpx_writer_write_gpx10_email() {
+    let gpx = Gpx {
+        version: GpxVersion::Gpx10,
+        metadata: Some(Metadata {
+            author: Some(Person {
+                name: Some("Me".into()),
+                email: Some("me@example.com".into()),
+                link: None,
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let mut buffer: Vec<u8> = Vec::new();
+    write(&gpx, &mut buffer).unwrap();
+    let written = String::from_utf8(buffer).unwrap();
+    assert!(written.contains("<email>me@example.com</email>"));
+    assert_eq!(read(written.as_bytes()).unwrap().metadata, gpx.metadata);
+}
+
+#[test]
+fn gpx_writer_write_out_of_range() {
+    let mut waypoint = Waypoint::new(Point::new(2.0, 1.0));
+    waypoint.details_mut().dgpsid = Some(2000);
+    let gpx = Gpx {
+        version: GpxVersion::Gpx11,
+        waypoints: vec![waypoint],
+        ..Default::default()
+    };
+    let write = |out_of_range| {
+        let options = WriteOptions {
+            out_of_range,
+            ..Default::default()
+        };
+        let mut buffer: Vec<u8> = Vec::new();
+        write_with_options(&gpx, &mut buffer, &options).map(|_| read(buffer.as_slice()).unwrap())
+    };
+
+    assert_eq!(
+        write(RangePolicy::Keep).unwrap().waypoints[0]
+            .details()
+            .dgpsid,
+        Some(2000)
+    );
+    assert!(write(RangePolicy::Error).is_err());
+    assert_eq!(
+        write(RangePolicy::Clamp).unwrap().waypoints[0]
+            .details()
+            .dgpsid,
+        Some(1023)
+    );
+    assert_eq!(
+        write(RangePolicy::Discard).unwrap().waypoints[0]
+            .details()
+            .dgpsid,
+        None
+    );
+}
+
+#[test]
+fn gpx_writer_write_garmin_extensions() {
+    let mut waypoint = Waypoint::new(Point::new(2.0, 1.0));
+    waypoint.details_mut().waypoint_extension = Some(WaypointExtension {
+        proximity: Some(50.0),
+        depth: Some(12.5),
+        display_mode: Some(DisplayMode::SymbolAndName),
+        address: Some(Address {
+            street_address: vec!["1200 E 151st St".into()],
+            city: Some("Olathe".into()),
+            ..Default::default()
+        }),
+        phone_numbers: vec![PhoneNumber {
+            number: "913 397 8200".into(),
+            category: Some("Phone".into()),
+        }],
+        ..Default::default()
+    });
+    let mut route_point = Waypoint::new(Point::new(2.0, 1.0));
+    route_point.route_point_extension = Some(Box::new(RoutePointExtension {
+        subclass: Some("000000000000FFFFFFFFFFFFFFFFFFFFFFFF".into()),
+        points: vec![Point::new(2.5, 1.5)],
+    }));
+    let mut route = Route::new();
+    route.points.push(route_point);
+    let mut track = Track::new();
+    track.display_color = Some(DisplayColor::Magenta);
+    track.line_style = Some(LineStyle {
+        color: Some("00D7D7".into()),
+        opacity: Some(0.5),
+        width: Some(6.0),
+    });
+    let gpx = Gpx {
+        version: GpxVersion::Gpx11,
+        waypoints: vec![waypoint],
+        tracks: vec![track],
+        routes: vec![route],
+        ..Default::default()
+    };
+
+    let mut buffer: Vec<u8> = Vec::new();
+    write(&gpx, &mut buffer).unwrap();
+    let written = String::from_utf8(buffer).unwrap();
+    assert!(written.contains("<gpxx:Depth>12.5</gpxx:Depth>"));
+    assert!(
+        written.contains("<gpxx:PhoneNumber Category=\"Phone\">913 397 8200</gpxx:PhoneNumber>")
+    );
+    assert!(written.contains("<gpxx:rpt lat=\"1.5\" lon=\"2.5\" />"));
+    assert!(written.contains("<gpxx:DisplayColor>Magenta</gpxx:DisplayColor>"));
+    assert!(written.contains("<gpx_style:width>6</gpx_style:width>"));
+    // The namespaces are declared once, on the root element.
+    let root = written
+        .lines()
+        .find(|line| line.starts_with("<gpx "))
+        .unwrap();
+    assert!(root.contains("xmlns:gpxx=\"http://www.garmin.com/xmlschemas/GpxExtensions/v3\""));
+    assert_eq!(written.matches("xmlns:gpxx").count(), 1);
+    assert!(root.contains("xmlns:gpx_style=\"http://www.topografix.com/GPX/gpx_style/0/2\""));
+    assert_eq!(written.matches("xmlns:gpx_style").count(), 1);
+    assert!(!written.contains("xmlns:gpxtpx"));
+    let reread = read(written.as_bytes()).unwrap();
+    assert_eq!(reread.waypoints, gpx.waypoints);
+    assert_eq!(reread.tracks, gpx.tracks);
+    assert_eq!(reread.routes, gpx.routes);
+}
+
+#[test]
+fn gpx_writer_write_root_attributes() {
+    let data = "<gpx version=\"1.1\" creator=\"Test\" xmlns=\"http://www.topografix.com/GPX/1/1\" \
+        xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+        xmlns:vendor=\"https://example.com/vendor\" \
+        xsi:schemaLocation=\"http://www.topografix.com/GPX/1/1 http://www.topografix.com/GPX/1/1/gpx.xsd\">\
+        <wpt lat=\"1\" lon=\"2\"/></gpx>";
+    let gpx = read(data.as_bytes()).unwrap();
+    assert_eq!(
+        gpx.root_attributes,
+        [
+            ("xmlns:vendor".into(), "https://example.com/vendor".into()),
+            (
+                "xmlns:xsi".into(),
+                "http://www.w3.org/2001/XMLSchema-instance".into()
+            ),
+            (
+                "xsi:schemaLocation".into(),
+                "http://www.topografix.com/GPX/1/1 http://www.topografix.com/GPX/1/1/gpx.xsd"
+                    .into()
+            ),
+        ]
+    );
+
+    let reread = write_and_reread_gpx(&gpx);
+    assert_eq!(reread.root_attributes, gpx.root_attributes);
+    assert_eq!(reread.creator.as_deref(), Some("Test"));
+}
+
+#[test]
+fn gpx_writer_write_prefixed() {
+    let data =
+        "<g:gpx version=\"1.1\" creator=\"Test\" xmlns:g=\"http://www.topografix.com/GPX/1/1\">\
+        <g:metadata><g:name>Prefixed</g:name></g:metadata>\
+        <g:trk><g:name>Loop</g:name><g:trkseg>\
+        <g:trkpt lat=\"1\" lon=\"2\"><g:ele>3</g:ele></g:trkpt>\
+        </g:trkseg></g:trk></g:gpx>";
+    let gpx = read(data.as_bytes()).unwrap();
+    assert_eq!(gpx.version, GpxVersion::Gpx11);
+    assert_eq!(gpx.tracks[0].name.as_deref(), Some("Loop"));
+    assert_eq!(gpx.tracks[0].segments[0].points[0].elevation, Some(3.0));
+
+    let options = WriteOptions {
+        prefix: Some("gpx"),
+        ..Default::default()
+    };
+    let mut buffer: Vec<u8> = Vec::new();
+    write_with_options(&gpx, &mut buffer, &options).unwrap();
+    let written = String::from_utf8(buffer).unwrap();
+    assert!(written.contains("<gpx:gpx "));
+    assert!(written.contains("xmlns:gpx=\"http://www.topografix.com/GPX/1/1\""));
+    assert!(written.contains("<gpx:name>Loop</gpx:name>"));
+    assert!(!written.contains("xmlns=\""));
+    assert_eq!(read(written.as_bytes()).unwrap(), gpx);
+}
+
+#[test]
+fn gpx_writer_write_empty_policy() {
+    let mut gpx: Gpx = Gpx {
+        version: GpxVersion::Gpx11,
+        creator: Some("Test".to_string()),
+        ..Default::default()
+    };
+    let mut track = Track::new();
+    track.segments.push(TrackSegment::new());
+    gpx.tracks.push(track);
+    gpx.tracks.push(Track::new());
+    gpx.routes.push(Route::new());
+    let written = |gpx: &Gpx, empty| {
+        let mut buffer: Vec<u8> = Vec::new();
+        let options = WriteOptions {
+            empty,
+            ..Default::default()
+        };
+        write_with_options(gpx, &mut buffer, &options).map(|_| String::from_utf8(buffer).unwrap())
+    };
+
+    let all = written(&gpx, EmptyPolicy::Write).unwrap();
+    let compact: String = all.split_whitespace().collect();
+    assert!(compact.contains("<trk><trkseg/></trk><trk/><rte/>"));
+    assert_eq!(read(all.as_bytes()).unwrap(), gpx);
+
+    let skipped = written(&gpx, EmptyPolicy::Skip).unwrap();
+    assert!(!skipped.contains("<trk"));
+    assert!(!skipped.contains("<rte"));
+
+    assert!(matches!(
+        written(&gpx, EmptyPolicy::Error),
+        Err(GpxError::EmptyElement("trkseg"))
+    ));
+    gpx.tracks.clear();
+    assert!(matches!(
+        written(&gpx, EmptyPolicy::Error),
+        Err(GpxError::EmptyElement("rte"))
+    ));
+}
+
+#[test]
+fn gpx_writer_write_selection() {
+    let mut gpx = read_test_gpx_file("tests/fixtures/wikipedia_example.gpx");
+    gpx.waypoints.push(Waypoint::new(Point::new(1.0, 2.0)));
+    gpx.waypoints.push(Waypoint::new(Point::new(3.0, 4.0)));
+    gpx.routes.push(Route::new());
+
+    let mut buffer: Vec<u8> = Vec::new();
+    gpx.write_selection(&mut buffer, &WriteOptions::default(), |item| match item {
+        DocumentItem::Waypoint { index, .. } => index == 1,
+        DocumentItem::Track { .. } => true,
+        DocumentItem::Route { .. } => false,
+    })
+    .unwrap();
+    let written = read(buffer.as_slice()).unwrap();
+    assert_eq!(written.waypoints, gpx.waypoints[1..]);
+    assert_eq!(written.tracks, gpx.tracks);
+    assert!(written.routes.is_empty());
+    assert_eq!(written.metadata, gpx.metadata);
+}
+
+#[test]
+fn gpx_writer_write_selection_bounds_and_namespaces() {
+    let mut gpx = read_test_gpx_file("tests/fixtures/garmin_with_extensions.gpx");
+    gpx.root_attributes.clear();
+    gpx.metadata = Some(Metadata {
+        bounds: Some(Rect::new(
+            coord! { x: -180.0, y: -90.0 },
+            coord! { x: 180.0, y: 90.0 },
+        )),
+        ..Default::default()
+    });
+    gpx.waypoints.push(Waypoint::new(Point::new(1.0, 2.0)));
+    gpx.waypoints.push(Waypoint::new(Point::new(3.0, 4.0)));
+
+    let mut buffer: Vec<u8> = Vec::new();
+    gpx.write_selection(&mut buffer, &WriteOptions::default(), |item| {
+        matches!(item, DocumentItem::Waypoint { .. })
+    })
+    .unwrap();
+    let written = read(buffer.as_slice()).unwrap();
+    let bounds = written.metadata.unwrap().bounds.unwrap();
+    assert_eq!(bounds.min(), coord! { x: 1.0, y: 2.0 });
+    assert_eq!(bounds.max(), coord! { x: 3.0, y: 4.0 });
+    let text = String::from_utf8(buffer).unwrap();
+    assert!(!text.contains("xmlns:gpxx"));
+}
+
+#[test]
+fn gpx_writer_write_track_from_iter() {
+    let gpx = read_test_gpx_file("tests/fixtures/garmin_with_extensions.gpx");
+    let track = &gpx.tracks[0];
+    let points = track.segments.iter().flat_map(|s| s.points.iter().cloned());
+
+    let mut buffer: Vec<u8> = Vec::new();
+    write_track_from_iter(track, points, &mut buffer).unwrap();
+    let written = read(buffer.as_slice()).unwrap();
+    assert_eq!(written.tracks.len(), 1);
+    assert_eq!(written.tracks[0].name, track.name);
+    assert_eq!(written.tracks[0].segments.len(), 1);
+    let expected: Vec<_> = track.segments.iter().flat_map(|s| &s.points).collect();
+    let actual: Vec<_> = written.tracks[0].segments[0].points.iter().collect();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn gpx_writer_write_comments() {
+    let data = "<gpx version=\"1.1\" creator=\"Test\">\
+        <!-- Survey of 2024-05-02 -->\
+        <wpt lat=\"1\" lon=\"2\"><!-- dropped --><name>Cairn</name></wpt>\
+        <trk><name>Ridge</name></trk>\
+        <!-- Second pass, after the rain -->\
+        <trk><name>Ridge <!-- dropped -->again</name></trk>\
+        <!-- End of survey -->\
+        </gpx>";
+    let options = ReadOptions {
+        comments: true,
+        ..Default::default()
+    };
+    let gpx = read_with_options(data.as_bytes(), &options).unwrap();
+    assert_eq!(
+        gpx.comments,
+        [
+            Comment {
+                text: " Survey of 2024-05-02 ".to_string(),
+                position: CommentPosition::BeforeWaypoint(0),
+            },
+            Comment {
+                text: " Second pass, after the rain ".to_string(),
+                position: CommentPosition::BeforeTrack(1),
+            },
+            Comment {
+                text: " End of survey ".to_string(),
+                position: CommentPosition::End,
+            },
+        ]
+    );
+    assert_eq!(gpx.tracks[1].name.as_deref(), Some("Ridge again"));
+
+    let mut buffer: Vec<u8> = Vec::new();
+    write(&gpx, &mut buffer).unwrap();
+    let written = read_with_options(buffer.as_slice(), &options).unwrap();
+    assert_eq!(written, gpx);
+    assert!(read(buffer.as_slice()).unwrap().comments.is_empty());
+}
+
+#[test]
+fn gpx_writer_write_with_hook() {
+    let mut track = Track::new();
+    track.display_color = Some(DisplayColor::Red);
+    track.segments.push(TrackSegment {
+        points: vec![Waypoint::new(Point::new(1.0, 2.0))],
+    });
+    let mut gpx = Gpx {
+        version: GpxVersion::Gpx11,
+        creator: Some("Test".to_string()),
+        metadata: Some(Metadata::default()),
+        tracks: vec![track],
+        root_attributes: vec![("xmlns:x".to_string(), "urn:x".to_string())],
+        ..Default::default()
+    };
+    let mut hook = |element: HookElement<'_>, writer: &mut ExtensionWriter<'_>| {
+        let name = match element {
+            HookElement::Metadata(_) => "x:meta",
+            HookElement::Track(_) => "x:track",
+            HookElement::TrackPoint(_) => "x:point",
+            _ => return Ok(()),
+        };
+        writer.write_text(name, "1")
+    };
+
+    let mut buffer: Vec<u8> = Vec::new();
+    write_with_hook(&gpx, &mut buffer, &WriteOptions::default(), &mut hook).unwrap();
+    let written: String = String::from_utf8(buffer.clone())
+        .unwrap()
+        .split_whitespace()
+        .collect();
+    assert!(written.contains("<metadata><extensions><x:meta>1</x:meta></extensions></metadata>"));
+    assert!(written.contains(
+        "<gpxx:DisplayColor>Red</gpxx:DisplayColor></gpxx:TrackExtension><x:track>1</x:track></extensions><trkseg>"
+    ));
+    assert!(written.contains("<extensions><x:point>1</x:point></extensions></trkpt>"));
+    assert_eq!(read(buffer.as_slice()).unwrap().tracks, gpx.tracks);
+
+    gpx.version = GpxVersion::Gpx10;
+    let mut buffer: Vec<u8> = Vec::new();
+    write_with_hook(&gpx, &mut buffer, &WriteOptions::default(), &mut hook).unwrap();
+    let written: String = String::from_utf8(buffer)
+        .unwrap()
+        .split_whitespace()
+        .collect();
+    assert!(written.contains("<x:point>1</x:point></trkpt>"));
+    assert!(!written.contains("<extensions>"));
+}
+
 fn check_write_for_example_file(filename: &str) {
     let reference_gpx = read_test_gpx_file(filename);
     let written_gpx = write_and_reread_gpx(&reference_gpx);
 
     check_metadata_equal(&reference_gpx, &written_gpx);
     check_points_equal(&reference_gpx, &written_gpx);
+    assert!(reference_gpx.approx_eq(&written_gpx, &Tolerance::default()));
 }
 
 fn read_test_gpx_file(filename: &str) -> Gpx {
@@ -78,7 +481,7 @@ fn check_metadata_equal(reference_gpx: &Gpx, written_gpx: &Gpx) {
     check_links_equal(&reference.links, &written.links);
 }
 
-fn check_links_equal(reference: &Vec<Link>, written: &Vec<Link>) {
+fn check_links_equal(reference: &[Link], written: &[Link]) {
     assert_eq!(reference.len(), written.len());
     for (r, w) in reference.iter().zip(written) {
         assert_eq!(r.href, w.href);
@@ -98,27 +501,28 @@ fn check_points_equal(reference: &Gpx, written: &Gpx) {
     }
 }
 
-fn check_waypoints_equal(reference: &Vec<Waypoint>, written: &Vec<Waypoint>) {
+fn check_waypoints_equal(reference: &[Waypoint], written: &[Waypoint]) {
     assert_eq!(reference.len(), written.len());
     for (r_wp, w_wp) in reference.iter().zip(written) {
         assert_eq!(r_wp.point(), w_wp.point());
         assert_eq!(r_wp.elevation, w_wp.elevation);
         assert_eq!(r_wp.speed, w_wp.speed);
         assert_eq!(r_wp.time, w_wp.time);
-        assert_eq!(r_wp.geoidheight, w_wp.geoidheight);
-        assert_eq!(r_wp.name, w_wp.name);
-        assert_eq!(r_wp.comment, w_wp.comment);
-        assert_eq!(r_wp.description, w_wp.description);
-        assert_eq!(r_wp.source, w_wp.source);
-        check_links_equal(&r_wp.links, &w_wp.links);
-        assert_eq!(r_wp.symbol, w_wp.symbol);
-        assert_eq!(r_wp.type_, w_wp.type_);
-        assert_eq!(r_wp.fix, w_wp.fix);
-        assert_eq!(r_wp.sat, w_wp.sat);
-        assert_eq!(r_wp.hdop, w_wp.hdop);
-        assert_eq!(r_wp.vdop, w_wp.vdop);
-        assert_eq!(r_wp.pdop, w_wp.pdop);
-        assert_eq!(r_wp.dgps_age, w_wp.dgps_age);
-        assert_eq!(r_wp.dgpsid, w_wp.dgpsid);
+        assert_eq!(r_wp.details().geoidheight, w_wp.details().geoidheight);
+        assert_eq!(r_wp.details().name, w_wp.details().name);
+        assert_eq!(r_wp.details().comment, w_wp.details().comment);
+        assert_eq!(r_wp.details().description, w_wp.details().description);
+        assert_eq!(r_wp.details().source, w_wp.details().source);
+        check_links_equal(&r_wp.details().links, &w_wp.details().links);
+        assert_eq!(r_wp.details().symbol, w_wp.details().symbol);
+        assert_eq!(r_wp.details().type_, w_wp.details().type_);
+        assert_eq!(r_wp.details().fix, w_wp.details().fix);
+        assert_eq!(r_wp.details().sat, w_wp.details().sat);
+        assert_eq!(r_wp.details().hdop, w_wp.details().hdop);
+        assert_eq!(r_wp.details().vdop, w_wp.details().vdop);
+        assert_eq!(r_wp.details().pdop, w_wp.details().pdop);
+        assert_eq!(r_wp.details().dgps_age, w_wp.details().dgps_age);
+        assert_eq!(r_wp.details().dgpsid, w_wp.details().dgpsid);
+        assert_eq!(r_wp.track_point_extension, w_wp.track_point_extension);
     }
 }